@@ -266,6 +266,8 @@ fn main() -> anyhow::Result<()> {
         markers: Default::default(),
         panic_traceback: Default::default(),
         captured_stdout: String::new(),
+        max_stdout_bytes: None,
+        stdout_truncated: false,
     };
     let dynamic_layout_params = match args.run.cairo_layout_params_file {
         Some(file) => Some(CairoLayoutParams::from_file(&file)?),
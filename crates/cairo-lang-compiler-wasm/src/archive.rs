@@ -0,0 +1,330 @@
+//! Ingestion of a Cairo project from a zip or tar archive, such as the archives GitHub serves for
+//! a repository.
+
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read};
+
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::{limits, source};
+use flate2::read::GzDecoder;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// The manifest files kept from an archive, besides the Cairo sources.
+const MANIFEST_FILES: [&str; 2] = ["Scarb.toml", "cairo_project.toml"];
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ImportedRequest {
+    pub crate_name: String,
+    /// The Cairo files of the project, relative to its source directory.
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct ImportArchiveResponse {
+    pub success: bool,
+    /// The fields of a compile request for the imported project.
+    pub request: Option<ImportedRequest>,
+    /// The path of the project in the archive, after stripping the common top-level directory.
+    pub root: Option<String>,
+    /// The manifests of the project, relative to its root.
+    pub manifests: BTreeMap<String, String>,
+    pub error: Option<ApiError>,
+}
+
+/// Unpacks a zip, tar or gzipped tar archive of a Cairo project into a compile request.
+///
+/// The project root is the shallowest directory holding `src/lib.cairo` (the Scarb layout) or,
+/// failing that, `lib.cairo`. Only the `.cairo` files under its source directory and its manifests
+/// are kept. The crate name is taken from `Scarb.toml`, falling back to the root directory name.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn import_archive(archive: &[u8]) -> String {
+    let response = match error::catch_internal(|| import(archive)) {
+        Ok(Ok(response)) => response,
+        Ok(Err(error)) | Err(error) => {
+            ImportArchiveResponse { error: Some(error), ..Default::default() }
+        }
+    };
+    serde_json::to_string(&response).expect("serialize import archive response")
+}
+
+fn import(archive: &[u8]) -> Result<ImportArchiveResponse, ApiError> {
+    let entries = if archive.starts_with(b"PK\x03\x04") {
+        read_zip(archive)?
+    } else if archive.starts_with(&[0x1f, 0x8b]) {
+        let mut tar = Vec::new();
+        GzDecoder::new(archive).read_to_end(&mut tar).map_err(|error| {
+            ApiError::invalid_request(format!("Failed decompressing gzip archive: {error}"))
+        })?;
+        read_tar(&tar)?
+    } else {
+        read_tar(archive)?
+    };
+    let (entries, archive_dir) = strip_common_prefix(entries);
+
+    let (root, source_dir) = find_project_root(&entries)?;
+    let mut files = BTreeMap::new();
+    let mut manifests = BTreeMap::new();
+    for (path, content) in entries {
+        if let Some(relative) = path.strip_prefix(&source_dir)
+            && relative.ends_with(".cairo")
+        {
+            files.insert(relative.to_string(), decode(&path, content)?);
+        } else if let Some(relative) = path.strip_prefix(&root)
+            && MANIFEST_FILES.contains(&relative)
+        {
+            manifests.insert(relative.to_string(), decode(&path, content)?);
+        }
+    }
+    limits::current().check_files(&files)?;
+
+    let root_dir = root.trim_end_matches('/').rsplit('/').next().filter(|dir| !dir.is_empty());
+    let crate_name = manifests
+        .get("Scarb.toml")
+        .and_then(|manifest| scarb_package_name(manifest))
+        .or_else(|| root_dir.or(archive_dir.as_deref()).map(crate_name_from_dir))
+        .unwrap_or_else(|| "main".into());
+
+    Ok(ImportArchiveResponse {
+        success: true,
+        request: Some(ImportedRequest { crate_name, files }),
+        root: Some(root.trim_end_matches('/').to_string()),
+        manifests,
+        error: None,
+    })
+}
+
+/// An archive entry: a normalized `/`-separated path and the file content.
+type Entry = (String, Vec<u8>);
+
+fn read_zip(archive: &[u8]) -> Result<Vec<Entry>, ApiError> {
+    let invalid = |error: zip::result::ZipError| {
+        ApiError::invalid_request(format!("Failed reading zip archive: {error}"))
+    };
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive)).map_err(invalid)?;
+    let mut entries = Vec::new();
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index).map_err(invalid)?;
+        if file.is_dir() {
+            continue;
+        }
+        let Some(path) = normalize_path(file.name()) else { continue };
+        if !is_relevant(&path) {
+            continue;
+        }
+        limits::current().check_file_size(&path, file.size() as usize)?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).map_err(|error| {
+            ApiError::invalid_request(format!("Failed reading `{path}` from zip archive: {error}"))
+        })?;
+        entries.push((path, content));
+    }
+    Ok(entries)
+}
+
+/// Reads the regular files of a ustar or GNU tar archive, honoring long names from GNU `L` and
+/// PAX `x` headers.
+fn read_tar(archive: &[u8]) -> Result<Vec<Entry>, ApiError> {
+    const BLOCK: usize = 512;
+    let truncated =
+        || ApiError::invalid_request("Archive is not a zip or tar archive, or is truncated.");
+
+    let mut entries = Vec::new();
+    let mut long_name = None;
+    let mut offset = 0;
+    while offset + BLOCK <= archive.len() {
+        let header = &archive[offset..offset + BLOCK];
+        if header.iter().all(|byte| *byte == 0) {
+            return Ok(entries);
+        }
+        let size = parse_octal(&header[124..136]).ok_or_else(truncated)?;
+        let data_start = offset + BLOCK;
+        let data = archive.get(data_start..data_start + size).ok_or_else(truncated)?;
+        offset = data_start + size.div_ceil(BLOCK) * BLOCK;
+
+        match header[156] {
+            b'L' => long_name = Some(nul_terminated(data)),
+            b'x' => long_name = pax_path(data).or(long_name),
+            b'0' | 0 => {
+                let name = long_name.take().unwrap_or_else(|| {
+                    let name = nul_terminated(&header[0..100]);
+                    let prefix = nul_terminated(&header[345..500]);
+                    if &header[257..262] == b"ustar" && !prefix.is_empty() {
+                        format!("{prefix}/{name}")
+                    } else {
+                        name
+                    }
+                });
+                if let Some(path) = normalize_path(&name)
+                    && is_relevant(&path)
+                {
+                    limits::current().check_file_size(&path, size)?;
+                    entries.push((path, data.to_vec()));
+                }
+            }
+            // Directories, links and global headers.
+            _ => long_name = None,
+        }
+    }
+    if entries.is_empty() { Err(truncated()) } else { Ok(entries) }
+}
+
+fn nul_terminated(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|byte| *byte == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+fn parse_octal(bytes: &[u8]) -> Option<usize> {
+    let digits = nul_terminated(bytes);
+    let digits = digits.trim();
+    if digits.is_empty() { Some(0) } else { usize::from_str_radix(digits, 8).ok() }
+}
+
+/// Extracts the `path` record of a PAX extended header, made of `<length> <key>=<value>\n`
+/// records.
+fn pax_path(data: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(data)
+        .lines()
+        .find_map(|record| record.split_once(' ')?.1.strip_prefix("path=").map(str::to_string))
+}
+
+/// Normalizes an archive path to `/`-separated components. Returns `None` for paths escaping the
+/// archive.
+fn normalize_path(path: &str) -> Option<String> {
+    let components: Vec<_> =
+        path.split(['/', '\\']).filter(|component| !matches!(*component, "" | ".")).collect();
+    if components.is_empty() || components.contains(&"..") {
+        return None;
+    }
+    Some(components.join("/"))
+}
+
+fn is_relevant(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    path.ends_with(".cairo") || MANIFEST_FILES.contains(&file_name)
+}
+
+/// Strips the top-level directory shared by all entries, as in GitHub archives
+/// (`<repo>-<ref>/...`). Returns the stripped directory, if any.
+fn strip_common_prefix(entries: Vec<Entry>) -> (Vec<Entry>, Option<String>) {
+    let prefixes: Vec<_> = entries
+        .iter()
+        .map(|(path, _)| path.split_once('/').map(|(prefix, _)| prefix))
+        .unique()
+        .collect();
+    match prefixes.as_slice() {
+        [Some(prefix)] => {
+            let prefix = prefix.to_string();
+            let entries = entries
+                .into_iter()
+                .map(|(path, content)| (path[prefix.len() + 1..].to_string(), content))
+                .collect();
+            (entries, Some(prefix))
+        }
+        _ => (entries, None),
+    }
+}
+
+/// Returns the root directory of the project and its source directory, both either empty or
+/// ending with `/`.
+fn find_project_root(entries: &[Entry]) -> Result<(String, String), ApiError> {
+    for lib_file in ["src/lib.cairo", "lib.cairo"] {
+        let candidates = entries
+            .iter()
+            .filter_map(|(path, _)| {
+                if path == lib_file { Some("") } else { path.strip_suffix(&format!("/{lib_file}")) }
+            })
+            .min_set_by_key(|root| if root.is_empty() { 0 } else { root.split('/').count() });
+        match candidates.as_slice() {
+            [] => continue,
+            [root] => {
+                let root = if root.is_empty() { String::new() } else { format!("{root}/") };
+                let source_dir = format!("{root}{}", lib_file.trim_end_matches("lib.cairo"));
+                return Ok((root, source_dir));
+            }
+            roots => {
+                return Err(ApiError::invalid_request(format!(
+                    "Archive holds several Cairo projects: {}.",
+                    roots.iter().map(|root| format!("`{root}`")).join(", ")
+                )));
+            }
+        }
+    }
+    Err(ApiError::invalid_request("Archive holds no `src/lib.cairo` or `lib.cairo` file."))
+}
+
+fn decode(path: &str, content: Vec<u8>) -> Result<String, ApiError> {
+    match String::from_utf8(content) {
+        Ok(text) => Ok(source::normalize(&text)),
+        Err(error) => Err(ApiError::invalid_request(source::invalid_utf8_message(
+            path,
+            error.utf8_error(),
+            error.as_bytes(),
+        ))),
+    }
+}
+
+#[derive(Deserialize)]
+struct ScarbManifest {
+    package: Option<ScarbPackage>,
+}
+
+#[derive(Deserialize)]
+struct ScarbPackage {
+    name: String,
+}
+
+fn scarb_package_name(manifest: &str) -> Option<String> {
+    toml::from_str::<ScarbManifest>(manifest).ok()?.package.map(|package| package.name)
+}
+
+/// Converts a directory name such as `my-project-main` into a valid crate name.
+fn crate_name_from_dir(dir: &str) -> String {
+    dir.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use serde_json::{Value, json};
+    use zip::write::{FileOptions, ZipWriter};
+
+    use super::import_archive;
+
+    #[test]
+    fn import_scarb_project_from_github_zip() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, content) in [
+            ("hello-main/Scarb.toml", "[package]\nname = \"hello\"\nversion = \"0.1.0\"\n"),
+            ("hello-main/README.md", "# Hello"),
+            ("hello-main/src/lib.cairo", "mod utils;\nfn main() -> felt252 { utils::seven() }\n"),
+            ("hello-main/src/utils.cairo", "pub fn seven() -> felt252 { 7 }\n"),
+            ("hello-main/tests/test_hello.cairo", "#[test]\nfn test() {}\n"),
+        ] {
+            writer.start_file(path, FileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        let archive = writer.finish().unwrap().into_inner();
+
+        let response = import_archive(&archive);
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["root"], "");
+        assert_eq!(response_json["request"]["crate_name"], "hello");
+        assert_eq!(
+            response_json["request"]["files"],
+            json!({
+                "lib.cairo": "mod utils;\nfn main() -> felt252 { utils::seven() }\n",
+                "utils.cairo": "pub fn seven() -> felt252 { 7 }\n",
+            })
+        );
+        assert!(response_json["manifests"]["Scarb.toml"].is_string());
+    }
+}
@@ -0,0 +1,211 @@
+//! The CASM of a compiled program as assembly text, with labels for its functions and jump targets
+//! rather than raw offsets, so the output of the compilation pipeline can be read and taught.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_casm::hints::PythonicHint;
+use cairo_lang_casm::instructions::{
+    CallInstruction, Instruction, InstructionBody, JnzInstruction, JumpInstruction,
+};
+use cairo_lang_casm::operand::DerefOrImmediate;
+use cairo_lang_sierra::ProgramParser;
+use cairo_lang_sierra::program::Program;
+use cairo_lang_sierra_to_casm::compiler::{SierraToCasmConfig, compile};
+use cairo_lang_sierra_to_casm::metadata::{
+    MetadataComputationConfig, calc_metadata, calc_metadata_ap_change_only,
+};
+use cairo_lang_sierra_type_size::ProgramRegistryInfo;
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{compiled_programs, limits, parse};
+use num_traits::ToPrimitive;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DisassembleRequest {
+    /// A Sierra program, as text. Exclusive with `program`.
+    pub sierra: Option<String>,
+    /// The handle of a program compiled by `compile_program`, exclusive with `sierra`.
+    pub program: Option<u32>,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct DisassembleResponse {
+    pub success: bool,
+    /// The CASM of the program as text, as returned by `compile` with `casm`.
+    pub casm: Option<String>,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Compiles a Sierra program to CASM and returns it as text, with labels for its functions and
+/// jump targets, as `compile` does with `casm` for the program it compiles.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn disassemble(request_json: &str) -> String {
+    let request: DisassembleRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serde_json::to_string(&DisassembleResponse {
+                error: Some(error),
+                ..Default::default()
+            })
+            .expect("serialize error response");
+        }
+    };
+
+    let (result, debug_log) =
+        logging::capture(request.debug, || error::catch_internal(|| disassemble_request(request)));
+    let mut response = result
+        .and_then(|result| result)
+        .map(|casm| DisassembleResponse { success: true, casm: Some(casm), ..Default::default() })
+        .unwrap_or_else(|error| DisassembleResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+
+    serde_json::to_string(&response).expect("serialize disassemble response")
+}
+
+fn disassemble_request(request: DisassembleRequest) -> Result<String, ApiError> {
+    let program = match (request.sierra, request.program) {
+        (Some(sierra), None) => {
+            limits::current().check_source("Sierra program", &sierra)?;
+            ProgramParser::new().parse(&sierra).map_err(|error| {
+                ApiError::invalid_request(format!("Failed parsing Sierra program: {error:?}"))
+            })?
+        }
+        (None, Some(program)) => compiled_programs::get(program)?.program,
+        _ => {
+            return Err(ApiError::invalid_request(
+                "Exactly one of `sierra` and `program` is required.",
+            ));
+        }
+    };
+    casm_text(&program)
+}
+
+/// Compiles `program` to CASM and returns it as text, each function starting with a label of its
+/// name, and each jump or call to an immediate relative offset targeting a label.
+pub(crate) fn casm_text(program: &Program) -> Result<String, ApiError> {
+    let program_info = ProgramRegistryInfo::new(program).map_err(|error| {
+        ApiError::compilation(format!("Failed building the program registry: {error}"))
+    })?;
+    let requires_gas_counter = program.requires_gas_counter();
+    let metadata = if requires_gas_counter {
+        calc_metadata(program, &program_info, MetadataComputationConfig::default())
+    } else {
+        calc_metadata_ap_change_only(program, &program_info)
+    }
+    .map_err(|error| {
+        ApiError::compilation(format!("Failed computing the metadata of the program: {error}"))
+    })?;
+    let casm_program = compile(
+        program,
+        &program_info,
+        &metadata,
+        SierraToCasmConfig { gas_usage_check: requires_gas_counter, max_bytecode_size: usize::MAX },
+    )
+    .map_err(|error| ApiError::compilation(format!("Failed compiling to CASM: {error}")))?;
+
+    // The lines of the program with their bytecode offsets: the instructions, then the segments of
+    // the constants, each following a `ret` as in the bytecode.
+    let mut lines = vec![];
+    let mut offset = 0;
+    for instruction in &casm_program.instructions {
+        lines.push((offset, Line::Instruction(instruction)));
+        offset += instruction.body.op_size();
+    }
+    for segment in casm_program.consts_info.segments.values() {
+        lines.push((offset, Line::Text("ret".to_string())));
+        offset += 1;
+        for value in &segment.values {
+            lines.push((offset, Line::Text(format!("dw {value}"))));
+            offset += 1;
+        }
+    }
+
+    let mut labels = BTreeMap::new();
+    for func in &program.funcs {
+        let instruction_idx =
+            casm_program.debug_info.sierra_statement_info[func.entry_point.0].instruction_idx;
+        if let Some((offset, _)) = lines.get(instruction_idx) {
+            labels.entry(*offset).or_insert_with(|| func.id.to_string());
+        }
+    }
+    let mut targets = lines
+        .iter()
+        .filter_map(|(offset, line)| match line {
+            Line::Instruction(instruction) => jump_target(*offset, instruction),
+            Line::Text(_) => None,
+        })
+        .filter(|target| !labels.contains_key(target))
+        .collect::<Vec<_>>();
+    targets.sort();
+    targets.dedup();
+    for (index, target) in targets.into_iter().enumerate() {
+        labels.insert(target, format!(".L{index}"));
+    }
+
+    let mut text = String::new();
+    for (offset, line) in &lines {
+        if let Some(label) = labels.get(offset) {
+            // Functions are separated by an empty line.
+            if !label.starts_with('.') && !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&format!("{label}:\n"));
+        }
+        match line {
+            Line::Instruction(instruction) => {
+                for hint in &instruction.hints {
+                    text.push_str(&format!("    %{{ {} %}}\n", hint.get_pythonic_hint().trim()));
+                }
+                let body = jump_target(*offset, instruction)
+                    .and_then(|target| labels.get(&target))
+                    .map_or_else(
+                        || instruction.body.to_string(),
+                        |label| labeled_body(&instruction.body, label),
+                    );
+                let inc_ap = if instruction.inc_ap { ", ap++" } else { "" };
+                text.push_str(&format!("    {body}{inc_ap};\n"));
+            }
+            Line::Text(line) => text.push_str(&format!("    {line};\n")),
+        }
+    }
+    Ok(text)
+}
+
+/// A line of the CASM text.
+enum Line<'a> {
+    Instruction(&'a Instruction),
+    /// The `ret` and the values of the segments of constants.
+    Text(String),
+}
+
+/// Returns the offset `instruction`, at `offset`, jumps to or calls, if it is an immediate relative
+/// offset.
+fn jump_target(offset: usize, instruction: &Instruction) -> Option<usize> {
+    let relative_target = match &instruction.body {
+        InstructionBody::Call(CallInstruction { target, relative: true })
+        | InstructionBody::Jump(JumpInstruction { target, relative: true })
+        | InstructionBody::Jnz(JnzInstruction { jump_offset: target, .. }) => target,
+        _ => return None,
+    };
+    let DerefOrImmediate::Immediate(relative_target) = relative_target else {
+        return None;
+    };
+    offset.checked_add_signed(relative_target.value.to_isize()?)
+}
+
+/// Returns `body`, a relative jump or call, targeting `label` instead of its offset.
+fn labeled_body(body: &InstructionBody, label: &str) -> String {
+    match body {
+        InstructionBody::Call(_) => format!("call {label}"),
+        InstructionBody::Jnz(jnz) => format!("jmp {label} if {} != 0", jnz.condition),
+        _ => format!("jmp {label}"),
+    }
+}
@@ -0,0 +1,262 @@
+//! Extraction of the values of the constants and the tags of the enum variants of a project, for
+//! displaying them without running anything.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::{NamedLanguageElementId, TopLevelLanguageElementId};
+use cairo_lang_filesystem::ids::CrateInput;
+use cairo_lang_semantic::items::constant::{ConstValue, ConstValueId, ConstantSemantic};
+use cairo_lang_semantic::items::enm::EnumSemantic;
+use cairo_lang_semantic::items::structure::StructSemantic;
+use cairo_lang_semantic::{ConcreteTypeId, TypeLongId};
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{corelib, limits, parse, source};
+use salsa::Database;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::signature::prepare_checked_db;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ExtractConstantsRequest {
+    pub crate_name: String,
+    #[serde(deserialize_with = "source::deserialize_files")]
+    #[schemars(with = "BTreeMap<String, source::SourceContent>")]
+    pub files: BTreeMap<String, String>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// A module-level `const` item.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConstantEntry {
+    /// The full path of the constant.
+    pub path: String,
+    /// The fully qualified name of the type of the constant, `None` when it could not be
+    /// evaluated.
+    #[serde(rename = "type")]
+    pub ty: Option<String>,
+    /// The value: a decimal string for integers, an object for structs, an array for tuples and
+    /// fixed-size arrays, and `{ "variant", "value" }` for enums. `None` when it could not be
+    /// evaluated.
+    pub value: Option<Value>,
+}
+
+/// An enum and the tags of its variants.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct EnumEntry {
+    /// The full path of the enum.
+    pub path: String,
+    pub variants: Vec<EnumVariantEntry>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct EnumVariantEntry {
+    pub name: String,
+    /// The felt preceding the value of the variant in the serialization of the enum.
+    pub tag: usize,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct ExtractConstantsResponse {
+    pub success: bool,
+    pub constants: Vec<ConstantEntry>,
+    pub enums: Vec<EnumEntry>,
+    pub diagnostics: String,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Evaluates the module-level `const` items of the main crate and lists the tags of the variants
+/// of its enums, in the order of their declarations.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn extract_constants(request_json: &str) -> String {
+    let request: ExtractConstantsRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serde_json::to_string(&ExtractConstantsResponse {
+                error: Some(error),
+                ..Default::default()
+            })
+            .expect("serialize error response");
+        }
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| extract_constants_request(request))
+    });
+    let mut response = result.unwrap_or_else(|error| ExtractConstantsResponse {
+        error: Some(error),
+        ..Default::default()
+    });
+    response.debug_log = debug_log;
+
+    serde_json::to_string(&response).expect("serialize extract constants response")
+}
+
+fn extract_constants_request(request: ExtractConstantsRequest) -> ExtractConstantsResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return ExtractConstantsResponse { error: Some(error), ..Default::default() };
+    }
+
+    let project = InMemoryProject {
+        main_crate_name: request.crate_name,
+        main_crate_files: request.files,
+        corelib_files: corelib::resolve(request.corelib_files),
+        main_crate_settings: None,
+    };
+    let (db, main_crate_inputs, diagnostics) = match prepare_checked_db(&project) {
+        Ok(prepared) => prepared,
+        Err((error, diagnostics)) => {
+            return ExtractConstantsResponse {
+                diagnostics,
+                error: Some(error),
+                ..Default::default()
+            };
+        }
+    };
+
+    let db: &dyn Database = &db;
+    let module_ids = CrateInput::into_crate_ids(db, main_crate_inputs)
+        .into_iter()
+        .flat_map(|crate_id| db.crate_modules(crate_id).iter().copied())
+        .collect::<Vec<_>>();
+    let constants = module_ids
+        .iter()
+        .flat_map(|module_id| db.module_constants_ids(*module_id).into_iter().flatten())
+        .map(|constant_id| {
+            let value = db.constant_const_value(*constant_id).ok();
+            ConstantEntry {
+                path: constant_id.full_path(db),
+                ty: value.and_then(|value| value.ty(db).ok()).map(|ty| ty.format(db)),
+                value: value.and_then(|value| const_value_json(db, value)),
+            }
+        })
+        .collect();
+    let enums = module_ids
+        .iter()
+        .flat_map(|module_id| db.module_enums_ids(*module_id).into_iter().flatten())
+        .map(|enum_id| EnumEntry {
+            path: enum_id.full_path(db),
+            variants: db
+                .enum_variants(*enum_id)
+                .into_iter()
+                .flat_map(|variants| variants.keys())
+                .enumerate()
+                .map(|(tag, name)| EnumVariantEntry { name: name.long(db).to_string(), tag })
+                .collect(),
+        })
+        .collect();
+
+    ExtractConstantsResponse { success: true, constants, enums, diagnostics, ..Default::default() }
+}
+
+/// Returns the JSON representation of a constant value, or `None` if it is not fully evaluated.
+fn const_value_json<'db>(db: &'db dyn Database, value: ConstValueId<'db>) -> Option<Value> {
+    match value.long(db) {
+        ConstValue::Int(value, _) => Some(Value::String(value.to_string())),
+        ConstValue::Struct(members, ty) => {
+            if ty.format(db) == "core::integer::u256" {
+                // Shown as a single integer rather than its `low` and `high` limbs.
+                let [low, high] = &members[..] else { return None };
+                let (ConstValue::Int(low, _), ConstValue::Int(high, _)) =
+                    (low.long(db), high.long(db))
+                else {
+                    return None;
+                };
+                return Some(Value::String(((high << 128) + low).to_string()));
+            }
+            let values: Vec<Value> = members
+                .iter()
+                .map(|member| const_value_json(db, *member))
+                .collect::<Option<_>>()?;
+            match ty.long(db) {
+                TypeLongId::Concrete(ConcreteTypeId::Struct(concrete_struct_id)) => {
+                    let names = db.concrete_struct_members(*concrete_struct_id).ok()?.keys();
+                    Some(Value::Object(
+                        names.map(|name| name.long(db).to_string()).zip(values).collect(),
+                    ))
+                }
+                _ => Some(Value::Array(values)),
+            }
+        }
+        ConstValue::Enum(variant, value) => Some(json!({
+            "variant": variant.id.name(db).long(db).to_string(),
+            "value": const_value_json(db, *value)?,
+        })),
+        ConstValue::NonZero(value) => const_value_json(db, *value),
+        ConstValue::Generic(_)
+        | ConstValue::ImplConstant(_)
+        | ConstValue::Var(_, _)
+        | ConstValue::Missing(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::extract_constants;
+
+    #[test]
+    fn extract_constants_evaluates_values_and_tags() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+#[derive(Drop, Copy)]
+struct Config { fee: u64, owner: felt252 }
+
+#[derive(Drop, Copy)]
+enum Mode { Off, On: u8 }
+
+const FEE: u64 = 3 * 100;
+const SUPPLY: u256 = 0x100000000000000000000000000000001;
+const CONFIG: Config = Config { fee: FEE, owner: 'admin' };
+const MODE: Mode = Mode::On(2);
+"
+            }
+        });
+
+        let response = extract_constants(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(
+            response_json["constants"],
+            json!([
+                { "path": "test::FEE", "type": "core::integer::u64", "value": "300" },
+                {
+                    "path": "test::SUPPLY",
+                    "type": "core::integer::u256",
+                    "value": "340282366920938463463374607431768211457"
+                },
+                {
+                    "path": "test::CONFIG",
+                    "type": "test::Config",
+                    "value": { "fee": "300", "owner": "418296719726" }
+                },
+                { "path": "test::MODE", "type": "test::Mode", "value": { "variant": "On", "value": "2" } }
+            ])
+        );
+        assert_eq!(
+            response_json["enums"],
+            json!([{ "path": "test::Mode", "variants": [
+                { "name": "Off", "tag": 0 },
+                { "name": "On", "tag": 1 }
+            ] }])
+        );
+    }
+}
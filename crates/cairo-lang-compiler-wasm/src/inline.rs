@@ -0,0 +1,361 @@
+//! Inlining of a function at its call sites in the sources, as a refactoring of a project.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::project::{InMemoryProject, crate_source_root};
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::{FunctionWithBodyId, LanguageElementId, TopLevelLanguageElementId};
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::CrateInput;
+use cairo_lang_filesystem::span::TextSpan;
+use cairo_lang_semantic::items::free_function::FreeFunctionSemantic;
+use cairo_lang_semantic::items::function_with_body::FunctionWithBodySemantic;
+use cairo_lang_semantic::items::functions::GenericFunctionId;
+use cairo_lang_semantic::items::imp::ImplSemantic;
+use cairo_lang_semantic::{Expr, ExprFunctionCallArg, Mutability, Statement};
+use cairo_lang_syntax::node::TypedStablePtr;
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{corelib, item_path, limits, parse, source};
+use itertools::Itertools;
+use salsa::Database;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::modules;
+use crate::signature::prepare_checked_db;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct InlineFunctionRequest {
+    pub crate_name: String,
+    #[serde(deserialize_with = "source::deserialize_files")]
+    #[schemars(with = "BTreeMap<String, source::SourceContent>")]
+    pub files: BTreeMap<String, String>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct InlineFunctionResponse {
+    pub success: bool,
+    /// The full path of the inlined function.
+    pub function: Option<String>,
+    /// The files of the request, with the function inlined at its call sites.
+    pub files: BTreeMap<String, String>,
+    /// The number of inlined call sites.
+    pub inlined: usize,
+    pub diagnostics: String,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Returns the files of a project with a free function of the main crate inlined at its call
+/// sites.
+///
+/// `function` is matched as in `function_signature`. Each call is replaced by the body of the
+/// function, preceded by the bindings of its parameters to the arguments of the call. Only the
+/// calls of the module of the function are inlined, so the names used by its body resolve to the
+/// same items. The function itself is kept.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn inline_function(request_json: &str, function: &str) -> String {
+    let request: InlineFunctionRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serde_json::to_string(&InlineFunctionResponse {
+                error: Some(error),
+                ..Default::default()
+            })
+            .expect("serialize error response");
+        }
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| inline_function_request(request, function))
+    });
+    let mut response = result.unwrap_or_else(|error| InlineFunctionResponse {
+        error: Some(error),
+        ..Default::default()
+    });
+    response.debug_log = debug_log;
+
+    serde_json::to_string(&response).expect("serialize inline function response")
+}
+
+fn inline_function_request(
+    request: InlineFunctionRequest,
+    function: &str,
+) -> InlineFunctionResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return InlineFunctionResponse { error: Some(error), ..Default::default() };
+    }
+
+    let project = InMemoryProject {
+        main_crate_name: request.crate_name,
+        main_crate_files: request.files,
+        corelib_files: corelib::resolve(request.corelib_files),
+        main_crate_settings: None,
+    };
+    let (db, main_crate_inputs, diagnostics) = match prepare_checked_db(&project) {
+        Ok(prepared) => prepared,
+        Err((error, diagnostics)) => {
+            return InlineFunctionResponse {
+                diagnostics,
+                error: Some(error),
+                ..Default::default()
+            };
+        }
+    };
+    let error_response = |error| InlineFunctionResponse {
+        diagnostics: diagnostics.clone(),
+        error: Some(error),
+        ..Default::default()
+    };
+
+    let db: &dyn Database = &db;
+    let candidates = CrateInput::into_crate_ids(db, main_crate_inputs)
+        .into_iter()
+        .flat_map(|crate_id| db.crate_modules(crate_id))
+        .flat_map(|module_id| db.module_free_functions_ids(*module_id).into_iter().flatten())
+        .map(|free_function_id| (free_function_id.full_path(db), *free_function_id))
+        .filter(|(path, _)| item_path::matches_suffix(path, function))
+        .collect_vec();
+    let (path, free_function_id) = match &candidates[..] {
+        [candidate] => candidate.clone(),
+        [] => {
+            return error_response(ApiError::invalid_request(format!(
+                "Failed finding function `{function}`."
+            )));
+        }
+        _ => {
+            return error_response(ApiError::invalid_request(format!(
+                "Function `{function}` is ambiguous, matching {}.",
+                candidates.iter().map(|(path, _)| format!("`{path}`")).join(", ")
+            )));
+        }
+    };
+
+    let target = FunctionWithBodyId::Free(free_function_id);
+    let (Ok(signature), Ok(generic_params), Ok(body)) = (
+        db.free_function_signature(free_function_id),
+        db.free_function_generic_params(free_function_id),
+        db.function_body(target),
+    ) else {
+        return error_response(ApiError::compilation(format!(
+            "Failed resolving the body of `{path}`."
+        )));
+    };
+    if !generic_params.is_empty() {
+        return error_response(ApiError::invalid_request(format!(
+            "Inlining the generic function `{path}` is not supported."
+        )));
+    }
+    if signature.params.iter().any(|param| param.mutability == Mutability::Reference) {
+        return error_response(ApiError::invalid_request(format!(
+            "Inlining `{path}` is not supported, as it has `ref` parameters."
+        )));
+    }
+    // Early returns would return from the caller once inlined.
+    if body.arenas.statements.iter().any(|(_, statement)| matches!(statement, Statement::Return(_)))
+        || body.arenas.exprs.iter().any(|(_, expr)| matches!(expr, Expr::PropagateError(_)))
+    {
+        return error_response(ApiError::invalid_request(format!(
+            "Inlining `{path}` is not supported, as it returns early with `return` or `?`."
+        )));
+    }
+
+    // The path of the file in the request, from the virtual directory of the crate, whose paths are
+    // relative to the source root.
+    let body_node = body.arenas.exprs[body.body_expr].stable_ptr().untyped().lookup(db);
+    let file_id = body_node.file_id(db);
+    let content = db.file_content(file_id).unwrap_or_default();
+    let module_id = free_function_id.parent_module(db);
+    let mut crate_files = vec![];
+    if let Some(config) = db.crate_config(module_id.owning_crate(db)) {
+        let source_root = crate_source_root("main", &project.main_crate_files).unwrap_or_default();
+        modules::collect_file_paths(&config.root, source_root, &mut crate_files);
+    }
+    let file_paths = crate_files
+        .into_iter()
+        .filter(|(crate_file_id, _)| *crate_file_id == file_id)
+        .map(|(_, path)| path)
+        .collect_vec();
+    let [file_path] = &file_paths[..] else {
+        return error_response(ApiError::invalid_request(format!(
+            "Inlining `{path}` is not supported, as it is not defined in a single source file."
+        )));
+    };
+
+    // The body block with the parameters bound right after its opening brace.
+    let body_text = body_node.span_without_trivia(db).take(content);
+    let names = signature
+        .params
+        .iter()
+        .map(|param| {
+            let name = param.name.long(db);
+            if param.mutability == Mutability::Mutable {
+                format!("mut {name}")
+            } else {
+                name.to_string()
+            }
+        })
+        .collect_vec();
+    let types = signature.params.iter().map(|param| param.ty.format(db)).collect_vec();
+    let inline_call = |args: Vec<&str>| match &names[..] {
+        [] => body_text.to_string(),
+        [name] => format!("{{ let {name}: {} = {};{}", types[0], args[0], &body_text[1..]),
+        _ => format!(
+            "{{ let ({}): ({}) = ({});{}",
+            names.join(", "),
+            types.join(", "),
+            args.join(", "),
+            &body_text[1..]
+        ),
+    };
+
+    let callers = db
+        .module_free_functions_ids(module_id)
+        .into_iter()
+        .flatten()
+        .map(|id| FunctionWithBodyId::Free(*id))
+        .chain(
+            db.module_impls_ids(module_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|impl_def_id| db.impl_functions(*impl_def_id).ok())
+                .flat_map(|functions| functions.values())
+                .map(|id| FunctionWithBodyId::Impl(*id)),
+        )
+        .filter(|caller| *caller != target);
+    let mut edits = vec![];
+    for caller in callers {
+        let Ok(caller_body) = db.function_body(caller) else {
+            continue;
+        };
+        for (_, expr) in caller_body.arenas.exprs.iter() {
+            let Expr::FunctionCall(call) = expr else {
+                continue;
+            };
+            if call.function.get_concrete(db).generic_function
+                != GenericFunctionId::Free(free_function_id)
+            {
+                continue;
+            }
+            let call_node = call.stable_ptr.untyped().lookup(db);
+            // Calls generated by macros are not in the sources.
+            if call_node.file_id(db) != file_id {
+                continue;
+            }
+            let args = call
+                .args
+                .iter()
+                .map(|arg| match arg {
+                    ExprFunctionCallArg::Value(expr_id) => {
+                        let arg_node =
+                            caller_body.arenas.exprs[*expr_id].stable_ptr().untyped().lookup(db);
+                        arg_node.span_without_trivia(db).take(content)
+                    }
+                    ExprFunctionCallArg::Reference(_) | ExprFunctionCallArg::TempReference(_) => {
+                        unreachable!("`ref` parameters were rejected")
+                    }
+                })
+                .collect_vec();
+            edits.push((call_node.span_without_trivia(db), inline_call(args)));
+        }
+    }
+
+    // The calls nested in the arguments of another call are kept in its arguments.
+    edits.sort_by_key(|(span, _)| (span.start, std::cmp::Reverse(span.end)));
+    let mut outer_edits: Vec<(TextSpan, String)> = vec![];
+    for (span, replacement) in edits {
+        if outer_edits.last().is_none_or(|(outer, _)| !outer.contains(span)) {
+            outer_edits.push((span, replacement));
+        }
+    }
+    let mut inlined_content = content.to_string();
+    for (span, replacement) in outer_edits.iter().rev() {
+        inlined_content.replace_range(span.to_str_range(), replacement);
+    }
+
+    let mut files = project.main_crate_files.clone();
+    files.insert(file_path.clone(), inlined_content);
+    let inlined_project = InMemoryProject { main_crate_files: files, ..project };
+    if let Err((_, inlined_diagnostics)) = prepare_checked_db(&inlined_project) {
+        return InlineFunctionResponse {
+            function: Some(path),
+            diagnostics: inlined_diagnostics,
+            error: Some(ApiError::compilation(
+                "The project does not compile with the function inlined.",
+            )),
+            ..Default::default()
+        };
+    }
+
+    InlineFunctionResponse {
+        success: true,
+        function: Some(path),
+        files: inlined_project.main_crate_files,
+        inlined: outer_edits.len(),
+        diagnostics,
+        error: None,
+        debug_log: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::inline_function;
+
+    const SOURCE: &str = "\
+fn add_twice(a: u32, mut b: u32) -> u32 {
+    b += b;
+    a + b
+}
+
+fn main() -> u32 {
+    add_twice(1, add_twice(2, 3))
+}
+";
+
+    #[test]
+    fn inline_function_replaces_calls_with_body() {
+        // A copy of the sources outside of the source root is left untouched.
+        let request = json!({
+            "crate_name": "test",
+            "files": { "src/lib.cairo": SOURCE, "backup/lib.cairo": SOURCE }
+        });
+
+        let response = inline_function(&request.to_string(), "::add_twice");
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["function"], "test::add_twice");
+        assert_eq!(response_json["inlined"], 1);
+        assert_eq!(response_json["files"]["backup/lib.cairo"], SOURCE);
+        assert_eq!(
+            response_json["files"]["src/lib.cairo"],
+            "\
+fn add_twice(a: u32, mut b: u32) -> u32 {
+    b += b;
+    a + b
+}
+
+fn main() -> u32 {
+    { let (a, mut b): (core::integer::u32, core::integer::u32) = (1, add_twice(2, 3));
+    b += b;
+    a + b
+}
+}
+"
+        );
+    }
+}
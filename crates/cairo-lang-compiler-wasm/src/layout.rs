@@ -0,0 +1,311 @@
+//! Queries of the serialization layout of a type, for codecs, debuggers and storage inspectors.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::{EnumId, NamedLanguageElementId, StructId, TopLevelLanguageElementId};
+use cairo_lang_filesystem::ids::{CrateId, CrateInput};
+use cairo_lang_semantic::corelib::core_crate;
+use cairo_lang_semantic::items::enm::{EnumSemantic, SemanticEnumEx};
+use cairo_lang_semantic::items::structure::StructSemantic;
+use cairo_lang_semantic::types::{ConcreteEnumLongId, ConcreteStructLongId};
+use cairo_lang_semantic::{ConcreteEnumId, ConcreteStructId, ConcreteTypeId, TypeId, TypeLongId};
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{item_path, parse, source};
+use itertools::Itertools;
+use salsa::Database;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::signature::{TypeInfo, prepare_checked_db, request_project, type_info};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TypeLayoutRequest {
+    /// The name of the main crate, required with `files`.
+    pub crate_name: Option<String>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub files: Option<BTreeMap<String, String>>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    /// The handle of a program compiled by `compile_program`, whose sources are queried instead
+    /// of `files`.
+    pub program: Option<u32>,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// A member of a struct.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct MemberLayout {
+    pub name: String,
+    #[serde(flatten)]
+    pub ty: TypeInfo,
+    /// The index of the first felt of the member in the serialization of the struct, or `None`
+    /// when it follows a member of variable length.
+    pub offset: Option<usize>,
+}
+
+/// A variant of an enum.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct VariantLayout {
+    pub name: String,
+    /// The felt preceding the value of the variant in the serialization of the enum.
+    pub tag: usize,
+    #[serde(flatten)]
+    pub ty: TypeInfo,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct TypeLayoutResponse {
+    pub success: bool,
+    /// The type and its felt count.
+    #[serde(rename = "type")]
+    pub ty: Option<TypeInfo>,
+    /// The members of a struct.
+    pub members: Option<Vec<MemberLayout>>,
+    /// The variants of an enum.
+    pub variants: Option<Vec<VariantLayout>>,
+    pub diagnostics: String,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// A struct or an enum.
+#[derive(Clone, Copy)]
+enum TypeItem<'db> {
+    Struct(StructId<'db>),
+    Enum(EnumId<'db>),
+}
+
+impl<'db> TypeItem<'db> {
+    fn full_path(self, db: &'db dyn Database) -> String {
+        match self {
+            TypeItem::Struct(struct_id) => struct_id.full_path(db),
+            TypeItem::Enum(enum_id) => enum_id.full_path(db),
+        }
+    }
+}
+
+/// Returns how a struct or an enum of the main crate or the corelib serializes: its felt count, the
+/// offsets of its members or the tags of its variants.
+///
+/// `type_path` is matched against the trailing `::`-separated segments of the full paths of the
+/// types, as `function` is in [crate::function_signature] (e.g. `Point` or `::Point`). The types of
+/// the main crate are looked up first. Generic types are not supported.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn type_layout(request_json: &str, type_path: &str) -> String {
+    let request: TypeLayoutRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serde_json::to_string(&TypeLayoutResponse {
+                error: Some(error),
+                ..Default::default()
+            })
+            .expect("serialize error response");
+        }
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| type_layout_request(request, type_path))
+    });
+    let mut response = result
+        .unwrap_or_else(|error| TypeLayoutResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+
+    serde_json::to_string(&response).expect("serialize type layout response")
+}
+
+fn type_layout_request(request: TypeLayoutRequest, type_path: &str) -> TypeLayoutResponse {
+    let from_program = request.program.is_some();
+    let project = match request_project(
+        request.crate_name,
+        request.files,
+        request.corelib_files,
+        request.program,
+    ) {
+        Ok(project) => project,
+        Err(error) => return TypeLayoutResponse { error: Some(error), ..Default::default() },
+    };
+
+    let (db, main_crate_inputs, diagnostics) = match prepare_checked_db(&project) {
+        Ok(prepared) => prepared,
+        Err((error, diagnostics)) => {
+            return TypeLayoutResponse { diagnostics, error: Some(error), ..Default::default() };
+        }
+    };
+
+    // The diagnostics of a compiled program were returned by `compile_program`, with its hidden
+    // files redacted.
+    let diagnostics = if from_program { String::new() } else { diagnostics };
+
+    let db: &dyn Database = &db;
+    let mut candidates =
+        find_types(db, CrateInput::into_crate_ids(db, main_crate_inputs), type_path);
+    if candidates.is_empty() {
+        candidates = find_types(db, [core_crate(db)], type_path);
+    }
+    let error_response = |error| TypeLayoutResponse {
+        diagnostics: diagnostics.clone(),
+        error: Some(error),
+        ..Default::default()
+    };
+    let (path, item) = match &candidates[..] {
+        [candidate] => candidate.clone(),
+        [] => {
+            return error_response(ApiError::invalid_request(format!(
+                "Failed finding type `{type_path}`."
+            )));
+        }
+        _ => {
+            return error_response(ApiError::invalid_request(format!(
+                "Type `{type_path}` is ambiguous, matching {}.",
+                candidates.iter().map(|(path, _)| format!("`{path}`")).join(", ")
+            )));
+        }
+    };
+    let failed_resolving = || {
+        error_response(ApiError::compilation(format!("Failed resolving the members of `{path}`.")))
+    };
+
+    match item {
+        TypeItem::Struct(struct_id) => {
+            match db.struct_generic_params(struct_id) {
+                Ok([]) => {}
+                Ok(_) => return error_response(generic_type_error(&path)),
+                Err(_) => return failed_resolving(),
+            }
+            let concrete_struct_id =
+                ConcreteStructId::new(db, ConcreteStructLongId { struct_id, generic_args: vec![] });
+            let Ok(members) = db.concrete_struct_members(concrete_struct_id) else {
+                return failed_resolving();
+            };
+            let mut offset = Some(0);
+            let members = members
+                .iter()
+                .map(|(name, member)| {
+                    let ty = type_info(db, member.ty);
+                    let layout = MemberLayout { name: name.long(db).to_string(), offset, ty };
+                    offset = offset.zip(layout.ty.felts).map(|(offset, felts)| offset + felts);
+                    layout
+                })
+                .collect();
+            let ty =
+                TypeId::new(db, TypeLongId::Concrete(ConcreteTypeId::Struct(concrete_struct_id)));
+            TypeLayoutResponse {
+                success: true,
+                ty: Some(type_info(db, ty)),
+                members: Some(members),
+                diagnostics,
+                ..Default::default()
+            }
+        }
+        TypeItem::Enum(enum_id) => {
+            match db.enum_generic_params(enum_id) {
+                Ok([]) => {}
+                Ok(_) => return error_response(generic_type_error(&path)),
+                Err(_) => return failed_resolving(),
+            }
+            let concrete_enum_id =
+                ConcreteEnumId::new(db, ConcreteEnumLongId { enum_id, generic_args: vec![] });
+            let Ok(variants) = db.concrete_enum_variants(concrete_enum_id) else {
+                return failed_resolving();
+            };
+            let variants = variants
+                .into_iter()
+                .map(|variant| VariantLayout {
+                    name: variant.id.name(db).long(db).to_string(),
+                    tag: variant.idx,
+                    ty: type_info(db, variant.ty),
+                })
+                .collect();
+            let ty = TypeId::new(db, TypeLongId::Concrete(ConcreteTypeId::Enum(concrete_enum_id)));
+            TypeLayoutResponse {
+                success: true,
+                ty: Some(type_info(db, ty)),
+                variants: Some(variants),
+                diagnostics,
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// Returns the structs and enums of `crate_ids` whose full path ends with the segments of
+/// `type_path`.
+fn find_types<'db>(
+    db: &'db dyn Database,
+    crate_ids: impl IntoIterator<Item = CrateId<'db>>,
+    type_path: &str,
+) -> Vec<(String, TypeItem<'db>)> {
+    crate_ids
+        .into_iter()
+        .flat_map(|crate_id| db.crate_modules(crate_id))
+        .flat_map(|module_id| {
+            let structs = db.module_structs_ids(*module_id).into_iter().flatten();
+            let enums = db.module_enums_ids(*module_id).into_iter().flatten();
+            structs
+                .map(|struct_id| TypeItem::Struct(*struct_id))
+                .chain(enums.map(|enum_id| TypeItem::Enum(*enum_id)))
+        })
+        .map(|item| (item.full_path(db), item))
+        .filter(|(path, _)| item_path::matches_suffix(path, type_path))
+        .collect()
+}
+
+fn generic_type_error(path: &str) -> ApiError {
+    ApiError::invalid_request(format!("Type `{path}` is generic, which is not supported."))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::type_layout;
+
+    #[test]
+    fn type_layout_reports_offsets_and_tags() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+#[derive(Drop, Serde)]
+struct Order { id: felt252, items: Array<u64>, amount: u256 }
+
+#[derive(Drop, Serde)]
+enum Status { Pending, Paid: u256 }
+"
+            }
+        });
+
+        let response = type_layout(&request.to_string(), "::Order");
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["type"], json!({ "type": "test::Order", "felts": null }));
+        let offsets = response_json["members"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|member| (member["name"].as_str().unwrap(), member["offset"].as_u64()))
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, [("id", Some(0)), ("items", Some(1)), ("amount", None)]);
+
+        let response = type_layout(&request.to_string(), "::Status");
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["type"]["felts"], Value::Null, "response={response}");
+        assert_eq!(
+            response_json["variants"],
+            json!([
+                { "name": "Pending", "tag": 0, "type": "()", "felts": 0 },
+                { "name": "Paid", "tag": 1, "type": "core::integer::u256", "felts": 2 }
+            ])
+        );
+    }
+}
@@ -2,27 +2,80 @@ use std::collections::BTreeMap;
 
 use cairo_lang_compiler::diagnostics::DiagnosticsReporter;
 use cairo_lang_compiler::project::InMemoryProject;
-use cairo_lang_compiler::{CompilerConfig, compile_in_memory_project};
+use cairo_lang_compiler::{
+    CompilerConfig, compile_prepared_db_program, prepare_in_memory_project_db,
+};
+use cairo_lang_filesystem::ids::CrateInput;
 use cairo_lang_lowering::utils::InliningStrategy;
+use cairo_lang_wasm_utils::allocations::{self, AllocationStats};
+use cairo_lang_wasm_utils::diagnostic_counts::{self, FileDiagnosticCounts};
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{
+    build_info, clock, config, corelib, diagnostic_listener, limits, parse, plugins, source, state,
+    telemetry,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
 use wasm_bindgen::prelude::wasm_bindgen;
 
-include!(concat!(env!("OUT_DIR"), "/embedded_corelib.rs"));
+mod archive;
+mod casm;
+mod constants;
+mod inline;
+mod layout;
+mod minimize;
+mod modules;
+mod registry;
+mod signature;
+pub use archive::{ImportArchiveResponse, ImportedRequest, import_archive};
+pub use casm::{DisassembleRequest, DisassembleResponse, disassemble};
+pub use constants::{
+    ConstantEntry, EnumEntry, EnumVariantEntry, ExtractConstantsRequest, ExtractConstantsResponse,
+    extract_constants,
+};
+pub use inline::{InlineFunctionRequest, InlineFunctionResponse, inline_function};
+pub use layout::{MemberLayout, TypeLayoutRequest, TypeLayoutResponse, VariantLayout, type_layout};
+pub use minimize::{MinimizeRequest, MinimizeResponse, minimize};
+pub use modules::ModuleEntry;
+pub use registry::{LibfuncEntry, ProgramRegistrySummary, TypeEntry};
+pub use signature::{
+    FunctionSignatureRequest, FunctionSignatureResponse, ParamInfo, TypeInfo, function_signature,
+};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct CompileRequest {
     pub crate_name: String,
+    #[serde(deserialize_with = "source::deserialize_files")]
+    #[schemars(with = "BTreeMap<String, source::SourceContent>")]
     pub files: BTreeMap<String, String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
     pub corelib_files: Option<BTreeMap<String, String>>,
     #[serde(default)]
     pub replace_ids: bool,
     #[serde(default)]
     pub inlining_strategy: InliningStrategyArg,
+    /// The crates whose diagnostics are reported.
+    #[serde(default)]
+    pub diagnostics_scope: DiagnosticsScopeArg,
+    /// Whether to return the registry of the types and libfuncs of the program.
+    #[serde(default)]
+    pub registry: bool,
+    /// Whether to return the modules of the main crate with the files defining them.
+    #[serde(default)]
+    pub modules: bool,
+    /// Whether to return the CASM of the program as text, with labels for its functions and jump
+    /// targets.
+    #[serde(default)]
+    pub casm: bool,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum InliningStrategyArg {
     #[default]
@@ -30,40 +83,76 @@ pub enum InliningStrategyArg {
     Avoid,
 }
 
-#[derive(Debug, Serialize)]
+/// The crates whose diagnostics are reported.
+#[derive(Debug, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticsScopeArg {
+    /// The main crate and the corelib.
+    #[default]
+    All,
+    /// The main crate only. Errors in the corelib are then only reported as a failed compilation.
+    MainCrate,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
 pub struct CompileResponse {
     pub success: bool,
     pub sierra: Option<String>,
+    pub registry: Option<ProgramRegistrySummary>,
+    /// The modules of the main crate, parents first, when requested.
+    pub modules: Option<Vec<ModuleEntry>>,
+    /// The CASM of the program as text, when requested.
+    pub casm: Option<String>,
     pub diagnostics: String,
-    pub error: Option<String>,
+    /// The number of errors and warnings of each file of `diagnostics`.
+    pub diagnostics_by_file: BTreeMap<String, FileDiagnosticCounts>,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+    /// The heap allocations made while serving the request, when the `allocation-stats` feature
+    /// is enabled.
+    pub allocations: Option<AllocationStats>,
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
 pub fn compile(request_json: &str) -> String {
-    let request: CompileRequest = match serde_json::from_str(request_json) {
+    let request: CompileRequest = match parse::request(request_json) {
         Ok(request) => request,
         Err(error) => {
             return serde_json::to_string(&CompileResponse {
-                success: false,
-                sierra: None,
-                diagnostics: String::new(),
-                error: Some(format!("Failed parsing request JSON: {error}")),
+                error: Some(error),
+                ..Default::default()
             })
             .expect("serialize error response");
         }
     };
 
-    let mut diagnostics = String::new();
-    let compiler_config = CompilerConfig {
-        diagnostics_reporter: DiagnosticsReporter::write_to_string(&mut diagnostics),
-        replace_ids: request.replace_ids,
-        ..CompilerConfig::default()
-    };
+    let started_ms = clock::now_ms();
+    let source_bytes =
+        telemetry::source_bytes(request.files.iter().chain(request.corelib_files.iter().flatten()));
+    let ((result, debug_log), allocations) = allocations::measure(|| {
+        logging::capture(request.debug, || error::catch_internal(|| compile_request(request)))
+    });
+    let mut response =
+        result.unwrap_or_else(|error| CompileResponse { error: Some(error), ..Default::default() });
+    telemetry::report("compile", started_ms, source_bytes, response.error.as_ref());
+    response.debug_log = debug_log;
+    response.diagnostics_by_file = diagnostic_counts::by_file(&response.diagnostics);
+    response.allocations = allocations;
+
+    serde_json::to_string(&response).expect("serialize compile response")
+}
+
+fn compile_request(request: CompileRequest) -> CompileResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return CompileResponse { error: Some(error), ..Default::default() };
+    }
 
     let project = InMemoryProject {
         main_crate_name: request.crate_name,
         main_crate_files: request.files,
-        corelib_files: request.corelib_files.unwrap_or_else(embedded_corelib_files),
+        corelib_files: corelib::resolve(request.corelib_files),
         main_crate_settings: None,
     };
 
@@ -72,43 +161,146 @@ pub fn compile(request_json: &str) -> String {
         InliningStrategyArg::Avoid => InliningStrategy::Avoid,
     };
 
-    let response = match compile_in_memory_project(&project, compiler_config, inlining_strategy) {
-        Ok(program) => CompileResponse {
-            success: true,
-            sierra: Some(program.to_string()),
-            diagnostics,
-            error: None,
-        },
+    let mut diagnostics = String::new();
+    let compiled = prepare_in_memory_project_db(
+        &project,
+        inlining_strategy,
+        plugins::in_memory_project_suite(),
+    )
+    .and_then(|(db, main_crate_inputs)| {
+        let mut diagnostics_reporter = DiagnosticsReporter::callback(|diagnostic| {
+            let diagnostic = diagnostic.to_string();
+            diagnostic_listener::report(&diagnostic);
+            diagnostics.push_str(&diagnostic);
+        });
+        if let DiagnosticsScopeArg::MainCrate = request.diagnostics_scope {
+            diagnostics_reporter = diagnostics_reporter.with_crates(&main_crate_inputs);
+        }
+        let compiler_config = CompilerConfig {
+            diagnostics_reporter,
+            replace_ids: request.replace_ids,
+            ..CompilerConfig::default()
+        };
+        let main_crate_ids = CrateInput::into_crate_ids(&db, main_crate_inputs);
+        let modules = request.modules.then(|| modules::modules(&db, &main_crate_ids));
+        compile_prepared_db_program(&db, main_crate_ids, compiler_config)
+            .map(|program| (program, modules))
+    });
+
+    match compiled {
+        Ok((program, modules)) => {
+            let registry = match request.registry.then(|| registry::program_registry(&program)) {
+                Some(Ok(registry)) => Some(registry),
+                Some(Err(error)) => {
+                    return CompileResponse {
+                        diagnostics,
+                        error: Some(error),
+                        ..Default::default()
+                    };
+                }
+                None => None,
+            };
+            let casm = match request.casm.then(|| casm::casm_text(&program)) {
+                Some(Ok(casm)) => Some(casm),
+                Some(Err(error)) => {
+                    return CompileResponse {
+                        diagnostics,
+                        error: Some(error),
+                        ..Default::default()
+                    };
+                }
+                None => None,
+            };
+            CompileResponse {
+                success: true,
+                sierra: Some(program.to_string()),
+                registry,
+                modules,
+                casm,
+                diagnostics,
+                diagnostics_by_file: BTreeMap::new(),
+                error: None,
+                debug_log: None,
+                allocations: None,
+            }
+        }
         Err(error) => CompileResponse {
-            success: false,
-            sierra: None,
             diagnostics,
-            error: Some(error.to_string()),
+            error: Some(ApiError::compilation(error.to_string())),
+            ..Default::default()
         },
-    };
+    }
+}
 
-    serde_json::to_string(&response).expect("serialize compile response")
+/// Returns the provenance of the module, such as its git commit and features. See
+/// [cairo_lang_wasm_utils::build_info::BuildInfo].
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn build_info() -> String {
+    build_info::build_info_json(&[("wasm-exports", cfg!(feature = "wasm-exports"))])
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub fn embedded_corelib_manifest() -> String {
-    let files =
-        EMBEDDED_CORELIB_FILES.iter().map(|(path, _)| (*path).to_string()).collect::<Vec<_>>();
-    serde_json::to_string(&files).expect("serialize corelib manifest")
+/// Applies the module-wide configuration, such as input limits. See
+/// [cairo_lang_wasm_utils::config::HostConfig].
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn configure(config_json: &str) -> String {
+    config::configure(config_json)
+}
+
+/// Returns the module state, such as its configuration, for restoring it in other instances with
+/// `restore_state`. See [cairo_lang_wasm_utils::state::ModuleState].
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn snapshot_state() -> String {
+    state::snapshot_state(&[])
+}
+
+/// Restores a module state returned by `snapshot_state`.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn restore_state(state_json: &str) -> String {
+    state::restore_state(state_json, &[])
+}
+
+/// Makes the compilations call `callback` with each diagnostic as it is reported, serialized as a
+/// [cairo_lang_wasm_utils::diagnostic_listener::StreamedDiagnostic].
+#[cfg(target_arch = "wasm32")]
+#[cfg_attr(feature = "wasm-exports", wasm_bindgen)]
+pub fn set_diagnostics_callback(callback: js_sys::Function) {
+    diagnostic_listener::set_callback(callback);
+}
+
+/// Removes the diagnostics callback of the compilations.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn clear_diagnostics_callback() {
+    diagnostic_listener::set_listener(None);
+}
+
+/// Makes the compilations call `callback` with their outcomes, serialized as a
+/// [cairo_lang_wasm_utils::telemetry::TelemetryEvent].
+#[cfg(target_arch = "wasm32")]
+#[cfg_attr(feature = "wasm-exports", wasm_bindgen)]
+pub fn set_telemetry_callback(callback: js_sys::Function) {
+    telemetry::set_callback(callback);
 }
 
-fn embedded_corelib_files() -> BTreeMap<String, String> {
-    EMBEDDED_CORELIB_FILES
-        .iter()
-        .map(|(path, content)| ((*path).to_string(), (*content).to_string()))
-        .collect()
+/// Removes the telemetry callback of the compilations.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn clear_telemetry_callback() {
+    telemetry::set_listener(None);
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn embedded_corelib_manifest() -> String {
+    corelib::manifest_json()
 }
 
 #[cfg(test)]
 mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use cairo_lang_wasm_utils::{corelib, diagnostic_listener, telemetry};
     use serde_json::{Value, json};
 
-    use super::compile;
+    use super::{compile, configure, disassemble};
 
     #[test]
     fn compile_executable_program() {
@@ -127,4 +319,249 @@ mod tests {
         assert_eq!(response_json["error"], Value::Null);
         assert!(response_json["sierra"].is_string());
     }
+
+    #[test]
+    fn compile_resolves_module_path_attributes() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "#[path(\"arith/ops.cairo\")]\nmod math;\nfn main() -> felt252 { math::seven() }",
+                "arith/ops.cairo": "pub fn seven() -> felt252 { 7 }"
+            }
+        });
+
+        let response = compile(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+    }
+
+    #[test]
+    fn compile_returns_registry() {
+        let request = json!({
+            "crate_name": "test",
+            "files": { "lib.cairo": "fn main() -> u256 { 7 }" },
+            "replace_ids": true,
+            "registry": true
+        });
+
+        let response = compile(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let types = response_json["registry"]["types"].as_array().unwrap();
+        let u256 = types.iter().find(|ty| ty["id"] == "core::integer::u256").unwrap();
+        assert_eq!(u256["size"], 2);
+        assert_eq!(u256["long_id"], "Struct<ut@core::integer::u256, u128, u128>");
+        assert!(!response_json["registry"]["libfuncs"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn compile_ignores_test_code_from_scarb_projects() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> felt252 { 7 }
+
+#[test]
+#[available_gas(100000)]
+fn test_main() { assert_eq!(main(), 7); }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[should_panic]
+    fn test_panics() { panic!(\"boom\"); }
+}
+"
+            }
+        });
+
+        let response = compile(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["diagnostics"], "");
+    }
+
+    #[test]
+    fn compile_returns_labeled_casm() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main(x: felt252) -> felt252 { if x == 0 { helper() } else { x } }
+#[inline(never)]
+fn helper() -> felt252 { 7 }
+"
+            },
+            "replace_ids": true,
+            "casm": true
+        });
+
+        let response = compile(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let casm = response_json["casm"].as_str().unwrap();
+        assert!(casm.starts_with("test::main:\n"), "casm={casm}");
+        assert!(casm.contains("\ntest::helper:\n"), "casm={casm}");
+        assert!(casm.contains("call test::helper;"), "casm={casm}");
+        assert!(casm.contains("jmp .L0 if "), "casm={casm}");
+        assert!(!casm.contains(" rel "), "casm={casm}");
+
+        let request = json!({ "sierra": response_json["sierra"] });
+        let response: Value = serde_json::from_str(&disassemble(&request.to_string())).unwrap();
+        assert_eq!(response["success"], true, "response={response}");
+        assert_eq!(response["casm"], casm);
+    }
+
+    #[test]
+    fn compile_returns_modules() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "mod utils;\nmod inner { fn f() {} }\nfn main() {}",
+                "utils.cairo": "fn g() {}"
+            },
+            "modules": true
+        });
+
+        let response = compile(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(
+            response_json["modules"],
+            json!([
+                { "path": "test", "file": "lib.cairo", "inline": false },
+                { "path": "test::utils", "file": "utils.cairo", "inline": false },
+                { "path": "test::inner", "file": "lib.cairo", "inline": true }
+            ]),
+            "response={response}"
+        );
+    }
+
+    #[test]
+    fn compile_restricts_diagnostics_to_main_crate() {
+        let mut corelib_files = corelib::embedded_files();
+        corelib_files.get_mut("lib.cairo").unwrap().push_str("\nfn unused() { let x = 1; }\n");
+        let request = |diagnostics_scope| {
+            json!({
+                "crate_name": "test",
+                "files": { "lib.cairo": "fn main() -> felt252 { 7 }" },
+                "corelib_files": corelib_files,
+                "diagnostics_scope": diagnostics_scope
+            })
+            .to_string()
+        };
+
+        let response_json: Value = serde_json::from_str(&compile(&request("all"))).unwrap();
+        assert_eq!(response_json["success"], false, "response={response_json}");
+        assert!(response_json["diagnostics"].as_str().unwrap().contains("Unused variable."));
+
+        let response_json: Value = serde_json::from_str(&compile(&request("main-crate"))).unwrap();
+        assert_eq!(response_json["success"], true, "response={response_json}");
+        assert_eq!(response_json["diagnostics"], "");
+    }
+
+    #[test]
+    fn compile_counts_diagnostics_by_file() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "mod utils;\nfn main() -> felt252 { missing }",
+                "utils.cairo": "fn unused() { let x = 1; }"
+            }
+        });
+
+        let response = compile(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(
+            response_json["diagnostics_by_file"],
+            json!({
+                "lib.cairo": { "errors": 1, "warnings": 0 },
+                "utils.cairo": { "errors": 0, "warnings": 1 }
+            }),
+            "response={response}"
+        );
+    }
+
+    #[test]
+    fn compile_streams_diagnostics_to_listener() {
+        let streamed = Rc::new(RefCell::new(vec![]));
+        let listener_streamed = streamed.clone();
+        diagnostic_listener::set_listener(Some(Box::new(move |diagnostic| {
+            listener_streamed.borrow_mut().push(serde_json::to_value(diagnostic).unwrap());
+        })));
+        let request = json!({
+            "crate_name": "test",
+            "files": { "lib.cairo": "fn main() -> felt252 {\n    missing\n}" }
+        });
+
+        let response = compile(&request.to_string());
+        diagnostic_listener::set_listener(None);
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], false, "response={response}");
+        let streamed = streamed.borrow();
+        assert_eq!(streamed[0]["severity"], "error", "streamed={streamed:?}");
+        assert_eq!(streamed[0]["location"], json!({ "file": "lib.cairo", "line": 2, "column": 5 }));
+        let diagnostics = response_json["diagnostics"].as_str().unwrap();
+        assert!(diagnostics.contains(streamed[0]["text"].as_str().unwrap()));
+    }
+
+    #[test]
+    fn compile_reports_telemetry_to_listener() {
+        let events = Rc::new(RefCell::new(vec![]));
+        let listener_events = events.clone();
+        telemetry::set_listener(Some(Box::new(move |event| {
+            listener_events.borrow_mut().push(serde_json::to_value(event).unwrap());
+        })));
+        let request = |body: &str| {
+            json!({ "crate_name": "test", "files": { "lib.cairo": body } }).to_string()
+        };
+
+        compile(&request("fn main() -> felt252 { 7 }"));
+        compile(&request("fn main() -> felt252 { missing }"));
+        telemetry::set_listener(None);
+
+        let events = events.borrow();
+        assert_eq!(events[0]["endpoint"], "compile", "events={events:?}");
+        assert_eq!(events[0]["success"], true);
+        assert_eq!(events[0]["error_kind"], Value::Null);
+        assert_eq!(events[0]["source_size_bucket"], 1024);
+        assert_eq!(events[1]["success"], false);
+        assert_eq!(events[1]["error_kind"], "compilation");
+        // The events hold no part of the request.
+        assert!(!events[1].to_string().contains("missing"));
+    }
+
+    #[test]
+    fn malformed_request_is_an_invalid_request_error() {
+        let response = compile("{\"crate_name\": 7}");
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], false);
+        assert_eq!(response_json["error"]["kind"], "invalid-request", "response={response}");
+        assert_eq!(response_json["error"]["backtrace"], Value::Null);
+    }
+
+    #[test]
+    fn configured_limits_reject_large_requests() {
+        let config = json!({ "limits": { "max_file_bytes": 16 } });
+        let response_json: Value = serde_json::from_str(&configure(&config.to_string())).unwrap();
+        assert_eq!(response_json["success"], true);
+
+        let request = json!({
+            "crate_name": "test",
+            "files": { "lib.cairo": "fn main() -> felt252 { 7 }" }
+        });
+        let response = compile(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], false);
+        assert_eq!(response_json["error"]["kind"], "limit-exceeded", "response={response}");
+    }
 }
@@ -0,0 +1,366 @@
+//! Minimization of projects raising an internal compiler error, for reporting the crash with a
+//! small reproduction. Experimental: the reduction works on lines, not on the syntax tree.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use cairo_lang_compiler::diagnostics::DiagnosticsReporter;
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_compiler::{
+    CompilerConfig, compile_prepared_db_program, prepare_in_memory_project_db,
+};
+use cairo_lang_filesystem::ids::CrateInput;
+use cairo_lang_lowering::utils::InliningStrategy;
+use cairo_lang_wasm_utils::error::{self, ApiError, ErrorKind};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{cancellation, corelib, limits, parse, plugins, source};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::InliningStrategyArg;
+
+/// The number of candidate projects compiled when the request sets no `max_attempts`.
+const DEFAULT_MAX_ATTEMPTS: usize = 200;
+
+/// The file of the crate root, which is never removed.
+const CRATE_ROOT: &str = "lib.cairo";
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MinimizeRequest {
+    pub crate_name: String,
+    #[serde(deserialize_with = "source::deserialize_files")]
+    #[schemars(with = "BTreeMap<String, source::SourceContent>")]
+    pub files: BTreeMap<String, String>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    pub inlining_strategy: InliningStrategyArg,
+    /// The maximal number of candidate projects to compile, 200 by default.
+    pub max_attempts: Option<usize>,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct MinimizeResponse {
+    pub success: bool,
+    /// The minimized files, raising the same internal error as the files of the request.
+    pub files: BTreeMap<String, String>,
+    /// The message of the internal compiler error.
+    pub internal_error: Option<String>,
+    /// The number of lines removed from the files of the request.
+    pub removed_lines: usize,
+    /// The number of candidate projects compiled.
+    pub attempts: usize,
+    /// Whether no line of `files` can be removed anymore, rather than the minimization stopping
+    /// at `max_attempts` or on cancellation.
+    pub complete: bool,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Minimizes a project raising an internal compiler error: removes files, items, statements and
+/// lines from it while it still raises the same error, and returns the smallest project found.
+///
+/// Every candidate is compiled from scratch, so the minimization takes as many compilations as
+/// `attempts`. The host may stop it through the cancellation check, keeping what was removed.
+///
+/// Internal errors are detected by catching panics, so on `wasm32` this requires a module built
+/// with unwinding, as by `scripts/wasm_pack.sh`: see [error::catch_internal].
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn minimize(request_json: &str) -> String {
+    let request: MinimizeRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serde_json::to_string(&MinimizeResponse {
+                error: Some(error),
+                ..Default::default()
+            })
+            .expect("serialize error response");
+        }
+    };
+
+    let (result, debug_log) =
+        logging::capture(request.debug, || error::catch_internal(|| minimize_request(request)));
+    let mut response = result
+        .unwrap_or_else(|error| MinimizeResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+
+    serde_json::to_string(&response).expect("serialize minimize response")
+}
+
+fn minimize_request(request: MinimizeRequest) -> MinimizeResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return MinimizeResponse { error: Some(error), ..Default::default() };
+    }
+    let inlining_strategy = match request.inlining_strategy {
+        InliningStrategyArg::Default => InliningStrategy::Default,
+        InliningStrategyArg::Avoid => InliningStrategy::Avoid,
+    };
+    let corelib_files = corelib::resolve(request.corelib_files);
+    let raised_error = |files: &BTreeMap<String, String>| {
+        let project = InMemoryProject {
+            main_crate_name: request.crate_name.clone(),
+            main_crate_files: files.clone(),
+            corelib_files: corelib_files.clone(),
+            main_crate_settings: None,
+        };
+        internal_error(&project, inlining_strategy)
+    };
+
+    let Some(original_error) = raised_error(&request.files) else {
+        return MinimizeResponse {
+            error: Some(ApiError::invalid_request(
+                "The project compiles without an internal error to minimize.",
+            )),
+            ..Default::default()
+        };
+    };
+    let original_lines = line_count(&request.files);
+    let reduction =
+        reduce(request.files, request.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS), |files| {
+            raised_error(files).as_ref() == Some(&original_error)
+        });
+    MinimizeResponse {
+        success: true,
+        removed_lines: original_lines - line_count(&reduction.files),
+        files: reduction.files,
+        internal_error: Some(original_error),
+        attempts: reduction.attempts,
+        complete: reduction.complete,
+        ..Default::default()
+    }
+}
+
+/// Compiles `project`, returning the message of the internal error it raises, if any.
+fn internal_error(
+    project: &InMemoryProject,
+    inlining_strategy: InliningStrategy,
+) -> Option<String> {
+    let result = error::catch_internal(|| {
+        let (db, main_crate_inputs) = prepare_in_memory_project_db(
+            project,
+            inlining_strategy,
+            plugins::in_memory_project_suite(),
+        )?;
+        let main_crate_ids = CrateInput::into_crate_ids(&db, main_crate_inputs);
+        let compiler_config = CompilerConfig {
+            diagnostics_reporter: DiagnosticsReporter::ignoring(),
+            ..CompilerConfig::default()
+        };
+        compile_prepared_db_program(&db, main_crate_ids, compiler_config).map(|_| ())
+    });
+    match result {
+        Err(error) if error.kind == ErrorKind::Internal => Some(error.message),
+        _ => None,
+    }
+}
+
+/// The result of [reduce].
+struct Reduction {
+    files: BTreeMap<String, String>,
+    attempts: usize,
+    complete: bool,
+}
+
+/// Removes the files and line ranges of `files` that `reproduces` does not need, largest first,
+/// until none can be removed or `max_attempts` candidates were tried.
+fn reduce(
+    mut files: BTreeMap<String, String>,
+    max_attempts: usize,
+    mut reproduces: impl FnMut(&BTreeMap<String, String>) -> bool,
+) -> Reduction {
+    let mut attempts = 0;
+    // Returns whether `candidate` reproduces, or `None` if no attempt is left.
+    let mut attempt = |attempts: &mut usize, candidate: &BTreeMap<String, String>| {
+        if *attempts == max_attempts || cancellation::is_cancelled() {
+            return None;
+        }
+        *attempts += 1;
+        Some(reproduces(candidate))
+    };
+
+    let paths = files.keys().filter(|path| *path != CRATE_ROOT).cloned().collect::<Vec<_>>();
+    for path in paths {
+        let mut candidate = files.clone();
+        candidate.remove(&path);
+        match attempt(&mut attempts, &candidate) {
+            Some(true) => files = candidate,
+            Some(false) => {}
+            None => return Reduction { files, attempts, complete: false },
+        }
+    }
+
+    let paths = files.keys().cloned().collect::<Vec<_>>();
+    loop {
+        let mut removed = false;
+        for path in &paths {
+            // The ranges are computed again after each removal, as the lines moved.
+            'ranges: loop {
+                let lines = files[path].split_inclusive('\n').collect::<Vec<_>>();
+                for range in removable_ranges(&lines) {
+                    let mut candidate = files.clone();
+                    let text = [&lines[..range.start], &lines[range.end..]].concat().concat();
+                    candidate.insert(path.clone(), text);
+                    match attempt(&mut attempts, &candidate) {
+                        Some(true) => {
+                            files = candidate;
+                            removed = true;
+                            continue 'ranges;
+                        }
+                        Some(false) => {}
+                        None => return Reduction { files, attempts, complete: false },
+                    }
+                }
+                break;
+            }
+        }
+        if !removed {
+            return Reduction { files, attempts, complete: true };
+        }
+    }
+}
+
+/// Returns the ranges of `lines` whose removal keeps the braces of the file balanced, largest
+/// first: items, statements and blocks, with the blank lines following them. A range starts at
+/// each non-blank line and ends at the first line closing the braces it opens.
+fn removable_ranges(lines: &[&str]) -> Vec<Range<usize>> {
+    let depth_changes = lines.iter().map(|line| depth_change(line)).collect::<Vec<_>>();
+    let mut ranges = vec![];
+    for (start, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut depth = 0;
+        for (end, (lowest, delta)) in depth_changes.iter().enumerate().skip(start) {
+            if depth + lowest < 0 {
+                break;
+            }
+            depth += delta;
+            if depth == 0 {
+                let mut end = end + 1;
+                while end < lines.len() && lines[end].trim().is_empty() {
+                    end += 1;
+                }
+                ranges.push(start..end);
+                break;
+            }
+        }
+    }
+    ranges.sort_by_key(|range| (std::cmp::Reverse(range.len()), range.start));
+    ranges
+}
+
+/// Returns the lowest brace depth reached within `line` and the depth at its end, relative to its
+/// start. Braces in strings and comments are skipped.
+fn depth_change(line: &str) -> (i32, i32) {
+    let (mut lowest, mut depth) = (0, 0);
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '/') if chars.as_str().starts_with('/') => break,
+            (None, '{' | '(' | '[') => depth += 1,
+            (None, '}' | ')' | ']') => {
+                depth -= 1;
+                lowest = lowest.min(depth);
+            }
+            (None, _) => {}
+        }
+    }
+    (lowest, depth)
+}
+
+fn line_count(files: &BTreeMap<String, String>) -> usize {
+    files.values().map(|text| text.lines().count()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::{Value, json};
+
+    use super::{minimize, reduce};
+
+    #[test]
+    fn reduce_keeps_lines_needed_to_reproduce() {
+        let files = BTreeMap::from([
+            (
+                "lib.cairo".to_string(),
+                "\
+mod utils;
+use core::num::traits::Zero;
+
+fn helper() -> felt252 {
+    1
+}
+
+fn crash() {
+    let x = 'boom';
+    let y = helper();
+}
+"
+                .to_string(),
+            ),
+            ("utils.cairo".to_string(), "fn unused() {}\n".to_string()),
+        ]);
+
+        let reduction = reduce(files, 100, |files| files["lib.cairo"].contains("'boom'"));
+
+        assert!(reduction.complete);
+        assert_eq!(
+            reduction.files,
+            BTreeMap::from([(
+                "lib.cairo".to_string(),
+                "fn crash() {\n    let x = 'boom';\n}\n".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn minimize_reduces_project_raising_internal_error() {
+        // A corelib without the core types makes the compiler panic.
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "mod utils;\nfn main() -> felt252 {\n    7\n}\n",
+                "utils.cairo": "fn unused() {}\n"
+            },
+            "corelib_files": { "lib.cairo": "" }
+        });
+
+        let response = minimize(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert!(response_json["internal_error"].is_string(), "response={response}");
+        assert!(response_json["attempts"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn minimize_rejects_projects_without_internal_error() {
+        let request = json!({
+            "crate_name": "test",
+            "files": { "lib.cairo": "fn main() -> felt252 { 7 }" }
+        });
+
+        let response = minimize(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], false, "response={response}");
+        assert_eq!(response_json["error"]["kind"], "invalid-request");
+    }
+}
@@ -0,0 +1,72 @@
+//! The module tree of the main crate with the files defining each module, for tools navigating from
+//! the fully-qualified paths of the items in Sierra back to their sources.
+
+use std::collections::HashMap;
+
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::ModuleId;
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::{CrateId, Directory, FileId};
+use salsa::Database;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// A module of the main crate.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ModuleEntry {
+    /// The fully-qualified path of the module, e.g. `app::utils::math`.
+    pub path: String,
+    /// The path of the file defining the module, as given in the `files` of the request.
+    pub file: String,
+    /// Whether the module is defined inline, in the file of its parent.
+    pub inline: bool,
+}
+
+/// Returns the modules of `crate_ids`, parents first.
+pub(crate) fn modules<'db>(db: &'db dyn Database, crate_ids: &[CrateId<'db>]) -> Vec<ModuleEntry> {
+    let mut entries = vec![];
+    for crate_id in crate_ids {
+        let mut paths = vec![];
+        if let Some(config) = db.crate_config(*crate_id) {
+            collect_file_paths(&config.root, "", &mut paths);
+        }
+        let paths = paths.into_iter().collect::<HashMap<_, _>>();
+        for module_id in db.crate_modules(*crate_id) {
+            // Macro-generated modules have no file of their own.
+            if let ModuleId::MacroCall { .. } = module_id {
+                continue;
+            }
+            let Ok(file_id) = db.module_main_file(*module_id) else {
+                continue;
+            };
+            entries.push(ModuleEntry {
+                path: module_id.full_path(db),
+                file: paths.get(&file_id).cloned().unwrap_or_else(|| file_id.full_path(db)),
+                inline: matches!(
+                    module_id,
+                    ModuleId::Submodule(submodule_id) if db.is_submodule_inline(*submodule_id)
+                ),
+            });
+        }
+    }
+    entries
+}
+
+/// Collects the files of the virtual directory `dir` with their paths, prefixed by `prefix`.
+///
+/// A file may be listed under several paths, as files with the same name and content share an id.
+pub(crate) fn collect_file_paths<'db>(
+    dir: &Directory<'db>,
+    prefix: &str,
+    paths: &mut Vec<(FileId<'db>, String)>,
+) {
+    let Directory::Virtual { files, dirs } = dir else {
+        return;
+    };
+    for (name, file_id) in files {
+        paths.push((*file_id, format!("{prefix}{name}")));
+    }
+    for (name, subdir) in dirs {
+        collect_file_paths(subdir, &format!("{prefix}{name}/"), paths);
+    }
+}
@@ -0,0 +1,95 @@
+//! The registry of the concrete types and libfuncs of a compiled program, for tools computing
+//! argument serializations or storage layouts from the compiler's own answers.
+
+use cairo_lang_sierra::extensions::{ConcreteLibfunc, ConcreteType};
+use cairo_lang_sierra::program::Program;
+use cairo_lang_sierra_type_size::ProgramRegistryInfo;
+use cairo_lang_wasm_utils::error::ApiError;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ProgramRegistrySummary {
+    pub types: Vec<TypeEntry>,
+    pub libfuncs: Vec<LibfuncEntry>,
+}
+
+/// A concrete type declared by the program.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TypeEntry {
+    /// The id of the type in the Sierra program.
+    pub id: String,
+    /// The generic type with its arguments, e.g. `Array<felt252>`.
+    pub long_id: String,
+    /// The number of felts a value of the type takes in memory.
+    pub size: i16,
+    pub storable: bool,
+    pub droppable: bool,
+    pub duplicatable: bool,
+    pub zero_sized: bool,
+}
+
+/// A concrete libfunc declared by the program.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct LibfuncEntry {
+    /// The id of the libfunc in the Sierra program.
+    pub id: String,
+    /// The generic libfunc with its arguments, e.g. `array_append<felt252>`.
+    pub long_id: String,
+    /// The ids of the types of the parameters.
+    pub params: Vec<String>,
+    /// The ids of the types of the outputs of each branch.
+    pub branches: Vec<Vec<String>>,
+}
+
+/// Returns the concrete types and libfuncs of `program`, in the order of their declarations.
+pub(crate) fn program_registry(program: &Program) -> Result<ProgramRegistrySummary, ApiError> {
+    let info = ProgramRegistryInfo::new(program).map_err(|error| {
+        ApiError::compilation(format!("Failed building the program registry: {error}"))
+    })?;
+    let types = program
+        .type_declarations
+        .iter()
+        .map(|declaration| {
+            let type_info = info
+                .registry
+                .get_type(&declaration.id)
+                .expect("declared types are in the registry")
+                .info();
+            TypeEntry {
+                id: declaration.id.to_string(),
+                long_id: declaration.long_id.to_string(),
+                size: info.type_sizes[&declaration.id],
+                storable: type_info.storable,
+                droppable: type_info.droppable,
+                duplicatable: type_info.duplicatable,
+                zero_sized: type_info.zero_sized,
+            }
+        })
+        .collect();
+    let libfuncs = program
+        .libfunc_declarations
+        .iter()
+        .map(|declaration| {
+            let libfunc = info
+                .registry
+                .get_libfunc(&declaration.id)
+                .expect("declared libfuncs are in the registry");
+            LibfuncEntry {
+                id: declaration.id.to_string(),
+                long_id: declaration.long_id.to_string(),
+                params: libfunc
+                    .param_signatures()
+                    .iter()
+                    .map(|param| param.ty.to_string())
+                    .collect(),
+                branches: libfunc
+                    .branch_signatures()
+                    .iter()
+                    .map(|branch| branch.vars.iter().map(|var| var.ty.to_string()).collect())
+                    .collect(),
+            }
+        })
+        .collect();
+    Ok(ProgramRegistrySummary { types, libfuncs })
+}
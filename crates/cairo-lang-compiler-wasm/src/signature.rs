@@ -0,0 +1,404 @@
+//! Extraction of the signature of a function, for UIs generating input forms of entry points.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::db::RootDatabase;
+use cairo_lang_compiler::diagnostics::DiagnosticsReporter;
+use cairo_lang_compiler::prepare_in_memory_project_db;
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::{NamedLanguageElementId, TopLevelLanguageElementId};
+use cairo_lang_filesystem::ids::CrateInput;
+use cairo_lang_lowering::utils::InliningStrategy;
+use cairo_lang_semantic::items::enm::SemanticEnumEx;
+use cairo_lang_semantic::items::free_function::FreeFunctionSemantic;
+use cairo_lang_semantic::items::structure::StructSemantic;
+use cairo_lang_semantic::{ConcreteTypeId, GenericArgumentId, TypeId, TypeLongId};
+use cairo_lang_wasm_utils::compiled_programs::{self, ProjectSources};
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{corelib, item_path, limits, parse, plugins, source};
+use itertools::Itertools;
+use num_traits::ToPrimitive;
+use salsa::Database;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Extern types serialized to a variable number of felts.
+const VARIABLE_LENGTH_EXTERN_TYPES: [&str; 4] =
+    ["Array", "Felt252Dict", "SquashedFelt252Dict", "Nullable"];
+
+/// Extern types serialized as the type they wrap.
+const WRAPPING_EXTERN_TYPES: [&str; 2] = ["Box", "NonZero"];
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FunctionSignatureRequest {
+    /// The name of the main crate, required with `files`.
+    pub crate_name: Option<String>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub files: Option<BTreeMap<String, String>>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    /// The handle of a program compiled by `compile_program`, whose sources are queried instead
+    /// of `files`.
+    pub program: Option<u32>,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TypeInfo {
+    /// The fully qualified name of the type.
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// The number of felts the type serializes to, or `None` when it depends on the value (e.g.
+    /// arrays).
+    pub felts: Option<usize>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ParamInfo {
+    pub name: String,
+    #[serde(flatten)]
+    pub ty: TypeInfo,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct FunctionSignatureResponse {
+    pub success: bool,
+    /// The full path of the function.
+    pub function: Option<String>,
+    pub params: Vec<ParamInfo>,
+    pub return_type: Option<TypeInfo>,
+    /// Whether the function may panic.
+    pub panicable: bool,
+    pub diagnostics: String,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Returns the parameters and the return type of a function of the main crate.
+///
+/// `function` is matched against the trailing `::`-separated segments of the full paths of the
+/// crate's free functions (e.g. `main` or `::main`). The felt counts follow the `Serde`
+/// serialization of the types.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn function_signature(request_json: &str, function: &str) -> String {
+    let request: FunctionSignatureRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serde_json::to_string(&FunctionSignatureResponse {
+                error: Some(error),
+                ..Default::default()
+            })
+            .expect("serialize error response");
+        }
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| function_signature_request(request, function))
+    });
+    let mut response = result.unwrap_or_else(|error| FunctionSignatureResponse {
+        error: Some(error),
+        ..Default::default()
+    });
+    response.debug_log = debug_log;
+
+    serde_json::to_string(&response).expect("serialize function signature response")
+}
+
+fn function_signature_request(
+    request: FunctionSignatureRequest,
+    function: &str,
+) -> FunctionSignatureResponse {
+    let from_program = request.program.is_some();
+    let project = match request_project(
+        request.crate_name,
+        request.files,
+        request.corelib_files,
+        request.program,
+    ) {
+        Ok(project) => project,
+        Err(error) => {
+            return FunctionSignatureResponse { error: Some(error), ..Default::default() };
+        }
+    };
+
+    let (db, main_crate_inputs, diagnostics) = match prepare_checked_db(&project) {
+        Ok(prepared) => prepared,
+        Err((error, diagnostics)) => {
+            return FunctionSignatureResponse {
+                diagnostics,
+                error: Some(error),
+                ..Default::default()
+            };
+        }
+    };
+
+    // The diagnostics of a compiled program were returned by `compile_program`, with its hidden
+    // files redacted.
+    let diagnostics = if from_program { String::new() } else { diagnostics };
+
+    let db: &dyn Database = &db;
+    let candidates = CrateInput::into_crate_ids(db, main_crate_inputs)
+        .into_iter()
+        .flat_map(|crate_id| db.crate_modules(crate_id))
+        .flat_map(|module_id| db.module_free_functions_ids(*module_id).into_iter().flatten())
+        .map(|free_function_id| (free_function_id.full_path(db), *free_function_id))
+        .filter(|(path, _)| item_path::matches_suffix(path, function))
+        .collect_vec();
+    let (path, free_function_id) = match &candidates[..] {
+        [candidate] => candidate.clone(),
+        [] => {
+            return FunctionSignatureResponse {
+                diagnostics,
+                error: Some(ApiError::invalid_request(format!(
+                    "Failed finding function `{function}`."
+                ))),
+                ..Default::default()
+            };
+        }
+        _ => {
+            return FunctionSignatureResponse {
+                diagnostics,
+                error: Some(ApiError::invalid_request(format!(
+                    "Function `{function}` is ambiguous, matching {}.",
+                    candidates.iter().map(|(path, _)| format!("`{path}`")).join(", ")
+                ))),
+                ..Default::default()
+            };
+        }
+    };
+
+    let Ok(signature) = db.free_function_signature(free_function_id) else {
+        return FunctionSignatureResponse {
+            diagnostics,
+            error: Some(ApiError::compilation(format!(
+                "Failed resolving the signature of `{path}`."
+            ))),
+            ..Default::default()
+        };
+    };
+
+    FunctionSignatureResponse {
+        success: true,
+        function: Some(path),
+        params: signature
+            .params
+            .iter()
+            .map(|param| ParamInfo {
+                name: param.name.long(db).to_string(),
+                ty: type_info(db, param.ty),
+            })
+            .collect(),
+        return_type: Some(type_info(db, signature.return_type)),
+        panicable: signature.panicable,
+        diagnostics,
+        error: None,
+        debug_log: None,
+    }
+}
+
+/// Prepares the database of `project` and checks the diagnostics of its main crate, returning them
+/// along with the database.
+/// Returns the project of a request, given by its `files` or by the `program` handle of a program
+/// compiled by `compile_program`, which keeps its sources.
+pub(crate) fn request_project(
+    crate_name: Option<String>,
+    files: Option<BTreeMap<String, String>>,
+    corelib_files: Option<BTreeMap<String, String>>,
+    program: Option<u32>,
+) -> Result<InMemoryProject, ApiError> {
+    let (crate_name, files, corelib_files) = match (files, program) {
+        (Some(files), None) => {
+            let crate_name = crate_name.ok_or_else(|| {
+                ApiError::invalid_request("`crate_name` is required with `files`.")
+            })?;
+            (crate_name, files, corelib_files)
+        }
+        (None, Some(program)) => {
+            if crate_name.is_some() || corelib_files.is_some() {
+                return Err(ApiError::invalid_request(
+                    "`crate_name` and `corelib_files` are those of `program`.",
+                ));
+            }
+            let ProjectSources { crate_name, files, corelib_files } =
+                compiled_programs::get(program)?.project;
+            (crate_name, files, corelib_files)
+        }
+        _ => {
+            return Err(ApiError::invalid_request(
+                "Exactly one of `files` and `program` is required.",
+            ));
+        }
+    };
+    limits::current().check_files(files.iter().chain(corelib_files.iter().flatten()))?;
+    Ok(InMemoryProject {
+        main_crate_name: crate_name,
+        main_crate_files: files,
+        corelib_files: corelib::resolve(corelib_files),
+        main_crate_settings: None,
+    })
+}
+
+pub(crate) fn prepare_checked_db(
+    project: &InMemoryProject,
+) -> Result<(RootDatabase, Vec<CrateInput>, String), (ApiError, String)> {
+    let (db, main_crate_inputs) = prepare_in_memory_project_db(
+        project,
+        InliningStrategy::Default,
+        plugins::in_memory_project_suite(),
+    )
+    .map_err(|error| (ApiError::invalid_request(error.to_string()), String::new()))?;
+
+    let mut diagnostics = String::new();
+    if DiagnosticsReporter::write_to_string(&mut diagnostics)
+        .with_crates(&main_crate_inputs)
+        .allow_warnings()
+        .check(&db)
+    {
+        return Err((ApiError::compilation("Compilation failed."), diagnostics));
+    }
+    Ok((db, main_crate_inputs, diagnostics))
+}
+
+pub(crate) fn type_info<'db>(db: &'db dyn Database, ty: TypeId<'db>) -> TypeInfo {
+    TypeInfo { ty: ty.format(db), felts: serialized_felts(db, ty) }
+}
+
+/// Returns the number of felts `ty` serializes to, or `None` if it is not fixed.
+pub(crate) fn serialized_felts<'db>(db: &'db dyn Database, ty: TypeId<'db>) -> Option<usize> {
+    match ty.long(db) {
+        TypeLongId::Concrete(ConcreteTypeId::Struct(concrete_struct_id)) => db
+            .concrete_struct_members(*concrete_struct_id)
+            .ok()?
+            .values()
+            .map(|member| serialized_felts(db, member.ty))
+            .sum(),
+        TypeLongId::Concrete(ConcreteTypeId::Enum(concrete_enum_id)) => {
+            // The variant index, followed by the variant's value.
+            let variant_felts = db
+                .concrete_enum_variants(*concrete_enum_id)
+                .ok()?
+                .into_iter()
+                .map(|variant| serialized_felts(db, variant.ty))
+                .collect::<Option<Vec<_>>>()?;
+            match variant_felts.into_iter().all_equal_value() {
+                Ok(felts) => Some(1 + felts),
+                Err(None) => Some(1),
+                Err(Some(_)) => None,
+            }
+        }
+        TypeLongId::Concrete(ConcreteTypeId::Extern(concrete_extern_type_id)) => {
+            let name = concrete_extern_type_id.extern_type_id(db).name(db).long(db).as_str();
+            if VARIABLE_LENGTH_EXTERN_TYPES.contains(&name) {
+                return None;
+            }
+            if WRAPPING_EXTERN_TYPES.contains(&name) {
+                let [GenericArgumentId::Type(inner)] =
+                    concrete_extern_type_id.long(db).generic_args[..]
+                else {
+                    return None;
+                };
+                return serialized_felts(db, inner);
+            }
+            Some(1)
+        }
+        TypeLongId::Tuple(tys) => tys.iter().map(|ty| serialized_felts(db, *ty)).sum(),
+        TypeLongId::Snapshot(inner) => serialized_felts(db, *inner),
+        TypeLongId::FixedSizeArray { type_id, size } => {
+            Some(serialized_felts(db, *type_id)? * size.long(db).to_int()?.to_usize()?)
+        }
+        TypeLongId::GenericParameter(_)
+        | TypeLongId::Var(_)
+        | TypeLongId::Coupon(_)
+        | TypeLongId::ImplType(_)
+        | TypeLongId::Closure(_)
+        | TypeLongId::Missing(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use cairo_lang_sierra::program::Program;
+    use cairo_lang_wasm_utils::compiled_programs::{self, CompiledProgram, ProjectSources};
+    use serde_json::{Value, json};
+
+    use super::function_signature;
+
+    #[test]
+    fn function_signature_reports_params_and_felt_counts() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+#[derive(Drop, Serde)]
+struct Point { x: felt252, y: u256 }
+
+fn transfer(to: Point, amounts: Array<u64>, flag: bool) -> u256 {
+    let _ = (to, amounts, flag);
+    7
+}
+
+fn safe_transfer() {}
+"
+            }
+        });
+
+        let response = function_signature(&request.to_string(), "transfer");
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["function"], "test::transfer");
+        let params = response_json["params"].as_array().unwrap();
+        let names_and_felts = params
+            .iter()
+            .map(|param| (param["name"].as_str().unwrap(), param["felts"].as_u64()))
+            .collect::<Vec<_>>();
+        assert_eq!(names_and_felts, [("to", Some(3)), ("amounts", None), ("flag", Some(1))]);
+        assert_eq!(response_json["return_type"]["type"], "core::integer::u256");
+        assert_eq!(response_json["return_type"]["felts"], 2);
+    }
+
+    #[test]
+    fn function_signature_reads_sources_of_compiled_program() {
+        // The Sierra of the program is not needed to query its sources.
+        let program = compiled_programs::insert(CompiledProgram {
+            project: ProjectSources {
+                crate_name: "test".into(),
+                files: BTreeMap::from([(
+                    "lib.cairo".to_string(),
+                    "fn double(x: u32) -> u32 { x * 2 }".to_string(),
+                )]),
+                corelib_files: None,
+            },
+            program: Program {
+                type_declarations: vec![],
+                libfunc_declarations: vec![],
+                statements: vec![],
+                funcs: vec![],
+            },
+            debug_info: Default::default(),
+        });
+
+        let request = json!({ "program": program });
+        let response = function_signature(&request.to_string(), "double");
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["function"], "test::double");
+        assert_eq!(response_json["return_type"]["type"], "core::integer::u32");
+
+        let request = json!({ "program": program, "crate_name": "other" });
+        let response = function_signature(&request.to_string(), "double");
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+        assert_eq!(response_json["error"]["kind"], "invalid-request", "response={response}");
+    }
+}
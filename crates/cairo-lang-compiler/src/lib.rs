@@ -16,6 +16,7 @@ use cairo_lang_lowering::optimizations::config::Optimizations;
 use cairo_lang_lowering::utils::InliningStrategy;
 use cairo_lang_parser::db::ParserGroup;
 use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_semantic::plugin::PluginSuite;
 use cairo_lang_sierra::debug_info::{Annotations, DebugInfo};
 use cairo_lang_sierra::program::{Program, ProgramArtifact};
 use cairo_lang_sierra_generator::db::SierraGenGroup;
@@ -120,24 +121,43 @@ pub fn compile(
 /// Compiles a Cairo project from in-memory files.
 ///
 /// This is intended for environments where host filesystem access is unavailable (e.g. browsers).
-/// `project.main_crate_files` and `project.corelib_files` must both include `lib.cairo`.
+/// `project.main_crate_files` and `project.corelib_files` must both include `lib.cairo`, either at
+/// their root or under a Scarb-style `src/` directory.
 pub fn compile_in_memory_project(
     project: &InMemoryProject,
     compiler_config: CompilerConfig<'_>,
     inlining_strategy: InliningStrategy,
 ) -> Result<Program> {
+    let (db, main_crate_ids) =
+        prepare_in_memory_project_db(project, inlining_strategy, PluginSuite::default())?;
+    compile_prepared_db_program(
+        &db,
+        CrateInput::into_crate_ids(&db, main_crate_ids),
+        compiler_config,
+    )
+}
+
+/// Builds a compilation database for an in-memory project.
+///
+/// Returns the database along with the inputs of the project's main crates. This is the setup
+/// step of [`compile_in_memory_project`], for callers that need to query the database directly
+/// (e.g. only to check diagnostics).
+///
+/// `plugin_suite` is added to the executable plugin suite the database is built with.
+pub fn prepare_in_memory_project_db(
+    project: &InMemoryProject,
+    inlining_strategy: InliningStrategy,
+    plugin_suite: PluginSuite,
+) -> Result<(RootDatabase, Vec<CrateInput>)> {
     let mut db = RootDatabase::builder()
         .with_optimizations(Optimizations::enabled_with_default_movable_functions(
             inlining_strategy,
         ))
         .with_default_plugin_suite(executable_plugin_suite())
+        .with_default_plugin_suite(plugin_suite)
         .build()?;
     let main_crate_ids = setup_in_memory_project(&mut db, project)?;
-    compile_prepared_db_program(
-        &db,
-        CrateInput::into_crate_ids(&db, main_crate_ids),
-        compiler_config,
-    )
+    Ok((db, main_crate_ids))
 }
 
 /// Runs Cairo compiler.
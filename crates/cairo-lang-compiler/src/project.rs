@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::ffi::OsStr;
 use std::path::Path;
@@ -45,6 +46,8 @@ pub enum InMemoryProjectError {
     MissingRequiredFile { crate_name: &'static str, path: String },
     #[error("Invalid virtual path `{path}` in `{crate_name}` crate.")]
     InvalidVirtualPath { crate_name: &'static str, path: String },
+    #[error("Ambiguous source root in `{crate_name}` crate: {}.", .roots.join(", "))]
+    AmbiguousSourceRoot { crate_name: &'static str, roots: Vec<String> },
 }
 
 /// Sets up the DB to compile the file at the given path.
@@ -144,8 +147,9 @@ pub fn setup_in_memory_project(
         return Err(InMemoryProjectError::EmptyMainCrateName);
     }
 
-    let core_root = build_virtual_directory(db, "core", &project.corelib_files)?;
-    if !project.corelib_files.contains_key("lib.cairo") {
+    let corelib_files = crate_source_files("core", &project.corelib_files)?;
+    let core_root = build_virtual_directory(db, "core", &corelib_files)?;
+    if !corelib_files.contains_key("lib.cairo") {
         return Err(InMemoryProjectError::MissingRequiredFile {
             crate_name: "core",
             path: "lib.cairo".into(),
@@ -161,8 +165,9 @@ pub fn setup_in_memory_project(
         })
     );
 
-    let main_root = build_virtual_directory(db, "main", &project.main_crate_files)?;
-    if !project.main_crate_files.contains_key("lib.cairo") {
+    let main_crate_files = crate_source_files("main", &project.main_crate_files)?;
+    let main_root = build_virtual_directory(db, "main", &main_crate_files)?;
+    if !main_crate_files.contains_key("lib.cairo") {
         return Err(InMemoryProjectError::MissingRequiredFile {
             crate_name: "main",
             path: "lib.cairo".into(),
@@ -255,6 +260,64 @@ impl<'db> VirtualDirectoryBuilder<'db> {
     }
 }
 
+/// Returns the files of an in-memory crate relative to its source root.
+fn crate_source_files<'a>(
+    crate_name: &'static str,
+    files: &'a BTreeMap<String, String>,
+) -> Result<Cow<'a, BTreeMap<String, String>>, InMemoryProjectError> {
+    let root = crate_source_root(crate_name, files)?;
+    if root.is_empty() {
+        return Ok(Cow::Borrowed(files));
+    }
+    Ok(Cow::Owned(
+        files
+            .iter()
+            .filter_map(|(path, content)| {
+                Some((path.strip_prefix(root)?.to_string(), content.clone()))
+            })
+            .collect(),
+    ))
+}
+
+/// Returns the directory of the files of an in-memory crate holding its sources, ending with `/`,
+/// or an empty string if the sources are rooted at the crate root.
+///
+/// Besides files rooted at the crate root (`lib.cairo`), Scarb's layout (`src/lib.cairo`) and
+/// packages nested in a directory (`my_package/src/lib.cairo`) are accepted. The source root is
+/// then the shallowest `src` directory holding `lib.cairo`, and files outside of it are dropped.
+pub fn crate_source_root<'a>(
+    crate_name: &'static str,
+    files: &'a BTreeMap<String, String>,
+) -> Result<&'a str, InMemoryProjectError> {
+    if files.contains_key("lib.cairo") {
+        return Ok("");
+    }
+    let source_roots = files
+        .keys()
+        .filter_map(|path| path.strip_suffix("lib.cairo"))
+        .filter(|dir| *dir == "src/" || dir.ends_with("/src/"))
+        .collect::<Vec<_>>();
+    let Some(min_depth) = source_roots.iter().map(|dir| dir.matches('/').count()).min() else {
+        return Ok("");
+    };
+    let roots = source_roots
+        .into_iter()
+        .filter(|dir| dir.matches('/').count() == min_depth)
+        .collect::<Vec<_>>();
+    let [root] = roots[..] else {
+        return Err(InMemoryProjectError::AmbiguousSourceRoot {
+            crate_name,
+            roots: roots.into_iter().map(String::from).collect(),
+        });
+    };
+    Ok(root)
+}
+
+/// Builds the virtual directory of an in-memory crate from its files, given relative to its
+/// source root.
+///
+/// The files are named after these paths, so that diagnostics tell apart files sharing a name in
+/// different directories.
 fn build_virtual_directory<'db>(
     db: &'db dyn Database,
     crate_name: &'static str,
@@ -265,10 +328,9 @@ fn build_virtual_directory<'db>(
         let path_parts = split_virtual_path(path).ok_or_else(|| {
             InMemoryProjectError::InvalidVirtualPath { crate_name, path: path.clone() }
         })?;
-        let file_name = path_parts.last().copied().unwrap();
         let file_id = FileLongId::Virtual(VirtualFile {
             parent: None,
-            name: SmolStrId::from(db, file_name),
+            name: SmolStrId::from(db, path.as_str()),
             content: SmolStrId::from(db, content.as_str()),
             code_mappings: Vec::new().into(),
             kind: FileKind::Module,
@@ -354,4 +416,67 @@ mod test {
         let core_module = db.module_main_file(ModuleId::CrateRoot(CrateId::core(&db))).unwrap();
         assert_eq!(db.file_content(core_module), Some(""));
     }
+
+    #[test]
+    fn setup_in_memory_project_maps_scarb_layout() {
+        let mut db = RootDatabase::builder().build().unwrap();
+        let project = InMemoryProject {
+            main_crate_name: "test".into(),
+            main_crate_files: BTreeMap::from([
+                ("my_package/src/lib.cairo".into(), "mod nested;".into()),
+                ("my_package/src/nested.cairo".into(), "fn x() {}".into()),
+                ("my_package/tests/test.cairo".into(), "fn y() {}".into()),
+            ]),
+            corelib_files: BTreeMap::from([("src/lib.cairo".into(), "".into())]),
+            main_crate_settings: None,
+        };
+
+        let inputs = setup_in_memory_project(&mut db, &project).unwrap();
+        let main_crate_id = CrateInput::into_crate_ids(&db, inputs).into_iter().next().unwrap();
+        let main_module = db.module_main_file(ModuleId::CrateRoot(main_crate_id)).unwrap();
+        assert_eq!(db.file_content(main_module), Some("mod nested;"));
+    }
+
+    #[test]
+    fn setup_in_memory_project_names_files_by_path() {
+        let mut db = RootDatabase::builder().build().unwrap();
+        let project = InMemoryProject {
+            main_crate_name: "test".into(),
+            main_crate_files: BTreeMap::from([
+                ("src/lib.cairo".into(), "mod tests;\nmod utils;".into()),
+                ("src/tests.cairo".into(), "mod utils;".into()),
+                ("src/tests/utils.cairo".into(), "fn x() {}".into()),
+                ("src/utils.cairo".into(), "fn x() {}".into()),
+            ]),
+            corelib_files: BTreeMap::from([("lib.cairo".into(), "".into())]),
+            main_crate_settings: None,
+        };
+
+        let inputs = setup_in_memory_project(&mut db, &project).unwrap();
+        let main_crate_id = CrateInput::into_crate_ids(&db, inputs).into_iter().next().unwrap();
+        let mut paths = db
+            .crate_modules(main_crate_id)
+            .iter()
+            .map(|module_id| db.module_main_file(*module_id).unwrap().full_path(&db))
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(paths, ["lib.cairo", "tests.cairo", "tests/utils.cairo", "utils.cairo"]);
+    }
+
+    #[test]
+    fn setup_in_memory_project_rejects_ambiguous_source_roots() {
+        let mut db = RootDatabase::builder().build().unwrap();
+        let project = InMemoryProject {
+            main_crate_name: "test".into(),
+            main_crate_files: BTreeMap::from([
+                ("a/src/lib.cairo".into(), "".into()),
+                ("b/src/lib.cairo".into(), "".into()),
+            ]),
+            corelib_files: BTreeMap::from([("lib.cairo".into(), "".into())]),
+            main_crate_settings: None,
+        };
+
+        let error = setup_in_memory_project(&mut db, &project).unwrap_err();
+        assert!(matches!(error, InMemoryProjectError::AmbiguousSourceRoot { .. }));
+    }
 }
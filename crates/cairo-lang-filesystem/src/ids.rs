@@ -455,13 +455,24 @@ impl<'db> Directory<'db> {
     pub fn file(&self, db: &'db dyn Database, name: &str) -> FileId<'db> {
         match self {
             Directory::Real(path) => FileId::new_on_disk(db, path.join(name)),
-            Directory::Virtual { files, dirs: _ } => files
-                .get(name)
-                .copied()
+            Directory::Virtual { .. } => self
+                .virtual_file(name)
                 .unwrap_or_else(|| FileId::new_on_disk(db, PathBuf::from(name))),
         }
     }
 
+    /// Returns the file at `path` inside this virtual directory, if any. The path may go through
+    /// subdirectories, as the paths given by `#[path(..)]` attributes.
+    fn virtual_file(&self, path: &str) -> Option<FileId<'db>> {
+        let Directory::Virtual { files, dirs } = self else {
+            return None;
+        };
+        match path.split_once('/') {
+            Some((dir, rest)) => dirs.get(dir)?.virtual_file(rest),
+            None => files.get(path).copied(),
+        }
+    }
+
     /// Returns a sub directory inside this directory. These directories don't necessarily exist on
     /// the file system. These are ids/paths to them.
     pub fn subdir(&self, name: &'db str) -> Directory<'db> {
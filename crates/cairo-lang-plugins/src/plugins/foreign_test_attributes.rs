@@ -0,0 +1,41 @@
+use cairo_lang_defs::plugin::{MacroPlugin, MacroPluginMetadata, PluginResult};
+use cairo_lang_filesystem::ids::SmolStrId;
+use cairo_lang_syntax::node::ast;
+use cairo_lang_syntax::node::helpers::QueryAttrs;
+use itertools::chain;
+use salsa::Database;
+
+/// The attribute marking a test function.
+const TEST_ATTR: &str = "test";
+/// Attributes of test functions, from `cairo-test` and Starknet Foundry.
+const TEST_FUNCTION_ATTRS: [&str; 6] =
+    ["should_panic", "available_gas", "ignore", "fuzzer", "fork", "test_case"];
+
+/// Plugin accepting test attributes in code compiled without a test runner, such as files pasted
+/// from a Scarb project.
+///
+/// Functions marked `#[test]` are removed, as they may rely on test-only dependencies. The other
+/// attributes of test functions are accepted and ignored.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct ForeignTestAttributesPlugin;
+
+impl MacroPlugin for ForeignTestAttributesPlugin {
+    fn generate_code<'db>(
+        &self,
+        db: &'db dyn Database,
+        item_ast: ast::ModuleItem<'db>,
+        _metadata: &MacroPluginMetadata<'_>,
+    ) -> PluginResult<'db> {
+        match item_ast {
+            ast::ModuleItem::FreeFunction(function) if function.has_attr(db, TEST_ATTR) => {
+                PluginResult { code: None, diagnostics: vec![], remove_original_item: true }
+            }
+            _ => PluginResult::default(),
+        }
+    }
+
+    fn declared_attributes<'db>(&self, db: &'db dyn Database) -> Vec<SmolStrId<'db>> {
+        chain!([TEST_ATTR], TEST_FUNCTION_ATTRS).map(|attr| SmolStrId::from(db, attr)).collect()
+    }
+}
@@ -2,6 +2,7 @@ pub use compile_error::*;
 pub use config::*;
 pub use derive::*;
 pub use external_attributes_validation::*;
+pub use foreign_test_attributes::*;
 pub use generate_trait::*;
 pub use panicable::*;
 
@@ -9,6 +10,7 @@ mod compile_error;
 mod config;
 mod derive;
 mod external_attributes_validation;
+mod foreign_test_attributes;
 mod generate_trait;
 mod panicable;
 pub mod utils;
@@ -25,6 +25,7 @@ use itertools::{Itertools, chain};
 use salsa::{Database, Setter};
 
 use crate::get_base_plugins;
+use crate::plugins::ForeignTestAttributesPlugin;
 use crate::test_utils::expand_module_text;
 
 cairo_lang_test_utils::test_file_test!(
@@ -52,6 +53,16 @@ cairo_lang_test_utils::test_file_test!(
     ["expect_diagnostics"]
 );
 
+cairo_lang_test_utils::test_file_test!(
+    expand_foreign_test_attributes_plugin,
+    "src/test_data",
+    {
+        foreign_test_attributes: "foreign_test_attributes",
+    },
+    test_foreign_test_attributes_plugin,
+    ["expect_diagnostics"]
+);
+
 #[salsa::db]
 #[derive(Clone)]
 pub struct DatabaseForTesting {
@@ -89,6 +100,14 @@ fn test_general_plugin(
     test_expand_plugin_inner(inputs, args, &[Arc::new(DoubleIndirectionPlugin)])
 }
 
+/// Tests expansion of given code, with the default plugins plus [ForeignTestAttributesPlugin].
+fn test_foreign_test_attributes_plugin(
+    inputs: &OrderedHashMap<String, String>,
+    args: &OrderedHashMap<String, String>,
+) -> TestRunnerResult {
+    test_expand_plugin_inner(inputs, args, &[Arc::new(ForeignTestAttributesPlugin::default())])
+}
+
 /// Tests expansion of given code, with the default plugins plus the given extra plugins.
 pub fn test_expand_plugin_inner(
     inputs: &OrderedHashMap<String, String>,
@@ -203,3 +222,17 @@ impl MacroPlugin for DoubleIndirectionPlugin {
         vec![SmolStrId::from(db, "first"), SmolStrId::from(db, "second")]
     }
 }
+
+#[test]
+fn foreign_test_attributes_plugin_declares_test_attributes() {
+    let db = DatabaseForTesting::default();
+    let declared = ForeignTestAttributesPlugin::default()
+        .declared_attributes(&db)
+        .into_iter()
+        .map(|attr| attr.to_string(&db))
+        .collect_vec();
+    assert_eq!(
+        declared,
+        ["test", "should_panic", "available_gas", "ignore", "fuzzer", "fork", "test_case"]
+    );
+}
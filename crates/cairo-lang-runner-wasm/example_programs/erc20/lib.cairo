@@ -0,0 +1,27 @@
+mod token;
+
+use token::{TokenTrait, TransferError};
+
+const ALICE: felt252 = 'alice';
+const BOB: felt252 = 'bob';
+const CAROL: felt252 = 'carol';
+
+fn main() -> u128 {
+    let mut token = TokenTrait::new("Example Token", ALICE, 1000);
+    println!("{}: total supply {}", token.name(), token.total_supply());
+
+    token.transfer(ALICE, BOB, 250).unwrap();
+    token.approve(BOB, CAROL, 100);
+    token.transfer_from(CAROL, BOB, CAROL, 60).unwrap();
+
+    // The remaining allowance of 40 does not cover another transfer of 60.
+    match token.transfer_from(CAROL, BOB, CAROL, 60) {
+        Err(TransferError::InsufficientAllowance) => {},
+        _ => panic!("allowance not enforced"),
+    }
+
+    println!("alice: {}", token.balance_of(ALICE));
+    println!("bob: {}", token.balance_of(BOB));
+    println!("carol: {}", token.balance_of(CAROL));
+    token.balance_of(BOB)
+}
@@ -0,0 +1,69 @@
+use core::pedersen::pedersen;
+
+#[derive(Drop, Debug)]
+pub enum TransferError {
+    InsufficientBalance,
+    InsufficientAllowance,
+}
+
+/// An ERC20-style token ledger. Balances and allowances are kept in dictionaries instead of
+/// contract storage, so the example runs without a Starknet context.
+#[derive(Destruct)]
+pub struct Token {
+    name: ByteArray,
+    total_supply: u128,
+    balances: Felt252Dict<u128>,
+    /// Keyed by the Pedersen hash of the owner and the spender.
+    allowances: Felt252Dict<u128>,
+}
+
+#[generate_trait]
+pub impl TokenImpl of TokenTrait {
+    fn new(name: ByteArray, owner: felt252, initial_supply: u128) -> Token {
+        let mut balances: Felt252Dict<u128> = Default::default();
+        balances.insert(owner, initial_supply);
+        Token { name, total_supply: initial_supply, balances, allowances: Default::default() }
+    }
+
+    fn name(ref self: Token) -> ByteArray {
+        self.name.clone()
+    }
+
+    fn total_supply(ref self: Token) -> u128 {
+        self.total_supply
+    }
+
+    fn balance_of(ref self: Token, account: felt252) -> u128 {
+        self.balances.get(account)
+    }
+
+    fn transfer(
+        ref self: Token, from: felt252, to: felt252, amount: u128,
+    ) -> Result<(), TransferError> {
+        let from_balance = self.balances.get(from);
+        if from_balance < amount {
+            return Err(TransferError::InsufficientBalance);
+        }
+        self.balances.insert(from, from_balance - amount);
+        let to_balance = self.balances.get(to);
+        self.balances.insert(to, to_balance + amount);
+        Ok(())
+    }
+
+    fn approve(ref self: Token, owner: felt252, spender: felt252, amount: u128) {
+        self.allowances.insert(pedersen(owner, spender), amount);
+    }
+
+    fn transfer_from(
+        ref self: Token, spender: felt252, from: felt252, to: felt252, amount: u128,
+    ) -> Result<(), TransferError> {
+        let key = pedersen(from, spender);
+        let allowance = self.allowances.get(key);
+        if allowance < amount {
+            return Err(TransferError::InsufficientAllowance);
+        }
+        self.transfer(from, to, amount)?;
+        self.allowances.insert(key, allowance - amount);
+        Ok(())
+    }
+}
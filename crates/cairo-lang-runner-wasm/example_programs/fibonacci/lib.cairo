@@ -0,0 +1,16 @@
+fn fib(n: u32) -> u64 {
+    let mut a: u64 = 0;
+    let mut b: u64 = 1;
+    for _ in 0..n {
+        let next = a + b;
+        a = b;
+        b = next;
+    }
+    a
+}
+
+fn main() -> u64 {
+    let result = fib(20);
+    println!("fib(20) = {}", result);
+    result
+}
@@ -0,0 +1,36 @@
+/// A key-value store with a write log, modeled on contract storage: reading a key that was never
+/// written returns zero.
+#[derive(Destruct)]
+struct Storage {
+    values: Felt252Dict<felt252>,
+    writes: Array<(felt252, felt252)>,
+}
+
+#[generate_trait]
+impl StorageImpl of StorageTrait {
+    fn new() -> Storage {
+        Storage { values: Default::default(), writes: array![] }
+    }
+
+    fn read(ref self: Storage, key: felt252) -> felt252 {
+        self.values.get(key)
+    }
+
+    fn write(ref self: Storage, key: felt252, value: felt252) {
+        self.values.insert(key, value);
+        self.writes.append((key, value));
+    }
+}
+
+fn main() -> felt252 {
+    let mut storage = StorageTrait::new();
+    storage.write('counter', 1);
+    storage.write('owner', 'alice');
+    let counter = storage.read('counter');
+    storage.write('counter', counter + 1);
+
+    println!("counter = {}", storage.read('counter'));
+    println!("unset = {}", storage.read('unset'));
+    println!("{} writes", storage.writes.len());
+    storage.read('counter')
+}
@@ -0,0 +1,102 @@
+//! A report of the executed operations whose semantics differ from the integer arithmetic of
+//! other platforms, such as the EVM, for users targeting provable code.
+
+use std::collections::HashMap;
+
+use cairo_lang_runnable_utils::builder::RunnableBuilder;
+use cairo_lang_runner::profiling::{ProfilingInfo, user_function_idx_by_sierra_statement_idx};
+use cairo_lang_sierra::program::{GenStatement, StatementIdx};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Libfuncs dividing field elements rather than integers.
+const FELT252_DIV_LIBFUNCS: [&str; 2] = ["felt252_div", "felt252_div_const"];
+
+/// Libfuncs dividing integers by a value only known at runtime.
+const DIVISION_LIBFUNCS: [&str; 8] = [
+    "u8_safe_divmod",
+    "u16_safe_divmod",
+    "u32_safe_divmod",
+    "u64_safe_divmod",
+    "u128_safe_divmod",
+    "u256_safe_divmod",
+    "u512_safe_divmod_by_u256",
+    "bounded_int_div_rem",
+];
+
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ArithmeticSiteKind {
+    /// A division in the field, `a / b` being the element `c` such that `b * c = a`.
+    Felt252Div,
+    /// An integer division by a variable.
+    Division,
+    /// A hint, whose result is provided by the prover and constrained by the following code.
+    Hint,
+}
+
+/// An executed Sierra statement worth the attention of users targeting provable code.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ArithmeticSite {
+    pub kind: ArithmeticSiteKind,
+    /// The invoked libfunc.
+    pub libfunc: String,
+    /// The function containing the statement.
+    pub function: String,
+    pub statement_idx: usize,
+    /// The number of steps spent in the statement.
+    pub steps: usize,
+    /// The location of the statement in the sources, when the program was compiled from them.
+    pub location: Option<String>,
+}
+
+/// Lists the sites of the executed statements of a run, in the order of the program.
+pub(crate) fn arithmetic_sites(
+    builder: &RunnableBuilder,
+    profiling_info: &ProfilingInfo,
+    locate_statement: Option<&dyn Fn(StatementIdx) -> Option<String>>,
+) -> Vec<ArithmeticSite> {
+    let program = builder.sierra_program();
+    let casm_program = builder.casm_program();
+    let statement_info = &casm_program.debug_info.sierra_statement_info;
+    let generic_libfuncs = program
+        .libfunc_declarations
+        .iter()
+        .map(|declaration| (&declaration.id, declaration.long_id.generic_id.0.as_str()))
+        .collect::<HashMap<_, _>>();
+
+    profiling_info
+        .sierra_statement_weights
+        .iter_sorted()
+        .filter_map(|(statement_idx, steps)| {
+            let GenStatement::Invocation(invocation) = &program.statements[statement_idx.0] else {
+                return None;
+            };
+            let generic_libfunc = generic_libfuncs[&invocation.libfunc_id];
+            let kind = if FELT252_DIV_LIBFUNCS.contains(&generic_libfunc) {
+                ArithmeticSiteKind::Felt252Div
+            } else if DIVISION_LIBFUNCS.contains(&generic_libfunc) {
+                ArithmeticSiteKind::Division
+            } else {
+                let first_instruction = statement_info[statement_idx.0].instruction_idx;
+                let end_instruction = statement_info
+                    .get(statement_idx.0 + 1)
+                    .map_or(casm_program.instructions.len(), |info| info.instruction_idx);
+                casm_program.instructions[first_instruction..end_instruction]
+                    .iter()
+                    .any(|instruction| !instruction.hints.is_empty())
+                    .then_some(ArithmeticSiteKind::Hint)?
+            };
+            let function_idx = user_function_idx_by_sierra_statement_idx(program, *statement_idx);
+            Some(ArithmeticSite {
+                kind,
+                libfunc: invocation.libfunc_id.to_string(),
+                function: program.funcs[function_idx].id.to_string(),
+                statement_idx: statement_idx.0,
+                steps: *steps,
+                location: locate_statement
+                    .and_then(|locate_statement| locate_statement(*statement_idx)),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,236 @@
+//! Decoding of the failures of comparison assertions, such as `assert_eq!`, into the compared
+//! values and their differences.
+//!
+//! The assertion macros panic with a `ByteArray` holding the compared expressions and the `Debug`
+//! representations of their values, which are parsed back into trees to find where they differ.
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The comparison operators of the assertion macros.
+const OPERATORS: [&str; 6] = ["==", "!=", "<=", ">=", "<", ">"];
+
+/// A failed comparison assertion.
+#[derive(Debug, PartialEq, Serialize, JsonSchema)]
+pub struct AssertionFailure {
+    /// The compared expressions with their operator, e.g. `a == b`.
+    pub expression: String,
+    /// The custom message of the assertion, if any.
+    pub message: Option<String>,
+    pub lhs: AssertionSide,
+    pub rhs: AssertionSide,
+    /// The parts of the values that differ, outermost first, for `==` assertions.
+    pub differences: Vec<ValueDifference>,
+}
+
+/// A side of a comparison.
+#[derive(Debug, PartialEq, Serialize, JsonSchema)]
+pub struct AssertionSide {
+    pub expression: String,
+    /// The `Debug` representation of the value.
+    pub value: String,
+}
+
+/// A part of the compared values that differs.
+#[derive(Debug, PartialEq, Serialize, JsonSchema)]
+pub struct ValueDifference {
+    /// The path of the part in the values, e.g. `.items[2]`, empty for the whole values.
+    pub path: String,
+    /// The `Debug` representation of the part in the left value.
+    pub left: String,
+    /// The `Debug` representation of the part in the right value.
+    pub right: String,
+}
+
+/// Decodes the panic message of a failed comparison assertion, such as:
+///
+/// ```text
+/// assertion `a == b` failed.
+/// a: 1
+/// b: 2
+/// ```
+///
+/// Returns `None` for other panic messages.
+pub(crate) fn decode(panic_message: &str) -> Option<AssertionFailure> {
+    let rest = panic_message.strip_prefix("assertion `")?;
+    // The expressions may contain backticks, so the end of the expression is found from the sides
+    // following it.
+    rest.match_indices("` failed").find_map(|(end, _)| {
+        let expression = &rest[..end];
+        let (message, sides) = split_message(&rest[end + "` failed".len()..])?;
+        OPERATORS.iter().find_map(|operator| {
+            expression.match_indices(&format!(" {operator} ")).find_map(|(split, separator)| {
+                let lhs = &expression[..split];
+                let rhs = &expression[split + separator.len()..];
+                let values = sides.strip_prefix(&format!("{lhs}: "))?;
+                let (left, right) = values.split_once(&format!("\n{rhs}: "))?;
+                Some(AssertionFailure {
+                    expression: expression.to_string(),
+                    message: message.map(str::to_string),
+                    differences: if *operator == "==" {
+                        differences(&parse_value(left), &parse_value(right))
+                    } else {
+                        vec![]
+                    },
+                    lhs: AssertionSide { expression: lhs.to_string(), value: left.to_string() },
+                    rhs: AssertionSide { expression: rhs.to_string(), value: right.to_string() },
+                })
+            })
+        })
+    })
+}
+
+/// Splits what follows the expression, `.` or `: <message>`, from the sides of the comparison on
+/// the next lines.
+fn split_message(rest: &str) -> Option<(Option<&str>, &str)> {
+    if let Some(sides) = rest.strip_prefix(".\n") {
+        return Some((None, sides));
+    }
+    let (message, sides) = rest.strip_prefix(": ")?.split_once('\n')?;
+    Some((Some(message), sides))
+}
+
+/// A value parsed from its `Debug` representation.
+enum Value<'a> {
+    /// A struct, enum variant, tuple or array, with its name or variant (empty for tuples and
+    /// arrays), its opening bracket, and its members, named for structs.
+    Composite { head: &'a str, open: char, members: Vec<(Option<&'a str>, Value<'a>)> },
+    /// Any other value, such as a number or a string.
+    Leaf(&'a str),
+}
+
+fn parse_value(text: &str) -> Value<'_> {
+    let text = text.trim();
+    let Some(open) = text.find(['{', '(', '[']) else {
+        return Value::Leaf(text);
+    };
+    let open_char = text[open..].chars().next().unwrap();
+    let close_char = match open_char {
+        '{' => '}',
+        '(' => ')',
+        _ => ']',
+    };
+    let head = text[..open].trim();
+    // Strings and other values containing brackets are leaves.
+    if head.contains(['"', ' ']) || !text.ends_with(close_char) {
+        return Value::Leaf(text);
+    }
+    let Some(items) = split_top_level(&text[open + 1..text.len() - 1]) else {
+        return Value::Leaf(text);
+    };
+    let members = items
+        .into_iter()
+        .map(|item| match open_char {
+            '{' => match item.split_once(": ") {
+                Some((name, value)) => (Some(name.trim()), value),
+                None => (None, item),
+            },
+            _ => (None, item),
+        })
+        .map(|(name, item)| (name, parse_value(item)))
+        .collect();
+    Value::Composite { head, open: open_char, members }
+}
+
+/// Splits `text` at its commas outside of brackets and strings, dropping a trailing empty item.
+/// Returns `None` if the brackets are unbalanced.
+fn split_top_level(text: &str) -> Option<Vec<&str>> {
+    let mut items = vec![];
+    let mut depth = 0_usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (idx, char) in text.char_indices() {
+        if in_string {
+            match char {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match char {
+            '"' => in_string = true,
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                items.push(&text[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 || in_string {
+        return None;
+    }
+    if !text[start..].trim().is_empty() {
+        items.push(&text[start..]);
+    }
+    Some(items)
+}
+
+/// Returns the differing parts of two values, descending into the members of composites of the
+/// same shape.
+fn differences(left: &Value<'_>, right: &Value<'_>) -> Vec<ValueDifference> {
+    let mut differences = vec![];
+    collect_differences(left, right, String::new(), &mut differences);
+    differences
+}
+
+fn collect_differences(
+    left: &Value<'_>,
+    right: &Value<'_>,
+    path: String,
+    differences: &mut Vec<ValueDifference>,
+) {
+    if let (
+        Value::Composite { head: left_head, open: left_open, members: left_members },
+        Value::Composite { head: right_head, open: right_open, members: right_members },
+    ) = (left, right)
+        && left_head == right_head
+        && left_open == right_open
+        && left_members.len() == right_members.len()
+        && left_members.iter().zip(right_members).all(|((left, _), (right, _))| left == right)
+    {
+        for (idx, ((name, left), (_, right))) in left_members.iter().zip(right_members).enumerate()
+        {
+            let member_path = match name {
+                Some(name) => format!("{path}.{name}"),
+                None => format!("{path}[{idx}]"),
+            };
+            collect_differences(left, right, member_path, differences);
+        }
+        return;
+    }
+    let (left, right) = (render(left), render(right));
+    if left != right {
+        differences.push(ValueDifference { path, left, right });
+    }
+}
+
+/// Renders a parsed value back to a `Debug`-like representation.
+fn render(value: &Value<'_>) -> String {
+    match value {
+        Value::Leaf(text) => text.to_string(),
+        Value::Composite { head, open, members } => {
+            let close = match open {
+                '{' => '}',
+                '(' => ')',
+                _ => ']',
+            };
+            let members = members
+                .iter()
+                .map(|(name, value)| match name {
+                    Some(name) => format!("{name}: {}", render(value)),
+                    None => render(value),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            match open {
+                '{' => format!("{head} {{ {members} }}"),
+                _ => format!("{head}{open}{members}{close}"),
+            }
+        }
+    }
+}
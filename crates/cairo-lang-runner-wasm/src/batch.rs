@@ -0,0 +1,209 @@
+//! Batch runs of several functions of one compiled program, sharing the output of a setup function
+//! run once, for benchmarking and grading without repeating expensive setups.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_wasm_utils::diagnostic_counts::{self, FileDiagnosticCounts};
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::source::{self, SourceFiles};
+use cairo_lang_wasm_utils::{corelib, limits, parse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    DiagnosticsScopeArg, InliningStrategyArg, ProgramRunner, RunOptions, RunResponse,
+    compile_project, default_function_name, default_replace_ids,
+};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CompileAndRunBatchRequest {
+    pub crate_name: String,
+    pub files: SourceFiles,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    #[serde(default = "default_replace_ids")]
+    pub replace_ids: bool,
+    #[serde(default)]
+    pub inlining_strategy: InliningStrategyArg,
+    /// The crates whose diagnostics are reported.
+    #[serde(default)]
+    pub diagnostics_scope: DiagnosticsScopeArg,
+    /// The gas available to each run.
+    pub available_gas: Option<usize>,
+    /// The number of VM steps after which each run is stopped.
+    pub max_steps: Option<usize>,
+    /// A function run once before the runs, whose return value is passed as the first argument of
+    /// each of them.
+    pub setup: Option<String>,
+    pub runs: Vec<BatchRun>,
+    /// Whether to return the compiler's and runner's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// A run of a batch.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct BatchRun {
+    #[serde(default = "default_function_name")]
+    pub function: String,
+    /// The arguments following the output of the setup function, as in `typed_args`.
+    #[serde(default)]
+    pub typed_args: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct BatchRunResponse {
+    /// The run of the setup function, if any.
+    pub setup: Option<RunResponse>,
+    /// The runs, in the order of the request.
+    pub runs: Vec<RunResponse>,
+    pub diagnostics: String,
+    /// The number of errors and warnings of each file of `diagnostics`.
+    pub diagnostics_by_file: BTreeMap<String, FileDiagnosticCounts>,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Compiles a project once, then runs several of its functions. See [CompileAndRunBatchRequest].
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn compile_and_run_batch(request_json: &str) -> String {
+    let request: CompileAndRunBatchRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serialize_batch_response(Err(error), None);
+        }
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| compile_and_run_batch_request(request))
+    });
+    serialize_batch_response(result, debug_log)
+}
+
+fn compile_and_run_batch_request(request: CompileAndRunBatchRequest) -> BatchRunResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.contents.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return BatchRunResponse { error: Some(error), ..Default::default() };
+    }
+
+    let project = InMemoryProject {
+        main_crate_name: request.crate_name,
+        main_crate_files: request.files.contents,
+        corelib_files: corelib::resolve(request.corelib_files),
+        main_crate_settings: None,
+    };
+    compile_project(
+        &project,
+        &request.files.hidden,
+        request.inlining_strategy,
+        request.replace_ids,
+        request.diagnostics_scope,
+        |program, locate_statement, _, diagnostics| {
+            let options = RunOptions {
+                available_gas: request.available_gas,
+                max_steps: request.max_steps,
+                locate_statement: Some(locate_statement),
+                ..Default::default()
+            };
+            let runner = match ProgramRunner::new(program, &options) {
+                Ok(runner) => runner,
+                Err(error) => {
+                    return BatchRunResponse {
+                        diagnostics,
+                        error: Some(error),
+                        ..Default::default()
+                    };
+                }
+            };
+
+            let setup = match request.setup {
+                Some(function) => {
+                    let response = runner.run(&function, vec![], None, request.available_gas);
+                    if !response.success {
+                        return BatchRunResponse {
+                            setup: Some(response),
+                            diagnostics,
+                            error: Some(ApiError::execution(format!(
+                                "Setup function `{function}` failed."
+                            ))),
+                            ..Default::default()
+                        };
+                    }
+                    Some(response)
+                }
+                None => None,
+            };
+            let setup_value = setup.as_ref().and_then(|response| response.decoded_values.clone());
+            let runs = request
+                .runs
+                .into_iter()
+                .map(|run| {
+                    let typed_args = setup_value.iter().cloned().chain(run.typed_args).collect();
+                    runner.run(&run.function, vec![], Some(typed_args), request.available_gas)
+                })
+                .collect();
+            BatchRunResponse { setup, runs, diagnostics, ..Default::default() }
+        },
+    )
+    .unwrap_or_else(|(error, diagnostics)| BatchRunResponse {
+        diagnostics,
+        error: Some(error),
+        ..Default::default()
+    })
+}
+
+fn serialize_batch_response(
+    result: Result<BatchRunResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+) -> String {
+    let mut response = result
+        .unwrap_or_else(|error| BatchRunResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+    response.diagnostics_by_file = diagnostic_counts::by_file(&response.diagnostics);
+    serde_json::to_string(&response).expect("serialize batch run response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::compile_and_run_batch;
+
+    #[test]
+    fn compile_and_run_batch_shares_setup_output() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn setup() -> Array<felt252> {
+    array![10, 20, 30]
+}
+
+fn lookup(table: Array<felt252>, index: u32) -> felt252 {
+    *table.at(index)
+}
+"
+            },
+            "available_gas": 1000000,
+            "setup": "::setup",
+            "runs": [
+                { "function": "::lookup", "typed_args": [0] },
+                { "function": "::lookup", "typed_args": [2] }
+            ]
+        });
+
+        let response = compile_and_run_batch(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["error"], Value::Null, "response={response}");
+        assert_eq!(response_json["setup"]["decoded_values"], json!(["10", "20", "30"]));
+        assert_eq!(response_json["runs"][0]["values"], json!(["10"]));
+        assert_eq!(response_json["runs"][1]["values"], json!(["30"]));
+    }
+}
@@ -0,0 +1,31 @@
+//! Packaging of the runs of executables as Cairo PIEs, the zip archives submitted to provers such
+//! as SHARP.
+
+use std::io::{self, Cursor, Write};
+
+use cairo_vm::vm::runners::cairo_pie::CairoPie;
+use serde::Serialize;
+use zip::result::ZipResult;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Returns the zip archive of `pie`, with the files written by `cairo-execute --output-path`.
+pub(crate) fn archive(pie: &CairoPie) -> ZipResult<Vec<u8>> {
+    let mut writer = ZipWriter::new(Cursor::new(vec![]));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, content) in [
+        ("version.json", to_json(&pie.version)?),
+        ("metadata.json", to_json(&pie.metadata)?),
+        ("memory.bin", pie.memory.to_bytes()),
+        ("additional_data.json", to_json(&pie.additional_data)?),
+        ("execution_resources.json", to_json(&pie.execution_resources)?),
+    ] {
+        writer.start_file(name, options)?;
+        writer.write_all(&content)?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+fn to_json(value: &impl Serialize) -> io::Result<Vec<u8>> {
+    Ok(serde_json::to_vec(value)?)
+}
@@ -0,0 +1,587 @@
+//! Execution of executables assembled elsewhere, such as by Scarb, running their CASM directly on
+//! the VM without compiling Sierra.
+
+use cairo_lang_casm::assembler::AssembledCairoProgram;
+use cairo_lang_executable::compile::CompiledFunction;
+use cairo_lang_executable::executable::{EntryPointKind, Executable, ExecutableEntryPoint};
+use cairo_lang_execute_utils::program_and_hints_from_executable;
+use cairo_lang_runnable_utils::builder::{EntryCodeConfig, RunnableBuilder};
+use cairo_lang_runner::{Arg, CairoHintProcessor};
+use cairo_lang_wasm_utils::error::{self, ApiError, ErrorKind};
+use cairo_lang_wasm_utils::{allocations, item_path, limits, logging, parse};
+use cairo_vm::cairo_run::{CairoRunConfig, cairo_run_program};
+use cairo_vm::types::builtin_name::BuiltinName;
+use cairo_vm::types::layout_name::LayoutName;
+use cairo_vm::vm::errors::cairo_run_errors::CairoRunError;
+use cairo_vm::vm::errors::runner_errors::RunnerError;
+use cairo_vm::vm::runners::cairo_runner::{ResourceTracker, RunResources};
+use num_bigint::BigInt;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use starknet_types_core::felt::Felt as Felt252;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    RunArg, RunResponse, VmResources, cairo_pie, casm_text, compiled_program, debug_output,
+    executable_wrapper_name, execution_trace, panic_message, prover_input, serialize_error,
+    serialize_run_response,
+};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RunCasmRequest {
+    /// An executable, as built by `cairo-execute --build-only` or Scarb: the assembled bytecode,
+    /// its hints, and its entry points with their offsets and builtins. Exclusive with `casm`.
+    pub executable: Option<serde_json::Value>,
+    /// CASM text to assemble and run instead of an executable, as printed by `compile` with
+    /// `casm`. Hints are not supported.
+    pub casm: Option<String>,
+    /// The handle of a program compiled by `compile_program`, whose `#[executable]` function
+    /// `function` is built into an executable and run, as by `cairo-execute`. Exclusive with
+    /// `executable` and `casm`.
+    pub program: Option<u32>,
+    /// The `#[executable]` function of `program`, matched against the trailing `::`-separated
+    /// segments of the function paths. Defaults to `::main`.
+    pub function: Option<String>,
+    /// The kind of the entry point to run.
+    #[serde(default)]
+    pub entrypoint: EntrypointKindArg,
+    /// The bytecode offset of the entry point, overriding the offset of the `entrypoint` of the
+    /// executable. Defaults to 0 with `casm`.
+    pub entry_offset: Option<usize>,
+    /// The builtins the entry point of `casm` takes, in order, such as `"output"`.
+    #[serde(default)]
+    #[schemars(with = "Vec<String>")]
+    pub builtins: Vec<BuiltinName>,
+    /// The arguments of the executable, as in the `args` of a run request.
+    #[serde(default)]
+    pub args: Vec<RunArg>,
+    /// The number of VM steps after which the run is stopped.
+    pub max_steps: Option<usize>,
+    /// The number of bytes of `stdout` after which the output stops being captured, the run going
+    /// on.
+    pub max_stdout_bytes: Option<usize>,
+    /// The layout of the run, which provers of proof-mode runs require to match theirs.
+    #[serde(default)]
+    pub layout: LayoutArg,
+    /// Whether to return the run as a Cairo PIE, for submission to provers.
+    #[serde(default)]
+    pub cairo_pie: bool,
+    /// Whether to return the public and private inputs, trace and memory of the run expected by
+    /// provers such as Stone and Stwo. Requires a standalone entry point.
+    #[serde(default)]
+    pub prove_mode: bool,
+    /// Whether to return the runner's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// The kind of the entry point of an executable.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EntrypointKindArg {
+    /// A function taking the builtins as parameters, as run by a bootloader.
+    #[default]
+    Bootloader,
+    /// A standalone program, run in proof mode.
+    Standalone,
+}
+
+/// A layout of the VM: the builtins it provides, and their ratio to the steps.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutArg {
+    Plain,
+    Small,
+    Dex,
+    Recursive,
+    Starknet,
+    StarknetWithKeccak,
+    RecursiveLargeOutput,
+    RecursiveWithPoseidon,
+    AllSolidity,
+    #[default]
+    AllCairo,
+}
+
+impl From<LayoutArg> for LayoutName {
+    fn from(layout: LayoutArg) -> Self {
+        match layout {
+            LayoutArg::Plain => LayoutName::plain,
+            LayoutArg::Small => LayoutName::small,
+            LayoutArg::Dex => LayoutName::dex,
+            LayoutArg::Recursive => LayoutName::recursive,
+            LayoutArg::Starknet => LayoutName::starknet,
+            LayoutArg::StarknetWithKeccak => LayoutName::starknet_with_keccak,
+            LayoutArg::RecursiveLargeOutput => LayoutName::recursive_large_output,
+            LayoutArg::RecursiveWithPoseidon => LayoutName::recursive_with_poseidon,
+            LayoutArg::AllSolidity => LayoutName::all_solidity,
+            LayoutArg::AllCairo => LayoutName::all_cairo,
+        }
+    }
+}
+
+/// Runs an entry point of an executable on the VM.
+///
+/// `values` holds the felts written to the output builtin on success, and the panic data
+/// otherwise.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn run_casm(request_json: &str) -> String {
+    run(request_json, false)
+}
+
+/// Serves a `run_casm` request, proving the run with Stwo when `prove` is set.
+pub(crate) fn run(request_json: &str, prove: bool) -> String {
+    let request: RunCasmRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serialize_error(error);
+        }
+    };
+
+    let ((result, debug_log), allocations) = allocations::measure(|| {
+        logging::capture(request.debug, || {
+            error::catch_internal(|| {
+                run_casm_request(request, prove).unwrap_or_else(|error| RunResponse {
+                    error: Some(error),
+                    ..Default::default()
+                })
+            })
+        })
+    });
+    serialize_run_response(result, debug_log, allocations)
+}
+
+fn run_casm_request(request: RunCasmRequest, prove: bool) -> Result<RunResponse, ApiError> {
+    let kind = match request.entrypoint {
+        EntrypointKindArg::Bootloader => EntryPointKind::Bootloader,
+        EntrypointKindArg::Standalone => EntryPointKind::Standalone,
+    };
+    if !request.builtins.is_empty() && request.casm.is_none() {
+        return Err(ApiError::invalid_request(
+            "`builtins` is only supported with `casm`, executables list theirs.",
+        ));
+    }
+    if request.function.is_some() && request.program.is_none() {
+        return Err(ApiError::invalid_request("`function` is only supported with `program`."));
+    }
+    let executable = match (request.executable, request.casm, request.program) {
+        (Some(executable), None, None) => serde_json::from_value(executable).map_err(|error| {
+            ApiError::invalid_request(format!("Failed parsing executable: {error}"))
+        })?,
+        (None, Some(casm), None) => {
+            limits::current().check_source("CASM", &casm)?;
+            Executable {
+                program: AssembledCairoProgram {
+                    bytecode: casm_text::assemble(&casm)?,
+                    hints: vec![],
+                },
+                entrypoints: vec![ExecutableEntryPoint {
+                    builtins: request.builtins,
+                    offset: request.entry_offset.unwrap_or_default(),
+                    kind: kind.clone(),
+                }],
+                debug_info: None,
+            }
+        }
+        (None, None, Some(program)) => {
+            program_executable(program, request.function.as_deref().unwrap_or("::main"))?
+        }
+        _ => {
+            return Err(ApiError::invalid_request(
+                "Exactly one of `executable`, `casm` and `program` is required.",
+            ));
+        }
+    };
+    // Provers run PIEs through their bootloader, while standalone programs are proven directly.
+    if request.cairo_pie && kind == EntryPointKind::Standalone {
+        return Err(ApiError::invalid_request(
+            "`cairo_pie` is only supported for bootloader entry points.",
+        ));
+    }
+    if request.prove_mode && kind != EntryPointKind::Standalone {
+        return Err(ApiError::invalid_request(
+            "`prove_mode` is only supported for standalone entry points, which run in proof mode.",
+        ));
+    }
+    if prove && kind != EntryPointKind::Standalone {
+        return Err(ApiError::invalid_request(
+            "Only standalone entry points, which run in proof mode, can be proven.",
+        ));
+    }
+    // The prover reads the relocated trace and memory of the run.
+    let relocate = request.prove_mode || prove;
+    let entrypoint =
+        executable.entrypoints.iter().find(|entrypoint| entrypoint.kind == kind).ok_or_else(
+            || ApiError::invalid_request(format!("Executable has no {kind:?} entry point.")),
+        )?;
+    let entrypoint = ExecutableEntryPoint {
+        offset: request.entry_offset.unwrap_or(entrypoint.offset),
+        ..entrypoint.clone()
+    };
+    if entrypoint.offset >= executable.program.bytecode.len() {
+        return Err(ApiError::invalid_request(format!(
+            "Entry offset {} is past the end of the bytecode.",
+            entrypoint.offset
+        )));
+    }
+    let (program, string_to_hint) = program_and_hints_from_executable(&executable, &entrypoint)
+        .map_err(|error| ApiError::invalid_request(format!("{error:#}")))?;
+
+    let mut args = vec![];
+    request.args.iter().try_for_each(|arg| arg.encode(&mut args))?;
+    let mut hint_processor = CairoHintProcessor {
+        runner: None,
+        user_args: vec![vec![Arg::Array(args)]],
+        string_to_hint,
+        starknet_state: Default::default(),
+        run_resources: request.max_steps.map(RunResources::new).unwrap_or_default(),
+        syscalls_used_resources: Default::default(),
+        no_temporary_segments: false,
+        markers: Default::default(),
+        panic_traceback: Default::default(),
+        captured_stdout: String::new(),
+        max_stdout_bytes: request.max_stdout_bytes,
+        stdout_truncated: false,
+    };
+    let standalone = kind == EntryPointKind::Standalone;
+    let config = CairoRunConfig {
+        trace_enabled: relocate,
+        relocate_mem: relocate,
+        relocate_trace: relocate,
+        layout: request.layout.into(),
+        proof_mode: standalone,
+        fill_holes: standalone,
+        ..Default::default()
+    };
+
+    let mut runner = match cairo_run_program(&program, &config, &mut hint_processor) {
+        Ok(runner) => runner,
+        Err(CairoRunError::Runner(RunnerError::NoBuiltinForInstance(missing))) => {
+            let (builtins, layout) = missing.as_ref();
+            let mut builtins = builtins.iter().map(|builtin| builtin.to_str()).collect::<Vec<_>>();
+            builtins.sort();
+            return Err(ApiError::invalid_request(format!(
+                "The `{layout}` layout lacks the builtins used by the entry point: {}.",
+                builtins.join(", ")
+            )));
+        }
+        Err(error) => {
+            let (stdout, stderr) =
+                debug_output::split(std::mem::take(&mut hint_processor.captured_stdout));
+            let stdout_truncated = hint_processor.stdout_truncated;
+            if let Some(max_steps) = request.max_steps.filter(|_| hint_processor.consumed()) {
+                return Ok(RunResponse {
+                    stdout,
+                    stdout_truncated,
+                    stderr,
+                    error: Some(ApiError::new(
+                        ErrorKind::StepLimitExceeded,
+                        format!(
+                            "Failed to run executable: Exceeded the limit of {max_steps} steps."
+                        ),
+                    )),
+                    ..Default::default()
+                });
+            }
+            // Panics record their data in a marker before failing the run.
+            let Some(panic_data) = hint_processor.markers.pop() else {
+                return Ok(RunResponse {
+                    stdout,
+                    stdout_truncated,
+                    stderr,
+                    error: Some(ApiError::execution(format!("Failed to run executable: {error}"))),
+                    ..Default::default()
+                });
+            };
+            let panic_message = panic_message::panic_message(&panic_data);
+            return Ok(RunResponse {
+                panicked: true,
+                stderr: panic_message::with_panic_line(stderr, Some(&panic_message)),
+                panic_message: Some(panic_message),
+                values: panic_data.iter().map(ToString::to_string).collect(),
+                stdout,
+                stdout_truncated,
+                ..Default::default()
+            });
+        }
+    };
+
+    let mut output = String::new();
+    runner.vm.write_output(&mut output).map_err(|error| {
+        ApiError::execution(format!("Failed reading the output of the executable: {error}"))
+    })?;
+    // The output is written as signed integers, one per line.
+    let values = output
+        .lines()
+        .map(|line| {
+            let value = line.parse::<BigInt>().map_err(|_| {
+                ApiError::execution(format!("Unexpected value `{line}` in the output."))
+            })?;
+            Ok(Felt252::from(&value).to_string())
+        })
+        .collect::<Result<_, ApiError>>()?;
+    let cairo_pie = if request.cairo_pie {
+        let pie = runner.get_cairo_pie().map_err(|error| {
+            ApiError::execution(format!("Failed getting the Cairo PIE of the run: {error}"))
+        })?;
+        let archive = cairo_pie::archive(&pie).map_err(|error| {
+            ApiError::execution(format!("Failed packaging the Cairo PIE: {error}"))
+        })?;
+        Some(execution_trace::base64(&archive))
+    } else {
+        None
+    };
+    let prover_input =
+        if request.prove_mode { Some(prover_input::prover_input(&runner)?) } else { None };
+    #[cfg(feature = "prove")]
+    let proof = if prove { Some(crate::prove::prove_run(&runner)?) } else { None };
+    #[cfg(not(feature = "prove"))]
+    let proof = None;
+    let (stdout, stderr) = debug_output::split(hint_processor.captured_stdout);
+    Ok(RunResponse {
+        success: true,
+        values,
+        stdout,
+        stdout_truncated: hint_processor.stdout_truncated,
+        stderr,
+        resources: runner.get_execution_resources().ok().as_ref().map(VmResources::from),
+        cairo_pie,
+        prover_input,
+        proof,
+        ..Default::default()
+    })
+}
+
+/// Builds the executable of the `#[executable]` function `function` of a program compiled by
+/// `compile_program`.
+fn program_executable(program: u32, function: &str) -> Result<Executable, ApiError> {
+    let program = compiled_program::get(program)?;
+    let wrapper_name = executable_wrapper_name(function);
+    let candidates = program
+        .funcs
+        .iter()
+        .filter(|func| {
+            func.id
+                .debug_name
+                .as_deref()
+                .is_some_and(|name| item_path::matches_suffix(name, &wrapper_name))
+        })
+        .collect::<Vec<_>>();
+    let func = match candidates[..] {
+        [func] => func.clone(),
+        [] => {
+            return Err(ApiError::invalid_request(format!(
+                "Failed finding the `#[executable]` function `{function}`."
+            )));
+        }
+        _ => {
+            let paths = candidates.iter().map(|func| format!("`{}`", func.id));
+            return Err(ApiError::invalid_request(format!(
+                "Function `{function}` is ambiguous, matching {}.",
+                paths.collect::<Vec<_>>().join(", ")
+            )));
+        }
+    };
+    let metadata_config = program.requires_gas_counter().then(Default::default);
+    let builder = RunnableBuilder::new(program, metadata_config).map_err(|error| {
+        ApiError::compilation(format!("Failed building the executable: {error}"))
+    })?;
+    let wrapper =
+        builder.create_wrapper_info(&func, EntryCodeConfig::executable(false, None)).map_err(
+            |error| ApiError::compilation(format!("Failed building the executable: {error}")),
+        )?;
+    Ok(Executable::new(CompiledFunction { program: builder.casm_program().clone(), wrapper }))
+}
+
+#[cfg(test)]
+mod tests {
+    use cairo_lang_casm::assembler::AssembledCairoProgram;
+    use cairo_lang_casm::casm;
+    use cairo_lang_executable::executable::{EntryPointKind, Executable, ExecutableEntryPoint};
+    use cairo_vm::types::builtin_name::BuiltinName;
+    use serde_json::{Value, json};
+
+    use super::run_casm;
+    use crate::casm_text::assemble;
+    use crate::compile_program;
+
+    fn output_executable() -> Executable {
+        // The header of standalone runs, as built by `Executable::new`, followed by a function
+        // writing 42 to the output builtin and returning the advanced output pointer.
+        let casm = casm! {
+            ap += 1;
+            call rel 4;
+            jmp rel 0;
+            [ap + 0] = 42, ap++;
+            [ap - 1] = [[fp - 3] + 0];
+            [ap + 0] = [fp - 3] + 1, ap++;
+            ret;
+        };
+        Executable {
+            program: AssembledCairoProgram {
+                bytecode: casm
+                    .instructions
+                    .iter()
+                    .flat_map(|instruction| instruction.assemble().encode())
+                    .collect(),
+                hints: vec![],
+            },
+            entrypoints: vec![
+                ExecutableEntryPoint {
+                    builtins: vec![BuiltinName::output],
+                    offset: 0,
+                    kind: EntryPointKind::Standalone,
+                },
+                ExecutableEntryPoint {
+                    builtins: vec![BuiltinName::output],
+                    offset: 6,
+                    kind: EntryPointKind::Bootloader,
+                },
+            ],
+            debug_info: None,
+        }
+    }
+
+    #[test]
+    fn run_casm_returns_output() {
+        let request = json!({ "executable": output_executable() });
+
+        let response = run_casm(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["42"]));
+    }
+
+    /// The CASM of [output_executable], with a label for its function.
+    const OUTPUT_CASM: &str = "\
+    ap += 1;
+    call write;
+    jmp rel 0;
+write:
+    [ap + 0] = 42, ap++;
+    [ap + -1] = [[fp + -3] + 0];
+    [ap + 0] = [fp + -3] + 1, ap++;
+    ret;
+";
+
+    #[test]
+    fn assembles_casm_text() {
+        assert_eq!(assemble(OUTPUT_CASM).unwrap(), output_executable().program.bytecode);
+    }
+
+    #[test]
+    fn run_casm_runs_casm_text() {
+        let request = json!({ "casm": OUTPUT_CASM, "entry_offset": 6, "builtins": ["output"] });
+
+        let response = run_casm(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["42"]));
+    }
+
+    #[test]
+    fn run_casm_reports_invalid_casm_lines() {
+        let request = json!({ "casm": "ret;\njmp nowhere;\n" });
+
+        let response = run_casm(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["error"]["kind"], "invalid-request", "response={response}");
+        assert_eq!(response_json["error"]["message"], "Line 2: Unknown label `nowhere`.");
+    }
+
+    #[test]
+    fn run_casm_runs_compiled_program() {
+        let request = json!({
+            "crate_name": "test",
+            "files": { "lib.cairo": "#[executable]\nfn main() -> felt252 {\n    6 * 7\n}\n" }
+        });
+        let response: Value = serde_json::from_str(&compile_program(&request.to_string())).unwrap();
+        let program = response["program"].as_u64().unwrap();
+
+        let request = json!({ "program": program, "function": "main" });
+        let response = run_casm(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["42"]));
+    }
+
+    #[test]
+    fn run_casm_returns_cairo_pie() {
+        let request = json!({ "executable": output_executable(), "cairo_pie": true });
+
+        let response = run_casm(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        // The base64 encoding of the signature of zip archives, `PK\x03\x04`.
+        assert!(
+            response_json["cairo_pie"].as_str().unwrap().starts_with("UEsDBA"),
+            "response={response}"
+        );
+    }
+
+    #[test]
+    fn run_casm_returns_prover_input() {
+        let request = json!({
+            "executable": output_executable(),
+            "entrypoint": "standalone",
+            "prove_mode": true
+        });
+
+        let response = run_casm(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let prover_input = &response_json["prover_input"];
+        assert_eq!(prover_input["public_input"]["layout"], "all_cairo", "response={response}");
+        assert_eq!(prover_input["private_input"]["trace_path"], "trace.bin");
+        assert!(prover_input["execution_trace"]["trace_entries"].as_u64().unwrap() > 0);
+    }
+
+    #[cfg(feature = "prove")]
+    #[test]
+    fn prove_returns_proof_of_standalone_run() {
+        let request = json!({ "executable": output_executable(), "entrypoint": "standalone" });
+
+        let response = crate::prove(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["42"]));
+        assert!(response_json["proof"].is_object(), "response={response}");
+    }
+
+    #[cfg(feature = "prove")]
+    #[test]
+    fn prove_rejects_bootloader_entry_points() {
+        let request = json!({ "executable": output_executable() });
+
+        let response = crate::prove(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["error"]["kind"], "invalid-request", "response={response}");
+    }
+
+    #[test]
+    fn run_casm_rejects_layout_without_used_builtins() {
+        let request = json!({
+            "executable": output_executable(),
+            "entrypoint": "standalone",
+            "layout": "plain"
+        });
+
+        let response = run_casm(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["error"]["kind"], "invalid-request", "response={response}");
+        assert_eq!(
+            response_json["error"]["message"],
+            "The `plain` layout lacks the builtins used by the entry point: output."
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Assembly of CASM text, as printed by the compiler or by `compile` with `casm`, into bytecode for
+//! `run_casm`, so hand-written and edited assembly can be run without building an executable.
+
+use std::collections::HashMap;
+
+use cairo_lang_casm::instructions::{
+    AddApInstruction, AssertEqInstruction, CallInstruction, Instruction, InstructionBody,
+    JnzInstruction, JumpInstruction, RetInstruction,
+};
+use cairo_lang_casm::operand::{
+    BinOpOperand, CellRef, DerefOrImmediate, Operation, Register, ResOperand,
+};
+use cairo_lang_wasm_utils::error::ApiError;
+use num_bigint::BigInt;
+
+/// A line of CASM text assembled into bytecode.
+enum Statement {
+    /// An instruction, with the label its relative jump or call targets, if any.
+    Instruction(Instruction, Option<String>),
+    /// A raw value, written with `dw`.
+    Value(BigInt),
+}
+
+/// Assembles `text` into bytecode, through the assembler of the compiler.
+///
+/// Each line holds an instruction or a `dw` value ended by `;`, or a label ended by `:`. Relative
+/// jumps and calls may target a label by name, as in `jmp loop if [ap + -1] != 0`. Comments start
+/// with `//`. Hints are not supported.
+pub(crate) fn assemble(text: &str) -> Result<Vec<BigInt>, ApiError> {
+    let mut statements = vec![];
+    let mut labels = HashMap::new();
+    let mut offset = 0;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let line_error =
+            |message: String| ApiError::invalid_request(format!("Line {}: {message}", index + 1));
+        if let Some(label) = line.strip_suffix(':') {
+            if labels.insert(label.to_string(), offset).is_some() {
+                return Err(line_error(format!("Duplicate label `{label}`.")));
+            }
+            continue;
+        }
+        if line.starts_with("%{") {
+            return Err(line_error("Hints are not supported in CASM text.".into()));
+        }
+        let statement = line.strip_suffix(';').unwrap_or(line).trim();
+        let statement = parse_statement(statement)
+            .ok_or_else(|| line_error(format!("Invalid CASM statement `{statement}`.")))?;
+        let size = match &statement {
+            // The assembler cannot encode an `ap++` on these instructions.
+            Statement::Instruction(instruction, _)
+                if instruction.inc_ap
+                    && matches!(
+                        instruction.body,
+                        InstructionBody::AddAp(_)
+                            | InstructionBody::Call(_)
+                            | InstructionBody::Ret(_)
+                    ) =>
+            {
+                return Err(line_error("This instruction cannot have an `ap++`.".into()));
+            }
+            Statement::Instruction(instruction, _) => instruction.body.op_size(),
+            Statement::Value(_) => 1,
+        };
+        statements.push((offset, index, statement));
+        offset += size;
+    }
+
+    let mut bytecode = vec![];
+    for (offset, index, statement) in statements {
+        match statement {
+            Statement::Instruction(mut instruction, label) => {
+                if let Some(label) = label {
+                    let target = labels.get(&label).ok_or_else(|| {
+                        ApiError::invalid_request(format!(
+                            "Line {}: Unknown label `{label}`.",
+                            index + 1
+                        ))
+                    })?;
+                    let relative = DerefOrImmediate::Immediate(
+                        (BigInt::from(*target) - BigInt::from(offset)).into(),
+                    );
+                    match &mut instruction.body {
+                        InstructionBody::Call(CallInstruction { target, .. })
+                        | InstructionBody::Jump(JumpInstruction { target, .. })
+                        | InstructionBody::Jnz(JnzInstruction { jump_offset: target, .. }) => {
+                            *target = relative
+                        }
+                        _ => unreachable!("Only jumps and calls target labels."),
+                    }
+                }
+                bytecode.extend(instruction.assemble().encode());
+            }
+            Statement::Value(value) => bytecode.push(value),
+        }
+    }
+    Ok(bytecode)
+}
+
+/// Parses a statement without its `;`, returning `None` if it is invalid.
+fn parse_statement(statement: &str) -> Option<Statement> {
+    if let Some(value) = statement.strip_prefix("dw ") {
+        return Some(Statement::Value(parse_int(value)?));
+    }
+    let (statement, inc_ap) = match statement.strip_suffix(", ap++") {
+        Some(statement) => (statement.trim(), true),
+        None => (statement, false),
+    };
+    let (body, label) = parse_body(statement)?;
+    Some(Statement::Instruction(Instruction::new(body, inc_ap), label))
+}
+
+/// Parses the body of an instruction, with the label it targets instead of a relative offset.
+fn parse_body(body: &str) -> Option<(InstructionBody, Option<String>)> {
+    if body == "ret" {
+        return Some((InstructionBody::Ret(RetInstruction {}), None));
+    }
+    if let Some(operand) = body.strip_prefix("ap += ") {
+        let operand = parse_res_operand(operand)?;
+        return Some((InstructionBody::AddAp(AddApInstruction { operand }), None));
+    }
+    if let Some(target) = body.strip_prefix("call ") {
+        let (target, relative, label) = parse_target(target)?;
+        return Some((InstructionBody::Call(CallInstruction { target, relative }), label));
+    }
+    if let Some(jump) = body.strip_prefix("jmp ") {
+        if let Some((target, condition)) = jump.split_once(" if ") {
+            let condition = parse_cell_ref(condition.strip_suffix(" != 0")?)?;
+            let (jump_offset, relative, label) = parse_target(target)?;
+            if !relative {
+                return None;
+            }
+            return Some((InstructionBody::Jnz(JnzInstruction { jump_offset, condition }), label));
+        }
+        let (target, relative, label) = parse_target(jump)?;
+        return Some((InstructionBody::Jump(JumpInstruction { target, relative }), label));
+    }
+    let (qm31, assert_eq) = match body.strip_prefix("{QM31} ") {
+        Some(assert_eq) => (true, assert_eq),
+        None => (false, body),
+    };
+    let (a, b) = assert_eq.split_once(" = ")?;
+    let assert_eq = AssertEqInstruction { a: parse_cell_ref(a)?, b: parse_res_operand(b)? };
+    let body = if qm31 {
+        InstructionBody::QM31AssertEq(assert_eq)
+    } else {
+        InstructionBody::AssertEq(assert_eq)
+    };
+    Some((body, None))
+}
+
+/// Parses the target of a jump or a call: `rel <operand>`, `abs <operand>`, or a label targeted
+/// relatively. Returns the target, whether it is relative, and the label.
+fn parse_target(target: &str) -> Option<(DerefOrImmediate, bool, Option<String>)> {
+    if let Some(operand) = target.strip_prefix("rel ") {
+        return Some((parse_deref_or_immediate(operand)?, true, None));
+    }
+    if let Some(operand) = target.strip_prefix("abs ") {
+        return Some((parse_deref_or_immediate(operand)?, false, None));
+    }
+    if target.is_empty() {
+        return None;
+    }
+    // Labels of functions may hold spaces, such as in tuple types. The offset is set once the
+    // labels are known, and is immediate so that the size of the instruction is known.
+    Some((DerefOrImmediate::Immediate(0.into()), true, Some(target.to_string())))
+}
+
+/// Parses `[reg + offset]`, `[[reg + offset] + offset]`, `[reg + offset] <op> <operand>` or an
+/// immediate.
+fn parse_res_operand(operand: &str) -> Option<ResOperand> {
+    if let Some(inner) = operand.strip_prefix('[').and_then(|inner| inner.strip_suffix(']'))
+        && inner.starts_with('[')
+    {
+        let (cell, offset) = split_cell_ref(inner)?;
+        return Some(ResOperand::DoubleDeref(cell, parse_offset(offset)?));
+    }
+    if !operand.starts_with('[') {
+        return Some(ResOperand::Immediate(parse_int(operand)?.into()));
+    }
+    let (a, rest) = split_cell_ref(operand)?;
+    let rest = rest.trim();
+    if rest.is_empty() {
+        return Some(ResOperand::Deref(a));
+    }
+    let (op, b) = if let Some(b) = rest.strip_prefix('+') {
+        (Operation::Add, b)
+    } else {
+        (Operation::Mul, rest.strip_prefix('*')?)
+    };
+    Some(ResOperand::BinOp(BinOpOperand { op, a, b: parse_deref_or_immediate(b.trim())? }))
+}
+
+fn parse_deref_or_immediate(operand: &str) -> Option<DerefOrImmediate> {
+    if operand.starts_with('[') {
+        Some(DerefOrImmediate::Deref(parse_cell_ref(operand)?))
+    } else {
+        Some(DerefOrImmediate::Immediate(parse_int(operand)?.into()))
+    }
+}
+
+fn parse_cell_ref(operand: &str) -> Option<CellRef> {
+    let (cell, rest) = split_cell_ref(operand.trim())?;
+    rest.trim().is_empty().then_some(cell)
+}
+
+/// Splits `[reg + offset]` off the start of `operand`, returning the cell and the rest.
+fn split_cell_ref(operand: &str) -> Option<(CellRef, &str)> {
+    let (cell, rest) = operand.strip_prefix('[')?.split_once(']')?;
+    let cell = cell.trim();
+    let (register, offset) = match cell.find([' ', '+', '-']) {
+        Some(index) => cell.split_at(index),
+        None => (cell, ""),
+    };
+    let register = match register {
+        "ap" => Register::AP,
+        "fp" => Register::FP,
+        _ => return None,
+    };
+    let offset = if offset.trim().is_empty() { 0 } else { parse_offset(offset)? };
+    Some((CellRef { register, offset }, rest))
+}
+
+/// Parses an offset following a cell: `+ 3`, `+ -3` or `- 3`.
+fn parse_offset(offset: &str) -> Option<i16> {
+    let offset = offset.trim();
+    if let Some(offset) = offset.strip_prefix('+') {
+        offset.trim().parse().ok()
+    } else {
+        offset.strip_prefix('-')?.trim().parse::<i16>().ok()?.checked_neg()
+    }
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal integer, possibly negative.
+fn parse_int(value: &str) -> Option<BigInt> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value),
+    };
+    let value = match value.strip_prefix("0x") {
+        Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16)?,
+        None => BigInt::parse_bytes(value.as_bytes(), 10)?,
+    };
+    Some(if negative { -value } else { value })
+}
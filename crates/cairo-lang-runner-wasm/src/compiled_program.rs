@@ -0,0 +1,225 @@
+//! Compiled programs kept by the module and referred to by a handle, so a UI can compile a project
+//! once, then run, analyze and explain the program without sending its Sierra back each time. The
+//! programs are stored in [cairo_lang_wasm_utils::compiled_programs], shared with the compiler
+//! crate.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_sierra::program::{Program, StatementIdx};
+use cairo_lang_wasm_utils::compiled_programs::{
+    self, CompiledProgram, ProjectSources, StatementLocations,
+};
+use cairo_lang_wasm_utils::diagnostic_counts::{self, FileDiagnosticCounts};
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::source::{self, SourceFiles};
+use cairo_lang_wasm_utils::{corelib, limits, parse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{DiagnosticsScopeArg, InliningStrategyArg, compile_project, default_replace_ids};
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CompileProgramRequest {
+    pub crate_name: String,
+    /// The files of the crate, some of which may be hidden from the response.
+    pub files: SourceFiles,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    #[serde(default = "default_replace_ids")]
+    pub replace_ids: bool,
+    #[serde(default)]
+    pub inlining_strategy: InliningStrategyArg,
+    /// The crates whose diagnostics are reported.
+    #[serde(default)]
+    pub diagnostics_scope: DiagnosticsScopeArg,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct CompileProgramResponse {
+    pub success: bool,
+    /// The handle of the compiled program, given as the `program` of the requests using it.
+    pub program: Option<u32>,
+    pub diagnostics: String,
+    /// The number of errors and warnings of each file of `diagnostics`.
+    pub diagnostics_by_file: BTreeMap<String, FileDiagnosticCounts>,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Compiles a project to Sierra and keeps the program with its sources and the locations of its
+/// statements, returning its handle. The handle is accepted as the `program` of `run_sierra`,
+/// `run_casm`, `list_entrypoints`, `libfunc_costs`, `explain_sierra_statement` and `slice_program`
+/// requests, and of the `function_signature`, `type_layout` and `disassemble` requests of the
+/// compiler crate in a module linking both, until the program is freed by [close_program].
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn compile_program(request_json: &str) -> String {
+    let request: CompileProgramRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => return serialize_compile_program_response(Err(error), None),
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| compile_program_request(request))
+    });
+    serialize_compile_program_response(result, debug_log)
+}
+
+/// Frees a program compiled by [compile_program]. Returns whether the program existed.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn close_program(program: u32) -> bool {
+    compiled_programs::remove(program)
+}
+
+/// Returns the Sierra program of a handle returned by [compile_program].
+pub(crate) fn get(program: u32) -> Result<Program, ApiError> {
+    compiled_programs::get(program).map(|compiled| compiled.program)
+}
+
+fn compile_program_request(request: CompileProgramRequest) -> CompileProgramResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.contents.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return CompileProgramResponse { error: Some(error), ..Default::default() };
+    }
+    let project = InMemoryProject {
+        main_crate_name: request.crate_name.clone(),
+        main_crate_files: request.files.contents.clone(),
+        corelib_files: corelib::resolve(request.corelib_files.clone()),
+        main_crate_settings: None,
+    };
+    compile_project(
+        &project,
+        &request.files.hidden,
+        request.inlining_strategy,
+        request.replace_ids,
+        request.diagnostics_scope,
+        |program, locate_statement, statement_lines, diagnostics| {
+            let statements = (0..program.statements.len()).map(StatementIdx);
+            let debug_info = StatementLocations {
+                locations: statements.clone().map(locate_statement).collect(),
+                lines: statements.map(statement_lines).collect(),
+            };
+            let program_id = compiled_programs::insert(CompiledProgram {
+                project: ProjectSources {
+                    crate_name: request.crate_name,
+                    files: request.files.contents,
+                    corelib_files: request.corelib_files,
+                },
+                program,
+                debug_info,
+            });
+            CompileProgramResponse {
+                success: true,
+                program: Some(program_id),
+                diagnostics,
+                ..Default::default()
+            }
+        },
+    )
+    .unwrap_or_else(|(error, diagnostics)| CompileProgramResponse {
+        diagnostics,
+        error: Some(error),
+        ..Default::default()
+    })
+}
+
+fn serialize_compile_program_response(
+    result: Result<CompileProgramResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+) -> String {
+    let mut response = result.unwrap_or_else(|error| CompileProgramResponse {
+        error: Some(error),
+        ..Default::default()
+    });
+    response.debug_log = debug_log;
+    response.diagnostics_by_file = diagnostic_counts::by_file(&response.diagnostics);
+    serde_json::to_string(&response).expect("serialize compile program response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{close_program, compile_program};
+    use crate::{list_entrypoints, restore_state, run_sierra, snapshot_state};
+
+    #[test]
+    fn compile_program_returns_reusable_handle() {
+        let request = json!({
+            "crate_name": "test",
+            "files": { "lib.cairo": "fn main() -> felt252 { 21 * 2 }" }
+        });
+        let response = compile_program(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+        assert_eq!(response_json["success"], true, "response={response}");
+        let program = response_json["program"].as_u64().unwrap();
+
+        let request = json!({ "program": program });
+        let response = list_entrypoints(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+        assert_eq!(response_json["entrypoints"][0]["function"], "test::main");
+
+        let run = || {
+            let request = json!({ "program": program });
+            serde_json::from_str::<Value>(&run_sierra(&request.to_string())).unwrap()
+        };
+        assert_eq!(run()["values"], json!(["42"]), "response={}", run());
+
+        assert!(close_program(program as u32));
+        assert_eq!(run()["error"]["kind"], "invalid-request");
+    }
+
+    #[test]
+    fn runs_by_handle_locate_statements() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> felt252 {
+    let i = 0;
+    loop {
+        if i == 10 {
+            break;
+        }
+    };
+    i
+}
+"
+            }
+        });
+        let response: Value = serde_json::from_str(&compile_program(&request.to_string())).unwrap();
+        let program = response["program"].as_u64().unwrap();
+
+        let request =
+            json!({ "program": program, "available_gas": 1000000000000_u64, "detect_loops": true });
+        let response: Value = serde_json::from_str(&run_sierra(&request.to_string())).unwrap();
+        assert_eq!(response["error"]["kind"], "execution", "response={response}");
+        assert!(response["aborted_at"].as_str().unwrap().contains("lib.cairo:"));
+    }
+
+    #[test]
+    fn restored_state_keeps_handles() {
+        let request = json!({
+            "crate_name": "test",
+            "files": { "lib.cairo": "fn main() -> felt252 { 6 * 7 }" }
+        });
+        let response: Value = serde_json::from_str(&compile_program(&request.to_string())).unwrap();
+        let program = response["program"].as_u64().unwrap();
+        let snapshot = snapshot_state();
+        assert!(close_program(program as u32));
+
+        let response: Value = serde_json::from_str(&restore_state(&snapshot)).unwrap();
+        assert_eq!(response["success"], true, "response={response}");
+        let request = json!({ "program": program });
+        let response: Value = serde_json::from_str(&run_sierra(&request.to_string())).unwrap();
+        assert_eq!(response["values"], json!(["42"]), "response={response}");
+    }
+}
@@ -0,0 +1,294 @@
+//! View-only execution of contract classes compiled elsewhere, such as classes fetched from an RPC
+//! node, against provided storage values.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_runner::casm_run::read_array_result_as_vec;
+use cairo_lang_runner::{Arg, RunResultValue, SierraCasmRunner};
+use cairo_lang_starknet_classes::contract_class::ContractClass;
+use cairo_lang_starknet_classes::keccak::starknet_keccak;
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::{allocations, gas, logging, parse};
+use num_bigint::BigUint;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use starknet_types_core::felt::Felt as Felt252;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::resources::ResourceCosts;
+use crate::{
+    GasBreakdown, RunResponse, StarknetContext, VmResources, debug_output, events, messages,
+    panic_message, serialize_error, serialize_run_response, starknet_session,
+};
+
+/// The gas available to a call when neither the request nor the gas policy of the host sets
+/// `available_gas`.
+const DEFAULT_CALL_GAS: usize = 10_000_000;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CallContractRequest {
+    /// A Sierra contract class, as returned by `starknet_getClass`.
+    pub contract_class: serde_json::Value,
+    /// The name of an external entry point, or its selector as a `0x`-prefixed hex string.
+    pub entry_point: String,
+    #[serde(default)]
+    pub calldata: Vec<String>,
+    /// The storage of the contract, mapping storage addresses to values.
+    #[serde(default)]
+    pub storage: BTreeMap<String, String>,
+    /// The address of the called contract, `0` by default.
+    pub contract_address: Option<String>,
+    pub available_gas: Option<usize>,
+    /// The block and transaction info seen by the Starknet syscalls of the call, whose contract
+    /// address is `contract_address`.
+    #[serde(default)]
+    pub starknet_context: StarknetContext,
+    /// The id of a session opened by `open_starknet_session`. The call then starts from the
+    /// storage and the execution context of the session instead of `starknet_context`, and its
+    /// storage writes are kept for the next calls of the session unless it panics.
+    pub session: Option<u32>,
+    /// Whether to return the runner's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// Calls an external entry point of a contract class, simulating a `call` without a node.
+///
+/// Storage writes of the entry point are not persisted, but returned in the `storage` of the
+/// response. Felts are given as decimal or `0x`-prefixed
+/// hex strings, and `values` holds the returned data on success and the panic data otherwise.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn call_contract(request_json: &str) -> String {
+    let request: CallContractRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serialize_error(error);
+        }
+    };
+
+    let ((result, debug_log), allocations) = allocations::measure(|| {
+        logging::capture(request.debug, || {
+            error::catch_internal(|| {
+                call_contract_request(request).unwrap_or_else(|error| RunResponse {
+                    error: Some(error),
+                    ..Default::default()
+                })
+            })
+        })
+    });
+    serialize_run_response(result, debug_log, allocations)
+}
+
+fn call_contract_request(request: CallContractRequest) -> Result<RunResponse, ApiError> {
+    let contract_class = parse_contract_class(request.contract_class)?;
+    let entry_point = &request.entry_point;
+    let selector = match entry_point.strip_prefix("0x") {
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16).ok_or_else(|| {
+            ApiError::invalid_request(format!("Invalid selector `{entry_point}`."))
+        })?,
+        None => starknet_keccak(entry_point.as_bytes()),
+    };
+    let function_idx = contract_class
+        .entry_points_by_type
+        .external
+        .iter()
+        .find(|external| external.selector == selector)
+        .ok_or_else(|| {
+            ApiError::invalid_request(format!(
+                "Contract class has no external entry point `{entry_point}`."
+            ))
+        })?
+        .function_idx;
+    let program = contract_class
+        .extract_sierra_program(true)
+        .map_err(|error| {
+            ApiError::invalid_request(format!("Failed extracting the Sierra program: {error}"))
+        })?
+        .program;
+    let function = program.funcs.get(function_idx).cloned().ok_or_else(|| {
+        ApiError::invalid_request(format!(
+            "Entry point `{entry_point}` refers to a missing function ({function_idx})."
+        ))
+    })?;
+
+    let calldata =
+        request.calldata.iter().map(|value| parse_felt(value)).collect::<Result<Vec<_>, _>>()?;
+    let contract_address =
+        request.contract_address.as_deref().map(parse_felt).transpose()?.unwrap_or_default();
+    if request.starknet_context.contract_address.is_some() {
+        return Err(ApiError::invalid_request(
+            "The address of the called contract is given by `contract_address`.",
+        ));
+    }
+    let mut starknet_state = match request.session {
+        Some(_) if request.starknet_context != StarknetContext::default() => {
+            return Err(ApiError::invalid_request(
+                "The execution context of a session is set by `warp`, `roll` and `prank`.",
+            ));
+        }
+        Some(session) => starknet_session::call_state(session, contract_address)?,
+        None => StarknetContext {
+            contract_address: request.contract_address.clone(),
+            ..request.starknet_context
+        }
+        .starknet_state()?,
+    };
+    for (address, value) in &request.storage {
+        starknet_state.set_storage(contract_address, parse_felt(address)?, parse_felt(value)?);
+    }
+
+    let gas_policy = gas::current();
+    let available_gas =
+        request.available_gas.or(gas_policy.default_available_gas).unwrap_or(DEFAULT_CALL_GAS);
+    let runner = SierraCasmRunner::new(program, Some(Default::default()), Default::default(), None)
+        .map_err(|error| ApiError::execution(format!("Failed setting up runner: {error}")))?;
+    let result = runner
+        .run_function_with_starknet_context(
+            &function,
+            vec![Arg::Array(calldata.into_iter().map(Arg::Value).collect())],
+            gas_policy.apply(Some(available_gas)),
+            starknet_state,
+        )
+        .map_err(|error| {
+            ApiError::execution(format!("Failed to run entry point `{entry_point}`: {error}"))
+        })?;
+
+    let (panicked, values) = match result.value {
+        RunResultValue::Success(values) => {
+            (false, read_array_result_as_vec(&result.memory, &values))
+        }
+        RunResultValue::Panic(values) => (true, values),
+    };
+    if let Some(session) = request.session
+        && !panicked
+    {
+        starknet_session::save_state(session, result.starknet_state.clone())?;
+    }
+    let panic_message = panicked.then(|| panic_message::panic_message(&values));
+    let (stdout, stderr) = debug_output::split(result.stdout);
+    let stderr = panic_message::with_panic_line(stderr, panic_message.as_deref());
+    Ok(RunResponse {
+        success: !panicked,
+        panicked,
+        assertion: None,
+        panic_message,
+        values: values.into_iter().map(|felt| felt.to_string()).collect(),
+        formatted_values: None,
+        decoded_values: None,
+        values_pretty: None,
+        return_variant: None,
+        stdout,
+        stdout_truncated: result.stdout_truncated,
+        stderr,
+        storage: Some(
+            result
+                .starknet_state
+                .contract_storage(contract_address)
+                .map(|(address, value)| (format!("{:#x}", address.to_biguint()), value.to_string()))
+                .collect(),
+        ),
+        events: events::events(&result.starknet_state),
+        l2_to_l1_messages: messages::l2_to_l1_messages(&result.starknet_state),
+        gas_counter: result.gas_counter.map(|gas| gas.to_string()),
+        resources: Some(VmResources::from(&result.used_resources)),
+        gas_breakdown: Some(GasBreakdown::new(
+            &result.used_resources,
+            |token_type| runner.token_gas_cost(token_type),
+            &ResourceCosts::default(),
+        )),
+        l2_gas: None,
+        aborted_at: None,
+        cancelled: false,
+        trace_summary: None,
+        arithmetic_report: None,
+        profile: None,
+        libfunc_profile: None,
+        statement_counts: None,
+        coverage: None,
+        execution_trace: None,
+        cairo_pie: None,
+        prover_input: None,
+        proof: None,
+        functions: None,
+        mismatches: None,
+        matches_expected: None,
+        diagnostics: String::new(),
+        diagnostics_by_file: Default::default(),
+        error: None,
+        debug_log: None,
+        allocations: None,
+        cached: false,
+    })
+}
+
+/// Parses a contract class. Its ABI is not needed for the call and is dropped, as RPC nodes return
+/// it as a string rather than as JSON.
+pub(crate) fn parse_contract_class(
+    mut contract_class: serde_json::Value,
+) -> Result<ContractClass, ApiError> {
+    if let Some(fields) = contract_class.as_object_mut() {
+        fields.remove("abi");
+    }
+    serde_json::from_value(contract_class).map_err(|error| {
+        ApiError::invalid_request(format!("Failed parsing contract class: {error}"))
+    })
+}
+
+/// Parses a felt from a decimal or a `0x`-prefixed hex string.
+pub(crate) fn parse_felt(value: &str) -> Result<Felt252, ApiError> {
+    let felt = if value.starts_with("0x") {
+        Felt252::from_hex(value)
+    } else {
+        Felt252::from_dec_str(value)
+    };
+    felt.map_err(|_| ApiError::invalid_request(format!("Invalid felt `{value}`.")))
+}
+
+#[cfg(test)]
+mod tests {
+    use cairo_lang_starknet_classes::keccak::starknet_keccak;
+    use serde_json::{Value, json};
+
+    use super::call_contract;
+
+    const HELLO_STARKNET_CLASS: &str = include_str!(
+        "../../cairo-lang-starknet/test_data/hello_starknet__hello_starknet.contract_class.json"
+    );
+
+    #[test]
+    fn call_contract_reads_provided_storage() {
+        let contract_class: Value = serde_json::from_str(HELLO_STARKNET_CLASS).unwrap();
+        let balance_address = format!("{:#x}", starknet_keccak(b"balance"));
+        let request = json!({
+            "contract_class": contract_class,
+            "entry_point": "get_balance",
+            "storage": { balance_address: "42" }
+        });
+
+        let response = call_contract(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["42"]));
+    }
+
+    #[test]
+    fn call_contract_counts_syscalls() {
+        let contract_class: Value = serde_json::from_str(HELLO_STARKNET_CLASS).unwrap();
+        let request = json!({
+            "contract_class": contract_class,
+            "entry_point": "increase_balance",
+            "calldata": ["5"]
+        });
+
+        let response = call_contract(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(
+            response_json["resources"]["syscalls"],
+            json!({ "storage_read": 1, "storage_write": 1 })
+        );
+    }
+}
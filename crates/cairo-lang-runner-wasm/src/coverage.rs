@@ -0,0 +1,49 @@
+//! A line coverage report of a run, mapping the executed Sierra statements back to the Cairo
+//! sources, so an editor can highlight the lines that ran.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use cairo_lang_runner::profiling::ProfilingInfo;
+use cairo_lang_sierra::program::{Program, StatementIdx};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The coverage of the lines of a source file.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FileCoverage {
+    /// The 1-based lines of the file with code that ran.
+    pub covered: Vec<usize>,
+    /// The 1-based lines of the file with code that did not run.
+    pub uncovered: Vec<usize>,
+}
+
+/// Returns the coverage of the lines of the files containing the statements of `program`, by
+/// path.
+///
+/// `statement_lines` returns the source lines a statement was compiled from, including the call
+/// sites of the functions inlined into it. A line is covered when any of its statements ran.
+pub(crate) fn coverage(
+    program: &Program,
+    profiling_info: &ProfilingInfo,
+    statement_lines: &dyn Fn(StatementIdx) -> Vec<(String, usize)>,
+) -> BTreeMap<String, FileCoverage> {
+    // The covered lines and all the lines with code of each file.
+    let mut lines = BTreeMap::<String, (BTreeSet<usize>, BTreeSet<usize>)>::new();
+    for statement_idx in (0..program.statements.len()).map(StatementIdx) {
+        let executed = profiling_info.sierra_statement_counts.contains_key(&statement_idx);
+        for (path, line) in statement_lines(statement_idx) {
+            let (covered, all) = lines.entry(path).or_default();
+            if executed {
+                covered.insert(line);
+            }
+            all.insert(line);
+        }
+    }
+    lines
+        .into_iter()
+        .map(|(path, (covered, all))| {
+            let uncovered = all.difference(&covered).copied().collect();
+            (path, FileCoverage { covered: covered.into_iter().collect(), uncovered })
+        })
+        .collect()
+}
@@ -0,0 +1,30 @@
+//! Splitting of the output captured from the debug prints of a run into its `stdout` and
+//! `stderr`.
+
+/// The prefix [format_for_debug] gives the lines of the values other than strings.
+///
+/// [format_for_debug]: cairo_lang_runner::casm_run::format_for_debug
+const DEBUG_LINE_PREFIX: &str = "[DEBUG]\t";
+
+/// Splits the output captured from the debug prints of a run, as formatted by [format_for_debug],
+/// into its strings, the `stdout`, and the lines of its other values, such as the `print` of
+/// `PrintTrait`, the `stderr`.
+///
+/// [format_for_debug]: cairo_lang_runner::casm_run::format_for_debug
+pub(crate) fn split(output: String) -> (String, String) {
+    if !output.contains(DEBUG_LINE_PREFIX) {
+        return (output, String::new());
+    }
+    let (mut stdout, mut stderr) = (String::new(), String::new());
+    for line in output.split_inclusive('\n') {
+        // A debug line follows the output of the previous print, which may not end a line.
+        match line.find(DEBUG_LINE_PREFIX) {
+            Some(start) => {
+                stdout.push_str(&line[..start]);
+                stderr.push_str(&line[start..]);
+            }
+            None => stdout.push_str(line),
+        }
+    }
+    (stdout, stderr)
+}
@@ -0,0 +1,170 @@
+//! Validation of contract classes against the limits the network enforces on declarations, before
+//! paying for a declare transaction that would be rejected.
+
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::parse;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::contract::parse_contract_class;
+
+/// The maximal number of felts of the CASM bytecode of a declared class on Starknet.
+const DEFAULT_MAX_BYTECODE_SIZE: usize = 81_920;
+/// The maximal size in bytes of a declared Sierra class on Starknet.
+const DEFAULT_MAX_CONTRACT_CLASS_SIZE: usize = 4_089_446;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ValidateDeclareRequest {
+    /// A Sierra contract class, as written by Scarb or returned by `starknet_getClass`.
+    pub contract_class: serde_json::Value,
+    /// The maximal number of felts of the CASM bytecode, 81,920 by default.
+    pub max_bytecode_size: Option<usize>,
+    /// The maximal size in bytes of the contract class JSON, 4,089,446 by default.
+    pub max_contract_class_size: Option<usize>,
+    /// The maximal size in bytes of the ABI, unchecked by default.
+    pub max_abi_size: Option<usize>,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// A measure of the contract class checked against a limit.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DeclareCheck {
+    /// `bytecode_size` (in felts), `contract_class_size` or `abi_size` (in bytes).
+    pub name: String,
+    pub size: usize,
+    pub limit: Option<usize>,
+    /// How far below the limit the size is, negative when it exceeds it.
+    pub margin: Option<i64>,
+    pub exceeded: bool,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct ValidateDeclareResponse {
+    /// Whether the class compiled and is within all the limits.
+    pub success: bool,
+    pub checks: Vec<DeclareCheck>,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Compiles a contract class to CASM, as the sequencer does, and checks its sizes against the
+/// limits of declarations.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn validate_declare(request_json: &str) -> String {
+    let request: ValidateDeclareRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serde_json::to_string(&ValidateDeclareResponse {
+                error: Some(error),
+                ..Default::default()
+            })
+            .expect("serialize error response");
+        }
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| {
+            validate_declare_request(request).unwrap_or_else(|error| ValidateDeclareResponse {
+                error: Some(error),
+                ..Default::default()
+            })
+        })
+    });
+    let mut response = result.unwrap_or_else(|error| ValidateDeclareResponse {
+        error: Some(error),
+        ..Default::default()
+    });
+    response.debug_log = debug_log;
+
+    serde_json::to_string(&response).expect("serialize validate declare response")
+}
+
+fn validate_declare_request(
+    request: ValidateDeclareRequest,
+) -> Result<ValidateDeclareResponse, ApiError> {
+    let contract_class_size = request.contract_class.to_string().len();
+    // RPC nodes return the ABI as a string, and Scarb writes it as JSON.
+    let abi_size = match request.contract_class.get("abi") {
+        Some(serde_json::Value::String(abi)) => abi.len(),
+        Some(abi) => abi.to_string().len(),
+        None => 0,
+    };
+
+    let contract_class = parse_contract_class(request.contract_class)?;
+    let extracted_program = contract_class.extract_sierra_program(false).map_err(|error| {
+        ApiError::invalid_request(format!("Failed extracting the Sierra program: {error}"))
+    })?;
+    let casm_class = CasmContractClass::from_contract_class(
+        contract_class,
+        extracted_program,
+        false,
+        usize::MAX,
+    )
+    .map_err(|error| {
+        ApiError::compilation(format!("Failed compiling the contract class to CASM: {error}"))
+    })?;
+
+    let checks = vec![
+        check(
+            "bytecode_size",
+            casm_class.bytecode.len(),
+            Some(request.max_bytecode_size.unwrap_or(DEFAULT_MAX_BYTECODE_SIZE)),
+        ),
+        check(
+            "contract_class_size",
+            contract_class_size,
+            Some(request.max_contract_class_size.unwrap_or(DEFAULT_MAX_CONTRACT_CLASS_SIZE)),
+        ),
+        check("abi_size", abi_size, request.max_abi_size),
+    ];
+    Ok(ValidateDeclareResponse {
+        success: checks.iter().all(|check| !check.exceeded),
+        checks,
+        ..Default::default()
+    })
+}
+
+fn check(name: &str, size: usize, limit: Option<usize>) -> DeclareCheck {
+    DeclareCheck {
+        name: name.to_string(),
+        size,
+        limit,
+        margin: limit.map(|limit| limit as i64 - size as i64),
+        exceeded: limit.is_some_and(|limit| size > limit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::validate_declare;
+
+    const HELLO_STARKNET_CLASS: &str = include_str!(
+        "../../cairo-lang-starknet/test_data/hello_starknet__hello_starknet.contract_class.json"
+    );
+
+    #[test]
+    fn validate_declare_reports_margins() {
+        let contract_class: Value = serde_json::from_str(HELLO_STARKNET_CLASS).unwrap();
+        let request = json!({ "contract_class": contract_class, "max_bytecode_size": 10 });
+
+        let response = validate_declare(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], false, "response={response}");
+        let checks = response_json["checks"].as_array().unwrap();
+        let bytecode = &checks[0];
+        assert_eq!(bytecode["name"], "bytecode_size");
+        assert_eq!(bytecode["exceeded"], true);
+        assert_eq!(bytecode["margin"], 10 - bytecode["size"].as_i64().unwrap());
+        assert_eq!(checks[1]["exceeded"], false);
+        assert_eq!(checks[2]["limit"], Value::Null);
+    }
+}
@@ -0,0 +1,279 @@
+//! Checking that a function runs the same way each time, before attempting to prove its runs, by
+//! comparing the outputs and traces of repeated runs.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_wasm_utils::diagnostic_counts::{self, FileDiagnosticCounts};
+use cairo_lang_wasm_utils::error::{self, ApiError, ErrorKind};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{corelib, limits, parse};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::grade::Mismatch;
+use crate::{
+    CompileAndRunRequest, ProgramRunner, RunOptions, RunResponse, compile_project, main_synthesis,
+};
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct DeterminismResponse {
+    /// Whether all the runs had the output and the trace of the first one.
+    pub deterministic: bool,
+    /// The number of runs made.
+    pub runs: usize,
+    /// The runs differing from the first one.
+    pub divergences: Vec<Divergence>,
+    /// The first run, with its trace only if `collect_trace` is set.
+    pub run: Option<RunResponse>,
+    pub diagnostics: String,
+    /// The number of errors and warnings of each file of `diagnostics`.
+    pub diagnostics_by_file: BTreeMap<String, FileDiagnosticCounts>,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// A run differing from the first one.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Divergence {
+    /// The index of the run, the first one being 0.
+    pub run: usize,
+    /// The fields of the run differing from those of the first one, given as `expected`. The
+    /// `execution_trace` field is given by its numbers of trace entries and memory cells.
+    pub mismatches: Vec<Mismatch>,
+}
+
+/// Compiles a project once, then runs its function `runs` times and compares the outputs and
+/// traces of the runs with those of the first one.
+///
+/// The request is a `compile_and_run` request.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn check_determinism(request_json: &str, runs: usize) -> String {
+    let request: CompileAndRunRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => return serialize_determinism_response(Err(error), None),
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| check_determinism_request(request, runs))
+    });
+    serialize_determinism_response(result, debug_log)
+}
+
+fn check_determinism_request(request: CompileAndRunRequest, runs: usize) -> DeterminismResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.contents.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return DeterminismResponse { error: Some(error), ..Default::default() };
+    }
+    let invalid_request = |message: &str| DeterminismResponse {
+        error: Some(ApiError::invalid_request(message)),
+        ..Default::default()
+    };
+    if runs < 2 {
+        return invalid_request("At least 2 runs are needed to compare them.");
+    }
+    if !request.args.is_empty() && request.typed_args.is_some() {
+        return invalid_request("`args` and `typed_args` are exclusive.");
+    }
+    if request.functions.is_some()
+        || request.executable_args.is_some()
+        || request.expected.is_some()
+    {
+        return invalid_request(
+            "`functions`, `executable_args` and `expected` are not supported when checking \
+             determinism.",
+        );
+    }
+    let mut args = vec![];
+    if let Err(error) = request.args.iter().try_for_each(|arg| arg.encode(&mut args)) {
+        return DeterminismResponse { error: Some(error), ..Default::default() };
+    }
+
+    let starknet_state = match request.starknet_context.starknet_state() {
+        Ok(starknet_state) => starknet_state,
+        Err(error) => return DeterminismResponse { error: Some(error), ..Default::default() },
+    };
+
+    let mut project = InMemoryProject {
+        main_crate_name: request.crate_name,
+        main_crate_files: request.files.contents,
+        corelib_files: corelib::resolve(request.corelib_files),
+        main_crate_settings: None,
+    };
+    if let Some(synthesized) = &request.synthesize_main
+        && let Err(error) = main_synthesis::add_main(&mut project, synthesized)
+    {
+        return DeterminismResponse { error: Some(error), ..Default::default() };
+    }
+    compile_project(
+        &project,
+        &request.files.hidden,
+        request.inlining_strategy,
+        request.replace_ids,
+        request.diagnostics_scope,
+        |program, locate_statement, _, diagnostics| {
+            let options = RunOptions {
+                available_gas: request.available_gas,
+                max_steps: request.max_steps,
+                max_stdout_bytes: request.max_stdout_bytes,
+                // The traces of the runs are compared.
+                collect_trace: true,
+                gas_costs: request.gas_costs,
+                report_l2_gas: request.report_l2_gas,
+                value_format: request.value_format,
+                starknet_state,
+                locate_statement: Some(locate_statement),
+                ..Default::default()
+            };
+            let runner = match ProgramRunner::new(program, &options) {
+                Ok(runner) => runner,
+                Err(error) => {
+                    return DeterminismResponse {
+                        diagnostics,
+                        error: Some(error),
+                        ..Default::default()
+                    };
+                }
+            };
+            let run = || {
+                runner.run(
+                    &request.function,
+                    args.clone(),
+                    request.typed_args.clone(),
+                    request.available_gas,
+                )
+            };
+
+            let mut first = None;
+            let mut divergences = vec![];
+            for index in 0..runs {
+                let response = run();
+                if response.error.as_ref().is_some_and(|error| error.kind == ErrorKind::Cancelled) {
+                    return DeterminismResponse {
+                        runs: index + 1,
+                        diagnostics,
+                        error: response.error,
+                        ..Default::default()
+                    };
+                }
+                let Some(first) = &first else {
+                    first = Some(response);
+                    continue;
+                };
+                let mismatches = compare(first, &response);
+                if !mismatches.is_empty() {
+                    divergences.push(Divergence { run: index, mismatches });
+                }
+            }
+            let mut first = first.expect("at least 2 runs");
+            if !request.collect_trace {
+                first.execution_trace = None;
+            }
+            DeterminismResponse {
+                deterministic: divergences.is_empty(),
+                runs,
+                divergences,
+                run: Some(first),
+                diagnostics,
+                ..Default::default()
+            }
+        },
+    )
+    .unwrap_or_else(|(error, diagnostics)| DeterminismResponse {
+        diagnostics,
+        error: Some(error),
+        ..Default::default()
+    })
+}
+
+/// Returns the fields of `run` differing from those of the `first` run.
+fn compare(first: &RunResponse, run: &RunResponse) -> Vec<Mismatch> {
+    let error_message = |run: &RunResponse| run.error.as_ref().map(|error| error.message.clone());
+    let trace_size = |run: &RunResponse| {
+        run.execution_trace.as_ref().map(|trace| {
+            serde_json::json!({
+                "trace_entries": trace.trace_entries,
+                "memory_cells": trace.memory_cells,
+            })
+        })
+    };
+    let fields: [(&'static str, Value, Value); 6] = [
+        ("panicked", first.panicked.into(), run.panicked.into()),
+        ("values", first.values.clone().into(), run.values.clone().into()),
+        ("stdout", first.stdout.clone().into(), run.stdout.clone().into()),
+        ("stderr", first.stderr.clone().into(), run.stderr.clone().into()),
+        ("gas_counter", first.gas_counter.clone().into(), run.gas_counter.clone().into()),
+        ("error", error_message(first).into(), error_message(run).into()),
+    ];
+    let mut mismatches = fields
+        .into_iter()
+        .filter(|(_, expected, actual)| expected != actual)
+        .map(|(field, expected, actual)| Mismatch { field, expected, actual })
+        .collect::<Vec<_>>();
+    let trace = |run: &RunResponse| {
+        run.execution_trace.as_ref().map(|trace| (trace.trace.clone(), trace.memory.clone()))
+    };
+    if trace(first) != trace(run) {
+        mismatches.push(Mismatch {
+            field: "execution_trace",
+            expected: trace_size(first).into(),
+            actual: trace_size(run).into(),
+        });
+    }
+    mismatches
+}
+
+fn serialize_determinism_response(
+    result: Result<DeterminismResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+) -> String {
+    let mut response = result
+        .unwrap_or_else(|error| DeterminismResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+    response.diagnostics_by_file = diagnostic_counts::by_file(&response.diagnostics);
+    serde_json::to_string(&response).expect("serialize determinism response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::check_determinism;
+
+    #[test]
+    fn check_determinism_compares_runs() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> u32 {
+    println!(\"counting\");
+    let mut i = 0;
+    while i != 10 {
+        i += 1;
+    }
+    i
+}
+"
+            },
+            "available_gas": 1000000
+        });
+
+        let response = check_determinism(&request.to_string(), 3);
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["deterministic"], true, "response={response}");
+        assert_eq!(response_json["runs"], 3);
+        assert_eq!(response_json["divergences"], json!([]));
+        assert_eq!(response_json["run"]["values"], json!(["10"]));
+        assert_eq!(response_json["run"]["execution_trace"], Value::Null);
+
+        let response = check_determinism(&request.to_string(), 1);
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+        assert_eq!(response_json["error"]["kind"], "invalid-request", "response={response}");
+    }
+}
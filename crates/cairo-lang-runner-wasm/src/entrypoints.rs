@@ -0,0 +1,271 @@
+//! Listing of the runnable functions of a program, for UIs offering a choice of the function to
+//! run.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_runnable_utils::builder::RunnableBuilder;
+use cairo_lang_sierra::ids::ConcreteTypeId;
+use cairo_lang_sierra::program::{GenericArg, Program};
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{corelib, limits, parse, source};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    DiagnosticsScopeArg, InliningStrategyArg, compile_project, compiled_program,
+    default_replace_ids, parse_sierra,
+};
+
+/// A request listing the functions of a project, given by `files`, or of a Sierra program.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListEntrypointsRequest {
+    pub crate_name: Option<String>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub files: Option<BTreeMap<String, String>>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    #[serde(default = "default_replace_ids")]
+    pub replace_ids: bool,
+    #[serde(default)]
+    pub inlining_strategy: InliningStrategyArg,
+    /// A Sierra program, exclusive with `files`.
+    pub sierra: Option<String>,
+    /// The handle of a program compiled by `compile_program`, exclusive with `files` and `sierra`.
+    pub program: Option<u32>,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// A runnable function.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Entrypoint {
+    /// The full path of the function.
+    pub function: String,
+    /// The Sierra types of the parameters, without the builtins.
+    pub params: Vec<String>,
+    /// The Sierra types of the returned values, without the builtins, unwrapped from the
+    /// `PanicResult` of panicable functions.
+    pub return_types: Vec<String>,
+    /// Whether the function may panic.
+    pub panicable: bool,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct ListEntrypointsResponse {
+    pub success: bool,
+    pub entrypoints: Vec<Entrypoint>,
+    pub diagnostics: String,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Lists the functions of a project or a Sierra program that can be run, excluding the corelib's.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn list_entrypoints(request_json: &str) -> String {
+    let request: ListEntrypointsRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serialize_entrypoints_response(Err(error), None);
+        }
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| list_entrypoints_request(request))
+    });
+    serialize_entrypoints_response(result, debug_log)
+}
+
+fn list_entrypoints_request(request: ListEntrypointsRequest) -> ListEntrypointsResponse {
+    match (request.files, request.sierra, request.program) {
+        (Some(files), None, None) => {
+            let Some(crate_name) = request.crate_name else {
+                return ListEntrypointsResponse {
+                    error: Some(ApiError::invalid_request(
+                        "`crate_name` is required with `files`.",
+                    )),
+                    ..Default::default()
+                };
+            };
+            if let Err(error) = limits::current()
+                .check_files(files.iter().chain(request.corelib_files.iter().flatten()))
+            {
+                return ListEntrypointsResponse { error: Some(error), ..Default::default() };
+            }
+            let project = InMemoryProject {
+                main_crate_name: crate_name,
+                main_crate_files: files,
+                corelib_files: corelib::resolve(request.corelib_files),
+                main_crate_settings: None,
+            };
+            compile_project(
+                &project,
+                &Default::default(),
+                request.inlining_strategy,
+                request.replace_ids,
+                DiagnosticsScopeArg::All,
+                |program, _, _, diagnostics| {
+                    let mut response = list_program_entrypoints(program);
+                    response.diagnostics = diagnostics;
+                    response
+                },
+            )
+            .unwrap_or_else(|(error, diagnostics)| ListEntrypointsResponse {
+                diagnostics,
+                error: Some(error),
+                ..Default::default()
+            })
+        }
+        (None, Some(sierra), None) => {
+            if let Err(error) = limits::current().check_source("Sierra program", &sierra) {
+                return ListEntrypointsResponse { error: Some(error), ..Default::default() };
+            }
+            match parse_sierra(&sierra) {
+                Ok(program) => list_program_entrypoints(program),
+                Err(error) => ListEntrypointsResponse { error: Some(error), ..Default::default() },
+            }
+        }
+        (None, None, Some(program)) => match compiled_program::get(program) {
+            Ok(program) => list_program_entrypoints(program),
+            Err(error) => ListEntrypointsResponse { error: Some(error), ..Default::default() },
+        },
+        _ => ListEntrypointsResponse {
+            error: Some(ApiError::invalid_request(
+                "Exactly one of `files`, `sierra` and `program` is required.",
+            )),
+            ..Default::default()
+        },
+    }
+}
+
+fn list_program_entrypoints(program: Program) -> ListEntrypointsResponse {
+    let builder = match RunnableBuilder::new(program, Some(Default::default())) {
+        Ok(builder) => builder,
+        Err(error) => {
+            return ListEntrypointsResponse {
+                error: Some(ApiError::execution(format!("Failed setting up runner: {error}"))),
+                ..Default::default()
+            };
+        }
+    };
+    let entrypoints = builder
+        .sierra_program()
+        .funcs
+        .iter()
+        .filter_map(|func| {
+            let function = func.id.debug_name.as_deref().filter(|name| is_user_function(name))?;
+            let user_types = |types: &[ConcreteTypeId]| {
+                types
+                    .iter()
+                    .filter(|ty| builder.is_user_arg_type(&builder.type_long_id(ty).generic_id))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            };
+            let mut return_types = user_types(&func.signature.ret_types);
+            let panic_result_ok_type = match &return_types[..] {
+                [ty] => match &builder.type_long_id(ty).generic_args[..] {
+                    [GenericArg::UserType(user_type), GenericArg::Type(ok_type), ..]
+                        if user_type.debug_name.as_deref().is_some_and(|name| {
+                            name.starts_with("core::panics::PanicResult::")
+                        }) =>
+                    {
+                        Some(ok_type.clone())
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+            // The values of a `PanicResult` on success are the members of its first variant, a
+            // tuple.
+            if let Some(ok_type) = &panic_result_ok_type {
+                return_types = builder
+                    .type_long_id(ok_type)
+                    .generic_args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        GenericArg::Type(ty) => Some(ty.clone()),
+                        _ => None,
+                    })
+                    .collect();
+            }
+            Some(Entrypoint {
+                function: function.to_string(),
+                params: user_types(&func.signature.param_types)
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                return_types: return_types.iter().map(ToString::to_string).collect(),
+                panicable: panic_result_ok_type.is_some(),
+            })
+        })
+        .collect();
+    ListEntrypointsResponse { success: true, entrypoints, ..Default::default() }
+}
+
+/// Returns whether the function named `function` is written by users, rather than in the corelib or
+/// generated by the compiler.
+pub(crate) fn is_user_function(function: &str) -> bool {
+    // Loops are compiled to functions named after the expression, e.g. `main[expr12]`.
+    !function.starts_with("core::") && !function.contains('[')
+}
+
+fn serialize_entrypoints_response(
+    result: Result<ListEntrypointsResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+) -> String {
+    let mut response = result.unwrap_or_else(|error| ListEntrypointsResponse {
+        error: Some(error),
+        ..Default::default()
+    });
+    response.debug_log = debug_log;
+    serde_json::to_string(&response).expect("serialize list entrypoints response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::list_entrypoints;
+
+    #[test]
+    fn list_entrypoints_reports_user_functions() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> felt252 {
+    add(1, 2).into()
+}
+
+#[inline(never)]
+fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+"
+            }
+        });
+
+        let response = list_entrypoints(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(
+            response_json["entrypoints"],
+            json!([
+                { "function": "test::main", "params": [], "return_types": ["felt252"], "panicable": true },
+                {
+                    "function": "test::add",
+                    "params": ["u32", "u32"],
+                    "return_types": ["u32"],
+                    "panicable": true
+                }
+            ])
+        );
+    }
+}
@@ -0,0 +1,71 @@
+//! The Starknet events emitted by a run, through which the logic of contracts is often only
+//! observable.
+
+use cairo_lang_runner::StarknetState;
+use schemars::JsonSchema;
+use serde::Serialize;
+use starknet_types_core::felt::Felt as Felt252;
+
+/// An event emitted by a contract, with its felts as hex strings, as Starknet nodes return them.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct Event {
+    /// The address of the emitting contract.
+    pub from_address: String,
+    /// The keys of the event, the first one being the selector of its name.
+    pub keys: Vec<String>,
+    pub data: Vec<String>,
+}
+
+/// Returns the events emitted during a run, in the order they were emitted. Events emitted by
+/// reverted calls are dropped with the rest of their state.
+pub(crate) fn events(starknet_state: &StarknetState) -> Vec<Event> {
+    let hex = |felts: &[Felt252]| {
+        felts.iter().map(|felt| format!("{:#x}", felt.to_biguint())).collect::<Vec<_>>()
+    };
+    starknet_state
+        .emitted_events()
+        .iter()
+        .map(|event| Event {
+            from_address: format!("{:#x}", event.from_address.to_biguint()),
+            keys: hex(&event.keys),
+            data: hex(&event.data),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use crate::compile_and_run;
+
+    #[test]
+    fn compile_and_run_returns_events() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() {
+    starknet::syscalls::emit_event_syscall(array!['Transfer', 1].span(), array![16].span())
+        .unwrap();
+}
+"
+            },
+            "available_gas": 1000000,
+            "starknet_context": { "contract_address": "0x100" }
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(
+            response_json["events"],
+            json!([{
+                "from_address": "0x100",
+                "keys": ["0x5472616e73666572", "0x1"],
+                "data": ["0x10"]
+            }])
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! A library of example programs, shipped with the module so that playgrounds don't need to keep
+//! their own copies in sync with the compiler version.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// An example program. `crate_name`, `files`, `function` and `available_gas` are named after the
+/// fields of a `compile_and_run` request, so an example can be spread into one directly.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Example {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub crate_name: &'static str,
+    pub files: BTreeMap<&'static str, &'static str>,
+    pub function: &'static str,
+    pub available_gas: Option<usize>,
+}
+
+/// Returns the example programs.
+pub fn all() -> Vec<Example> {
+    vec![
+        Example {
+            name: "hello_world",
+            description: "Prints a greeting with `println!`.",
+            crate_name: "hello_world",
+            files: BTreeMap::from([(
+                "lib.cairo",
+                include_str!("../example_programs/hello_world/lib.cairo"),
+            )]),
+            function: "::main",
+            available_gas: Some(1000000),
+        },
+        Example {
+            name: "fibonacci",
+            description: "Computes a Fibonacci number with a loop and returns it.",
+            crate_name: "fibonacci",
+            files: BTreeMap::from([(
+                "lib.cairo",
+                include_str!("../example_programs/fibonacci/lib.cairo"),
+            )]),
+            function: "::main",
+            available_gas: Some(1000000),
+        },
+        Example {
+            name: "erc20",
+            description: "An ERC20-style token with transfers and allowances, kept in \
+                          dictionaries so it runs without a Starknet context.",
+            crate_name: "erc20",
+            files: BTreeMap::from([
+                ("lib.cairo", include_str!("../example_programs/erc20/lib.cairo")),
+                ("token.cairo", include_str!("../example_programs/erc20/token.cairo")),
+            ]),
+            function: "::main",
+            available_gas: Some(10000000),
+        },
+        Example {
+            name: "storage",
+            description: "A key-value store modeled on contract storage, with a write log.",
+            crate_name: "storage",
+            files: BTreeMap::from([(
+                "lib.cairo",
+                include_str!("../example_programs/storage/lib.cairo"),
+            )]),
+            function: "::main",
+            available_gas: Some(10000000),
+        },
+    ]
+}
+
+/// Returns the example programs as a JSON array.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn examples() -> String {
+    serde_json::to_string(&all()).expect("serialize examples")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::all;
+    use crate::compile_and_run;
+
+    #[test]
+    fn examples_run_successfully() {
+        let expected_values = [
+            ("hello_world", json!([])),
+            ("fibonacci", json!(["6765"])),
+            ("erc20", json!(["190"])),
+            ("storage", json!(["2"])),
+        ];
+        for (example, (name, values)) in all().into_iter().zip(expected_values) {
+            assert_eq!(example.name, name);
+            let request = serde_json::to_string(&example).unwrap();
+
+            let response = compile_and_run(&request);
+            let response_json: Value =
+                serde_json::from_str(&response).expect("valid JSON response");
+
+            assert_eq!(response_json["success"], true, "example={name}, response={response}");
+            assert_eq!(response_json["values"], values, "example={name}");
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! The relocated trace and memory of a run, encoded as the trace and memory files of
+//! `cairo-execute`, for proving and debugging tools running in the same page.
+
+use cairo_vm::vm::trace::trace_entry::RelocatedTraceEntry;
+use schemars::JsonSchema;
+use serde::Serialize;
+use starknet_types_core::felt::Felt as Felt252;
+
+/// The alphabet of the standard base64 encoding.
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The relocated trace and memory of a run, in base64.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ExecutionTrace {
+    /// The entries of the trace, each encoded as its `ap`, `fp` and `pc` in 8 little-endian bytes.
+    pub trace: String,
+    /// The number of entries of `trace`.
+    pub trace_entries: usize,
+    /// The memory cells set by the run, each encoded as its address in 8 little-endian bytes and
+    /// its value in 32 little-endian bytes.
+    pub memory: String,
+    /// The number of cells of `memory`.
+    pub memory_cells: usize,
+}
+
+/// Encodes the relocated `trace` and `memory` of a run.
+pub(crate) fn export(trace: &[RelocatedTraceEntry], memory: &[Option<Felt252>]) -> ExecutionTrace {
+    let mut trace_bytes = Vec::with_capacity(trace.len() * 24);
+    for entry in trace {
+        for register in [entry.ap, entry.fp, entry.pc] {
+            trace_bytes.extend((register as u64).to_le_bytes());
+        }
+    }
+    let mut memory_bytes = vec![];
+    let mut memory_cells = 0;
+    for (address, value) in memory.iter().enumerate() {
+        let Some(value) = value else {
+            continue;
+        };
+        memory_bytes.extend((address as u64).to_le_bytes());
+        memory_bytes.extend(value.to_bytes_le());
+        memory_cells += 1;
+    }
+    ExecutionTrace {
+        trace: base64(&trace_bytes),
+        trace_entries: trace.len(),
+        memory: base64(&memory_bytes),
+        memory_cells,
+    }
+}
+
+/// Encodes `bytes` in padded standard base64.
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (idx, byte)| group | (u32::from(*byte) << (16 - 8 * idx)));
+        // A chunk of `n` bytes is encoded by `n + 1` characters, padded to 4.
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[((group >> (18 - 6 * idx)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
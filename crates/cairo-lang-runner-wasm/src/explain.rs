@@ -0,0 +1,325 @@
+//! Explanation of a statement of a Sierra program, for hover tooltips of Sierra viewers.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_sierra::extensions::ConcreteLibfunc;
+use cairo_lang_sierra::extensions::core::{CoreLibfunc, CoreType};
+use cairo_lang_sierra::program::{Program, Statement, StatementIdx};
+use cairo_lang_sierra::program_registry::ProgramRegistry;
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{corelib, limits, parse, source};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    DiagnosticsScopeArg, InliningStrategyArg, compile_project, compiled_program,
+    default_replace_ids, parse_sierra,
+};
+
+/// What the common generic libfuncs do, by generic libfunc.
+const LIBFUNC_DOCS: [(&str, &str); 32] = [
+    ("store_temp", "Stores a value in a temporary memory cell, so it can be used after a call."),
+    ("store_local", "Stores a value in a local memory cell of the function's frame."),
+    ("alloc_local", "Allocates a local memory cell, to be filled by `store_local`."),
+    ("finalize_locals", "Marks the end of the local allocations of the function's frame."),
+    ("rename", "Gives a value a new variable id, without generating any code."),
+    ("dup", "Duplicates a value, which must be `Copy`."),
+    ("drop", "Drops a value, which must be `Drop`."),
+    (
+        "branch_align",
+        "Aligns the gas and `ap` changes of a branch with those of the other branches.",
+    ),
+    ("jump", "Jumps to another statement unconditionally."),
+    ("function_call", "Calls a user function of the program."),
+    ("disable_ap_tracking", "Stops tracking `ap` changes, allowing branches of different lengths."),
+    ("enable_ap_tracking", "Resumes tracking `ap` changes."),
+    ("felt252_const", "Produces a constant `felt252`."),
+    ("felt252_add", "Adds two `felt252`s modulo the field prime."),
+    ("felt252_sub", "Subtracts two `felt252`s modulo the field prime."),
+    ("felt252_mul", "Multiplies two `felt252`s modulo the field prime."),
+    (
+        "felt252_is_zero",
+        "Branches on whether a `felt252` is zero, giving it as `NonZero` otherwise.",
+    ),
+    ("enum_init", "Builds an enum value of one of its variants."),
+    ("enum_match", "Branches on the variant of an enum value, giving its inner value."),
+    ("struct_construct", "Builds a struct value from its members."),
+    ("struct_deconstruct", "Splits a struct value into its members."),
+    ("array_new", "Creates an empty array."),
+    ("array_append", "Appends a value to the end of an array."),
+    ("array_pop_front", "Removes the first value of an array, branching on whether it was empty."),
+    (
+        "array_get",
+        "Gets a snapshot of a value of an array, branching on whether the index is valid.",
+    ),
+    ("array_len", "Returns the length of an array."),
+    ("snapshot_take", "Takes a snapshot of a value, keeping the original."),
+    (
+        "withdraw_gas",
+        "Withdraws the gas of the following code, branching on whether enough is left.",
+    ),
+    ("redeposit_gas", "Returns the gas unused by a branch to the gas counter."),
+    ("get_builtin_costs", "Gets the table of the gas costs of the builtins."),
+    ("u128_overflowing_add", "Adds two `u128`s, branching on whether the sum overflows."),
+    ("u128_overflowing_sub", "Subtracts two `u128`s, branching on whether the result underflows."),
+];
+
+/// A request explaining a statement of a project, given by `files`, or of a Sierra program.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ExplainStatementRequest {
+    pub crate_name: Option<String>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub files: Option<BTreeMap<String, String>>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    #[serde(default = "default_replace_ids")]
+    pub replace_ids: bool,
+    #[serde(default)]
+    pub inlining_strategy: InliningStrategyArg,
+    /// A Sierra program, exclusive with `files`.
+    pub sierra: Option<String>,
+    /// The handle of a program compiled by `compile_program`, exclusive with `files` and `sierra`.
+    pub program: Option<u32>,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct ExplainStatementResponse {
+    pub success: bool,
+    /// The statement, as in the text of the program.
+    pub statement: Option<String>,
+    /// The function holding the statement.
+    pub function: Option<String>,
+    /// The concrete libfunc invoked by the statement, e.g. `array_append<felt252>`, `None` for
+    /// `return` statements.
+    pub libfunc: Option<String>,
+    /// What the generic libfunc does, when documented.
+    pub documentation: Option<String>,
+    /// The types of the inputs of the libfunc in this instantiation.
+    pub params: Vec<String>,
+    /// The types of the outputs of each branch of the libfunc in this instantiation.
+    pub branches: Vec<Vec<String>>,
+    /// The source location the statement was compiled from, as `path:line`, for projects.
+    pub location: Option<String>,
+    pub diagnostics: String,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Explains the statement at `index` of a project or a Sierra program: its libfunc, the types it
+/// is instantiated with and the source location it comes from.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn explain_sierra_statement(request_json: &str, index: usize) -> String {
+    let request: ExplainStatementRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serialize_explain_response(Err(error), None);
+        }
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| explain_statement_request(request, index))
+    });
+    serialize_explain_response(result, debug_log)
+}
+
+fn explain_statement_request(
+    request: ExplainStatementRequest,
+    index: usize,
+) -> ExplainStatementResponse {
+    match (request.files, request.sierra, request.program) {
+        (Some(files), None, None) => {
+            let Some(crate_name) = request.crate_name else {
+                return ExplainStatementResponse {
+                    error: Some(ApiError::invalid_request(
+                        "`crate_name` is required with `files`.",
+                    )),
+                    ..Default::default()
+                };
+            };
+            if let Err(error) = limits::current()
+                .check_files(files.iter().chain(request.corelib_files.iter().flatten()))
+            {
+                return ExplainStatementResponse { error: Some(error), ..Default::default() };
+            }
+            let project = InMemoryProject {
+                main_crate_name: crate_name,
+                main_crate_files: files,
+                corelib_files: corelib::resolve(request.corelib_files),
+                main_crate_settings: None,
+            };
+            compile_project(
+                &project,
+                &Default::default(),
+                request.inlining_strategy,
+                request.replace_ids,
+                DiagnosticsScopeArg::All,
+                |program, locate_statement, _, diagnostics| {
+                    let mut response = explain_statement(program, index);
+                    if response.success {
+                        response.location = locate_statement(StatementIdx(index));
+                    }
+                    response.diagnostics = diagnostics;
+                    response
+                },
+            )
+            .unwrap_or_else(|(error, diagnostics)| ExplainStatementResponse {
+                diagnostics,
+                error: Some(error),
+                ..Default::default()
+            })
+        }
+        (None, Some(sierra), None) => {
+            if let Err(error) = limits::current().check_source("Sierra program", &sierra) {
+                return ExplainStatementResponse { error: Some(error), ..Default::default() };
+            }
+            match parse_sierra(&sierra) {
+                Ok(program) => explain_statement(program, index),
+                Err(error) => ExplainStatementResponse { error: Some(error), ..Default::default() },
+            }
+        }
+        (None, None, Some(program)) => match compiled_program::get(program) {
+            Ok(program) => explain_statement(program, index),
+            Err(error) => ExplainStatementResponse { error: Some(error), ..Default::default() },
+        },
+        _ => ExplainStatementResponse {
+            error: Some(ApiError::invalid_request(
+                "Exactly one of `files`, `sierra` and `program` is required.",
+            )),
+            ..Default::default()
+        },
+    }
+}
+
+fn explain_statement(program: Program, index: usize) -> ExplainStatementResponse {
+    let Some(statement) = program.statements.get(index) else {
+        return ExplainStatementResponse {
+            error: Some(ApiError::invalid_request(format!(
+                "Statement {index} is out of range: the program has {} statements.",
+                program.statements.len()
+            ))),
+            ..Default::default()
+        };
+    };
+    // The function holding the statement is the last one starting before it.
+    let function = program
+        .funcs
+        .iter()
+        .filter(|func| func.entry_point.0 <= index)
+        .max_by_key(|func| func.entry_point.0)
+        .map(|func| func.id.to_string());
+    let Statement::Invocation(invocation) = statement else {
+        return ExplainStatementResponse {
+            success: true,
+            statement: Some(statement.to_string()),
+            function,
+            documentation: Some("Returns the given variables from the function.".to_string()),
+            ..Default::default()
+        };
+    };
+    let long_id = program
+        .libfunc_declarations
+        .iter()
+        .find(|declaration| declaration.id == invocation.libfunc_id)
+        .map(|declaration| &declaration.long_id);
+    let registry = match ProgramRegistry::<CoreType, CoreLibfunc>::new(&program) {
+        Ok(registry) => registry,
+        Err(error) => {
+            return ExplainStatementResponse {
+                error: Some(ApiError::invalid_request(format!(
+                    "Failed building the program registry: {error}"
+                ))),
+                ..Default::default()
+            };
+        }
+    };
+    let libfunc = registry.get_libfunc(&invocation.libfunc_id).expect("declared libfunc");
+    ExplainStatementResponse {
+        success: true,
+        statement: Some(statement.to_string()),
+        function,
+        libfunc: Some(
+            long_id.map_or_else(|| invocation.libfunc_id.to_string(), ToString::to_string),
+        ),
+        documentation: long_id.and_then(|long_id| {
+            LIBFUNC_DOCS
+                .iter()
+                .find(|(name, _)| *name == long_id.generic_id.0.as_str())
+                .map(|(_, documentation)| documentation.to_string())
+        }),
+        params: libfunc.param_signatures().iter().map(|param| param.ty.to_string()).collect(),
+        branches: libfunc
+            .branch_signatures()
+            .iter()
+            .map(|branch| branch.vars.iter().map(|var| var.ty.to_string()).collect())
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn serialize_explain_response(
+    result: Result<ExplainStatementResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+) -> String {
+    let mut response = result.unwrap_or_else(|error| ExplainStatementResponse {
+        error: Some(error),
+        ..Default::default()
+    });
+    response.debug_log = debug_log;
+    serde_json::to_string(&response).expect("serialize explain statement response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::explain_sierra_statement;
+
+    #[test]
+    fn explain_sierra_statement_describes_libfunc_and_location() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> Array<felt252> {
+    let mut values = array![];
+    values.append(7);
+    values
+}
+"
+            }
+        });
+
+        let statement = |index: usize| -> Value {
+            serde_json::from_str(&explain_sierra_statement(&request.to_string(), index))
+                .expect("valid JSON response")
+        };
+        let index = (0..1000)
+            .find(|index| {
+                statement(*index)["libfunc"]
+                    .as_str()
+                    .is_some_and(|libfunc| libfunc.starts_with("array_append"))
+            })
+            .unwrap();
+        let response = statement(index);
+
+        assert_eq!(response["success"], true, "response={response}");
+        assert_eq!(response["function"], "test::main");
+        assert_eq!(response["libfunc"], "array_append<felt252>");
+        assert_eq!(response["documentation"], "Appends a value to the end of an array.");
+        assert_eq!(response["params"], json!(["Array<felt252>", "felt252"]));
+        assert_eq!(response["branches"], json!([["Array<felt252>"]]));
+        assert_eq!(response["location"], "lib.cairo:3");
+
+        let response = statement(1000);
+        assert_eq!(response["error"]["kind"], "invalid-request", "response={response}");
+    }
+}
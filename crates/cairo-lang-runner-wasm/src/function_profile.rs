@@ -0,0 +1,62 @@
+//! A breakdown of the steps and gas of a run by function, for users optimizing where the gas goes
+//! rather than only its total.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use cairo_lang_runner::profiling::{ProfilingInfo, user_function_idx_by_sierra_statement_idx};
+use cairo_lang_sierra::program::Program;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The steps a run spent in a function.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FunctionProfile {
+    /// The Sierra name of the function.
+    pub function: String,
+    /// The number of steps spent in the function itself.
+    pub steps: usize,
+    /// The number of steps spent in the function and the functions it called.
+    pub total_steps: usize,
+    /// The gas of `steps`, at the cost of a step, as overridden by `gas_costs`. Builtins and
+    /// memory holes are not accounted for, so functions using them consume more.
+    pub estimated_gas: usize,
+}
+
+/// Attributes the steps of a run to the functions of `program`, the most expensive first, with
+/// `step_gas_cost` as the cost of a step.
+pub(crate) fn profile(
+    program: &Program,
+    profiling_info: &ProfilingInfo,
+    step_gas_cost: usize,
+) -> Vec<FunctionProfile> {
+    // The own and total steps of each function, by index.
+    let mut steps = BTreeMap::<usize, (usize, usize)>::new();
+    for (statement_idx, weight) in profiling_info.sierra_statement_weights.iter_sorted() {
+        let function_idx = user_function_idx_by_sierra_statement_idx(program, *statement_idx);
+        steps.entry(function_idx).or_default().0 += weight;
+    }
+    // The weight of a stack trace includes the steps of the calls made by its innermost function.
+    for (stack_trace, weight) in profiling_info.stack_trace_weights.iter() {
+        let Some((function_idx, callers)) = stack_trace.split_last() else {
+            continue;
+        };
+        // The steps of a recursive call are already counted in the outermost call.
+        if !callers.contains(function_idx) {
+            steps.entry(*function_idx).or_default().1 += weight;
+        }
+    }
+
+    let mut profile = steps
+        .into_iter()
+        .map(|(function_idx, (steps, total_steps))| FunctionProfile {
+            function: program.funcs[function_idx].id.to_string(),
+            steps,
+            // Stack traces deeper than the limit of the runner are not collected.
+            total_steps: total_steps.max(steps),
+            estimated_gas: steps * step_gas_cost,
+        })
+        .collect::<Vec<_>>();
+    profile.sort_by_key(|function| Reverse(function.steps));
+    profile
+}
@@ -0,0 +1,390 @@
+//! Fuzzing of a function with random arguments generated from a seed, shrinking the first failing
+//! input to a smaller counterexample, for property-based testing in the browser.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_runnable_utils::builder::RunnableBuilder;
+use cairo_lang_sierra::ids::ConcreteTypeId;
+use cairo_lang_sierra::program::GenericArg;
+use cairo_lang_wasm_utils::diagnostic_counts::{self, FileDiagnosticCounts};
+use cairo_lang_wasm_utils::error::{self, ApiError, ErrorKind};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::source::{self, SourceFiles};
+use cairo_lang_wasm_utils::{corelib, limits, parse};
+use num_bigint::BigInt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::typed_args::INTEGER_TYPES;
+use crate::{
+    DiagnosticsScopeArg, InliningStrategyArg, ProgramRunner, RunOptions, RunResponse,
+    compile_project, default_function_name, default_replace_ids,
+};
+
+/// The maximal number of runs spent shrinking a failing input.
+const MAX_SHRINK_RUNS: usize = 1000;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FuzzRequest {
+    pub crate_name: String,
+    pub files: SourceFiles,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    #[serde(default = "default_replace_ids")]
+    pub replace_ids: bool,
+    #[serde(default)]
+    pub inlining_strategy: InliningStrategyArg,
+    /// The crates whose diagnostics are reported.
+    #[serde(default)]
+    pub diagnostics_scope: DiagnosticsScopeArg,
+    /// The function to fuzz, whose parameters must be integers, `felt252`s or `bool`s.
+    #[serde(default = "default_function_name")]
+    pub function: String,
+    /// The number of runs with random arguments.
+    #[serde(default = "default_fuzz_runs")]
+    pub runs: usize,
+    /// The seed of the random arguments: the same seed generates the same arguments.
+    #[serde(default)]
+    pub seed: u64,
+    /// The gas available to each run.
+    pub available_gas: Option<usize>,
+    /// The number of VM steps after which each run is stopped.
+    pub max_steps: Option<usize>,
+    /// Whether to return the compiler's and runner's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// A failing input found by fuzzing.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FuzzFailure {
+    /// The first failing arguments, as in `typed_args`.
+    pub input: Vec<Value>,
+    /// The arguments left after shrinking `input` towards zero while the run kept failing.
+    pub shrunk_input: Vec<Value>,
+    /// The run of `shrunk_input`.
+    pub run: RunResponse,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct FuzzResponse {
+    /// Whether the function compiled and was fuzzed, whether or not a failing input was found.
+    pub success: bool,
+    /// The number of runs with random arguments, up to the first failing one.
+    pub runs: usize,
+    /// The first failing input, if any.
+    pub failure: Option<FuzzFailure>,
+    pub diagnostics: String,
+    /// The number of errors and warnings of each file of `diagnostics`.
+    pub diagnostics_by_file: BTreeMap<String, FileDiagnosticCounts>,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// The values of a parameter, as generated by the fuzzer.
+enum Domain {
+    Integer { bits: u64, signed: bool },
+    Felt,
+    Bool,
+}
+
+/// Compiles a project, then runs a function with random arguments until it fails. See
+/// [FuzzRequest].
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn fuzz(request_json: &str) -> String {
+    let request: FuzzRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => return serialize_fuzz_response(Err(error), None),
+    };
+
+    let (result, debug_log) =
+        logging::capture(request.debug, || error::catch_internal(|| fuzz_request(request)));
+    serialize_fuzz_response(result, debug_log)
+}
+
+fn fuzz_request(request: FuzzRequest) -> FuzzResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.contents.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return FuzzResponse { error: Some(error), ..Default::default() };
+    }
+
+    let project = InMemoryProject {
+        main_crate_name: request.crate_name,
+        main_crate_files: request.files.contents,
+        corelib_files: corelib::resolve(request.corelib_files),
+        main_crate_settings: None,
+    };
+    compile_project(
+        &project,
+        &request.files.hidden,
+        request.inlining_strategy,
+        request.replace_ids,
+        request.diagnostics_scope,
+        |program, locate_statement, _, diagnostics| {
+            let options = RunOptions {
+                available_gas: request.available_gas,
+                max_steps: request.max_steps,
+                locate_statement: Some(locate_statement),
+                ..Default::default()
+            };
+            let runner = match ProgramRunner::new(program, &options) {
+                Ok(runner) => runner,
+                Err(error) => {
+                    return FuzzResponse { diagnostics, error: Some(error), ..Default::default() };
+                }
+            };
+            let domains = match runner.runner.find_function(&request.function) {
+                Ok(func) => {
+                    let builder = runner.runner.builder();
+                    func.signature
+                        .param_types
+                        .iter()
+                        .filter(|ty| builder.is_user_arg_type(&builder.type_long_id(ty).generic_id))
+                        .map(|ty| domain(builder, ty))
+                        .collect::<Result<Vec<_>, _>>()
+                }
+                // The run reports the missing function, with suggestions.
+                Err(_) => Err(runner
+                    .run(&request.function, vec![], None, request.available_gas)
+                    .error
+                    .expect("missing function error")),
+            };
+            let domains = match domains {
+                Ok(domains) => domains,
+                Err(error) => {
+                    return FuzzResponse { diagnostics, error: Some(error), ..Default::default() };
+                }
+            };
+
+            let run = |input: &[BigInt]| {
+                let typed_args = to_typed_args(&domains, input);
+                runner.run(&request.function, vec![], Some(typed_args), request.available_gas)
+            };
+            let mut rng = SplitMix64(request.seed);
+            for run_idx in 0..request.runs {
+                let input =
+                    domains.iter().map(|domain| domain.generate(&mut rng)).collect::<Vec<_>>();
+                let response = run(&input);
+                if is_cancelled(&response) {
+                    return FuzzResponse {
+                        runs: run_idx,
+                        diagnostics,
+                        error: response.error,
+                        ..Default::default()
+                    };
+                }
+                if response.success {
+                    continue;
+                }
+                let (shrunk_input, response) = shrink(input.clone(), response, &run);
+                return FuzzResponse {
+                    success: true,
+                    runs: run_idx + 1,
+                    failure: Some(FuzzFailure {
+                        input: to_typed_args(&domains, &input),
+                        shrunk_input: to_typed_args(&domains, &shrunk_input),
+                        run: response,
+                    }),
+                    diagnostics,
+                    ..Default::default()
+                };
+            }
+            FuzzResponse { success: true, runs: request.runs, diagnostics, ..Default::default() }
+        },
+    )
+    .unwrap_or_else(|(error, diagnostics)| FuzzResponse {
+        diagnostics,
+        error: Some(error),
+        ..Default::default()
+    })
+}
+
+/// Returns the domain of the parameters of type `ty`.
+fn domain(builder: &RunnableBuilder, ty: &ConcreteTypeId) -> Result<Domain, ApiError> {
+    let long_id = builder.type_long_id(ty);
+    let generic_id = long_id.generic_id.0.as_str();
+    if let Some((_, bits, signed)) = INTEGER_TYPES.iter().find(|(name, ..)| *name == generic_id) {
+        return Ok(Domain::Integer { bits: *bits, signed: *signed });
+    }
+    match (generic_id, &long_id.generic_args[..]) {
+        ("felt252", _) => Ok(Domain::Felt),
+        ("Enum", [GenericArg::UserType(user_type), ..])
+            if user_type.debug_name.as_deref() == Some("core::bool") =>
+        {
+            Ok(Domain::Bool)
+        }
+        _ => Err(ApiError::invalid_request(format!(
+            "Fuzzing parameters of type `{ty}` is not supported; only integers, `felt252` and \
+             `bool` are."
+        ))),
+    }
+}
+
+impl Domain {
+    /// Returns a random value of the domain, an edge case such as zero or a bound a quarter of the
+    /// time.
+    fn generate(&self, rng: &mut SplitMix64) -> BigInt {
+        let edge_cases = self.edge_cases();
+        if rng.next_u64() % 4 == 0 {
+            return edge_cases[(rng.next_u64() % edge_cases.len() as u64) as usize].clone();
+        }
+        match self {
+            Domain::Integer { bits, signed: false } => rng.bits(*bits),
+            Domain::Integer { bits, signed: true } => {
+                rng.bits(*bits) - (BigInt::from(1) << (bits - 1))
+            }
+            // Below the prime of the field.
+            Domain::Felt => rng.bits(251),
+            Domain::Bool => rng.bits(1),
+        }
+    }
+
+    fn edge_cases(&self) -> Vec<BigInt> {
+        match self {
+            Domain::Integer { bits, signed: false } => {
+                vec![0.into(), 1.into(), (BigInt::from(1) << *bits) - 1]
+            }
+            Domain::Integer { bits, signed: true } => {
+                let bound = BigInt::from(1) << (bits - 1);
+                vec![0.into(), 1.into(), (-1).into(), -&bound, bound - 1]
+            }
+            Domain::Felt => vec![0.into(), 1.into(), (-1).into()],
+            Domain::Bool => vec![0.into(), 1.into()],
+        }
+    }
+}
+
+/// Returns `input` as the `typed_args` of a run.
+fn to_typed_args(domains: &[Domain], input: &[BigInt]) -> Vec<Value> {
+    domains
+        .iter()
+        .zip(input)
+        .map(|(domain, value)| match domain {
+            Domain::Bool => Value::Bool(*value != BigInt::from(0)),
+            _ => Value::String(value.to_string()),
+        })
+        .collect()
+}
+
+/// Shrinks the failing `input`, whose run is `failing_run`, towards zero one argument at a time,
+/// keeping each smaller argument with which `run` still fails. Returns the shrunk input and its
+/// run.
+fn shrink(
+    mut input: Vec<BigInt>,
+    mut failing_run: RunResponse,
+    run: impl Fn(&[BigInt]) -> RunResponse,
+) -> (Vec<BigInt>, RunResponse) {
+    let mut remaining_runs = MAX_SHRINK_RUNS;
+    let mut shrunk = true;
+    while shrunk {
+        shrunk = false;
+        for index in 0..input.len() {
+            // Tries zero first, then values closer and closer to the current one.
+            let value = input[index].clone();
+            let mut delta = value.clone();
+            while delta != BigInt::from(0) {
+                if remaining_runs == 0 {
+                    return (input, failing_run);
+                }
+                remaining_runs -= 1;
+                let mut candidate = input.clone();
+                candidate[index] = &value - &delta;
+                let response = run(&candidate);
+                if is_cancelled(&response) {
+                    return (input, failing_run);
+                }
+                if !response.success {
+                    input = candidate;
+                    failing_run = response;
+                    shrunk = true;
+                    break;
+                }
+                delta /= 2;
+            }
+        }
+    }
+    (input, failing_run)
+}
+
+fn is_cancelled(response: &RunResponse) -> bool {
+    response.error.as_ref().is_some_and(|error| error.kind == ErrorKind::Cancelled)
+}
+
+/// The SplitMix64 generator: fast, seedable and identical on every platform.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random non-negative integer of `bits` bits.
+    fn bits(&mut self, bits: u64) -> BigInt {
+        let mut value = BigInt::from(0);
+        for _ in 0..bits.div_ceil(64) {
+            value = (value << 64) + self.next_u64();
+        }
+        value & ((BigInt::from(1) << bits) - 1)
+    }
+}
+
+fn default_fuzz_runs() -> usize {
+    100
+}
+
+fn serialize_fuzz_response(
+    result: Result<FuzzResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+) -> String {
+    let mut response =
+        result.unwrap_or_else(|error| FuzzResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+    response.diagnostics_by_file = diagnostic_counts::by_file(&response.diagnostics);
+    serde_json::to_string(&response).expect("serialize fuzz response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::fuzz;
+
+    #[test]
+    fn fuzz_shrinks_failing_input() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn check(a: u32, _flag: bool) {
+    assert!(a < 1000, \"too big\");
+}
+"
+            },
+            "function": "::check",
+            "runs": 50,
+            "seed": 7,
+            "available_gas": 1000000
+        });
+
+        let response = fuzz(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let failure = &response_json["failure"];
+        assert_eq!(failure["shrunk_input"], json!(["1000", false]), "failure={failure}");
+        assert!(failure["run"]["panic_message"].as_str().unwrap().contains("too big"));
+        // The same seed finds the same input.
+        let again: Value = serde_json::from_str(&fuzz(&request.to_string())).unwrap();
+        assert_eq!(again["failure"]["input"], failure["input"]);
+    }
+}
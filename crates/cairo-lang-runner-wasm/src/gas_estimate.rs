@@ -0,0 +1,278 @@
+//! Estimation of the smallest `available_gas` a run succeeds with, so users do not have to find it
+//! by trial and error.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_wasm_utils::diagnostic_counts::{self, FileDiagnosticCounts};
+use cairo_lang_wasm_utils::error::{self, ApiError, ErrorKind};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::source::{self, SourceFiles};
+use cairo_lang_wasm_utils::{corelib, gas, limits, parse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    DiagnosticsScopeArg, InliningStrategyArg, ProgramRunner, RunArg, RunOptions, RunResponse,
+    compile_project, default_function_name, default_replace_ids,
+};
+
+/// The largest `available_gas` tried by default, as `cairo-test` gives tests.
+const DEFAULT_MAX_AVAILABLE_GAS: usize = u32::MAX as usize;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct EstimateGasRequest {
+    pub crate_name: String,
+    pub files: SourceFiles,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    #[serde(default = "default_replace_ids")]
+    pub replace_ids: bool,
+    #[serde(default)]
+    pub inlining_strategy: InliningStrategyArg,
+    /// The crates whose diagnostics are reported.
+    #[serde(default)]
+    pub diagnostics_scope: DiagnosticsScopeArg,
+    #[serde(default = "default_function_name")]
+    pub function: String,
+    /// The arguments of the function, as in `compile_and_run`.
+    #[serde(default)]
+    pub args: Vec<RunArg>,
+    /// The arguments of the function as JSON values. Exclusive with `args`.
+    pub typed_args: Option<Vec<serde_json::Value>>,
+    /// The largest `available_gas` to try, `u32::MAX` by default.
+    pub max_available_gas: Option<usize>,
+    /// The number of VM steps after which each run is stopped.
+    pub max_steps: Option<usize>,
+    /// Whether to return the compiler's and runner's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct EstimateGasResponse {
+    /// Whether the run succeeded with some `available_gas` up to the maximal one.
+    pub success: bool,
+    /// The smallest `available_gas` the run succeeds with.
+    pub available_gas: Option<usize>,
+    /// The gas consumed by the run with `available_gas`.
+    pub gas_consumed: Option<usize>,
+    /// The number of runs made by the search.
+    pub runs: usize,
+    /// The run with `available_gas`, or the failed run with the maximal gas.
+    pub run: Option<RunResponse>,
+    pub diagnostics: String,
+    /// The number of errors and warnings of each file of `diagnostics`.
+    pub diagnostics_by_file: BTreeMap<String, FileDiagnosticCounts>,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Compiles a project, then binary-searches the smallest `available_gas` with which a function
+/// runs successfully. See [EstimateGasRequest].
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn estimate_gas(request_json: &str) -> String {
+    let request: EstimateGasRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => return serialize_estimate_gas_response(Err(error), None),
+    };
+
+    let (result, debug_log) =
+        logging::capture(request.debug, || error::catch_internal(|| estimate_gas_request(request)));
+    serialize_estimate_gas_response(result, debug_log)
+}
+
+fn estimate_gas_request(request: EstimateGasRequest) -> EstimateGasResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.contents.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return EstimateGasResponse { error: Some(error), ..Default::default() };
+    }
+    if !request.args.is_empty() && request.typed_args.is_some() {
+        return EstimateGasResponse {
+            error: Some(ApiError::invalid_request("`args` and `typed_args` are exclusive.")),
+            ..Default::default()
+        };
+    }
+    let mut args = vec![];
+    if let Err(error) = request.args.iter().try_for_each(|arg| arg.encode(&mut args)) {
+        return EstimateGasResponse { error: Some(error), ..Default::default() };
+    }
+    // The gas policy of the host caps the gas of the runs.
+    let max_available_gas = gas::current()
+        .apply(Some(request.max_available_gas.unwrap_or(DEFAULT_MAX_AVAILABLE_GAS)))
+        .expect("explicit available gas");
+
+    let project = InMemoryProject {
+        main_crate_name: request.crate_name,
+        main_crate_files: request.files.contents,
+        corelib_files: corelib::resolve(request.corelib_files),
+        main_crate_settings: None,
+    };
+    compile_project(
+        &project,
+        &request.files.hidden,
+        request.inlining_strategy,
+        request.replace_ids,
+        request.diagnostics_scope,
+        |program, locate_statement, _, diagnostics| {
+            if !program.requires_gas_counter() {
+                return EstimateGasResponse {
+                    diagnostics,
+                    error: Some(ApiError::invalid_request(
+                        "The program does not use gas, so any `available_gas` suffices.",
+                    )),
+                    ..Default::default()
+                };
+            }
+            let options = RunOptions {
+                available_gas: Some(max_available_gas),
+                max_steps: request.max_steps,
+                locate_statement: Some(locate_statement),
+                ..Default::default()
+            };
+            let runner = match ProgramRunner::new(program, &options) {
+                Ok(runner) => runner,
+                Err(error) => {
+                    return EstimateGasResponse {
+                        diagnostics,
+                        error: Some(error),
+                        ..Default::default()
+                    };
+                }
+            };
+            let run = |available_gas| {
+                runner.run(
+                    &request.function,
+                    args.clone(),
+                    request.typed_args.clone(),
+                    Some(available_gas),
+                )
+            };
+
+            let response = run(max_available_gas);
+            let mut runs = 1;
+            if !response.success {
+                let error = response.error.clone().unwrap_or_else(|| {
+                    ApiError::execution(format!(
+                        "The run fails even with {max_available_gas} available gas."
+                    ))
+                });
+                return EstimateGasResponse {
+                    runs,
+                    run: Some(response),
+                    diagnostics,
+                    error: Some(error),
+                    ..Default::default()
+                };
+            }
+            // The run cannot succeed with less gas than it consumes.
+            let (mut low, mut high) = (max_available_gas - gas_left(&response), max_available_gas);
+            let mut best = response;
+            while low < high {
+                let mid = low + (high - low) / 2;
+                let response = run(mid);
+                runs += 1;
+                if response.error.as_ref().is_some_and(|error| error.kind == ErrorKind::Cancelled) {
+                    return EstimateGasResponse {
+                        runs,
+                        diagnostics,
+                        error: response.error,
+                        ..Default::default()
+                    };
+                }
+                if response.success {
+                    high = mid;
+                    best = response;
+                } else {
+                    low = mid + 1;
+                }
+            }
+            EstimateGasResponse {
+                success: true,
+                available_gas: Some(high),
+                gas_consumed: Some(high - gas_left(&best)),
+                runs,
+                run: Some(best),
+                diagnostics,
+                ..Default::default()
+            }
+        },
+    )
+    .unwrap_or_else(|(error, diagnostics)| EstimateGasResponse {
+        diagnostics,
+        error: Some(error),
+        ..Default::default()
+    })
+}
+
+/// Returns the gas left by the successful run `response`.
+fn gas_left(response: &RunResponse) -> usize {
+    response
+        .gas_counter
+        .as_ref()
+        .and_then(|gas_counter| gas_counter.parse().ok())
+        .expect("gas counter of a run with gas")
+}
+
+fn serialize_estimate_gas_response(
+    result: Result<EstimateGasResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+) -> String {
+    let mut response = result
+        .unwrap_or_else(|error| EstimateGasResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+    response.diagnostics_by_file = diagnostic_counts::by_file(&response.diagnostics);
+    serde_json::to_string(&response).expect("serialize estimate gas response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::estimate_gas;
+
+    #[test]
+    fn estimate_gas_finds_smallest_available_gas() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn count(n: u32) -> u32 {
+    let mut i = 0;
+    while i != n {
+        i += 1;
+    }
+    i
+}
+"
+            },
+            "function": "::count",
+            "typed_args": [100]
+        });
+
+        let response = estimate_gas(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let available_gas = response_json["available_gas"].as_u64().unwrap();
+        assert!(available_gas >= response_json["gas_consumed"].as_u64().unwrap());
+        assert_eq!(response_json["run"]["values"], json!(["100"]));
+
+        // One less gas runs out.
+        let request = json!({
+            "crate_name": "test",
+            "files": request["files"],
+            "function": "::count",
+            "typed_args": [100],
+            "max_available_gas": available_gas - 1
+        });
+        let response = estimate_gas(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+        assert_eq!(response_json["success"], false, "response={response}");
+        assert_eq!(response_json["run"]["panicked"], true);
+    }
+}
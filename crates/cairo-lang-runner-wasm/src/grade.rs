@@ -0,0 +1,332 @@
+//! Grading of a program against test cases, compiling it once and comparing the output of each case
+//! with the expected one.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_wasm_utils::diagnostic_counts::{self, FileDiagnosticCounts};
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{corelib, limits, parse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::contract::parse_felt;
+use crate::{
+    CompileAndRunRequest, ProgramRunner, RunArg, RunOptions, RunResponse, compile_project,
+    main_synthesis,
+};
+
+/// A case of a grading: the arguments of a run and its expected output.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GradeCase {
+    /// A name identifying the case in the response.
+    pub name: Option<String>,
+    /// The function to run, instead of the `function` of the request.
+    pub function: Option<String>,
+    /// The arguments of the function, as in the `args` of a run request.
+    #[serde(default)]
+    pub args: Vec<RunArg>,
+    /// The arguments of the function as JSON values. Exclusive with `args`.
+    pub typed_args: Option<Vec<Value>>,
+    pub expected: ExpectedOutput,
+}
+
+/// The expected output of a run. Only the given fields are compared.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ExpectedOutput {
+    /// The returned felts, as decimal or `0x`-prefixed hex strings, or the panic data when the run
+    /// is expected to panic.
+    pub values: Option<Vec<String>>,
+    /// The return value, as in the `decoded_values` of a run response.
+    pub decoded_values: Option<Value>,
+    pub stdout: Option<String>,
+    /// Whether the run is expected to panic.
+    pub panicked: Option<bool>,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct GradeResponse {
+    /// The number of passed cases.
+    pub passed: usize,
+    /// The number of failed cases.
+    pub failed: usize,
+    /// The results of the cases, in the order of the request.
+    pub cases: Vec<CaseResult>,
+    pub diagnostics: String,
+    /// The number of errors and warnings of each file of `diagnostics`.
+    pub diagnostics_by_file: BTreeMap<String, FileDiagnosticCounts>,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CaseResult {
+    pub name: Option<String>,
+    /// Whether the run succeeded with the expected output.
+    pub passed: bool,
+    /// The fields of the output differing from the expected ones.
+    pub mismatches: Vec<Mismatch>,
+    pub run: RunResponse,
+}
+
+/// A field of the output of a run differing from the expected one.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Mismatch {
+    /// The compared field, such as `stdout`.
+    pub field: &'static str,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+/// Compiles a project once, then runs it on each of `cases_json` and compares the outputs with the
+/// expected ones.
+///
+/// The request is a `compile_and_run` request, whose arguments are given by the cases.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn grade(request_json: &str, cases_json: &str) -> String {
+    let parsed = parse::request::<CompileAndRunRequest>(request_json).and_then(|request| {
+        serde_json::from_str::<Vec<GradeCase>>(cases_json).map(|cases| (request, cases)).map_err(
+            |error| ApiError::invalid_request(format!("Failed parsing cases JSON: {error}")),
+        )
+    });
+    let (request, cases) = match parsed {
+        Ok(parsed) => parsed,
+        Err(error) => return serialize_grade_response(Err(error), None),
+    };
+
+    let (result, debug_log) =
+        logging::capture(request.debug, || error::catch_internal(|| grade_request(request, cases)));
+    serialize_grade_response(result, debug_log)
+}
+
+fn grade_request(request: CompileAndRunRequest, cases: Vec<GradeCase>) -> GradeResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.contents.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return GradeResponse { error: Some(error), ..Default::default() };
+    }
+    if !request.args.is_empty()
+        || request.typed_args.is_some()
+        || request.executable_args.is_some()
+        || request.functions.is_some()
+    {
+        return GradeResponse {
+            error: Some(ApiError::invalid_request(
+                "The arguments of the runs are given by the cases.",
+            )),
+            ..Default::default()
+        };
+    }
+    if request.expected.is_some() {
+        return GradeResponse {
+            error: Some(ApiError::invalid_request(
+                "The expected outputs of the runs are given by the cases.",
+            )),
+            ..Default::default()
+        };
+    }
+
+    let starknet_state = match request.starknet_context.starknet_state() {
+        Ok(starknet_state) => starknet_state,
+        Err(error) => return GradeResponse { error: Some(error), ..Default::default() },
+    };
+
+    let mut project = InMemoryProject {
+        main_crate_name: request.crate_name,
+        main_crate_files: request.files.contents,
+        corelib_files: corelib::resolve(request.corelib_files),
+        main_crate_settings: None,
+    };
+    if let Some(synthesized) = &request.synthesize_main
+        && let Err(error) = main_synthesis::add_main(&mut project, synthesized)
+    {
+        return GradeResponse { error: Some(error), ..Default::default() };
+    }
+    compile_project(
+        &project,
+        &request.files.hidden,
+        request.inlining_strategy,
+        request.replace_ids,
+        request.diagnostics_scope,
+        |program, locate_statement, _, diagnostics| {
+            let options = RunOptions {
+                available_gas: request.available_gas,
+                trace_summary: request.trace_summary,
+                detect_loops: request.detect_loops,
+                max_steps: request.max_steps,
+                strict_arithmetic: request.strict_arithmetic,
+                gas_costs: request.gas_costs,
+                value_format: request.value_format,
+                starknet_state,
+                locate_statement: Some(locate_statement),
+                ..Default::default()
+            };
+            let runner = match ProgramRunner::new(program, &options) {
+                Ok(runner) => runner,
+                Err(error) => {
+                    return GradeResponse { diagnostics, error: Some(error), ..Default::default() };
+                }
+            };
+
+            let mut response = GradeResponse { diagnostics, ..Default::default() };
+            for case in cases {
+                let result = match run_case(&runner, &request.function, request.available_gas, case)
+                {
+                    Ok(result) => result,
+                    Err(error) => return GradeResponse { error: Some(error), ..response },
+                };
+                if result.passed {
+                    response.passed += 1;
+                } else {
+                    response.failed += 1;
+                }
+                response.cases.push(result);
+            }
+            response
+        },
+    )
+    .unwrap_or_else(|(error, diagnostics)| GradeResponse {
+        diagnostics,
+        error: Some(error),
+        ..Default::default()
+    })
+}
+
+/// Runs a case and compares its output with the expected one.
+fn run_case(
+    runner: &ProgramRunner<'_>,
+    default_function: &str,
+    available_gas: Option<usize>,
+    case: GradeCase,
+) -> Result<CaseResult, ApiError> {
+    let GradeCase { name, function, args, typed_args, expected } = case;
+    if !args.is_empty() && typed_args.is_some() {
+        return Err(ApiError::invalid_request("`args` and `typed_args` are exclusive."));
+    }
+    let mut encoded_args = vec![];
+    args.iter().try_for_each(|arg| arg.encode(&mut encoded_args))?;
+
+    let run = runner.run(
+        function.as_deref().unwrap_or(default_function),
+        encoded_args,
+        typed_args,
+        available_gas,
+    );
+    let mismatches = compare(&expected, &run)?;
+    Ok(CaseResult { name, passed: matches(&expected, &run, &mismatches), mismatches, run })
+}
+
+/// Returns the fields of the output of `run` differing from `expected`.
+pub(crate) fn compare(
+    expected: &ExpectedOutput,
+    run: &RunResponse,
+) -> Result<Vec<Mismatch>, ApiError> {
+    let mut mismatches = vec![];
+    if let Some(panicked) = expected.panicked
+        && panicked != run.panicked
+    {
+        mismatches.push(Mismatch {
+            field: "panicked",
+            expected: panicked.into(),
+            actual: run.panicked.into(),
+        });
+    }
+    if let Some(values) = &expected.values {
+        let values = values
+            .iter()
+            .map(|value| Ok(parse_felt(value)?.to_string()))
+            .collect::<Result<Vec<_>, ApiError>>()?;
+        if values != run.values {
+            mismatches.push(Mismatch {
+                field: "values",
+                expected: values.into(),
+                actual: run.values.clone().into(),
+            });
+        }
+    }
+    if let Some(decoded_values) = &expected.decoded_values
+        && Some(decoded_values) != run.decoded_values.as_ref()
+    {
+        mismatches.push(Mismatch {
+            field: "decoded_values",
+            expected: decoded_values.clone(),
+            actual: run.decoded_values.clone().unwrap_or_default(),
+        });
+    }
+    if let Some(stdout) = &expected.stdout
+        && *stdout != run.stdout
+    {
+        mismatches.push(Mismatch {
+            field: "stdout",
+            expected: stdout.clone().into(),
+            actual: run.stdout.clone().into(),
+        });
+    }
+    Ok(mismatches)
+}
+
+/// Returns whether `run`, whose output differs from `expected` by `mismatches`, matches it: it
+/// must succeed, or panic if a panic is expected.
+pub(crate) fn matches(
+    expected: &ExpectedOutput,
+    run: &RunResponse,
+    mismatches: &[Mismatch],
+) -> bool {
+    let succeeded = run.success || (run.panicked && expected.panicked == Some(true));
+    succeeded && mismatches.is_empty()
+}
+
+fn serialize_grade_response(
+    result: Result<GradeResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+) -> String {
+    let mut response =
+        result.unwrap_or_else(|error| GradeResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+    response.diagnostics_by_file = diagnostic_counts::by_file(&response.diagnostics);
+    serde_json::to_string(&response).expect("serialize grade response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::grade;
+
+    #[test]
+    fn grade_reports_failed_cases() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn double(x: u32) -> u32 {
+    println!(\"doubling {x}\");
+    x * 2
+}
+"
+            },
+            "available_gas": 1000000,
+            "function": "::double"
+        });
+        let cases = json!([
+            { "name": "small", "args": ["2"], "expected": { "values": ["4"], "stdout": "doubling 2\n" } },
+            { "name": "wrong", "args": ["3"], "expected": { "decoded_values": "7" } }
+        ]);
+
+        let response = grade(&request.to_string(), &cases.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["error"], Value::Null, "response={response}");
+        assert_eq!(response_json["passed"], 1);
+        assert_eq!(response_json["failed"], 1);
+        assert_eq!(response_json["cases"][0]["passed"], true);
+        assert_eq!(
+            response_json["cases"][1]["mismatches"],
+            json!([{ "field": "decoded_values", "expected": "7", "actual": "6" }])
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! JUnit XML reports of test runs, for CI systems and dashboards ingesting test results.
+
+use std::fmt::Write;
+
+use crate::test_harness::TestRun;
+use crate::test_session::TestStatus;
+
+/// Returns the JUnit XML report of the `tests` of the crate `crate_name`.
+///
+/// Each test is a `testcase` whose `classname` is the path of its module. Tests that could not be
+/// run are reported as errors rather than failures.
+pub(crate) fn report(crate_name: &str, tests: &[TestRun]) -> String {
+    let count = |status: Option<TestStatus>| {
+        tests.iter().filter(|test| test.result.status == status).count()
+    };
+    let (failures, skipped, errors) =
+        (count(Some(TestStatus::Failed)), count(Some(TestStatus::Ignored)), count(None));
+    let counts = format!(
+        "tests=\"{}\" failures=\"{failures}\" errors=\"{errors}\" skipped=\"{skipped}\"",
+        tests.len()
+    );
+    let crate_name = escape(crate_name);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(xml, "<testsuites name=\"{crate_name}\" {counts}>").unwrap();
+    writeln!(xml, "  <testsuite name=\"{crate_name}\" {counts}>").unwrap();
+    for test in tests {
+        let (classname, name) = test.name.rsplit_once("::").unwrap_or(("", &test.name));
+        write!(xml, "    <testcase name=\"{}\" classname=\"{}\"", escape(name), escape(classname))
+            .unwrap();
+        let result = &test.result;
+        let outcome = match result.status {
+            Some(TestStatus::Passed) => String::new(),
+            Some(TestStatus::Ignored) => "      <skipped/>\n".to_string(),
+            Some(TestStatus::Failed) => format!(
+                "      <failure message=\"{}\">{}</failure>\n",
+                escape(result.failure.as_deref().unwrap_or("The test failed.")),
+                escape(result.panic_message.as_deref().unwrap_or_default())
+            ),
+            None => format!(
+                "      <error message=\"{}\"/>\n",
+                escape(
+                    result
+                        .error
+                        .as_ref()
+                        .map_or("The test could not be run.", |error| error.message.as_str())
+                )
+            ),
+        };
+        if outcome.is_empty() && result.stdout.is_empty() && result.stderr.is_empty() {
+            xml.push_str("/>\n");
+            continue;
+        }
+        xml.push_str(">\n");
+        xml.push_str(&outcome);
+        if !result.stdout.is_empty() {
+            writeln!(xml, "      <system-out>{}</system-out>", escape(&result.stdout)).unwrap();
+        }
+        if !result.stderr.is_empty() {
+            writeln!(xml, "      <system-err>{}</system-err>", escape(&result.stderr)).unwrap();
+        }
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// Escapes `text` for an XML attribute value or element content.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tabs and newlines are not allowed in XML 1.0.
+            c if c.is_control() && c != '\t' && c != '\n' && c != '\r' => {
+                write!(escaped, "\\u{{{:x}}}", c as u32).unwrap()
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,42 +1,378 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 use cairo_lang_compiler::diagnostics::DiagnosticsReporter;
-use cairo_lang_compiler::project::InMemoryProject;
-use cairo_lang_compiler::{CompilerConfig, compile_in_memory_project};
+use cairo_lang_compiler::project::{InMemoryProject, crate_source_root};
+use cairo_lang_compiler::{CompilerConfig, compile_prepared_db, prepare_in_memory_project_db};
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_executable_plugin::EXECUTABLE_PREFIX;
+use cairo_lang_filesystem::ids::CrateInput;
 use cairo_lang_lowering::utils::InliningStrategy;
-use cairo_lang_runner::{RunResultValue, SierraCasmRunner, StarknetState};
+use cairo_lang_runner::partial_run::PartialRun;
+use cairo_lang_runner::{
+    Arg, CancellationCheck, ProfilingInfoCollectionConfig, RunResultValue, RunnerError,
+    SierraCasmRunner, StarknetState,
+};
 use cairo_lang_sierra::ProgramParser;
-use cairo_lang_sierra::program::Program;
+use cairo_lang_sierra::debug_info::DebugInfo;
+use cairo_lang_sierra::extensions::core::{CoreLibfunc, CoreType};
+use cairo_lang_sierra::extensions::gas::CostTokenType;
+use cairo_lang_sierra::extensions::{GenericLibfunc, GenericType};
+use cairo_lang_sierra::program::{Program, StatementIdx, VersionedProgram};
+use cairo_lang_sierra_generator::program_generator::SierraProgramWithDebug;
+use cairo_lang_starknet_classes::felt252_serde::sierra_from_felt252s;
+use cairo_lang_utils::bigint::BigUintAsHex;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use cairo_lang_wasm_utils::allocations::{self, AllocationStats};
+use cairo_lang_wasm_utils::diagnostic_counts::{self, FileDiagnosticCounts};
+use cairo_lang_wasm_utils::error::{self, ApiError, ErrorKind};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::source::{self, SourceFiles};
+use cairo_lang_wasm_utils::state::StateCache;
+use cairo_lang_wasm_utils::{
+    build_info, cancellation, clock, compiled_programs, config, corelib, diagnostic_listener, gas,
+    limits, parse, plugins, state, telemetry,
+};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
 use wasm_bindgen::prelude::wasm_bindgen;
 
-include!(concat!(env!("OUT_DIR"), "/embedded_corelib.rs"));
+use crate::resources::ResourceCosts;
+
+mod arithmetic_report;
+mod assertion;
+mod batch;
+mod cairo_pie;
+mod casm;
+mod casm_text;
+mod compiled_program;
+mod contract;
+mod coverage;
+mod debug_output;
+mod declare;
+mod determinism;
+mod entrypoints;
+mod events;
+pub mod examples;
+mod execution_trace;
+mod explain;
+mod function_profile;
+mod fuzz;
+mod gas_estimate;
+mod grade;
+mod junit;
+mod libfunc_costs;
+mod libfunc_profile;
+mod main_synthesis;
+mod messages;
+mod panic_message;
+#[cfg(feature = "prove")]
+mod prove;
+mod prover_input;
+mod reentrancy;
+mod resources;
+mod run_cache;
+mod self_test;
+mod slicing;
+mod starknet_context;
+mod starknet_session;
+mod storage;
+mod suggestions;
+mod test_harness;
+mod test_session;
+mod testing;
+mod trace_summary;
+mod typed_args;
+mod typed_values;
+mod value_format;
+pub use arithmetic_report::{ArithmeticSite, ArithmeticSiteKind};
+pub use assertion::{AssertionFailure, AssertionSide, ValueDifference};
+pub use batch::{BatchRun, BatchRunResponse, CompileAndRunBatchRequest, compile_and_run_batch};
+pub use casm::{EntrypointKindArg, LayoutArg, RunCasmRequest, run_casm};
+pub use compiled_program::{
+    CompileProgramRequest, CompileProgramResponse, close_program, compile_program,
+};
+pub use contract::{CallContractRequest, call_contract};
+pub use coverage::FileCoverage;
+pub use declare::{
+    DeclareCheck, ValidateDeclareRequest, ValidateDeclareResponse, validate_declare,
+};
+pub use determinism::{DeterminismResponse, Divergence, check_determinism};
+pub use entrypoints::{
+    Entrypoint, ListEntrypointsRequest, ListEntrypointsResponse, list_entrypoints,
+};
+pub use events::Event;
+pub use examples::examples;
+pub use execution_trace::ExecutionTrace;
+pub use explain::{ExplainStatementRequest, ExplainStatementResponse, explain_sierra_statement};
+pub use function_profile::FunctionProfile;
+pub use fuzz::{FuzzFailure, FuzzRequest, FuzzResponse, fuzz};
+pub use gas_estimate::{EstimateGasRequest, EstimateGasResponse, estimate_gas};
+pub use grade::{CaseResult, ExpectedOutput, GradeCase, GradeResponse, Mismatch, grade};
+pub use libfunc_costs::{
+    BranchCost, LibfuncCost, LibfuncCostsRequest, LibfuncCostsResponse, libfunc_costs,
+};
+pub use libfunc_profile::LibfuncProfile;
+pub use main_synthesis::SynthesizedMain;
+pub use messages::L2ToL1Message;
+#[cfg(feature = "prove")]
+pub use prove::prove;
+pub use prover_input::ProverInput;
+pub use reentrancy::{EntryPointCall, ReentrantCall};
+pub use resources::{GasBreakdown, VmResources};
+pub use self_test::{SelfTestResponse, self_test};
+pub use slicing::{SliceProgramRequest, SliceProgramResponse, slice_program};
+pub use starknet_context::StarknetContext;
+pub use starknet_session::{
+    StarknetSessionResponse, close_starknet_session, open_starknet_session, prank, roll, warp,
+};
+pub use storage::{ReadStorageRequest, ReadStorageResponse, read_storage};
+pub use test_harness::{
+    ReportFormat, RunTestsRequest, RunTestsResponse, TestRun, TestSummary, run_tests,
+};
+pub use test_session::{RunTestResponse, TestStatus, close_test_session, run_test};
+pub use testing::{ListTestsRequest, ListTestsResponse, TestCase, list_tests};
+pub use trace_summary::TraceBucket;
+pub use typed_values::ReturnVariant;
+pub use value_format::{FormattedValue, ValueFormat};
 
-#[derive(Debug, Deserialize)]
+/// The number of steps between two polls of the cancellation check installed by the host.
+const CANCELLATION_CHECK_INTERVAL: usize = 10_000;
+
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct CompileAndRunRequest {
     pub crate_name: String,
-    pub files: BTreeMap<String, String>,
-    #[serde(default)]
+    /// The files of the crate, some of which may be hidden from the response.
+    pub files: SourceFiles,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
     pub corelib_files: Option<BTreeMap<String, String>>,
     #[serde(default = "default_replace_ids")]
     pub replace_ids: bool,
     #[serde(default)]
     pub inlining_strategy: InliningStrategyArg,
+    /// The crates whose diagnostics are reported.
+    #[serde(default)]
+    pub diagnostics_scope: DiagnosticsScopeArg,
     pub available_gas: Option<usize>,
     #[serde(default = "default_function_name")]
     pub function: String,
+    /// The arguments of the function, as decimal or `0x`-prefixed hex felts, or typed values
+    /// encoded to several felts.
+    #[serde(default)]
+    pub args: Vec<RunArg>,
+    /// The arguments of the function as JSON values, one for each parameter, encoded according to
+    /// the parameter types. Exclusive with `args`.
+    pub typed_args: Option<Vec<serde_json::Value>>,
+    /// The arguments of an `#[executable]` function, fed to it as its input: the function is run
+    /// through its executable wrapper, which deserializes its parameters from them, and the values
+    /// of the run are the serialization of its return value. Exclusive with `args` and
+    /// `typed_args`.
+    pub executable_args: Option<Vec<RunArg>>,
+    /// The number of buckets of the trace summary to return, if any.
+    pub trace_summary: Option<usize>,
+    /// Whether to stop the run once it looks like an infinite loop.
+    #[serde(default)]
+    pub detect_loops: bool,
+    /// The number of VM steps after which the run is stopped.
+    pub max_steps: Option<usize>,
+    /// The number of bytes of `stdout` after which the output stops being captured, the run going
+    /// on.
+    pub max_stdout_bytes: Option<usize>,
+    /// Whether to report the executed divisions and hints, whose semantics differ from integer
+    /// arithmetic on other platforms.
+    #[serde(default)]
+    pub strict_arithmetic: bool,
+    /// Whether to attribute the steps and gas of the run to the functions of the program.
+    #[serde(default)]
+    pub profile: bool,
+    /// Whether to aggregate the invocations and steps of the run by libfunc.
+    #[serde(default)]
+    pub libfunc_profile: bool,
+    /// Whether to return the number of executions of each Sierra statement.
+    #[serde(default)]
+    pub statement_counts: bool,
+    /// Whether to return which lines of the sources ran.
+    #[serde(default)]
+    pub coverage: bool,
+    /// Whether to return the relocated trace and memory of the run.
+    #[serde(default)]
+    pub collect_trace: bool,
+    /// Gas costs of builtins overriding the defaults, keyed by builtin name, such as `pedersen`.
+    /// The costs of `step`, `memory_hole`, `range_check`, `range_check96` and of syscalls, such as
+    /// `storage_read`, only reprice `gas_breakdown` and `profile`.
+    #[serde(default)]
+    pub gas_costs: BTreeMap<String, usize>,
+    /// Whether to report `l2_gas`. The run is charged the same gas either way.
+    #[serde(default)]
+    pub report_l2_gas: bool,
+    /// The representations of the returned felts: `decimal`, or `all` to add `formatted_values`.
+    #[serde(default)]
+    pub value_format: ValueFormat,
+    /// The block and transaction info seen by the Starknet syscalls of the run.
+    #[serde(default)]
+    pub starknet_context: StarknetContext,
+    /// Several functions to run instead of `function`, whose results are returned in `functions`.
+    pub functions: Option<Vec<FunctionRun>>,
+    /// A `main` function to add to a crate without one, calling one of its functions, to run
+    /// snippets without an entry point.
+    pub synthesize_main: Option<SynthesizedMain>,
+    /// The expected output of the run, compared with the actual one in `mismatches`. Exclusive
+    /// with `functions`.
+    pub expected: Option<ExpectedOutput>,
+    /// Whether to return the response of an identical earlier request, run since the module was
+    /// loaded, instead of running again, and to cache the response otherwise.
+    #[serde(default)]
+    pub cache: bool,
+    /// Whether to return the compiler's and runner's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// A run of the `functions` of a [CompileAndRunRequest].
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FunctionRun {
+    pub name: String,
+    /// The arguments of the function, as in the `args` of the request.
+    #[serde(default)]
+    pub args: Vec<RunArg>,
+    /// The arguments of the function as JSON values. Exclusive with `args`.
+    pub typed_args: Option<Vec<serde_json::Value>>,
+    /// The gas available to the run, instead of the `available_gas` of the request.
+    pub available_gas: Option<usize>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct RunSierraRequest {
-    pub sierra: String,
+    /// The Sierra program, as text or as the JSON of a `VersionedProgram`. Exclusive with
+    /// `sierra_program`.
+    pub sierra: Option<String>,
+    /// The Sierra program as the felts of the `sierra_program` of a contract class, as decimal or
+    /// `0x`-prefixed hex strings. Exclusive with `sierra`.
+    pub sierra_program: Option<Vec<String>>,
+    /// The `sierra_program_debug_info` of the contract class, naming the functions of
+    /// `sierra_program`.
+    #[schemars(with = "Option<serde_json::Value>")]
+    pub sierra_program_debug_info: Option<DebugInfo>,
+    /// The handle of a program compiled by `compile_program`. Exclusive with `sierra` and
+    /// `sierra_program`.
+    pub program: Option<u32>,
     pub available_gas: Option<usize>,
     #[serde(default = "default_function_name")]
     pub function: String,
+    /// The number of buckets of the trace summary to return, if any.
+    pub trace_summary: Option<usize>,
+    /// Whether to stop the run once it looks like an infinite loop.
+    #[serde(default)]
+    pub detect_loops: bool,
+    /// The number of VM steps after which the run is stopped.
+    pub max_steps: Option<usize>,
+    /// The number of bytes of `stdout` after which the output stops being captured, the run going
+    /// on.
+    pub max_stdout_bytes: Option<usize>,
+    /// Whether to report the executed divisions and hints, whose semantics differ from integer
+    /// arithmetic on other platforms.
+    #[serde(default)]
+    pub strict_arithmetic: bool,
+    /// Whether to attribute the steps and gas of the run to the functions of the program.
+    #[serde(default)]
+    pub profile: bool,
+    /// Whether to aggregate the invocations and steps of the run by libfunc.
+    #[serde(default)]
+    pub libfunc_profile: bool,
+    /// Whether to return the number of executions of each Sierra statement.
+    #[serde(default)]
+    pub statement_counts: bool,
+    /// Whether to return the relocated trace and memory of the run.
+    #[serde(default)]
+    pub collect_trace: bool,
+    /// Gas costs of builtins overriding the defaults, keyed by builtin name, such as `pedersen`.
+    /// The costs of `step`, `memory_hole`, `range_check`, `range_check96` and of syscalls, such as
+    /// `storage_read`, only reprice `gas_breakdown` and `profile`.
+    #[serde(default)]
+    pub gas_costs: BTreeMap<String, usize>,
+    /// Whether to report `l2_gas`. The run is charged the same gas either way.
+    #[serde(default)]
+    pub report_l2_gas: bool,
+    /// The representations of the returned felts: `decimal`, or `all` to add `formatted_values`.
+    #[serde(default)]
+    pub value_format: ValueFormat,
+    /// The block and transaction info seen by the Starknet syscalls of the run.
+    #[serde(default)]
+    pub starknet_context: StarknetContext,
+    /// Whether to return the response of an identical earlier request, run since the module was
+    /// loaded, instead of running again, and to cache the response otherwise.
+    #[serde(default)]
+    pub cache: bool,
+    /// Whether to return the runner's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// An argument of a run request.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum RunArg {
+    /// A felt, as a decimal or `0x`-prefixed hex string.
+    Felt(String),
+    Typed(TypedRunArg),
+}
+
+/// An argument encoded to several felts, given as `{ "type": .., "value": .. }`.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+pub enum TypedRunArg {
+    /// A string, encoded as a `ByteArray`.
+    ByteArray(String),
+}
+
+impl RunArg {
+    /// Appends the encoding of the argument to `args`.
+    fn encode(&self, args: &mut Vec<Arg>) -> Result<(), ApiError> {
+        match self {
+            RunArg::Felt(value) => args.push(Arg::Value(contract::parse_felt(value)?)),
+            RunArg::Typed(TypedRunArg::ByteArray(value)) => {
+                typed_args::encode_byte_array(value, args)
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Options of a run, shared by the run endpoints.
+#[derive(Default)]
+struct RunOptions<'a> {
+    available_gas: Option<usize>,
+    /// The arguments passed to the function.
+    args: Vec<Arg>,
+    /// The arguments passed to the function, before their encoding. Replace `args` when set.
+    typed_args: Option<Vec<serde_json::Value>>,
+    /// The number of buckets of the trace summary, if one is requested.
+    trace_summary: Option<usize>,
+    detect_loops: bool,
+    /// The number of steps after which the runs are stopped.
+    max_steps: Option<usize>,
+    /// The number of bytes of stdout the runs capture.
+    max_stdout_bytes: Option<usize>,
+    strict_arithmetic: bool,
+    profile: bool,
+    libfunc_profile: bool,
+    statement_counts: bool,
+    coverage: bool,
+    collect_trace: bool,
+    /// Gas costs of builtins, VM resources and syscalls overriding the defaults, keyed by name.
+    gas_costs: BTreeMap<String, usize>,
+    report_l2_gas: bool,
+    value_format: ValueFormat,
+    /// The state the runs start from, holding their execution context.
+    starknet_state: StarknetState,
+    /// Returns the source location of a Sierra statement, when the program was compiled here.
+    locate_statement: Option<&'a dyn Fn(StatementIdx) -> Option<String>>,
+    /// Returns the source lines of a Sierra statement, when the program was compiled here.
+    statement_lines: Option<&'a dyn Fn(StatementIdx) -> Vec<(String, usize)>>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum InliningStrategyArg {
     #[default]
@@ -44,178 +380,1059 @@ pub enum InliningStrategyArg {
     Avoid,
 }
 
-#[derive(Debug, Serialize)]
+/// The crates whose diagnostics are reported.
+#[derive(Debug, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticsScopeArg {
+    /// The main crate and the corelib.
+    #[default]
+    All,
+    /// The main crate only. Errors in the corelib are then only reported as a failed compilation.
+    MainCrate,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
 pub struct RunResponse {
     pub success: bool,
     pub panicked: bool,
     pub values: Vec<String>,
+    /// The returned felts in hex and as short strings, when requested through `value_format`.
+    pub formatted_values: Option<Vec<FormattedValue>>,
+    /// The return value decoded according to the return type of the function, when supported.
+    pub decoded_values: Option<serde_json::Value>,
+    /// The return value rendered with its type, such as `u32: 10` or `ByteArray: "hi"`, with an
+    /// entry for each member of a returned tuple, when supported.
+    pub values_pretty: Option<Vec<String>>,
+    /// The variant returned by a function returning an `Option` or a `Result`, with its decoded
+    /// payload.
+    pub return_variant: Option<ReturnVariant>,
+    /// The panic data decoded to text, when the program panicked.
+    pub panic_message: Option<String>,
+    /// The compared values of a failed comparison assertion, such as `assert_eq!`, and where they
+    /// differ, when the program panicked with one.
+    pub assertion: Option<AssertionFailure>,
     pub stdout: String,
+    /// Whether `stdout` was truncated to the `max_stdout_bytes` of the request.
+    pub stdout_truncated: bool,
+    /// The output of debug prints of values other than strings, such as `PrintTrait::print`,
+    /// followed by the panic message when the program panicked.
+    pub stderr: String,
+    /// The storage of the called contract after a `call_contract` run, writes included, mapping
+    /// hex addresses to values.
+    pub storage: Option<BTreeMap<String, String>>,
+    /// The Starknet events emitted by the run, in the order they were emitted.
+    pub events: Vec<Event>,
+    /// The messages sent to L1 by the run, in the order they were sent.
+    pub l2_to_l1_messages: Vec<L2ToL1Message>,
     pub gas_counter: Option<String>,
+    /// The steps, memory holes and builtin instances used by the run, when it completed.
+    pub resources: Option<VmResources>,
+    /// The gas of `resources` by component, when the run completed.
+    pub gas_breakdown: Option<GasBreakdown>,
+    /// The L2 gas consumed by the run, when it completed and `report_l2_gas` is set.
+    pub l2_gas: Option<usize>,
+    /// The source location the run reached, when it was aborted by an error.
+    pub aborted_at: Option<String>,
+    /// Whether the run was stopped by the host, see `set_cancellation_flag`.
+    pub cancelled: bool,
+    /// The execution timeline, when requested through `trace_summary`.
+    pub trace_summary: Option<Vec<TraceBucket>>,
+    /// The executed divisions and hints, when requested through `strict_arithmetic`.
+    pub arithmetic_report: Option<Vec<ArithmeticSite>>,
+    /// The steps and estimated gas of each function, the most expensive first, when requested
+    /// through `profile`.
+    pub profile: Option<Vec<FunctionProfile>>,
+    /// The invocations and steps of each libfunc, the most expensive first, when requested
+    /// through `libfunc_profile`.
+    pub libfunc_profile: Option<Vec<LibfuncProfile>>,
+    /// The number of executions of each executed Sierra statement, by statement index, when
+    /// requested through `statement_counts`.
+    pub statement_counts: Option<BTreeMap<usize, usize>>,
+    /// The lines of each source file that ran and did not run, by path, when requested through
+    /// `coverage`.
+    pub coverage: Option<BTreeMap<String, FileCoverage>>,
+    /// The relocated trace and memory of the run, when requested through `collect_trace`.
+    pub execution_trace: Option<ExecutionTrace>,
+    /// The run as a base64-encoded Cairo PIE zip archive, when requested through the `cairo_pie`
+    /// of `run_casm`.
+    pub cairo_pie: Option<String>,
+    /// The public and private inputs, trace and memory of a proof-mode run, when requested
+    /// through the `prove_mode` of `run_casm`.
+    pub prover_input: Option<ProverInput>,
+    /// The Stwo proof of the run, when requested through `prove`.
+    pub proof: Option<serde_json::Value>,
+    /// The results of the runs of the `functions` of the request, in their order.
+    pub functions: Option<Vec<RunResponse>>,
+    /// The fields of the output differing from the `expected` output of the request, when given.
+    pub mismatches: Option<Vec<Mismatch>>,
+    /// Whether the run matched the `expected` output of the request, when given: it succeeded, or
+    /// panicked as expected, without mismatches.
+    pub matches_expected: Option<bool>,
     pub diagnostics: String,
-    pub error: Option<String>,
+    /// The number of errors and warnings of each file of `diagnostics`.
+    pub diagnostics_by_file: BTreeMap<String, FileDiagnosticCounts>,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+    /// The heap allocations made while serving the request, when the `allocation-stats` feature
+    /// is enabled.
+    pub allocations: Option<AllocationStats>,
+    /// Whether the response is the cached one of an identical earlier request, see `cache`.
+    pub cached: bool,
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
 pub fn compile_and_run(request_json: &str) -> String {
-    let request: CompileAndRunRequest = match serde_json::from_str(request_json) {
+    let request: CompileAndRunRequest = match parse::request(request_json) {
         Ok(request) => request,
         Err(error) => {
-            return serialize_error(String::new(), format!("Failed parsing request JSON: {error}"));
+            return serialize_error(error);
         }
     };
 
-    let mut diagnostics = String::new();
-    let compiler_config = CompilerConfig {
-        diagnostics_reporter: DiagnosticsReporter::write_to_string(&mut diagnostics),
-        replace_ids: request.replace_ids,
-        ..CompilerConfig::default()
+    let source_bytes = telemetry::source_bytes(
+        request.files.contents.iter().chain(request.corelib_files.iter().flatten()),
+    );
+    let cache_key = request.cache.then(|| run_cache::key(request_json));
+    if let Some(response) = cache_key
+        .as_ref()
+        .and_then(|key| run_cache::serve("compile_and_run", key, request.debug, source_bytes))
+    {
+        return response;
+    }
+    let started_ms = clock::now_ms();
+    let ((result, debug_log), allocations) = allocations::measure(|| {
+        logging::capture(request.debug, || {
+            error::catch_internal(|| compile_and_run_request(request))
+        })
+    });
+    telemetry::report(
+        "compile_and_run",
+        started_ms,
+        source_bytes,
+        result.as_ref().map_or_else(Some, |response| response.error.as_ref()),
+    );
+    serialize_cacheable_run_response(cache_key, result, debug_log, allocations)
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn run_sierra(request_json: &str) -> String {
+    let request: RunSierraRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serialize_error(error);
+        }
     };
 
-    let inlining_strategy = match request.inlining_strategy {
-        InliningStrategyArg::Default => InliningStrategy::Default,
-        InliningStrategyArg::Avoid => InliningStrategy::Avoid,
+    let source_bytes = request.sierra.as_ref().map_or(0, String::len)
+        + request.sierra_program.iter().flatten().map(String::len).sum::<usize>();
+    let cache_key = request.cache.then(|| run_cache::key(request_json));
+    if let Some(response) = cache_key
+        .as_ref()
+        .and_then(|key| run_cache::serve("run_sierra", key, request.debug, source_bytes))
+    {
+        return response;
+    }
+    let started_ms = clock::now_ms();
+    let ((result, debug_log), allocations) = allocations::measure(|| {
+        logging::capture(request.debug, || error::catch_internal(|| run_sierra_request(request)))
+    });
+    telemetry::report(
+        "run_sierra",
+        started_ms,
+        source_bytes,
+        result.as_ref().map_or_else(Some, |response| response.error.as_ref()),
+    );
+    serialize_cacheable_run_response(cache_key, result, debug_log, allocations)
+}
+
+fn compile_and_run_request(request: CompileAndRunRequest) -> RunResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.contents.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return RunResponse { error: Some(error), ..Default::default() };
+    }
+    if !request.args.is_empty() && request.typed_args.is_some() {
+        return RunResponse {
+            error: Some(ApiError::invalid_request("`args` and `typed_args` are exclusive.")),
+            ..Default::default()
+        };
+    }
+    if request.functions.is_some() && (!request.args.is_empty() || request.typed_args.is_some()) {
+        return RunResponse {
+            error: Some(ApiError::invalid_request(
+                "The arguments of the runs of `functions` are given by each function.",
+            )),
+            ..Default::default()
+        };
+    }
+    if request.expected.is_some() && request.functions.is_some() {
+        return RunResponse {
+            error: Some(ApiError::invalid_request("`expected` and `functions` are exclusive.")),
+            ..Default::default()
+        };
+    }
+    if request.executable_args.is_some()
+        && (!request.args.is_empty() || request.typed_args.is_some() || request.functions.is_some())
+    {
+        return RunResponse {
+            error: Some(ApiError::invalid_request(
+                "`executable_args` are exclusive with `args`, `typed_args` and `functions`.",
+            )),
+            ..Default::default()
+        };
+    }
+    let mut args = vec![];
+    if let Err(error) = request.args.iter().try_for_each(|arg| arg.encode(&mut args)) {
+        return RunResponse { error: Some(error), ..Default::default() };
+    }
+    let starknet_state = match request.starknet_context.starknet_state() {
+        Ok(starknet_state) => starknet_state,
+        Err(error) => return RunResponse { error: Some(error), ..Default::default() },
+    };
+    let function = match &request.executable_args {
+        Some(executable_args) => {
+            let mut input = vec![];
+            if let Err(error) = executable_args.iter().try_for_each(|arg| arg.encode(&mut input)) {
+                return RunResponse { error: Some(error), ..Default::default() };
+            }
+            // The wrapper takes the input as a span, and appends the output to an empty array.
+            args = vec![Arg::Array(input), Arg::Array(vec![])];
+            executable_wrapper_name(&request.function)
+        }
+        None => request.function,
     };
-    let project = InMemoryProject {
+
+    let mut project = InMemoryProject {
         main_crate_name: request.crate_name,
-        main_crate_files: request.files,
-        corelib_files: request.corelib_files.unwrap_or_else(embedded_corelib_files),
+        main_crate_files: request.files.contents,
+        corelib_files: corelib::resolve(request.corelib_files),
         main_crate_settings: None,
     };
+    if let Some(synthesized) = &request.synthesize_main
+        && let Err(error) = main_synthesis::add_main(&mut project, synthesized)
+    {
+        return RunResponse { error: Some(error), ..Default::default() };
+    }
+    compile_project(
+        &project,
+        &request.files.hidden,
+        request.inlining_strategy,
+        request.replace_ids,
+        request.diagnostics_scope,
+        |program, locate_statement, statement_lines, diagnostics| {
+            let options = RunOptions {
+                available_gas: request.available_gas,
+                args,
+                typed_args: request.typed_args,
+                trace_summary: request.trace_summary,
+                detect_loops: request.detect_loops,
+                max_steps: request.max_steps,
+                max_stdout_bytes: request.max_stdout_bytes,
+                strict_arithmetic: request.strict_arithmetic,
+                profile: request.profile,
+                libfunc_profile: request.libfunc_profile,
+                statement_counts: request.statement_counts,
+                coverage: request.coverage,
+                collect_trace: request.collect_trace,
+                gas_costs: request.gas_costs,
+                report_l2_gas: request.report_l2_gas,
+                value_format: request.value_format,
+                starknet_state,
+                locate_statement: Some(locate_statement),
+                statement_lines: Some(statement_lines),
+            };
+            match request.functions {
+                Some(functions) => run_functions(program, functions, options, diagnostics),
+                None => {
+                    let mut response = run_program(program, &function, options, diagnostics);
+                    if let Some(expected) = &request.expected {
+                        match grade::compare(expected, &response) {
+                            Ok(mismatches) => {
+                                response.matches_expected =
+                                    Some(grade::matches(expected, &response, &mismatches));
+                                response.mismatches = Some(mismatches);
+                            }
+                            Err(error) => response.error = Some(error),
+                        }
+                    }
+                    response
+                }
+            }
+        },
+    )
+    .unwrap_or_else(|(error, diagnostics)| RunResponse {
+        diagnostics,
+        error: Some(error),
+        ..Default::default()
+    })
+}
+
+/// Compiles `project`, then calls `run` with its Sierra program, functions locating its statements
+/// in the sources and listing their source lines, and the compiler diagnostics.
+///
+/// The diagnostics and locations pointing into `hidden_files` are redacted. Returns the compilation
+/// error along with the diagnostics on failure.
+fn compile_project<R>(
+    project: &InMemoryProject,
+    hidden_files: &BTreeSet<String>,
+    inlining_strategy: InliningStrategyArg,
+    replace_ids: bool,
+    diagnostics_scope: DiagnosticsScopeArg,
+    run: impl FnOnce(
+        Program,
+        &dyn Fn(StatementIdx) -> Option<String>,
+        &dyn Fn(StatementIdx) -> Vec<(String, usize)>,
+        String,
+    ) -> R,
+) -> Result<R, (ApiError, String)> {
+    let inlining_strategy = match inlining_strategy {
+        InliningStrategyArg::Default => InliningStrategy::Default,
+        InliningStrategyArg::Avoid => InliningStrategy::Avoid,
+    };
+    let (db, main_crate_inputs) = prepare_in_memory_project_db(
+        project,
+        inlining_strategy,
+        plugins::in_memory_project_suite(),
+    )
+    .map_err(|error| (ApiError::compilation(error.to_string()), String::new()))?;
+    // The compiler names the files after their paths relative to the source root of the crate.
+    let source_root = crate_source_root("main", &project.main_crate_files).unwrap_or_default();
+    let hidden_files = hidden_files
+        .iter()
+        .filter_map(|path| path.strip_prefix(source_root))
+        .collect::<BTreeSet<_>>();
+    let hidden_files = &hidden_files;
+
+    let mut diagnostics = String::new();
+    let mut diagnostics_reporter = DiagnosticsReporter::callback(|diagnostic| {
+        let diagnostic = redact_diagnostic(diagnostic.to_string(), hidden_files);
+        diagnostic_listener::report(&diagnostic);
+        diagnostics.push_str(&diagnostic);
+    });
+    if let DiagnosticsScopeArg::MainCrate = diagnostics_scope {
+        diagnostics_reporter = diagnostics_reporter.with_crates(&main_crate_inputs);
+    }
+    let compiler_config =
+        CompilerConfig { diagnostics_reporter, replace_ids, ..CompilerConfig::default() };
+    let main_crate_ids = CrateInput::into_crate_ids(&db, main_crate_inputs);
+    // The source lines are only listed in the files of the main crates, not in the corelib.
+    let main_crate_files = main_crate_ids
+        .iter()
+        .flat_map(|crate_id| db.crate_modules(*crate_id).iter())
+        .filter_map(|module_id| db.module_main_file(*module_id).ok())
+        .collect::<HashSet<_>>();
+    // The debug info is kept to locate the statements reported by the runner in the sources.
+    let SierraProgramWithDebug { program, debug_info } =
+        match compile_prepared_db(&db, main_crate_ids, compiler_config) {
+            Ok(compiled) => compiled,
+            Err(error) => return Err((ApiError::compilation(error.to_string()), diagnostics)),
+        };
+    let locate_statement = |statement_idx| {
+        let location =
+            debug_info.statements_locations.statement_diagnostic_location(&db, statement_idx)?;
+        let path = location.file_id.full_path(&db);
+        if is_hidden(&path, hidden_files) {
+            return None;
+        }
+        let position = location.span.start.position_in_file(&db, location.file_id)?;
+        Some(format!("{path}:{}", position.line + 1))
+    };
+    // Unlike `locate_statement`, lists the lines of all the locations of the statement, which
+    // include the call sites of the functions inlined into it.
+    let statement_lines = |statement_idx: StatementIdx| {
+        let Some(locations) = debug_info.statements_locations.locations.get(&statement_idx) else {
+            return vec![];
+        };
+        locations
+            .iter()
+            .filter_map(|location| {
+                let location = location.span_in_file(&db).user_location(&db);
+                if !main_crate_files.contains(&location.file_id) {
+                    return None;
+                }
+                let path = location.file_id.full_path(&db);
+                if is_hidden(&path, hidden_files) {
+                    return None;
+                }
+                let position = location.span.start.position_in_file(&db, location.file_id)?;
+                Some((path, position.line + 1))
+            })
+            .collect()
+    };
+    Ok(run(program, &locate_statement, &statement_lines, diagnostics))
+}
+
+/// Replaces the locations and snippets of a diagnostic pointing into `hidden_files` with a
+/// placeholder, keeping its message.
+fn redact_diagnostic(diagnostic: String, hidden_files: &BTreeSet<&str>) -> String {
+    let points_into_hidden_file = diagnostic
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("--> "))
+        .any(|location| is_hidden(location, hidden_files));
+    if !points_into_hidden_file {
+        return diagnostic;
+    }
+    let message = diagnostic.lines().next().unwrap_or_default();
+    let trailing_newlines = &diagnostic[diagnostic.trim_end_matches('\n').len()..];
+    format!("{message}\n --> <hidden file>{trailing_newlines}")
+}
 
-    let program = match compile_in_memory_project(&project, compiler_config, inlining_strategy) {
+/// Returns whether the file at `path`, as reported by the compiler, is one of `hidden_files`.
+///
+/// `path` may be followed by a position, and the compiler names the files generated by macros
+/// after the location they are generated at, as in `tests.cairo:3:1: 3:9[expanded]`.
+fn is_hidden(path: &str, hidden_files: &BTreeSet<&str>) -> bool {
+    let path = path.split([':', '[']).next().unwrap_or(path);
+    hidden_files.contains(path)
+}
+
+fn run_sierra_request(request: RunSierraRequest) -> RunResponse {
+    // Only compiled programs locate their statements in the sources.
+    let program = match (request.sierra, request.sierra_program, request.program) {
+        (Some(sierra), None, None) => limits::current()
+            .check_source("Sierra program", &sierra)
+            .and_then(|()| parse_sierra(&sierra))
+            .map(|program| (program, None)),
+        (None, Some(felts), None) => limits::current()
+            .check_source("Sierra program", &felts.concat())
+            .and_then(|()| decode_sierra_felts(&felts, request.sierra_program_debug_info.as_ref()))
+            .map(|program| (program, None)),
+        (None, None, Some(program)) => compiled_programs::get(program)
+            .map(|compiled| (compiled.program, Some(compiled.debug_info))),
+        _ => Err(ApiError::invalid_request(
+            "Exactly one of `sierra`, `sierra_program` and `program` must be given.",
+        )),
+    };
+    let (program, debug_info) = match program.and_then(|(program, debug_info)| {
+        check_supported_extensions(&program)?;
+        Ok((program, debug_info))
+    }) {
         Ok(program) => program,
-        Err(error) => return serialize_error(diagnostics, error.to_string()),
+        Err(error) => return RunResponse { error: Some(error), ..Default::default() },
+    };
+    let locate_statement = |statement_idx| debug_info.as_ref()?.location(statement_idx);
+    let statement_lines = |statement_idx| {
+        debug_info.as_ref().map(|debug_info| debug_info.lines(statement_idx)).unwrap_or_default()
     };
 
-    serialize_run_response(run_program(
-        program,
-        &request.function,
-        request.available_gas,
-        diagnostics,
-    ))
+    let starknet_state = match request.starknet_context.starknet_state() {
+        Ok(starknet_state) => starknet_state,
+        Err(error) => return RunResponse { error: Some(error), ..Default::default() },
+    };
+    let options = RunOptions {
+        available_gas: request.available_gas,
+        trace_summary: request.trace_summary,
+        detect_loops: request.detect_loops,
+        max_steps: request.max_steps,
+        max_stdout_bytes: request.max_stdout_bytes,
+        strict_arithmetic: request.strict_arithmetic,
+        profile: request.profile,
+        libfunc_profile: request.libfunc_profile,
+        statement_counts: request.statement_counts,
+        collect_trace: request.collect_trace,
+        gas_costs: request.gas_costs,
+        report_l2_gas: request.report_l2_gas,
+        value_format: request.value_format,
+        starknet_state,
+        locate_statement: debug_info.is_some().then_some(&locate_statement as _),
+        statement_lines: debug_info.is_some().then_some(&statement_lines as _),
+        ..Default::default()
+    };
+    run_program(program, &request.function, options, String::new())
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
-pub fn run_sierra(request_json: &str) -> String {
-    let request: RunSierraRequest = match serde_json::from_str(request_json) {
-        Ok(request) => request,
-        Err(error) => {
-            return serialize_error(String::new(), format!("Failed parsing request JSON: {error}"));
+/// Parses a Sierra program, either as text or as the JSON of a `VersionedProgram`, such as the
+/// `*.sierra.json` artifacts of Scarb.
+fn parse_sierra(sierra: &str) -> Result<Program, ApiError> {
+    if sierra.trim_start().starts_with('{') {
+        let versioned: VersionedProgram = serde_json::from_str(sierra).map_err(|error| {
+            ApiError::invalid_request(format!("Failed parsing Sierra program JSON: {error}"))
+        })?;
+        let artifact =
+            versioned.into_v1().map_err(|error| ApiError::invalid_request(format!("{error:#}")))?;
+        return Ok(artifact.program);
+    }
+    ProgramParser::new().parse(sierra).map_err(|error| {
+        ApiError::invalid_request(format!("Failed parsing Sierra program: {error:?}"))
+    })
+}
+
+/// Decodes a Sierra program from the felts of the `sierra_program` of a contract class.
+///
+/// The functions are named after `debug_info` when given, and after their Sierra ids otherwise,
+/// e.g. `[3]`, as the felts leave out their names.
+fn decode_sierra_felts(
+    felts: &[String],
+    debug_info: Option<&DebugInfo>,
+) -> Result<Program, ApiError> {
+    let felts = felts
+        .iter()
+        .map(|felt| Ok(BigUintAsHex { value: contract::parse_felt(felt)?.to_biguint() }))
+        .collect::<Result<Vec<_>, ApiError>>()?;
+    let (_, _, mut program) = sierra_from_felt252s(&felts).map_err(|error| {
+        ApiError::invalid_request(format!("Failed decoding the Sierra program felts: {error}"))
+    })?;
+    if let Some(debug_info) = debug_info {
+        debug_info.populate(&mut program);
+    }
+    for function in &mut program.funcs {
+        if function.id.debug_name.is_none() {
+            function.id.debug_name = Some(function.id.to_string().into());
         }
-    };
+    }
+    Ok(program)
+}
 
-    let program = match ProgramParser::new().parse(&request.sierra) {
-        Ok(program) => program,
-        Err(error) => {
-            return serialize_error(
-                String::new(),
-                format!("Failed parsing Sierra program: {error:?}"),
-            );
+/// Checks that the runner supports the types and libfuncs declared by `program`, which it may not
+/// when the program was compiled by another version of the compiler.
+fn check_supported_extensions(program: &Program) -> Result<(), ApiError> {
+    let unsupported_types = program
+        .type_declarations
+        .iter()
+        .map(|declaration| &declaration.long_id.generic_id)
+        .filter(|generic_id| CoreType::by_id(generic_id).is_none())
+        .map(|generic_id| format!("`{generic_id}`"))
+        .collect::<BTreeSet<_>>();
+    let unsupported_libfuncs = program
+        .libfunc_declarations
+        .iter()
+        .map(|declaration| &declaration.long_id.generic_id)
+        .filter(|generic_id| CoreLibfunc::by_id(generic_id).is_none())
+        .map(|generic_id| format!("`{generic_id}`"))
+        .collect::<BTreeSet<_>>();
+    let mut unsupported = vec![];
+    for (kind, ids) in [("types", unsupported_types), ("libfuncs", unsupported_libfuncs)] {
+        if !ids.is_empty() {
+            unsupported.push(format!("{kind} {}", ids.into_iter().collect::<Vec<_>>().join(", ")));
         }
-    };
+    }
+    if unsupported.is_empty() {
+        return Ok(());
+    }
+    Err(ApiError::invalid_request(format!(
+        "The Sierra program was likely compiled by another version of the compiler than the one \
+         of this runner, Cairo {}. Unsupported {}.",
+        env!("CARGO_PKG_VERSION"),
+        unsupported.join(" and ")
+    )))
+}
+
+/// Returns the provenance of the module, such as its git commit and features. See
+/// [cairo_lang_wasm_utils::build_info::BuildInfo].
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn build_info() -> String {
+    build_info::build_info_json(&[("wasm-exports", cfg!(feature = "wasm-exports"))])
+}
+
+/// Applies the module-wide configuration, such as input limits. See
+/// [cairo_lang_wasm_utils::config::HostConfig].
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn configure(config_json: &str) -> String {
+    config::configure(config_json)
+}
+
+/// The caches kept by the runner across requests, and the programs of `compile_program`, captured
+/// in module state snapshots.
+pub const STATE_CACHES: &[StateCache] = &[run_cache::STATE_CACHE, compiled_programs::STATE_CACHE];
+
+/// Returns the module state, such as its configuration, cached runs and compiled programs, for
+/// restoring it in other instances with `restore_state`. See
+/// [cairo_lang_wasm_utils::state::ModuleState].
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn snapshot_state() -> String {
+    state::snapshot_state(STATE_CACHES)
+}
+
+/// Restores a module state returned by `snapshot_state`.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn restore_state(state_json: &str) -> String {
+    state::restore_state(state_json, STATE_CACHES)
+}
+
+/// Makes the runs poll `flag`, an `Int32Array` usually viewing a `SharedArrayBuffer` shared with
+/// the host, and stop with a `cancelled` response once its first element is non-zero. The host
+/// resets the flag before the next run.
+#[cfg(target_arch = "wasm32")]
+#[cfg_attr(feature = "wasm-exports", wasm_bindgen)]
+pub fn set_cancellation_flag(flag: js_sys::Int32Array) {
+    cancellation::set_flag(flag);
+}
 
-    serialize_run_response(run_program(
-        program,
-        &request.function,
-        request.available_gas,
-        String::new(),
-    ))
+/// Makes the runs call `callback` periodically, and stop with a `cancelled` response once it
+/// returns a truthy value.
+#[cfg(target_arch = "wasm32")]
+#[cfg_attr(feature = "wasm-exports", wasm_bindgen)]
+pub fn set_cancellation_callback(callback: js_sys::Function) {
+    cancellation::set_callback(callback);
+}
+
+/// Removes the cancellation flag or callback of the runs.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn clear_cancellation() {
+    cancellation::set_check(None);
+}
+
+/// Makes the compilations call `callback` with each diagnostic as it is reported, serialized as a
+/// [cairo_lang_wasm_utils::diagnostic_listener::StreamedDiagnostic].
+#[cfg(target_arch = "wasm32")]
+#[cfg_attr(feature = "wasm-exports", wasm_bindgen)]
+pub fn set_diagnostics_callback(callback: js_sys::Function) {
+    diagnostic_listener::set_callback(callback);
+}
+
+/// Removes the diagnostics callback of the compilations.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn clear_diagnostics_callback() {
+    diagnostic_listener::set_listener(None);
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+/// Makes the requests call `callback` with their outcomes, serialized as a
+/// [cairo_lang_wasm_utils::telemetry::TelemetryEvent].
+#[cfg(target_arch = "wasm32")]
+#[cfg_attr(feature = "wasm-exports", wasm_bindgen)]
+pub fn set_telemetry_callback(callback: js_sys::Function) {
+    telemetry::set_callback(callback);
+}
+
+/// Removes the telemetry callback of the requests.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn clear_telemetry_callback() {
+    telemetry::set_listener(None);
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
 pub fn embedded_corelib_manifest() -> String {
-    let files =
-        EMBEDDED_CORELIB_FILES.iter().map(|(path, _)| (*path).to_string()).collect::<Vec<_>>();
-    serde_json::to_string(&files).expect("serialize corelib manifest")
+    corelib::manifest_json()
 }
 
 fn run_program(
     program: Program,
     function: &str,
-    available_gas: Option<usize>,
+    options: RunOptions<'_>,
     diagnostics: String,
 ) -> RunResponse {
-    if available_gas.is_none() && program.requires_gas_counter() {
-        return RunResponse {
-            success: false,
-            panicked: false,
-            values: vec![],
-            stdout: String::new(),
-            gas_counter: None,
-            diagnostics,
-            error: Some("Program requires gas counter; provide `available_gas`.".into()),
-        };
+    let mut response = match ProgramRunner::new(program, &options) {
+        Ok(runner) => runner.run(function, options.args, options.typed_args, options.available_gas),
+        Err(error) => RunResponse { error: Some(error), ..Default::default() },
+    };
+    response.diagnostics = diagnostics;
+    response
+}
+
+/// Returns the name of the wrapper generated for the `#[executable]` function `function`.
+fn executable_wrapper_name(function: &str) -> String {
+    match function.rsplit_once("::") {
+        Some((path, name)) => format!("{path}::{EXECUTABLE_PREFIX}{name}"),
+        None => format!("{EXECUTABLE_PREFIX}{function}"),
     }
+}
 
-    let runner = match SierraCasmRunner::new(
-        program,
-        if available_gas.is_some() { Some(Default::default()) } else { None },
-        Default::default(),
-        None,
-    ) {
+/// Runs each of `functions` with a single runner of `program`.
+fn run_functions(
+    program: Program,
+    functions: Vec<FunctionRun>,
+    mut options: RunOptions<'_>,
+    diagnostics: String,
+) -> RunResponse {
+    // The runner is set up for gas if any run has some.
+    let request_gas = options.available_gas;
+    options.available_gas =
+        request_gas.or_else(|| functions.iter().find_map(|function| function.available_gas));
+    let runner = match ProgramRunner::new(program, &options) {
         Ok(runner) => runner,
-        Err(error) => {
-            return RunResponse {
-                success: false,
-                panicked: false,
-                values: vec![],
-                stdout: String::new(),
-                gas_counter: None,
-                diagnostics,
-                error: Some(format!("Failed setting up runner: {error}")),
-            };
-        }
+        Err(error) => return RunResponse { diagnostics, error: Some(error), ..Default::default() },
     };
+    let runs = functions
+        .into_iter()
+        .map(|function| {
+            if !function.args.is_empty() && function.typed_args.is_some() {
+                return RunResponse {
+                    error: Some(ApiError::invalid_request(
+                        "`args` and `typed_args` are exclusive.",
+                    )),
+                    ..Default::default()
+                };
+            }
+            let mut args = vec![];
+            if let Err(error) = function.args.iter().try_for_each(|arg| arg.encode(&mut args)) {
+                return RunResponse { error: Some(error), ..Default::default() };
+            }
+            let available_gas = function.available_gas.or(request_gas);
+            runner.run(&function.name, args, function.typed_args, available_gas)
+        })
+        .collect::<Vec<_>>();
+    RunResponse {
+        success: runs.iter().all(|run| run.success),
+        functions: Some(runs),
+        diagnostics,
+        ..Default::default()
+    }
+}
 
-    let func = match runner.find_function(function) {
-        Ok(func) => func,
-        Err(error) => {
-            return RunResponse {
-                success: false,
-                panicked: false,
-                values: vec![],
-                stdout: String::new(),
-                gas_counter: None,
-                diagnostics,
-                error: Some(format!("Failed finding function `{function}`: {error}")),
-            };
+/// A runner of a program, shared by the runs of a request.
+struct ProgramRunner<'a> {
+    runner: SierraCasmRunner,
+    requires_gas_counter: bool,
+    strict_arithmetic: bool,
+    profile: bool,
+    libfunc_profile: bool,
+    statement_counts: bool,
+    coverage: bool,
+    report_l2_gas: bool,
+    value_format: ValueFormat,
+    /// The names of the functions of the program, if a trace summary is requested.
+    function_names: Option<Vec<String>>,
+    locate_statement: Option<&'a dyn Fn(StatementIdx) -> Option<String>>,
+    statement_lines: Option<&'a dyn Fn(StatementIdx) -> Vec<(String, usize)>>,
+    /// The costs pricing the gas breakdown and the profile of the runs.
+    resource_costs: ResourceCosts,
+    /// The state the runs start from.
+    starknet_state: StarknetState,
+}
+
+impl<'a> ProgramRunner<'a> {
+    /// Sets up a runner of `program` with the options of `options` shared by all runs.
+    fn new(program: Program, options: &RunOptions<'a>) -> Result<Self, ApiError> {
+        if options.trace_summary == Some(0) {
+            return Err(ApiError::invalid_request("`trace_summary` must be at least 1."));
         }
-    };
+        let requires_gas_counter = program.requires_gas_counter();
+        let available_gas = gas::current().apply(options.available_gas);
+        if available_gas.is_none() && requires_gas_counter {
+            return Err(missing_gas_error());
+        }
+        let (gas_costs, resource_costs) = parse_gas_costs(&options.gas_costs)?;
 
-    let result = match runner.run_function_with_starknet_context(
-        func,
-        vec![],
-        available_gas,
-        StarknetState::default(),
-    ) {
-        Ok(result) => result,
-        Err(error) => {
-            return RunResponse {
-                success: false,
-                panicked: false,
-                values: vec![],
-                stdout: String::new(),
-                gas_counter: None,
-                diagnostics,
-                error: Some(format!("Failed to run function `{function}`: {error}")),
-            };
+        // The trace summary refers to functions by name, and the program is moved into the
+        // runner.
+        let function_names = options
+            .trace_summary
+            .map(|_| program.funcs.iter().map(|func| func.id.to_string()).collect::<Vec<_>>());
+        let run_profiler = (options.trace_summary.is_some()
+            || options.strict_arithmetic
+            || options.profile
+            || options.libfunc_profile
+            || options.statement_counts
+            || options.coverage)
+            .then(|| ProfilingInfoCollectionConfig {
+                timeline_buckets: options.trace_summary,
+                ..Default::default()
+            });
+        let runner = SierraCasmRunner::new(
+            program,
+            if available_gas.is_some() { Some(Default::default()) } else { None },
+            Default::default(),
+            run_profiler,
+        )
+        .map_err(|error| ApiError::execution(format!("Failed setting up runner: {error}")))?
+        .with_token_gas_costs(gas_costs);
+        let runner = if options.detect_loops {
+            runner.with_loop_detection(Default::default())
+        } else {
+            runner
+        };
+        let runner = if options.collect_trace { runner.with_trace_collection() } else { runner };
+        let runner = match options.max_steps {
+            Some(max_steps) => runner.with_max_steps(max_steps),
+            None => runner,
+        };
+        let runner = match options.max_stdout_bytes {
+            Some(max_stdout_bytes) => runner.with_max_stdout_bytes(max_stdout_bytes),
+            None => runner,
+        };
+        let runner = if cancellation::is_enabled() {
+            runner.with_cancellation(CancellationCheck {
+                check_interval: CANCELLATION_CHECK_INTERVAL,
+                is_cancelled: Box::new(cancellation::is_cancelled),
+            })
+        } else {
+            runner
+        };
+        Ok(Self {
+            runner,
+            requires_gas_counter,
+            strict_arithmetic: options.strict_arithmetic,
+            profile: options.profile,
+            libfunc_profile: options.libfunc_profile,
+            statement_counts: options.statement_counts,
+            coverage: options.coverage,
+            report_l2_gas: options.report_l2_gas,
+            value_format: options.value_format,
+            function_names,
+            locate_statement: options.locate_statement,
+            statement_lines: options.statement_lines,
+            resource_costs,
+            starknet_state: options.starknet_state.clone(),
+        })
+    }
+
+    /// Runs `function` with `args`, or `typed_args` when set.
+    ///
+    /// The runner must have been set up with `available_gas` if it is set. The gas policy of the
+    /// host applies to `available_gas`.
+    fn run(
+        &self,
+        function: &str,
+        args: Vec<Arg>,
+        typed_args: Option<Vec<serde_json::Value>>,
+        available_gas: Option<usize>,
+    ) -> RunResponse {
+        let Self {
+            runner,
+            requires_gas_counter,
+            strict_arithmetic,
+            profile,
+            libfunc_profile,
+            statement_counts,
+            coverage,
+            report_l2_gas,
+            value_format,
+            function_names,
+            locate_statement,
+            statement_lines,
+            resource_costs,
+            starknet_state,
+        } = self;
+        let available_gas = gas::current().apply(available_gas);
+        if available_gas.is_none() && *requires_gas_counter {
+            return RunResponse { error: Some(missing_gas_error()), ..Default::default() };
         }
-    };
+        let func = match runner.find_function(function) {
+            Ok(func) => func,
+            Err(error) => {
+                let functions = runner.builder().sierra_program().funcs.iter();
+                let suggestions = suggestions::function_suggestions(
+                    function,
+                    functions.filter_map(|func| func.id.debug_name.as_deref()),
+                );
+                return RunResponse {
+                    error: Some(
+                        ApiError::invalid_request(format!(
+                            "Failed finding function `{function}`: {error}"
+                        ))
+                        .with_suggestions(suggestions),
+                    ),
+                    ..Default::default()
+                };
+            }
+        };
 
-    let (panicked, values) = match result.value {
-        RunResultValue::Success(values) => (false, values),
-        RunResultValue::Panic(values) => (true, values),
-    };
+        let args = match typed_args {
+            Some(values) => match typed_args::encode_args(runner.builder(), func, &values) {
+                Ok(args) => args,
+                Err(error) => return RunResponse { error: Some(error), ..Default::default() },
+            },
+            None => args,
+        };
 
-    RunResponse {
-        success: !panicked,
-        panicked,
-        values: values.into_iter().map(|felt| felt.to_string()).collect(),
-        stdout: result.stdout,
-        gas_counter: result.gas_counter.map(|gas| gas.to_string()),
-        diagnostics,
-        error: None,
+        let result = match runner.run_function_with_starknet_context(
+            func,
+            args,
+            available_gas,
+            starknet_state.clone(),
+        ) {
+            Ok(result) => result,
+            Err(error) => {
+                let (error, partial_run) = match error {
+                    RunnerError::Aborted { error, partial_run } => (*error, *partial_run),
+                    error => (error, PartialRun::default()),
+                };
+                let location = match &error {
+                    RunnerError::PossibleInfiniteLoop { statement_idx, .. } => locate_statement
+                        .and_then(|locate_statement| locate_statement(*statement_idx)),
+                    _ => None,
+                };
+                let message = match location {
+                    Some(location) => {
+                        format!(
+                            "Failed to run function `{function}`: {error} The loop is at \
+                             {location}."
+                        )
+                    }
+                    None => format!("Failed to run function `{function}`: {error}"),
+                };
+                let kind = match error {
+                    RunnerError::StepLimitExceeded { .. } => ErrorKind::StepLimitExceeded,
+                    RunnerError::Cancelled => ErrorKind::Cancelled,
+                    _ => ErrorKind::Execution,
+                };
+                let (stdout, stderr) = debug_output::split(partial_run.stdout);
+                return RunResponse {
+                    stdout,
+                    stdout_truncated: partial_run.stdout_truncated,
+                    stderr,
+                    gas_counter: partial_run.gas_counter.map(|gas| gas.to_string()),
+                    aborted_at: partial_run.statement_idx.zip(*locate_statement).and_then(
+                        |(statement_idx, locate_statement)| locate_statement(statement_idx),
+                    ),
+                    cancelled: kind == ErrorKind::Cancelled,
+                    error: Some(ApiError::new(kind, message)),
+                    ..Default::default()
+                };
+            }
+        };
+
+        let (panicked, values) = match result.value {
+            RunResultValue::Success(values) => (false, values),
+            RunResultValue::Panic(values) => (true, values),
+        };
+        // The wrapper of an executable returns the array it serialized the return value to.
+        let executable =
+            function.rsplit("::").next().is_some_and(|name| name.starts_with(EXECUTABLE_PREFIX));
+        let values = if executable && !panicked {
+            match typed_values::executable_output(&values, &result.memory) {
+                Ok(values) => values,
+                Err(error) => {
+                    return RunResponse {
+                        error: Some(ApiError::execution(error)),
+                        ..Default::default()
+                    };
+                }
+            }
+        } else {
+            values
+        };
+
+        let decoded_values = (!panicked && !executable)
+            .then(|| {
+                typed_values::decode_return_values(runner.builder(), func, &values, &result.memory)
+            })
+            .and_then(Result::ok);
+        let values_pretty = (!panicked && !executable)
+            .then(|| {
+                typed_values::pretty_return_values(runner.builder(), func, &values, &result.memory)
+            })
+            .and_then(Result::ok);
+        let return_variant = (!panicked && !executable)
+            .then(|| typed_values::return_variant(runner.builder(), func, &values, &result.memory))
+            .and_then(Result::ok)
+            .flatten();
+        let arithmetic_report =
+            result.profiling_info.as_ref().filter(|_| *strict_arithmetic).map(|profiling_info| {
+                arithmetic_report::arithmetic_sites(
+                    runner.builder(),
+                    profiling_info,
+                    *locate_statement,
+                )
+            });
+        let profile = result.profiling_info.as_ref().filter(|_| *profile).map(|profiling_info| {
+            function_profile::profile(
+                runner.builder().sierra_program(),
+                profiling_info,
+                resource_costs.step(),
+            )
+        });
+        let libfunc_profile =
+            result.profiling_info.as_ref().filter(|_| *libfunc_profile).map(|profiling_info| {
+                libfunc_profile::profile(runner.builder().sierra_program(), profiling_info)
+            });
+        let statement_counts =
+            result.profiling_info.as_ref().filter(|_| *statement_counts).map(|profiling_info| {
+                let counts = profiling_info.sierra_statement_counts.iter_sorted();
+                counts.map(|(statement_idx, count)| (statement_idx.0, *count)).collect()
+            });
+        let coverage =
+            result.profiling_info.as_ref().filter(|_| *coverage).zip(*statement_lines).map(
+                |(profiling_info, statement_lines)| {
+                    coverage::coverage(
+                        runner.builder().sierra_program(),
+                        profiling_info,
+                        statement_lines,
+                    )
+                },
+            );
+        let execution_trace =
+            result.trace.as_deref().map(|trace| execution_trace::export(trace, &result.memory));
+        let gas_breakdown = GasBreakdown::new(
+            &result.used_resources,
+            |token_type| runner.token_gas_cost(token_type),
+            resource_costs,
+        );
+        let gas_counter = result.gas_counter.map(|gas| gas.to_string());
+        // Programs without a gas counter consume the gas of their resources.
+        let l2_gas = report_l2_gas.then(|| {
+            available_gas
+                .zip(gas_counter.as_ref().and_then(|gas| gas.parse::<usize>().ok()))
+                .map_or(gas_breakdown.total, |(available_gas, gas_left)| available_gas - gas_left)
+        });
+        let panic_message = panicked.then(|| panic_message::panic_message(&values));
+        let (stdout, stderr) = debug_output::split(result.stdout);
+        let stderr = panic_message::with_panic_line(stderr, panic_message.as_deref());
+        RunResponse {
+            success: !panicked,
+            panicked,
+            assertion: panic_message.as_deref().and_then(assertion::decode),
+            panic_message,
+            formatted_values: (*value_format == ValueFormat::All)
+                .then(|| values.iter().map(FormattedValue::new).collect()),
+            values: values.into_iter().map(|felt| felt.to_string()).collect(),
+            decoded_values,
+            values_pretty,
+            return_variant,
+            stdout,
+            stdout_truncated: result.stdout_truncated,
+            stderr,
+            storage: None,
+            events: events::events(&result.starknet_state),
+            l2_to_l1_messages: messages::l2_to_l1_messages(&result.starknet_state),
+            gas_counter,
+            resources: Some(VmResources::from(&result.used_resources)),
+            gas_breakdown: Some(gas_breakdown),
+            l2_gas,
+            aborted_at: None,
+            cancelled: false,
+            trace_summary: result
+                .profiling_info
+                .and_then(|profiling_info| profiling_info.timeline)
+                .zip(function_names.as_ref())
+                .map(|(timeline, function_names)| {
+                    trace_summary::summarize(timeline, function_names)
+                }),
+            arithmetic_report,
+            profile,
+            libfunc_profile,
+            statement_counts,
+            coverage,
+            execution_trace,
+            cairo_pie: None,
+            prover_input: None,
+            proof: None,
+            functions: None,
+            mismatches: None,
+            matches_expected: None,
+            diagnostics: String::new(),
+            diagnostics_by_file: BTreeMap::new(),
+            error: None,
+            debug_log: None,
+            allocations: None,
+            cached: false,
+        }
+    }
+}
+
+/// Parses gas costs keyed by builtin name into the costs of their token types, and the costs keyed
+/// by VM resource or syscall name into the costs pricing the gas breakdown.
+fn parse_gas_costs(
+    gas_costs: &BTreeMap<String, usize>,
+) -> Result<(OrderedHashMap<CostTokenType, usize>, ResourceCosts), ApiError> {
+    let mut token_gas_costs = OrderedHashMap::default();
+    let mut resource_costs = ResourceCosts::default();
+    for (name, cost) in gas_costs {
+        if let Some(token_type) =
+            CostTokenType::iter_precost().find(|token_type| token_type.name() == *name)
+        {
+            token_gas_costs.insert(*token_type, *cost);
+        } else if !resource_costs.insert(name, *cost) {
+            let names = CostTokenType::iter_precost().map(CostTokenType::name);
+            return Err(ApiError::invalid_request(format!(
+                "Unknown cost `{name}` in `gas_costs`, expected a builtin among {}, `step`, \
+                 `memory_hole`, `range_check`, `range_check96` or a syscall such as \
+                 `storage_read`.",
+                names.map(|name| format!("`{name}`")).collect::<Vec<_>>().join(", ")
+            )));
+        }
     }
+    Ok((token_gas_costs, resource_costs))
 }
 
 fn default_function_name() -> String {
@@ -226,34 +1443,55 @@ fn default_replace_ids() -> bool {
     true
 }
 
-fn embedded_corelib_files() -> BTreeMap<String, String> {
-    EMBEDDED_CORELIB_FILES
-        .iter()
-        .map(|(path, content)| ((*path).to_string(), (*content).to_string()))
-        .collect()
+fn missing_gas_error() -> ApiError {
+    ApiError::invalid_request("Program requires gas counter; provide `available_gas`.")
 }
 
-fn serialize_error(diagnostics: String, error: String) -> String {
-    serialize_run_response(RunResponse {
-        success: false,
-        panicked: false,
-        values: vec![],
-        stdout: String::new(),
-        gas_counter: None,
-        diagnostics,
-        error: Some(error),
-    })
+/// Serializes a run response, caching it under `cache_key` when set.
+fn serialize_cacheable_run_response(
+    cache_key: Option<run_cache::Key>,
+    result: Result<RunResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+    allocations: Option<AllocationStats>,
+) -> String {
+    let cacheable = run_cache::is_cacheable(&result);
+    let response = serialize_run_response(result, debug_log, allocations);
+    if let Some(cache_key) = cache_key
+        && cacheable
+    {
+        run_cache::insert(cache_key, &response);
+    }
+    response
+}
+
+fn serialize_error(error: ApiError) -> String {
+    serialize_run_response(Ok(RunResponse { error: Some(error), ..Default::default() }), None, None)
 }
 
-fn serialize_run_response(response: RunResponse) -> String {
+fn serialize_run_response(
+    result: Result<RunResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+    allocations: Option<AllocationStats>,
+) -> String {
+    let mut response =
+        result.unwrap_or_else(|error| RunResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+    response.diagnostics_by_file = diagnostic_counts::by_file(&response.diagnostics);
+    response.allocations = allocations;
     serde_json::to_string(&response).expect("serialize run response")
 }
 
 #[cfg(test)]
 mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use cairo_lang_sierra::ProgramParser;
+    use cairo_lang_sierra::program::{ProgramArtifact, VersionedProgram};
+    use cairo_lang_wasm_utils::{cancellation, telemetry};
     use serde_json::{Value, json};
 
-    use super::compile_and_run;
+    use super::{compile_and_run, configure, run_sierra};
 
     #[test]
     fn compile_and_run_simple_program() {
@@ -276,40 +1514,1151 @@ mod tests {
     }
 
     #[test]
-    fn compile_and_run_hello_world() {
-        let request = json!({
-            "crate_name": "test",
-            "files": {
-                "lib.cairo": "fn main() { println!(\"Hello World\"); }"
-            },
-            "available_gas": 1000000
+    fn configured_gas_policy_fills_in_and_caps_available_gas() {
+        let config = json!({
+            "gas": { "default_available_gas": 1000000, "max_available_gas": 2000000 }
         });
+        let response_json: Value = serde_json::from_str(&configure(&config.to_string())).unwrap();
+        assert_eq!(response_json["success"], true);
+        let run = |available_gas: Option<u64>| -> Value {
+            let request = json!({
+                "crate_name": "test",
+                "files": {
+                    "lib.cairo": "\
+fn main() -> u32 {
+    let mut i = 0;
+    while i != 100 {
+        i += 1;
+    }
+    i
+}
+"
+                },
+                "available_gas": available_gas
+            });
+            serde_json::from_str(&compile_and_run(&request.to_string())).unwrap()
+        };
 
-        let response = compile_and_run(&request.to_string());
-        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+        let gas_counter = |response_json: &Value| -> u64 {
+            response_json["gas_counter"].as_str().unwrap().parse().unwrap()
+        };
 
-        assert_eq!(response_json["success"], true, "response={response}");
-        assert_eq!(response_json["panicked"], false);
-        assert_eq!(response_json["error"], Value::Null);
-        assert_eq!(response_json["stdout"], "Hello World\n");
+        let default_response_json = run(None);
+        assert_eq!(
+            default_response_json["values"],
+            json!(["100"]),
+            "response={default_response_json}"
+        );
+        // The requested gas is lowered to the cap, twice the default.
+        let capped_response_json = run(Some(1000000000000));
+        assert_eq!(
+            gas_counter(&capped_response_json) - gas_counter(&default_response_json),
+            1000000
+        );
     }
 
     #[test]
-    fn compile_and_run_executable_hello_world() {
+    fn compile_and_run_compares_expected_output() {
+        let run = |expected: Value| -> Value {
+            let request = json!({
+                "crate_name": "test",
+                "files": {
+                    "lib.cairo": "\
+fn main() -> u32 {
+    println!(\"hello\");
+    7
+}
+"
+                },
+                "available_gas": 1000000,
+                "expected": expected
+            });
+            serde_json::from_str(&compile_and_run(&request.to_string())).unwrap()
+        };
+
+        let response_json = run(json!({ "values": ["0x7"], "stdout": "hello\n" }));
+        assert_eq!(response_json["mismatches"], json!([]), "response={response_json}");
+        assert_eq!(response_json["matches_expected"], true);
+
+        let response_json = run(json!({ "stdout": "hi\n", "panicked": true }));
+        assert_eq!(response_json["matches_expected"], false, "response={response_json}");
+        assert_eq!(
+            response_json["mismatches"],
+            json!([
+                { "field": "panicked", "expected": true, "actual": false },
+                { "field": "stdout", "expected": "hi\n", "actual": "hello\n" }
+            ])
+        );
+    }
+
+    #[test]
+    fn compile_and_run_caches_responses() {
+        let request = |cache: bool| {
+            json!({
+                "crate_name": "test",
+                "files": { "lib.cairo": "fn main() -> felt252 { 7 }" },
+                "available_gas": 1000000,
+                "cache": cache
+            })
+            .to_string()
+        };
+
+        let first: Value = serde_json::from_str(&compile_and_run(&request(true))).unwrap();
+        assert_eq!(first["cached"], false, "response={first}");
+        let second: Value = serde_json::from_str(&compile_and_run(&request(true))).unwrap();
+        assert_eq!(second["cached"], true, "response={second}");
+        assert_eq!(second["values"], first["values"]);
+        let uncached: Value = serde_json::from_str(&compile_and_run(&request(false))).unwrap();
+        assert_eq!(uncached["cached"], false);
+    }
+
+    #[test]
+    fn compile_and_run_reports_cache_hits() {
+        let events = Rc::new(RefCell::new(vec![]));
+        let listener_events = events.clone();
+        telemetry::set_listener(Some(Box::new(move |event| {
+            listener_events.borrow_mut().push(serde_json::to_value(event).unwrap());
+        })));
         let request = json!({
             "crate_name": "test",
-            "files": {
-                "lib.cairo": "#[executable]\nfn main() { println!(\"Hello executable\"); }"
-            },
-            "available_gas": 1000000
-        });
+            "files": { "lib.cairo": "fn main() -> felt252 { 8 }" },
+            "available_gas": 1000000,
+            "cache": true,
+            "debug": true
+        })
+        .to_string();
 
-        let response = compile_and_run(&request.to_string());
-        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+        compile_and_run(&request);
+        let second: Value = serde_json::from_str(&compile_and_run(&request)).unwrap();
+        telemetry::set_listener(None);
 
-        assert_eq!(response_json["success"], true, "response={response}");
-        assert_eq!(response_json["panicked"], false);
-        assert_eq!(response_json["error"], Value::Null);
+        assert_eq!(second["cached"], true, "response={second}");
+        // The debug log is that of serving the cached response, which is not cached itself.
+        assert!(second["debug_log"]["entries"].is_array(), "response={second}");
+        let events = events.borrow();
+        assert_eq!(events.len(), 2, "events={events:?}");
+        assert_eq!(events[1]["endpoint"], "compile_and_run");
+        assert_eq!(events[1]["success"], true);
+    }
+
+    #[test]
+    fn strict_request_rejects_unknown_fields() {
+        let request = |strict: bool| {
+            json!({
+                "crate_name": "test",
+                "files": { "lib.cairo": "fn main() -> felt252 { 7 }" },
+                "availible_gas": 1000000,
+                "strict": strict
+            })
+            .to_string()
+        };
+
+        let response_json: Value = serde_json::from_str(&compile_and_run(&request(false))).unwrap();
+        assert_ne!(response_json["error"]["kind"], "invalid-request", "response={response_json}");
+
+        let response = compile_and_run(&request(true));
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+        assert_eq!(response_json["error"]["kind"], "invalid-request", "response={response}");
+        assert_eq!(
+            response_json["error"]["message"],
+            "Unknown fields in the strict request: `availible_gas`."
+        );
+    }
+
+    #[test]
+    fn compile_and_run_separates_stderr() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+use core::debug::PrintTrait;
+
+fn main() {
+    println!(\"hello\");
+    'checked'.print();
+    panic!(\"boom\");
+}
+"
+            }
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["panicked"], true, "response={response}");
+        assert_eq!(response_json["stdout"], "hello\n");
+        assert_eq!(
+            response_json["stderr"],
+            "[DEBUG]\t0x636865636b6564 ('checked')\nPanicked with boom\n"
+        );
+    }
+
+    #[test]
+    fn compile_and_run_hello_world() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "fn main() { println!(\"Hello World\"); }"
+            },
+            "available_gas": 1000000
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["panicked"], false);
+        assert_eq!(response_json["error"], Value::Null);
+        assert_eq!(response_json["stdout"], "Hello World\n");
+    }
+
+    #[test]
+    fn compile_and_run_executable_hello_world() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "#[executable]\nfn main() { println!(\"Hello executable\"); }"
+            },
+            "available_gas": 1000000
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["panicked"], false);
+        assert_eq!(response_json["error"], Value::Null);
         assert_eq!(response_json["stdout"], "Hello executable\n");
     }
+
+    #[test]
+    fn compile_and_run_passes_executable_args() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "#[executable]\nfn main(a: felt252, b: u32) -> felt252 { a * b.into() }"
+            },
+            "available_gas": 1000000,
+            "executable_args": ["6", "7"]
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["42"]));
+    }
+
+    #[test]
+    fn compile_and_run_passes_args() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "fn main(a: felt252, b: felt252) -> felt252 { a * b }"
+            },
+            "available_gas": 1000000,
+            "args": ["6", "0x7"]
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["42"]));
+    }
+
+    #[test]
+    fn compile_and_run_passes_byte_array_args() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main(greeting: ByteArray, times: u32) -> u32 {
+    println!(\"{greeting}\");
+    greeting.len() * times
+}
+"
+            },
+            "available_gas": 1000000,
+            "args": [{ "type": "bytearray", "value": "hello from a long enough string" }, "2"]
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["stdout"], "hello from a long enough string\n");
+        assert_eq!(response_json["values"], json!(["62"]));
+    }
+
+    #[test]
+    fn compile_and_run_encodes_typed_args() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+#[derive(Drop)]
+struct Order { amount: u256, tags: Span<felt252>, discount: Option<u8> }
+
+fn main(order: Order, paid: bool) -> (u256, usize, u8, bool) {
+    (order.amount, order.tags.len(), order.discount.unwrap_or(0), paid)
+}
+"
+            },
+            "available_gas": 1000000,
+            "typed_args": [["0x100000000000000000000000000000001", [1, 2, 3], 7], true]
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["1", "1", "3", "7", "1"]));
+    }
+
+    #[test]
+    fn compile_and_run_stops_infinite_loops() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> felt252 {
+    let i = 0;
+    loop {
+        if i == 10 {
+            break;
+        }
+    };
+    i
+}
+"
+            },
+            "available_gas": 1000000000000_u64,
+            "detect_loops": true
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], false, "response={response}");
+        let message = response_json["error"]["message"].as_str().unwrap();
+        assert!(message.contains("Possible infinite loop"), "message={message}");
+        assert!(message.contains("lib.cairo:"), "message={message}");
+    }
+
+    #[test]
+    fn compile_and_run_returns_partial_results_of_aborted_runs() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> felt252 {
+    println!(\"started\");
+    let i = 0;
+    loop {
+        if i == 10 {
+            break;
+        }
+    };
+    i
+}
+"
+            },
+            "available_gas": 1000000000000_u64,
+            "detect_loops": true
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["error"]["kind"], "execution", "response={response}");
+        assert_eq!(response_json["stdout"], "started\n");
+        let gas = response_json["gas_counter"].as_str().unwrap().parse::<u64>().unwrap();
+        assert!(gas < 1000000000000, "response={response}");
+        assert!(response_json["aborted_at"].as_str().unwrap().contains("lib.cairo:"));
+    }
+
+    #[test]
+    fn compile_and_run_reports_vm_resources() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> felt252 {
+    core::poseidon::poseidon_hash_span(array![1, 2].span())
+}
+"
+            },
+            "available_gas": 1000000
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let resources = &response_json["resources"];
+        assert!(resources["n_steps"].as_u64().unwrap() > 0, "response={response}");
+        assert!(resources["builtins"]["poseidon"].as_u64().unwrap() > 0, "response={response}");
+        assert!(resources["builtins"]["range_check"].as_u64().unwrap() > 0, "response={response}");
+    }
+
+    #[test]
+    fn compile_and_run_stops_at_max_steps() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> felt252 {
+    println!(\"started\");
+    let i = 0;
+    loop {
+        if i == 10 {
+            break;
+        }
+    };
+    i
+}
+"
+            },
+            "available_gas": 1000000000000_u64,
+            "max_steps": 10000
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["error"]["kind"], "step-limit-exceeded", "response={response}");
+        assert!(response_json["error"]["message"].as_str().unwrap().contains("10000 steps"));
+        assert_eq!(response_json["stdout"], "started\n");
+    }
+
+    #[test]
+    fn compile_and_run_truncates_stdout() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> u32 {
+    let mut i = 0;
+    while i != 100 {
+        println!(\"line {i}\");
+        i += 1;
+    }
+    i
+}
+"
+            },
+            "available_gas": 100000000,
+            "max_stdout_bytes": 20
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        // The run goes on after the limit.
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["100"]));
+        assert_eq!(response_json["stdout"], "line 0\nline 1\nline 2");
+        assert_eq!(response_json["stdout_truncated"], true);
+    }
+
+    #[test]
+    fn compile_and_run_stops_cancelled_runs() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> felt252 {
+    let i = 0;
+    loop {
+        if i == 10 {
+            break;
+        }
+    };
+    i
+}
+"
+            },
+            "available_gas": 1000000000000_u64
+        });
+
+        cancellation::set_check(Some(Box::new(|| true)));
+        let response = compile_and_run(&request.to_string());
+        cancellation::set_check(None);
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["cancelled"], true, "response={response}");
+        assert_eq!(response_json["error"]["kind"], "cancelled");
+    }
+
+    #[test]
+    fn compile_and_run_returns_trace_summary() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn fib(a: u128, b: u128, n: u128) -> u128 {
+    if n == 0 { a } else { fib(b, a + b, n - 1) }
+}
+
+fn main() -> u128 { fib(0, 1, 50) }
+"
+            },
+            "available_gas": 1000000,
+            "trace_summary": 4
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let buckets = response_json["trace_summary"].as_array().unwrap();
+        assert!((1..=4).contains(&buckets.len()), "response={response}");
+        assert_eq!(buckets[0]["first_step"], 0);
+        assert!(buckets.iter().any(|bucket| bucket["dominant_function"] == "test::fib"));
+        assert!(buckets.iter().any(|bucket| bucket["builtins"]["RangeCheck"].as_u64() > Some(0)));
+    }
+
+    #[test]
+    fn compile_and_run_returns_function_profile() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn fib(a: u128, b: u128, n: u128) -> u128 {
+    if n == 0 { a } else { fib(b, a + b, n - 1) }
+}
+
+fn main() -> u128 { fib(0, 1, 50) }
+"
+            },
+            "available_gas": 1000000,
+            "profile": true
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let profile = response_json["profile"].as_array().unwrap();
+        let function = |name: &str| {
+            profile.iter().find(|function| function["function"] == name).unwrap_or_else(|| {
+                panic!("missing function `{name}`, response={response}");
+            })
+        };
+        assert_eq!(profile[0]["function"], "test::fib", "response={response}");
+        let (fib, main) = (function("test::fib"), function("test::main"));
+        assert_eq!(fib["estimated_gas"], fib["steps"].as_u64().unwrap() * 100);
+        assert!(fib["total_steps"].as_u64() >= fib["steps"].as_u64());
+        assert!(main["total_steps"].as_u64() > fib["total_steps"].as_u64());
+    }
+
+    #[test]
+    fn compile_and_run_returns_libfunc_profile() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> usize {
+    let mut values = array![];
+    for i in 0..10_u32 {
+        values.append(i);
+    }
+    values.len()
+}
+"
+            },
+            "available_gas": 1000000,
+            "libfunc_profile": true
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let profile = response_json["libfunc_profile"].as_array().unwrap();
+        let array_append = profile
+            .iter()
+            .find(|libfunc| libfunc["libfunc"] == "array_append")
+            .unwrap_or_else(|| panic!("missing `array_append`, response={response}"));
+        assert_eq!(array_append["invocations"], 10);
+        assert!(
+            profile.windows(2).all(|pair| pair[0]["steps"].as_u64() >= pair[1]["steps"].as_u64())
+        );
+    }
+
+    #[test]
+    fn compile_and_run_returns_statement_counts() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "fn main() -> felt252 { 7 }"
+            },
+            "available_gas": 1000000,
+            "statement_counts": true
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let counts = response_json["statement_counts"].as_object().unwrap();
+        // The program has no branches, so each of its statements runs once.
+        assert!(!counts.is_empty(), "response={response}");
+        assert!(counts.values().all(|count| *count == 1), "response={response}");
+    }
+
+    #[test]
+    fn compile_and_run_returns_coverage() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> felt252 {
+    pick(0)
+}
+
+#[inline(never)]
+fn pick(x: felt252) -> felt252 {
+    if x == 0 {
+        1
+    } else {
+        2
+    }
+}
+"
+            },
+            "available_gas": 1000000,
+            "coverage": true
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let coverage = &response_json["coverage"]["lib.cairo"];
+        let covered = coverage["covered"].as_array().unwrap();
+        assert!(covered.contains(&json!(2)) && covered.contains(&json!(8)), "response={response}");
+        assert!(
+            coverage["uncovered"].as_array().unwrap().contains(&json!(10)),
+            "response={response}"
+        );
+    }
+
+    #[test]
+    fn compile_and_run_returns_execution_trace() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "fn main() -> felt252 { 7 }"
+            },
+            "collect_trace": true
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let execution_trace = &response_json["execution_trace"];
+        // An entry takes 24 bytes, encoded by 32 characters, and a cell 40 bytes.
+        let trace_entries = execution_trace["trace_entries"].as_u64().unwrap() as usize;
+        assert!(trace_entries > 0, "response={response}");
+        assert_eq!(execution_trace["trace"].as_str().unwrap().len(), trace_entries * 32);
+        let memory_cells = execution_trace["memory_cells"].as_u64().unwrap() as usize;
+        assert_eq!(
+            execution_trace["memory"].as_str().unwrap().len(),
+            (memory_cells * 40).div_ceil(3) * 4
+        );
+    }
+
+    #[test]
+    fn compile_and_run_breaks_down_gas() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "fn main() -> felt252 { core::pedersen::pedersen(1, 2) }"
+            },
+            "available_gas": 1000000
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let gas_breakdown = &response_json["gas_breakdown"];
+        let n_steps = response_json["resources"]["n_steps"].as_u64().unwrap();
+        assert_eq!(gas_breakdown["steps"], n_steps * 100, "response={response}");
+        assert_eq!(gas_breakdown["builtins"]["pedersen"], 4050, "response={response}");
+        let components = ["steps", "memory_holes"].map(|key| gas_breakdown[key].as_u64().unwrap());
+        let builtins = gas_breakdown["builtins"].as_object().unwrap().values();
+        assert_eq!(
+            gas_breakdown["total"].as_u64().unwrap(),
+            components.iter().sum::<u64>() + builtins.map(|gas| gas.as_u64().unwrap()).sum::<u64>()
+        );
+    }
+
+    #[test]
+    fn compile_and_run_synthesizes_main() {
+        let run = |source: &str| {
+            let request = json!({
+                "crate_name": "test",
+                "files": { "lib.cairo": source },
+                "available_gas": 1000000,
+                "synthesize_main": { "function": "add", "args": ["2"] }
+            });
+            let response = compile_and_run(&request.to_string());
+            serde_json::from_str::<Value>(&response).expect("valid JSON response")
+        };
+
+        // `add` designates a whole path segment, so `safe_add` does not make it ambiguous.
+        let response =
+            run("fn add(a: u32, b: u32) -> u32 { a + b }\nfn safe_add(a: u32) -> u32 { a }");
+        assert_eq!(response["success"], true, "response={response}");
+        // The parameter left out is passed its default value.
+        assert_eq!(response["values"], json!(["2"]));
+
+        let response = run("fn add(a: u32, b: u32) -> u32 { a + b }\nfn main() {}");
+        assert_eq!(response["error"]["kind"], "invalid-request", "response={response}");
+    }
+
+    #[test]
+    fn compile_and_run_applies_gas_costs() {
+        let run = |gas_costs: Value| {
+            let request = json!({
+                "crate_name": "test",
+                "files": {
+                    "lib.cairo": "fn main() -> felt252 { core::pedersen::pedersen(1, 2) }"
+                },
+                "available_gas": 1000000,
+                "gas_costs": gas_costs
+            });
+            let response = compile_and_run(&request.to_string());
+            serde_json::from_str::<Value>(&response).expect("valid JSON response")
+        };
+
+        let default_gas = run(json!({}))["gas_counter"].as_str().unwrap().parse::<u64>().unwrap();
+        let response_json = run(json!({ "pedersen": 5050 }));
+        let gas = response_json["gas_counter"].as_str().unwrap().parse::<u64>().unwrap();
+        assert_eq!(default_gas - gas, 1000, "response={response_json}");
+
+        let response_json = run(json!({ "sha256": 1 }));
+        assert_eq!(response_json["error"]["kind"], "invalid-request");
+    }
+
+    #[test]
+    fn compile_and_run_reprices_gas_breakdown() {
+        let run = |gas_costs: Value| {
+            let request = json!({
+                "crate_name": "test",
+                "files": { "lib.cairo": "fn main() -> felt252 { 1 + 2 }" },
+                "gas_costs": gas_costs
+            });
+            let response = compile_and_run(&request.to_string());
+            serde_json::from_str::<Value>(&response).expect("valid JSON response")
+        };
+
+        let default_steps = run(json!({}))["gas_breakdown"]["steps"].as_u64().unwrap();
+        let response_json = run(json!({ "step": 200, "storage_read": 1 }));
+        let breakdown = &response_json["gas_breakdown"];
+        assert_eq!(
+            breakdown["steps"].as_u64().unwrap(),
+            2 * default_steps,
+            "response={response_json}"
+        );
+
+        let response_json = run(json!({ "storage_reed": 1 }));
+        assert_eq!(response_json["error"]["kind"], "invalid-request");
+    }
+
+    #[test]
+    fn compile_and_run_reports_l2_gas() {
+        let run = |report_l2_gas: bool| {
+            let request = json!({
+                "crate_name": "test",
+                "files": { "lib.cairo": "fn main() -> felt252 { core::pedersen::pedersen(1, 2) }" },
+                "available_gas": 1000000,
+                "report_l2_gas": report_l2_gas
+            });
+            let response = compile_and_run(&request.to_string());
+            serde_json::from_str::<Value>(&response).expect("valid JSON response")
+        };
+
+        let response_json = run(true);
+        assert_eq!(response_json["success"], true, "response={response_json}");
+        let gas_left = response_json["gas_counter"].as_str().unwrap().parse::<u64>().unwrap();
+        assert_eq!(response_json["l2_gas"].as_u64().unwrap(), 1000000 - gas_left);
+
+        assert_eq!(run(false)["l2_gas"], Value::Null);
+    }
+
+    #[test]
+    fn compile_and_run_sets_starknet_context() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> (u64, u64, felt252) {
+    let block_info = starknet::get_block_info().unbox();
+    (block_info.block_number, block_info.block_timestamp, starknet::get_caller_address().into())
+}
+"
+            },
+            "available_gas": 1000000,
+            "starknet_context": {
+                "block_number": 7,
+                "block_timestamp": 1700000000,
+                "caller_address": "0x123"
+            }
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["7", "1700000000", "291"]));
+    }
+
+    #[test]
+    fn compile_and_run_decodes_return_values() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> (u256, ByteArray, Option<u8>, Array<i8>, bool) {
+    (0x100000000000000000000000000000001_u256, \"hi\", Some(3), array![-1, 2], true)
+}
+"
+            },
+            "available_gas": 1000000
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(
+            response_json["decoded_values"],
+            json!(["340282366920938463463374607431768211457", "hi", "3", ["-1", "2"], true])
+        );
+    }
+
+    #[test]
+    fn compile_and_run_reports_return_variant() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> Result<u32, felt252> {
+    Err('too big')
+}
+"
+            }
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["return_variant"]["variant"], "Err");
+        assert_eq!(response_json["return_variant"]["error_message"], "too big");
+    }
+
+    #[test]
+    fn compile_and_run_renders_pretty_values() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> (u256, ByteArray, Option<u8>, Array<i8>) {
+    (0x100000000000000000000000000000001_u256, \"hi\", None, array![-1, 2])
+}
+"
+            },
+            "available_gas": 1000000
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(
+            response_json["values_pretty"],
+            json!([
+                "u256: 340282366920938463463374607431768211457",
+                "ByteArray: \"hi\"",
+                "Option<u8>: None",
+                "Array<i8>: [-1, 2]"
+            ])
+        );
+    }
+
+    #[test]
+    fn compile_and_run_redacts_hidden_files() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "mod harness;\nfn main() -> felt252 { harness::check() }",
+                "harness.cairo": {
+                    "content": "pub fn check() -> felt252 { secret_expected_value }",
+                    "hidden": true
+                }
+            }
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["error"]["kind"], "compilation", "response={response}");
+        let diagnostics = response_json["diagnostics"].as_str().unwrap();
+        assert!(diagnostics.contains(" --> <hidden file>"), "diagnostics={diagnostics}");
+        assert!(!diagnostics.contains("secret_expected_value"), "diagnostics={diagnostics}");
+    }
+
+    #[test]
+    fn compile_and_run_redacts_only_the_hidden_file_of_a_shared_name() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "src/lib.cairo": "mod tests;\nmod utils;\nfn main() -> felt252 { utils::value() }",
+                "src/tests.cairo": "mod utils;",
+                "src/tests/utils.cairo": {
+                    "content": "pub fn check() -> felt252 { secret_expected_value }",
+                    "hidden": true
+                },
+                "src/utils.cairo": "pub fn value() -> felt252 { visible_typo }"
+            }
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["error"]["kind"], "compilation", "response={response}");
+        let diagnostics = response_json["diagnostics"].as_str().unwrap();
+        assert!(diagnostics.contains(" --> <hidden file>"), "diagnostics={diagnostics}");
+        assert!(!diagnostics.contains("secret_expected_value"), "diagnostics={diagnostics}");
+        assert!(diagnostics.contains("--> utils.cairo:1:"), "diagnostics={diagnostics}");
+        assert!(diagnostics.contains("visible_typo"), "diagnostics={diagnostics}");
+    }
+
+    #[test]
+    fn compile_and_run_suggests_close_functions() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "fn main() -> felt252 { 1 }"
+            },
+            "function": "::mian"
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["error"]["kind"], "invalid-request", "response={response}");
+        assert_eq!(response_json["error"]["suggestions"], json!(["test::main"]));
+    }
+
+    #[test]
+    fn compile_and_run_runs_several_functions() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn square(x: u64) -> u64 {
+    x * x
+}
+
+fn greet() {
+    println!(\"hello\");
+}
+"
+            },
+            "functions": [
+                { "name": "::square", "args": ["3"], "available_gas": 1000000 },
+                { "name": "::greet", "available_gas": 1000000 },
+                { "name": "::square", "args": ["4"] }
+            ]
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], false, "response={response}");
+        let functions = &response_json["functions"];
+        assert_eq!(functions[0]["values"], json!(["9"]));
+        assert_eq!(functions[1]["stdout"], "hello\n");
+        assert_eq!(functions[2]["error"]["kind"], "invalid-request");
+    }
+
+    #[test]
+    fn compile_and_run_formats_values() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "fn main() -> felt252 { 'hi' }"
+            },
+            "value_format": "all"
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(
+            response_json["formatted_values"],
+            json!([{ "decimal": "26729", "hex": "0x6869", "short_string": "hi" }])
+        );
+    }
+
+    #[test]
+    fn compile_and_run_decodes_panic_message() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> felt252 {
+    let value: Option<felt252> = None;
+    value.unwrap()
+}
+"
+            },
+            "available_gas": 1000000
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["panicked"], true, "response={response}");
+        assert_eq!(response_json["panic_message"], "Option::unwrap failed.");
+    }
+
+    #[test]
+    fn compile_and_run_reports_arithmetic_sites() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main(a: u128, b: u128, x: felt252, y: felt252) -> (u128, felt252) {
+    (a / b, core::felt252_div(x, y.try_into().unwrap()))
+}
+"
+            },
+            "available_gas": 1000000,
+            "args": ["7", "2", "6", "3"],
+            "strict_arithmetic": true
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let sites = response_json["arithmetic_report"].as_array().unwrap();
+        let division = sites.iter().find(|site| site["kind"] == "division").unwrap();
+        assert_eq!(division["function"], "test::main");
+        assert!(division["location"].as_str().unwrap().contains("lib.cairo:2"));
+        assert!(sites.iter().any(|site| site["kind"] == "felt252_div"), "response={response}");
+    }
+
+    #[test]
+    fn compile_and_run_returns_debug_log_on_request() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "fn main() -> felt252 { 7 }"
+            },
+            "available_gas": 1000000,
+            "debug": true
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert!(response_json["debug_log"]["entries"].is_array());
+    }
+
+    #[test]
+    #[cfg(feature = "allocation-stats")]
+    fn compile_and_run_reports_allocations() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "fn main() -> felt252 { 7 }"
+            },
+            "available_gas": 1000000
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        // Compiling the corelib alone allocates far more than a megabyte.
+        assert!(response_json["allocations"]["allocated_bytes"].as_u64().unwrap() > 1 << 20);
+        assert!(response_json["allocations"]["allocations"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn run_sierra_reports_unsupported_libfuncs() {
+        let request = json!({
+            "sierra": "\
+type felt252 = felt252;
+libfunc felt252_from_the_future = felt252_from_the_future;
+felt252_from_the_future() -> ([0]);
+return([0]);
+test::main@0() -> (felt252);
+"
+        });
+
+        let response = run_sierra(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["error"]["kind"], "invalid-request", "response={response}");
+        let message = response_json["error"]["message"].as_str().unwrap();
+        assert!(message.contains("Unsupported libfuncs `felt252_from_the_future`."), "{message}");
+    }
+
+    #[test]
+    fn run_sierra_accepts_contract_class_felts() {
+        let contract_class: Value = serde_json::from_str(include_str!(
+            "../../cairo-lang-starknet/test_data/hello_starknet__hello_starknet.contract_class.\
+             json"
+        ))
+        .unwrap();
+        // Without debug info, functions are named after their ids.
+        let request = json!({
+            "sierra_program": contract_class["sierra_program"],
+            "function": "[5]",
+            "available_gas": 1000000
+        });
+
+        let response = run_sierra(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["panicked"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["375233589013918064796019"]));
+    }
+
+    #[test]
+    fn run_sierra_accepts_versioned_program_json() {
+        let program = ProgramParser::new()
+            .parse(
+                "\
+type felt252 = felt252;
+libfunc felt252_const<7> = felt252_const<7>;
+libfunc store_temp<felt252> = store_temp<felt252>;
+felt252_const<7>() -> ([0]);
+store_temp<felt252>([0]) -> ([0]);
+return([0]);
+test::main@0() -> (felt252);
+",
+            )
+            .unwrap();
+        let versioned = VersionedProgram::v1(ProgramArtifact::stripped(program));
+        let request = json!({ "sierra": serde_json::to_string(&versioned).unwrap() });
+
+        let response = run_sierra(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["7"]));
+    }
 }
@@ -0,0 +1,302 @@
+//! The gas costs of the libfuncs of a program, as computed by the compiler the module was built
+//! with, so documentation and analyzers can show the costs of the engine actually used.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_sierra::extensions::circuit::CircuitInfo;
+use cairo_lang_sierra::extensions::gas::CostTokenType;
+use cairo_lang_sierra::ids::ConcreteTypeId;
+use cairo_lang_sierra::program::{Program, Statement, StatementIdx};
+use cairo_lang_sierra_gas::core_libfunc_cost::{InvocationCostInfoProvider, core_libfunc_cost};
+use cairo_lang_sierra_to_casm::circuit::CircuitsInfo;
+use cairo_lang_sierra_to_casm::metadata::{Metadata, MetadataComputationConfig, calc_metadata};
+use cairo_lang_sierra_type_size::ProgramRegistryInfo;
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{corelib, limits, parse, source};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::resources::RESOURCE_GAS_COSTS;
+use crate::{
+    DiagnosticsScopeArg, InliningStrategyArg, compile_project, compiled_program,
+    default_replace_ids, parse_sierra,
+};
+
+/// A request for the libfunc costs of a project, given by `files`, or of a Sierra program.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct LibfuncCostsRequest {
+    pub crate_name: Option<String>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub files: Option<BTreeMap<String, String>>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    #[serde(default = "default_replace_ids")]
+    pub replace_ids: bool,
+    #[serde(default)]
+    pub inlining_strategy: InliningStrategyArg,
+    /// A Sierra program, exclusive with `files`.
+    pub sierra: Option<String>,
+    /// The handle of a program compiled by `compile_program`, exclusive with `files` and `sierra`.
+    pub program: Option<u32>,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// The cost of a branch of a libfunc.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct BranchCost {
+    /// The gas known at compile time, from the steps, memory holes and range checks of the branch.
+    pub gas: i64,
+    /// The number of instances of each builtin charging gas, by name, e.g. `pedersen`, each
+    /// costing its gas cost.
+    pub builtins: BTreeMap<String, i64>,
+}
+
+/// The costs of a concrete libfunc of a program.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct LibfuncCost {
+    /// The concrete libfunc, e.g. `u128_overflowing_add`.
+    pub libfunc: String,
+    /// The cost of each branch of the libfunc.
+    pub branches: Vec<BranchCost>,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct LibfuncCostsResponse {
+    pub success: bool,
+    /// The costs of the invoked libfuncs of the program, in the order of their declarations.
+    pub libfuncs: Vec<LibfuncCost>,
+    /// The gas costs of a `step`, a `memory_hole`, a `range_check` and a `range_check96`, of
+    /// which the compile-time `gas` of the branches is made.
+    pub resource_costs: BTreeMap<String, usize>,
+    pub diagnostics: String,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Returns the gas costs of the branches of each libfunc invoked by a project or a Sierra program,
+/// as charged by the compiler of the module.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn libfunc_costs(request_json: &str) -> String {
+    let request: LibfuncCostsRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serialize_libfunc_costs_response(Err(error), None);
+        }
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| libfunc_costs_request(request))
+    });
+    serialize_libfunc_costs_response(result, debug_log)
+}
+
+fn libfunc_costs_request(request: LibfuncCostsRequest) -> LibfuncCostsResponse {
+    match (request.files, request.sierra, request.program) {
+        (Some(files), None, None) => {
+            let Some(crate_name) = request.crate_name else {
+                return LibfuncCostsResponse {
+                    error: Some(ApiError::invalid_request(
+                        "`crate_name` is required with `files`.",
+                    )),
+                    ..Default::default()
+                };
+            };
+            if let Err(error) = limits::current()
+                .check_files(files.iter().chain(request.corelib_files.iter().flatten()))
+            {
+                return LibfuncCostsResponse { error: Some(error), ..Default::default() };
+            }
+            let project = InMemoryProject {
+                main_crate_name: crate_name,
+                main_crate_files: files,
+                corelib_files: corelib::resolve(request.corelib_files),
+                main_crate_settings: None,
+            };
+            compile_project(
+                &project,
+                &Default::default(),
+                request.inlining_strategy,
+                request.replace_ids,
+                DiagnosticsScopeArg::All,
+                |program, _, _, diagnostics| LibfuncCostsResponse {
+                    diagnostics,
+                    ..program_libfunc_costs(&program)
+                },
+            )
+            .unwrap_or_else(|(error, diagnostics)| LibfuncCostsResponse {
+                diagnostics,
+                error: Some(error),
+                ..Default::default()
+            })
+        }
+        (None, Some(sierra), None) => {
+            if let Err(error) = limits::current().check_source("Sierra program", &sierra) {
+                return LibfuncCostsResponse { error: Some(error), ..Default::default() };
+            }
+            match parse_sierra(&sierra) {
+                Ok(program) => program_libfunc_costs(&program),
+                Err(error) => LibfuncCostsResponse { error: Some(error), ..Default::default() },
+            }
+        }
+        (None, None, Some(program)) => match compiled_program::get(program) {
+            Ok(program) => program_libfunc_costs(&program),
+            Err(error) => LibfuncCostsResponse { error: Some(error), ..Default::default() },
+        },
+        _ => LibfuncCostsResponse {
+            error: Some(ApiError::invalid_request(
+                "Exactly one of `files`, `sierra` and `program` is required.",
+            )),
+            ..Default::default()
+        },
+    }
+}
+
+fn program_libfunc_costs(program: &Program) -> LibfuncCostsResponse {
+    let error_response = |message: String| LibfuncCostsResponse {
+        error: Some(ApiError::invalid_request(message)),
+        ..Default::default()
+    };
+    let program_info = match ProgramRegistryInfo::new(program) {
+        Ok(program_info) => program_info,
+        Err(error) => {
+            return error_response(format!("Failed building the program registry: {error}"));
+        }
+    };
+    let metadata = match calc_metadata(program, &program_info, MetadataComputationConfig::default())
+    {
+        Ok(metadata) => metadata,
+        Err(error) => {
+            return error_response(format!("Failed computing the gas of the program: {error}"));
+        }
+    };
+    let circuits_info = match CircuitsInfo::new(
+        &program_info.registry,
+        program.type_declarations.iter().map(|declaration| &declaration.id),
+    ) {
+        Ok(circuits_info) => circuits_info,
+        Err(error) => return error_response(format!("Failed computing the circuits: {error}")),
+    };
+
+    // The costs of gas libfuncs, such as `withdraw_gas`, depend on the statement invoking them,
+    // so the first invocation of each libfunc is priced.
+    let mut first_invocations = BTreeMap::new();
+    for (index, statement) in program.statements.iter().enumerate() {
+        if let Statement::Invocation(invocation) = statement {
+            first_invocations.entry(invocation.libfunc_id.clone()).or_insert(StatementIdx(index));
+        }
+    }
+    let libfuncs = program
+        .libfunc_declarations
+        .iter()
+        .filter_map(|declaration| {
+            let idx = *first_invocations.get(&declaration.id)?;
+            let libfunc = program_info.registry.get_libfunc(&declaration.id).ok()?;
+            let info_provider = CostInfoProvider {
+                program_info: &program_info,
+                metadata: &metadata,
+                circuits_info: &circuits_info,
+                idx,
+            };
+            let branches = core_libfunc_cost(&metadata.gas_info, idx, libfunc, &info_provider)
+                .into_iter()
+                .map(|costs| BranchCost {
+                    gas: costs.get(&CostTokenType::Const).copied().unwrap_or_default(),
+                    builtins: costs
+                        .iter()
+                        .filter(|(token_type, cost)| {
+                            **token_type != CostTokenType::Const && **cost != 0
+                        })
+                        .map(|(token_type, cost)| (token_type.name(), *cost))
+                        .collect(),
+                })
+                .collect();
+            Some(LibfuncCost { libfunc: declaration.long_id.to_string(), branches })
+        })
+        .collect();
+    LibfuncCostsResponse {
+        success: true,
+        libfuncs,
+        resource_costs: RESOURCE_GAS_COSTS
+            .iter()
+            .map(|(name, cost)| (name.to_string(), *cost))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// The information the cost of the libfunc invoked at `idx` depends on, as used by the compiler.
+struct CostInfoProvider<'a> {
+    program_info: &'a ProgramRegistryInfo,
+    metadata: &'a Metadata,
+    circuits_info: &'a CircuitsInfo,
+    idx: StatementIdx,
+}
+
+impl InvocationCostInfoProvider for CostInfoProvider<'_> {
+    fn type_size(&self, ty: &ConcreteTypeId) -> usize {
+        self.program_info.type_sizes[ty] as usize
+    }
+
+    fn token_usages(&self, token_type: CostTokenType) -> usize {
+        self.metadata.gas_info.variable_values.get(&(self.idx, token_type)).copied().unwrap_or(0)
+            as usize
+    }
+
+    fn ap_change_var_value(&self) -> usize {
+        self.metadata.ap_change_info.variable_values.get(&self.idx).copied().unwrap_or_default()
+    }
+
+    fn circuit_info(&self, ty: &ConcreteTypeId) -> &CircuitInfo {
+        self.circuits_info.circuits.get(ty).expect("circuit type")
+    }
+}
+
+fn serialize_libfunc_costs_response(
+    result: Result<LibfuncCostsResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+) -> String {
+    let mut response = result
+        .unwrap_or_else(|error| LibfuncCostsResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+    serde_json::to_string(&response).expect("serialize libfunc costs response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::libfunc_costs;
+
+    #[test]
+    fn libfunc_costs_prices_branches() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "fn main(a: u128, b: u128) -> u128 { a + b }"
+            }
+        });
+
+        let response = libfunc_costs(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["resource_costs"]["step"], 100);
+        let libfuncs = response_json["libfuncs"].as_array().unwrap();
+        let add = libfuncs
+            .iter()
+            .find(|libfunc| libfunc["libfunc"] == "u128_overflowing_add")
+            .unwrap_or_else(|| panic!("response={response}"));
+        let branches = add["branches"].as_array().unwrap();
+        assert_eq!(branches.len(), 2);
+        // Both branches check the sum with a range check.
+        assert!(branches.iter().all(|branch| branch["gas"].as_i64().unwrap() >= 70));
+    }
+}
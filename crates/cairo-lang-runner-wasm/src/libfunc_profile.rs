@@ -0,0 +1,56 @@
+//! A breakdown of the executions and steps of a run by libfunc, as `cairo-profiler` reports them.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use cairo_lang_runner::profiling::ProfilingInfo;
+use cairo_lang_sierra::program::{GenStatement, Program};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The name under which the return statements are reported.
+const RETURN: &str = "return";
+
+/// The executions of a libfunc in a run.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct LibfuncProfile {
+    /// The generic libfunc, e.g. `array_append`, or `return` for the return statements.
+    pub libfunc: String,
+    /// The number of invocations of the libfunc.
+    pub invocations: usize,
+    /// The number of steps spent in the libfunc.
+    pub steps: usize,
+}
+
+/// Aggregates the executed statements of a run by libfunc, the most expensive first.
+pub(crate) fn profile(program: &Program, profiling_info: &ProfilingInfo) -> Vec<LibfuncProfile> {
+    let generic_libfuncs = program
+        .libfunc_declarations
+        .iter()
+        .map(|declaration| (&declaration.id, declaration.long_id.generic_id.0.as_str()))
+        .collect::<HashMap<_, _>>();
+    let libfunc = |statement_idx: usize| match &program.statements[statement_idx] {
+        GenStatement::Invocation(invocation) => generic_libfuncs[&invocation.libfunc_id],
+        GenStatement::Return(_) => RETURN,
+    };
+
+    // The invocations and steps of each libfunc, by name.
+    let mut libfuncs = BTreeMap::<&str, (usize, usize)>::new();
+    for (statement_idx, steps) in profiling_info.sierra_statement_weights.iter_sorted() {
+        libfuncs.entry(libfunc(statement_idx.0)).or_default().1 += steps;
+    }
+    for (statement_idx, count) in profiling_info.sierra_statement_counts.iter_sorted() {
+        libfuncs.entry(libfunc(statement_idx.0)).or_default().0 += count;
+    }
+
+    let mut profile = libfuncs
+        .into_iter()
+        .map(|(libfunc, (invocations, steps))| LibfuncProfile {
+            libfunc: libfunc.to_string(),
+            invocations,
+            steps,
+        })
+        .collect::<Vec<_>>();
+    profile.sort_by_key(|libfunc| Reverse(libfunc.steps));
+    profile
+}
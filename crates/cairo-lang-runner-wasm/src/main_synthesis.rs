@@ -0,0 +1,126 @@
+//! Synthesis of a `main` function calling a function of a snippet, so snippets copied from docs
+//! without an entry point can be run.
+
+use cairo_lang_compiler::prepare_in_memory_project_db;
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::TopLevelLanguageElementId;
+use cairo_lang_filesystem::ids::CrateInput;
+use cairo_lang_lowering::utils::InliningStrategy;
+use cairo_lang_semantic::items::free_function::FreeFunctionSemantic;
+use cairo_lang_wasm_utils::error::ApiError;
+use cairo_lang_wasm_utils::{item_path, plugins};
+use salsa::Database;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::suggestions;
+
+/// A `main` function to add to the crate, calling `function`.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SynthesizedMain {
+    /// The function to call, matched against the trailing `::`-separated segments of the full
+    /// paths of the functions of the crate.
+    pub function: String,
+    /// The arguments of the call as Cairo expressions, such as `array![1, 2]`, for the first
+    /// parameters of the function. The other parameters are passed `Default::default()`.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// Appends to the `lib.cairo` of `project` a `main` function returning the result of the call
+/// described by `synthesized`.
+pub(crate) fn add_main(
+    project: &mut InMemoryProject,
+    synthesized: &SynthesizedMain,
+) -> Result<(), ApiError> {
+    let main = main_source(project, synthesized)?;
+    // The crate root is either `lib.cairo` or, in Scarb's layout, the shallowest `src/lib.cairo`.
+    let lib = project
+        .main_crate_files
+        .iter_mut()
+        .filter(|(path, _)| {
+            *path == "lib.cairo" || *path == "src/lib.cairo" || path.ends_with("/src/lib.cairo")
+        })
+        .min_by_key(|(path, _)| path.matches('/').count())
+        .map(|(_, content)| content)
+        .expect("The crate root was found when preparing the database.");
+    if !lib.ends_with('\n') {
+        lib.push('\n');
+    }
+    lib.push('\n');
+    lib.push_str(&main);
+    Ok(())
+}
+
+/// Returns the source of the `main` function calling the function of `synthesized`.
+fn main_source(
+    project: &InMemoryProject,
+    synthesized: &SynthesizedMain,
+) -> Result<String, ApiError> {
+    let (db, main_crate_inputs) = prepare_in_memory_project_db(
+        project,
+        InliningStrategy::Default,
+        plugins::in_memory_project_suite(),
+    )
+    .map_err(|error| ApiError::compilation(error.to_string()))?;
+    let db: &dyn Database = &db;
+    let functions = CrateInput::into_crate_ids(db, main_crate_inputs)
+        .into_iter()
+        .flat_map(|crate_id| db.crate_modules(crate_id).iter().copied())
+        .flat_map(|module_id| db.module_free_functions_ids(module_id).into_iter().flatten())
+        .map(|function_id| (function_id.full_path(db), *function_id))
+        .collect::<Vec<_>>();
+    if let Some((path, _)) = functions.iter().find(|(path, _)| path.ends_with("::main")) {
+        return Err(ApiError::invalid_request(format!(
+            "The crate already has a `main` function, `{path}`."
+        )));
+    }
+
+    let function = synthesized.function.as_str();
+    let candidates = functions
+        .iter()
+        .filter(|(path, _)| item_path::matches_suffix(path, function))
+        .collect::<Vec<_>>();
+    let (path, function_id) = match candidates[..] {
+        [candidate] => candidate,
+        [] => {
+            let suggestions = suggestions::function_suggestions(
+                function,
+                functions.iter().map(|(path, _)| path.as_str()),
+            );
+            return Err(ApiError::invalid_request(format!(
+                "Failed finding function `{function}`."
+            ))
+            .with_suggestions(suggestions));
+        }
+        _ => {
+            let paths = candidates.iter().map(|(path, _)| format!("`{path}`"));
+            return Err(ApiError::invalid_request(format!(
+                "Function `{function}` is ambiguous, matching {}.",
+                paths.collect::<Vec<_>>().join(", ")
+            )));
+        }
+    };
+    let signature = db.free_function_signature(*function_id).map_err(|_| {
+        ApiError::compilation(format!("Failed resolving the signature of `{path}`."))
+    })?;
+    if synthesized.args.len() > signature.params.len() {
+        return Err(ApiError::invalid_request(format!(
+            "`{path}` takes {} arguments, but {} were given.",
+            signature.params.len(),
+            synthesized.args.len()
+        )));
+    }
+
+    // The path is relative to the crate, as the crate may not be visible by its own name.
+    let (_, crate_path) = path.split_once("::").expect("Function paths start with their crate.");
+    let args = (0..signature.params.len())
+        .map(|idx| synthesized.args.get(idx).map_or("Default::default()", String::as_str))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Ok(format!(
+        "fn main() -> {} {{\n    crate::{crate_path}({args})\n}}\n",
+        signature.return_type.format(db)
+    ))
+}
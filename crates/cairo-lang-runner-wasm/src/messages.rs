@@ -0,0 +1,66 @@
+//! The messages sent to L1 by a run, for exercising the L2 side of bridging flows.
+
+use cairo_lang_runner::StarknetState;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// A message sent to L1 by a contract through `send_message_to_l1_syscall`, with its felts as hex
+/// strings.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct L2ToL1Message {
+    /// The address of the sending contract.
+    pub from_address: String,
+    /// The L1 address of the recipient.
+    pub to_address: String,
+    pub payload: Vec<String>,
+}
+
+/// Returns the messages sent to L1 during a run, in the order they were sent. Messages sent by
+/// reverted calls are dropped with the rest of their state.
+pub(crate) fn l2_to_l1_messages(starknet_state: &StarknetState) -> Vec<L2ToL1Message> {
+    starknet_state
+        .sent_l2_to_l1_messages()
+        .iter()
+        .map(|message| L2ToL1Message {
+            from_address: format!("{:#x}", message.from_address.to_biguint()),
+            to_address: format!("{:#x}", message.to_address.to_biguint()),
+            payload: message
+                .payload
+                .iter()
+                .map(|felt| format!("{:#x}", felt.to_biguint()))
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use crate::compile_and_run;
+
+    #[test]
+    fn compile_and_run_returns_l2_to_l1_messages() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() {
+    starknet::syscalls::send_message_to_l1_syscall(0xabc, array![1, 2].span()).unwrap();
+}
+"
+            },
+            "available_gas": 1000000,
+            "starknet_context": { "contract_address": "0x100" }
+        });
+
+        let response = compile_and_run(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(
+            response_json["l2_to_l1_messages"],
+            json!([{ "from_address": "0x100", "to_address": "0xabc", "payload": ["0x1", "0x2"] }])
+        );
+    }
+}
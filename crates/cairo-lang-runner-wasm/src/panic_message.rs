@@ -0,0 +1,36 @@
+//! Decoding of the panic data of a run into a readable message.
+
+use cairo_lang_runner::casm_run::format_next_item;
+use cairo_lang_runner::short_string::as_cairo_short_string;
+use starknet_types_core::felt::Felt as Felt252;
+
+/// Decodes panic data into a message, such as `Option::unwrap failed.`.
+///
+/// `ByteArray`s and short strings are decoded to their text, and other felts are given in hex. The
+/// items are joined with `, `.
+pub(crate) fn panic_message(values: &[Felt252]) -> String {
+    let mut felts = values.iter().copied();
+    let mut items = vec![];
+    while let Some(first_felt) = felts.clone().next() {
+        let Some(item) = format_next_item(&mut felts) else { break };
+        if item.is_string() {
+            items.push(item.get());
+            continue;
+        }
+        items.push(
+            as_cairo_short_string(&first_felt)
+                .filter(|text| !text.is_empty())
+                .unwrap_or_else(|| format!("{:#x}", first_felt.to_biguint())),
+        );
+    }
+    items.join(", ")
+}
+
+/// Appends the line reporting the panic with `panic_message` to the `stderr` of a run, when it
+/// panicked.
+pub(crate) fn with_panic_line(mut stderr: String, panic_message: Option<&str>) -> String {
+    if let Some(panic_message) = panic_message {
+        stderr.push_str(&format!("Panicked with {panic_message}\n"));
+    }
+    stderr
+}
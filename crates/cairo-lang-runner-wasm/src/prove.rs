@@ -0,0 +1,35 @@
+//! Proving of the proof-mode runs of executables with the Stwo prover, linked into the module by
+//! the `prove` feature, so a page can compile, run and prove a program without a proving service.
+
+use cairo_lang_wasm_utils::error::ApiError;
+use cairo_vm::vm::runners::cairo_runner::CairoRunner;
+use stwo_cairo_adapter::adapter::adapt;
+use stwo_cairo_prover::prover::{ProverParameters, default_prod_prover_parameters, prove_cairo};
+use stwo_cairo_prover::stwo_prover::core::vcs::blake2_merkle::Blake2sMerkleChannel;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::casm;
+
+/// Runs a standalone entry point of an executable in proof mode and proves the run with Stwo.
+///
+/// The request is that of `run_casm`. The response is that of `run_casm`, with the proof of the
+/// run in `proof` on success.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn prove(request_json: &str) -> String {
+    casm::run(request_json, true)
+}
+
+/// Proves a completed proof-mode run whose trace and memory were relocated, returning the proof
+/// as JSON.
+pub(crate) fn prove_run(runner: &CairoRunner) -> Result<serde_json::Value, ApiError> {
+    let input = adapt(runner).map_err(|error| {
+        ApiError::execution(format!("Failed adapting the run to the prover: {error}"))
+    })?;
+    // The parameters the proofs verified on chain are made with.
+    let ProverParameters { pcs_config, preprocessed_trace, .. } = default_prod_prover_parameters();
+    let proof = prove_cairo::<Blake2sMerkleChannel>(input, pcs_config, preprocessed_trace)
+        .map_err(|error| ApiError::execution(format!("Failed proving the run: {error}")))?;
+    serde_json::to_value(&proof)
+        .map_err(|error| ApiError::execution(format!("Failed serializing the proof: {error}")))
+}
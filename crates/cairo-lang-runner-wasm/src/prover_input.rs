@@ -0,0 +1,56 @@
+//! The inputs of provers such as Stone and Stwo for the proof-mode runs of executables: the files
+//! written by `cairo-execute --standalone` with its proof outputs.
+
+use cairo_lang_wasm_utils::error::ApiError;
+use cairo_vm::vm::runners::cairo_runner::CairoRunner;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::execution_trace::{self, ExecutionTrace};
+
+/// The name of the trace file, as referenced by the private input.
+const TRACE_FILE: &str = "trace.bin";
+/// The name of the memory file, as referenced by the private input.
+const MEMORY_FILE: &str = "memory.bin";
+
+/// The artifacts of a proof-mode run expected by provers.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ProverInput {
+    /// The public input of the AIR: the layout, the number of steps, the memory segments and the
+    /// public memory.
+    pub public_input: serde_json::Value,
+    /// The private input of the AIR: the inputs of the builtins, and the paths of the trace and
+    /// memory files, `trace.bin` and `memory.bin`.
+    pub private_input: serde_json::Value,
+    /// The contents of the trace and memory files.
+    pub execution_trace: ExecutionTrace,
+}
+
+/// Returns the prover input of a proof-mode run whose trace and memory were relocated.
+pub(crate) fn prover_input(runner: &CairoRunner) -> Result<ProverInput, ApiError> {
+    let public_input = runner
+        .get_air_public_input()
+        .map_err(|error| {
+            ApiError::execution(format!("Failed getting the public input of the run: {error}"))
+        })
+        .and_then(|public_input| {
+            serde_json::to_value(public_input).map_err(|error| {
+                ApiError::execution(format!("Failed serializing the public input: {error}"))
+            })
+        })?;
+    let private_input = serde_json::to_value(
+        runner.get_air_private_input().to_serializable(TRACE_FILE.into(), MEMORY_FILE.into()),
+    )
+    .map_err(|error| {
+        ApiError::execution(format!("Failed serializing the private input: {error}"))
+    })?;
+    let trace = runner
+        .relocated_trace
+        .as_ref()
+        .ok_or_else(|| ApiError::execution("The trace of the run was not relocated."))?;
+    Ok(ProverInput {
+        public_input,
+        private_input,
+        execution_trace: execution_trace::export(trace, &runner.relocated_memory),
+    })
+}
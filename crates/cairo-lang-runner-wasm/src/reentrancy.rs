@@ -0,0 +1,42 @@
+//! The chains of contract calls re-entering a contract, such as `A -> B -> A`, for users testing
+//! re-entrancy attacks.
+
+use cairo_lang_runner::StarknetState;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// A chain of calls re-entering a contract, from the call entering it to the call re-entering it.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ReentrantCall {
+    /// The address of the re-entered contract, as a hex string.
+    pub contract_address: String,
+    /// The calls of the chain, the outermost first.
+    pub calls: Vec<EntryPointCall>,
+}
+
+/// A call to an entry point of a contract.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct EntryPointCall {
+    /// The address of the called contract, as a hex string.
+    pub contract_address: String,
+    /// The Sierra function of the entry point, e.g. `test::vault::Vault::__wrapper__withdraw`.
+    pub entry_point: String,
+}
+
+/// Returns the re-entrant calls of a run, in the order they were made.
+pub(crate) fn reentrant_calls(starknet_state: &StarknetState) -> Vec<ReentrantCall> {
+    starknet_state
+        .reentrant_calls()
+        .iter()
+        .map(|chain| {
+            let calls = chain
+                .iter()
+                .map(|call| EntryPointCall {
+                    contract_address: format!("{:#x}", call.contract_address.to_biguint()),
+                    entry_point: call.entry_point.to_string(),
+                })
+                .collect::<Vec<_>>();
+            ReentrantCall { contract_address: calls[0].contract_address.clone(), calls }
+        })
+        .collect()
+}
@@ -0,0 +1,185 @@
+//! The VM resources used by runs, for estimating their on-chain and proving costs.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_runner::StarknetExecutionResources;
+use cairo_lang_runner::casm_run::syscall_gas_cost;
+use cairo_lang_sierra::extensions::gas::CostTokenType;
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The gas costs of a step, a memory hole and the range check builtins, as charged by the Sierra
+/// gas model, by name in the `gas_costs` of a request.
+pub(crate) const RESOURCE_GAS_COSTS: [(&str, usize); 4] =
+    [("step", 100), ("memory_hole", 10), ("range_check", 70), ("range_check96", 56)];
+
+/// The gas costs of the VM resources and the syscalls pricing a [GasBreakdown], with the overrides
+/// of the `gas_costs` of a request.
+///
+/// Unlike the costs of the other builtins, these only reprice the breakdown: the gas a program
+/// charges for them is computed by the compiler, and the syscalls deduct fixed amounts.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ResourceCosts {
+    /// The overridden costs, by resource or syscall name, e.g. `step` or `storage_read`.
+    overrides: BTreeMap<String, usize>,
+}
+
+impl ResourceCosts {
+    /// Overrides the cost of the resource or syscall `name`. Returns `false` if there is none.
+    pub(crate) fn insert(&mut self, name: &str, cost: usize) -> bool {
+        let known = RESOURCE_GAS_COSTS.iter().any(|(resource, _)| *resource == name)
+            || syscall_gas_cost(&camel_case(name)).is_some();
+        if known {
+            self.overrides.insert(name.to_string(), cost);
+        }
+        known
+    }
+
+    /// Returns the cost of a step.
+    pub(crate) fn step(&self) -> usize {
+        self.resource("step")
+    }
+
+    /// Returns the cost of the resource `name`, one of [RESOURCE_GAS_COSTS].
+    fn resource(&self, name: &str) -> usize {
+        self.overrides.get(name).copied().unwrap_or_else(|| {
+            RESOURCE_GAS_COSTS.iter().find(|(resource, _)| *resource == name).unwrap().1
+        })
+    }
+
+    /// Returns the gas deducted by the syscall `selector`, e.g. `StorageRead`, if any.
+    fn syscall(&self, selector: &str) -> Option<usize> {
+        self.overrides.get(&snake_case(selector)).copied().or_else(|| syscall_gas_cost(selector))
+    }
+}
+
+/// The VM resources used by a run.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct VmResources {
+    pub n_steps: usize,
+    pub n_memory_holes: usize,
+    /// The number of instances of each used builtin, by name, e.g. `range_check` or `poseidon`.
+    pub builtins: BTreeMap<String, usize>,
+    /// The number of invocations of each syscall, by name, e.g. `storage_read` or `emit_event`.
+    /// Empty for runs without the Starknet context.
+    pub syscalls: BTreeMap<String, usize>,
+}
+
+impl From<&ExecutionResources> for VmResources {
+    fn from(resources: &ExecutionResources) -> Self {
+        Self {
+            n_steps: resources.n_steps,
+            n_memory_holes: resources.n_memory_holes,
+            builtins: resources
+                .builtin_instance_counter
+                .iter()
+                .filter(|(_, count)| **count != 0)
+                .map(|(builtin, count)| (builtin.to_str().to_string(), *count))
+                .collect(),
+            syscalls: BTreeMap::new(),
+        }
+    }
+}
+
+impl From<&StarknetExecutionResources> for VmResources {
+    fn from(resources: &StarknetExecutionResources) -> Self {
+        Self {
+            syscalls: resources
+                .syscalls
+                .iter()
+                .map(|(selector, count)| (snake_case(selector), *count))
+                .collect(),
+            ..Self::from(&resources.basic_resources)
+        }
+    }
+}
+
+/// The gas of the resources used by a run, by component.
+///
+/// The gas charged by a program is computed by the compiler for each of its branches, so
+/// `total` may differ slightly from the gas the run consumed.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GasBreakdown {
+    /// The gas of the steps.
+    pub steps: usize,
+    /// The gas of the memory holes.
+    pub memory_holes: usize,
+    /// The gas of the instances of each builtin charging gas, by name, e.g. `range_check`.
+    pub builtins: BTreeMap<String, usize>,
+    /// The gas deducted by the invocations of each syscall, by name, e.g. `storage_read`.
+    pub syscalls: BTreeMap<String, usize>,
+    /// The sum of the components.
+    pub total: usize,
+}
+
+impl GasBreakdown {
+    /// Prices `resources` with the gas costs of the builtins returned by `token_gas_cost` and
+    /// `resource_costs`.
+    pub(crate) fn new(
+        resources: &StarknetExecutionResources,
+        token_gas_cost: impl Fn(CostTokenType) -> usize,
+        resource_costs: &ResourceCosts,
+    ) -> Self {
+        let basic_resources = &resources.basic_resources;
+        let builtins = basic_resources
+            .builtin_instance_counter
+            .iter()
+            .filter(|(_, count)| **count != 0)
+            .filter_map(|(builtin, count)| {
+                let name = builtin.to_str();
+                let cost = match name {
+                    "range_check" | "range_check96" => resource_costs.resource(name),
+                    // Builtins without a cost token, such as `segment_arena`, charge no gas.
+                    _ => token_gas_cost(
+                        *CostTokenType::iter_precost()
+                            .find(|token_type| token_type.name() == name)?,
+                    ),
+                };
+                Some((name.to_string(), count * cost))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let syscalls = resources
+            .syscalls
+            .iter()
+            .filter_map(|(selector, count)| {
+                Some((snake_case(selector), count * resource_costs.syscall(selector)?))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let steps = basic_resources.n_steps * resource_costs.step();
+        let memory_holes = basic_resources.n_memory_holes * resource_costs.resource("memory_hole");
+        let total = steps
+            + memory_holes
+            + builtins.values().sum::<usize>()
+            + syscalls.values().sum::<usize>();
+        Self { steps, memory_holes, builtins, syscalls, total }
+    }
+}
+
+/// Converts a syscall selector, such as `StorageRead`, to snake case.
+fn snake_case(selector: &str) -> String {
+    let mut name = String::new();
+    let mut previous = None;
+    for char in selector.chars() {
+        if char.is_ascii_uppercase()
+            && previous.is_some_and(|previous: char| previous.is_ascii_alphanumeric())
+        {
+            name.push('_');
+        }
+        name.push(char.to_ascii_lowercase());
+        previous = Some(char);
+    }
+    name
+}
+
+/// Converts a syscall name in snake case, such as `storage_read`, to its selector.
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect()
+}
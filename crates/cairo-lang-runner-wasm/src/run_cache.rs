@@ -0,0 +1,115 @@
+//! Memoization of run responses within a module instance, so that rerunning unchanged code, such
+//! as repeated clicks on "Run" in a playground, returns instantly.
+//!
+//! Runs are deterministic, so a response only depends on its request and on the host
+//! configuration, such as the gas policy, which together make up the key.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use cairo_lang_wasm_utils::error::{ApiError, ErrorKind};
+use cairo_lang_wasm_utils::state::StateCache;
+use cairo_lang_wasm_utils::{allocations, clock, config, logging, telemetry};
+use serde_json::{Value, json};
+
+use crate::RunResponse;
+
+/// The number of cached responses, the oldest of which are evicted first.
+const CAPACITY: usize = 16;
+
+thread_local! {
+    /// The cached serialized responses, with their keys, the oldest first.
+    static CACHE: RefCell<VecDeque<(Key, String)>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// The cached responses in module state snapshots, so that restored instances answer the runs
+/// already made by the instance that took the snapshot.
+pub(crate) const STATE_CACHE: StateCache = StateCache { name: "run_responses", snapshot, restore };
+
+/// The request and the host configuration a response answers, both serialized. They are compared
+/// whole, so that a response is never returned for another request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Key {
+    request: String,
+    config: String,
+}
+
+/// Returns the key of the response to `request_json`.
+pub(crate) fn key(request_json: &str) -> Key {
+    Key {
+        request: request_json.to_string(),
+        config: serde_json::to_string(&config::current()).expect("serialize host config"),
+    }
+}
+
+/// Returns the cached response of `key`, if any, as served by `endpoint`.
+///
+/// The allocations and debug log of the response are those of this call, and the call is
+/// reported to the telemetry listener as any other, `source_bytes` being the size of the sources
+/// of the request.
+pub(crate) fn serve(endpoint: &str, key: &Key, debug: bool, source_bytes: usize) -> Option<String> {
+    let started_ms = clock::now_ms();
+    let ((response, debug_log), allocations) =
+        allocations::measure(|| logging::capture(debug, || get(key)));
+    let mut response: Value = serde_json::from_str(&response?).expect("valid run response");
+    response["debug_log"] = serde_json::to_value(debug_log).expect("serialize debug log");
+    response["allocations"] = serde_json::to_value(allocations).expect("serialize allocations");
+    let error_kind = serde_json::from_value::<Option<ErrorKind>>(response["error"]["kind"].clone())
+        .expect("valid run response error");
+    telemetry::report_outcome(endpoint, started_ms, source_bytes, error_kind);
+    Some(response.to_string())
+}
+
+/// Returns the cached serialized response of `key`, if any.
+fn get(key: &Key) -> Option<String> {
+    CACHE.with_borrow(|cache| {
+        cache.iter().find(|(cached_key, _)| cached_key == key).map(|(_, response)| response.clone())
+    })
+}
+
+/// Returns whether the response of `result` may be cached: runs stopped by the host or by an
+/// internal error may end differently when repeated.
+pub(crate) fn is_cacheable(result: &Result<RunResponse, ApiError>) -> bool {
+    result.as_ref().is_ok_and(|response| {
+        !response.cancelled
+            && response.error.as_ref().is_none_or(|error| error.kind != ErrorKind::Cancelled)
+    })
+}
+
+/// Caches the serialized `response` of `key`, marked as `cached`, without the allocations and
+/// debug log of the call that made it.
+pub(crate) fn insert(key: Key, response: &str) {
+    let mut response: Value = serde_json::from_str(response).expect("valid run response");
+    response["cached"] = Value::Bool(true);
+    response["allocations"] = Value::Null;
+    response["debug_log"] = Value::Null;
+    let response = response.to_string();
+    CACHE.with_borrow_mut(|cache| {
+        if cache.len() == CAPACITY {
+            cache.pop_front();
+        }
+        cache.push_back((key, response));
+    });
+}
+
+/// Returns the cached responses, the oldest first, each with its request and host configuration.
+fn snapshot() -> Value {
+    CACHE.with_borrow(|cache| {
+        cache.iter().map(|(key, response)| json!([key.request, key.config, response])).collect()
+    })
+}
+
+/// Replaces the cached responses by those returned by [snapshot].
+fn restore(contents: Value) -> Result<(), ApiError> {
+    let invalid = || ApiError::invalid_request("Invalid `run_responses` cache in module state.");
+    let entries = serde_json::from_value::<Vec<(String, String, String)>>(contents)
+        .map_err(|_| invalid())?
+        .into_iter()
+        .map(|(request, config, response)| (Key { request, config }, response))
+        .collect::<VecDeque<_>>();
+    if entries.len() > CAPACITY {
+        return Err(invalid());
+    }
+    CACHE.set(entries);
+    Ok(())
+}
@@ -0,0 +1,130 @@
+//! A health check compiling and running a tiny embedded program.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::diagnostics::DiagnosticsReporter;
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_compiler::{CompilerConfig, compile_in_memory_project};
+use cairo_lang_lowering::utils::InliningStrategy;
+use cairo_lang_wasm_utils::error::{self, ApiError, ErrorKind};
+use cairo_lang_wasm_utils::{clock, corelib};
+use schemars::JsonSchema;
+use serde::Serialize;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{RunOptions, run_program};
+
+/// The program run by [self_test]. Exercises function calls, recursion and gas withdrawal.
+const SELF_TEST_PROGRAM: &str = "\
+fn fib(a: felt252, b: felt252, n: felt252) -> felt252 {
+    if n == 0 {
+        a
+    } else {
+        fib(b, a + b, n - 1)
+    }
+}
+
+fn main() -> felt252 {
+    fib(0, 1, 10)
+}
+";
+const SELF_TEST_EXPECTED_VALUES: [&str; 1] = ["55"];
+const SELF_TEST_AVAILABLE_GAS: usize = 1000000;
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct SelfTestResponse {
+    pub success: bool,
+    pub compile_ms: f64,
+    pub run_ms: f64,
+    pub total_ms: f64,
+    pub error: Option<ApiError>,
+}
+
+/// Compiles and runs an embedded program against the embedded corelib, reporting how long each
+/// step took. A host can call it on a freshly instantiated module before routing requests to it.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn self_test() -> String {
+    let response = error::catch_internal(run_self_test)
+        .unwrap_or_else(|error| SelfTestResponse { error: Some(error), ..Default::default() });
+    serde_json::to_string(&response).expect("serialize self-test response")
+}
+
+fn run_self_test() -> SelfTestResponse {
+    let compile_start = clock::now_ms();
+    let project = InMemoryProject {
+        main_crate_name: "self_test".into(),
+        main_crate_files: BTreeMap::from([("lib.cairo".into(), SELF_TEST_PROGRAM.into())]),
+        corelib_files: corelib::embedded_files(),
+        main_crate_settings: None,
+    };
+    let mut diagnostics = String::new();
+    let compiler_config = CompilerConfig {
+        diagnostics_reporter: DiagnosticsReporter::write_to_string(&mut diagnostics),
+        replace_ids: true,
+        ..CompilerConfig::default()
+    };
+    let compiled = compile_in_memory_project(&project, compiler_config, InliningStrategy::Default);
+    let compile_ms = clock::now_ms() - compile_start;
+    let program = match compiled {
+        Ok(program) => program,
+        Err(error) => {
+            return SelfTestResponse {
+                compile_ms,
+                total_ms: compile_ms,
+                error: Some(ApiError::new(
+                    ErrorKind::Internal,
+                    format!("Self-test program failed to compile: {error}\n{diagnostics}"),
+                )),
+                ..Default::default()
+            };
+        }
+    };
+
+    let run_start = clock::now_ms();
+    let options = RunOptions { available_gas: Some(SELF_TEST_AVAILABLE_GAS), ..Default::default() };
+    let response = run_program(program, "::main", options, String::new());
+    let run_ms = clock::now_ms() - run_start;
+
+    let error = if let Some(error) = response.error {
+        Some(ApiError::new(
+            ErrorKind::Internal,
+            format!("Self-test program failed to run: {}", error.message),
+        ))
+    } else if response.values != SELF_TEST_EXPECTED_VALUES {
+        Some(ApiError::new(
+            ErrorKind::Internal,
+            format!(
+                "Self-test program returned {:?}, expected {SELF_TEST_EXPECTED_VALUES:?}.",
+                response.values
+            ),
+        ))
+    } else {
+        None
+    };
+
+    SelfTestResponse {
+        success: error.is_none(),
+        compile_ms,
+        run_ms,
+        total_ms: compile_ms + run_ms,
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::self_test;
+
+    #[test]
+    fn self_test_passes() {
+        let response = self_test();
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["error"], Value::Null);
+        assert!(response_json["total_ms"].as_f64().unwrap() >= 0.0);
+    }
+}
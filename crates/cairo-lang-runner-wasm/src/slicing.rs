@@ -0,0 +1,345 @@
+//! Slicing a Sierra program down to what one function reaches, for sharing minimal reproductions
+//! of code generation issues.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_sierra::ids::{ConcreteLibfuncId, ConcreteTypeId, FunctionId};
+use cairo_lang_sierra::program::{Function, GenericArg, Program, Statement, StatementIdx};
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{corelib, item_path, limits, parse, source};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    DiagnosticsScopeArg, InliningStrategyArg, compile_project, compiled_program,
+    default_replace_ids, parse_sierra, suggestions,
+};
+
+/// A request slicing a project, given by `files`, or a Sierra program.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SliceProgramRequest {
+    pub crate_name: Option<String>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub files: Option<BTreeMap<String, String>>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    #[serde(default = "default_replace_ids")]
+    pub replace_ids: bool,
+    #[serde(default)]
+    pub inlining_strategy: InliningStrategyArg,
+    /// A Sierra program, exclusive with `files`.
+    pub sierra: Option<String>,
+    /// The handle of a program compiled by `compile_program`, exclusive with `files` and `sierra`.
+    pub program: Option<u32>,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct SliceProgramResponse {
+    pub success: bool,
+    /// The text of the sliced program.
+    pub sierra: Option<String>,
+    /// The functions of the sliced program, the sliced function first.
+    pub functions: Vec<String>,
+    /// The number of statements of the sliced program.
+    pub statements: usize,
+    /// The number of statements of the original program.
+    pub original_statements: usize,
+    pub diagnostics: String,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Slices a project or a Sierra program down to `function`: the statements reachable from it,
+/// the functions it calls, and the types and libfuncs they use. `function` is matched against the
+/// trailing `::`-separated segments of the function paths, as in `compile_and_run`, and must match
+/// a single function.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn slice_program(request_json: &str, function: &str) -> String {
+    let request: SliceProgramRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => return serialize_slice_response(Err(error), None),
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| slice_program_request(request, function))
+    });
+    serialize_slice_response(result, debug_log)
+}
+
+fn slice_program_request(request: SliceProgramRequest, function: &str) -> SliceProgramResponse {
+    let program = match (request.files, request.sierra, request.program) {
+        (Some(files), None, None) => {
+            let Some(crate_name) = request.crate_name else {
+                return SliceProgramResponse {
+                    error: Some(ApiError::invalid_request(
+                        "`crate_name` is required with `files`.",
+                    )),
+                    ..Default::default()
+                };
+            };
+            if let Err(error) = limits::current()
+                .check_files(files.iter().chain(request.corelib_files.iter().flatten()))
+            {
+                return SliceProgramResponse { error: Some(error), ..Default::default() };
+            }
+            let project = InMemoryProject {
+                main_crate_name: crate_name,
+                main_crate_files: files,
+                corelib_files: corelib::resolve(request.corelib_files),
+                main_crate_settings: None,
+            };
+            return compile_project(
+                &project,
+                &Default::default(),
+                request.inlining_strategy,
+                request.replace_ids,
+                DiagnosticsScopeArg::All,
+                |program, _, _, diagnostics| SliceProgramResponse {
+                    diagnostics,
+                    ..slice_response(&program, function)
+                },
+            )
+            .unwrap_or_else(|(error, diagnostics)| SliceProgramResponse {
+                diagnostics,
+                error: Some(error),
+                ..Default::default()
+            });
+        }
+        (None, Some(sierra), None) => limits::current()
+            .check_source("Sierra program", &sierra)
+            .and_then(|()| parse_sierra(&sierra)),
+        (None, None, Some(program)) => compiled_program::get(program),
+        _ => Err(ApiError::invalid_request(
+            "Exactly one of `files`, `sierra` and `program` is required.",
+        )),
+    };
+    match program {
+        Ok(program) => slice_response(&program, function),
+        Err(error) => SliceProgramResponse { error: Some(error), ..Default::default() },
+    }
+}
+
+fn slice_response(program: &Program, function: &str) -> SliceProgramResponse {
+    let candidates = program
+        .funcs
+        .iter()
+        .filter(|func| {
+            func.id
+                .debug_name
+                .as_deref()
+                .is_some_and(|name| item_path::matches_suffix(name, function))
+        })
+        .collect::<Vec<_>>();
+    let func = match candidates[..] {
+        [func] => func,
+        [] => {
+            let suggestions = suggestions::function_suggestions(
+                function,
+                program.funcs.iter().filter_map(|func| func.id.debug_name.as_deref()),
+            );
+            return SliceProgramResponse {
+                error: Some(
+                    ApiError::invalid_request(format!("Missing function `{function}`."))
+                        .with_suggestions(suggestions),
+                ),
+                ..Default::default()
+            };
+        }
+        _ => {
+            let paths = candidates.iter().map(|func| format!("`{}`", func.id));
+            return SliceProgramResponse {
+                error: Some(ApiError::invalid_request(format!(
+                    "Function `{function}` is ambiguous, matching {}.",
+                    paths.collect::<Vec<_>>().join(", ")
+                ))),
+                ..Default::default()
+            };
+        }
+    };
+    let sliced = slice(program, &func.id);
+    SliceProgramResponse {
+        success: true,
+        sierra: Some(sliced.to_string()),
+        functions: sliced.funcs.iter().map(|func| func.id.to_string()).collect(),
+        statements: sliced.statements.len(),
+        original_statements: program.statements.len(),
+        ..Default::default()
+    }
+}
+
+/// Returns the program made of the statements reachable from `root`, with the declarations they
+/// use. Statements keep their order, so falling through still leads to the next one.
+fn slice(program: &Program, root: &FunctionId) -> Program {
+    let funcs_by_id =
+        program.funcs.iter().map(|func| (func.id.clone(), func)).collect::<HashMap<_, _>>();
+    let libfuncs_by_id = program
+        .libfunc_declarations
+        .iter()
+        .map(|declaration| (declaration.id.clone(), declaration))
+        .collect::<HashMap<_, _>>();
+
+    let mut funcs = vec![root.clone()];
+    let mut pending_funcs = vec![root.clone()];
+    let mut statements = BTreeSet::new();
+    let mut libfuncs = HashSet::<ConcreteLibfuncId>::new();
+    while let Some(func_id) = pending_funcs.pop() {
+        let mut pending_statements = vec![funcs_by_id[&func_id].entry_point];
+        while let Some(idx) = pending_statements.pop() {
+            if !statements.insert(idx) {
+                continue;
+            }
+            let Some(Statement::Invocation(invocation)) = program.get_statement(idx) else {
+                continue;
+            };
+            pending_statements
+                .extend(invocation.branches.iter().map(|branch| idx.next(branch.target)));
+            if !libfuncs.insert(invocation.libfunc_id.clone()) {
+                continue;
+            }
+            // Called functions are given as generic arguments of libfuncs such as `function_call`.
+            for arg in &libfuncs_by_id[&invocation.libfunc_id].long_id.generic_args {
+                if let GenericArg::UserFunc(callee) = arg
+                    && !funcs.contains(callee)
+                {
+                    funcs.push(callee.clone());
+                    pending_funcs.push(callee.clone());
+                }
+            }
+        }
+    }
+
+    let new_idx = statements
+        .iter()
+        .enumerate()
+        .map(|(new_idx, idx)| (*idx, StatementIdx(new_idx)))
+        .collect::<HashMap<_, _>>();
+    let funcs = funcs
+        .iter()
+        .map(|func_id| {
+            let func = funcs_by_id[func_id];
+            Function { entry_point: new_idx[&func.entry_point], ..func.clone() }
+        })
+        .collect::<Vec<_>>();
+    let libfunc_declarations = program
+        .libfunc_declarations
+        .iter()
+        .filter(|declaration| libfuncs.contains(&declaration.id))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    // The types used by the signatures and the libfuncs, and those their own arguments use.
+    let types_by_id = program
+        .type_declarations
+        .iter()
+        .map(|declaration| (declaration.id.clone(), declaration))
+        .collect::<HashMap<_, _>>();
+    let mut pending_types = funcs
+        .iter()
+        .flat_map(|func| func.signature.param_types.iter().chain(&func.signature.ret_types))
+        .chain(libfunc_declarations.iter().flat_map(|declaration| {
+            declaration.long_id.generic_args.iter().filter_map(|arg| match arg {
+                GenericArg::Type(ty) => Some(ty),
+                _ => None,
+            })
+        }))
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut types = HashSet::<ConcreteTypeId>::new();
+    while let Some(ty) = pending_types.pop() {
+        if !types.insert(ty.clone()) {
+            continue;
+        }
+        for arg in &types_by_id[&ty].long_id.generic_args {
+            if let GenericArg::Type(inner) = arg {
+                pending_types.push(inner.clone());
+            }
+        }
+    }
+
+    Program {
+        type_declarations: program
+            .type_declarations
+            .iter()
+            .filter(|declaration| types.contains(&declaration.id))
+            .cloned()
+            .collect(),
+        libfunc_declarations,
+        statements: statements
+            .iter()
+            .map(|idx| program.statements[idx.0].clone().map(|target| new_idx[&target]))
+            .collect(),
+        funcs,
+    }
+}
+
+fn serialize_slice_response(
+    result: Result<SliceProgramResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+) -> String {
+    let mut response = result
+        .unwrap_or_else(|error| SliceProgramResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+    serde_json::to_string(&response).expect("serialize slice program response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::slice_program;
+    use crate::run_sierra;
+
+    #[test]
+    fn slice_program_keeps_reachable_functions() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn main() -> felt252 {
+    helper(20) + 2
+}
+
+fn safe_main() -> felt252 {
+    main()
+}
+
+#[inline(never)]
+fn helper(value: felt252) -> felt252 {
+    value * 2
+}
+
+fn unused() -> felt252 {
+    7
+}
+"
+            }
+        });
+
+        // `main` designates a whole path segment, so `safe_main` does not make it ambiguous.
+        let response = slice_program(&request.to_string(), "main");
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["functions"], json!(["test::main", "test::helper"]));
+        let sierra = response_json["sierra"].as_str().unwrap();
+        assert!(!sierra.contains("test::unused"), "sierra={sierra}");
+        assert!(!sierra.contains("test::safe_main"), "sierra={sierra}");
+        assert!(
+            response_json["statements"].as_u64() < response_json["original_statements"].as_u64()
+        );
+
+        let request = json!({ "sierra": sierra, "function": "::main" });
+        let response_json: Value =
+            serde_json::from_str(&run_sierra(&request.to_string())).expect("valid JSON response");
+        assert_eq!(response_json["values"], json!(["42"]), "response={response_json}");
+    }
+}
@@ -0,0 +1,51 @@
+//! The execution context seen by the Starknet syscalls of a run, such as `get_block_timestamp()`,
+//! which is otherwise all zeros.
+
+use cairo_lang_runner::StarknetState;
+use cairo_lang_wasm_utils::error::ApiError;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use starknet_types_core::felt::Felt as Felt252;
+
+use crate::contract::parse_felt;
+
+/// The block and transaction info of a run. Unset fields are `0`. Addresses and the chain id are
+/// given as decimal or `0x`-prefixed hex strings.
+#[derive(Debug, Default, PartialEq, Deserialize, JsonSchema)]
+pub struct StarknetContext {
+    pub block_number: Option<u64>,
+    /// The timestamp of the block, in seconds since the Unix epoch.
+    pub block_timestamp: Option<u64>,
+    pub sequencer_address: Option<String>,
+    /// The address returned by `get_caller_address()`.
+    pub caller_address: Option<String>,
+    /// The address returned by `get_contract_address()`.
+    pub contract_address: Option<String>,
+    /// The chain id, such as `0x534e5f5345504f4c4941` for `SN_SEPOLIA`.
+    pub chain_id: Option<String>,
+}
+
+impl StarknetContext {
+    /// Returns the Starknet state a run starts from.
+    pub(crate) fn starknet_state(&self) -> Result<StarknetState, ApiError> {
+        let felt = |value: &Option<String>| value.as_deref().map(parse_felt).transpose();
+        let mut state = StarknetState::default();
+        if let Some(block_number) = self.block_number {
+            state.set_block_number(block_number.into());
+        }
+        if let Some(block_timestamp) = self.block_timestamp {
+            state.set_block_timestamp(block_timestamp.into());
+        }
+        if let Some(sequencer_address) = felt(&self.sequencer_address)? {
+            state.set_sequencer_address(sequencer_address);
+        }
+        if let Some(chain_id) = felt(&self.chain_id)? {
+            state.set_chain_id(chain_id);
+        }
+        state.open_caller_context((
+            felt(&self.contract_address)?.unwrap_or(Felt252::ZERO),
+            felt(&self.caller_address)?.unwrap_or(Felt252::ZERO),
+        ));
+        Ok(state)
+    }
+}
@@ -0,0 +1,158 @@
+//! Sessions holding the Starknet state of successive contract calls, whose execution context is
+//! adjusted between calls by `warp`, `roll` and `prank`, as the cheatcodes of Starknet Foundry do.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use cairo_lang_runner::StarknetState;
+use cairo_lang_wasm_utils::error::ApiError;
+use schemars::JsonSchema;
+use serde::Serialize;
+use starknet_types_core::felt::Felt as Felt252;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::contract::parse_felt;
+
+/// The state left by the calls of a session, and the caller of its next calls.
+#[derive(Default)]
+struct StarknetSession {
+    state: StarknetState,
+    caller_address: Felt252,
+}
+
+thread_local! {
+    static SESSIONS: RefCell<HashMap<u32, StarknetSession>> = RefCell::new(HashMap::new());
+    static NEXT_SESSION_ID: Cell<u32> = const { Cell::new(1) };
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct StarknetSessionResponse {
+    pub success: bool,
+    pub error: Option<ApiError>,
+}
+
+/// Opens a session whose id is given as the `session` of `call_contract` requests, which then
+/// start from the storage and execution context left by the earlier calls of the session.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn open_starknet_session() -> u32 {
+    let session_id = NEXT_SESSION_ID.get();
+    NEXT_SESSION_ID.set(session_id + 1);
+    SESSIONS.with_borrow_mut(|sessions| sessions.insert(session_id, StarknetSession::default()));
+    session_id
+}
+
+/// Frees the state of a session. Returns whether the session existed.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn close_starknet_session(session: u32) -> bool {
+    SESSIONS.with_borrow_mut(|sessions| sessions.remove(&session).is_some())
+}
+
+/// Sets the block timestamp seen by the next calls of a session.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn warp(session: u32, block_timestamp: u64) -> String {
+    update_session(session, |session| {
+        session.state.set_block_timestamp(block_timestamp.into());
+        Ok(())
+    })
+}
+
+/// Sets the block number seen by the next calls of a session.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn roll(session: u32, block_number: u64) -> String {
+    update_session(session, |session| {
+        session.state.set_block_number(block_number.into());
+        Ok(())
+    })
+}
+
+/// Sets the caller address of the next calls of a session, as a decimal or `0x`-prefixed hex felt.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn prank(session: u32, caller_address: &str) -> String {
+    update_session(session, |session| {
+        session.caller_address = parse_felt(caller_address)?;
+        Ok(())
+    })
+}
+
+/// Returns the state a call of a session to `contract_address` starts from.
+pub(crate) fn call_state(
+    session: u32,
+    contract_address: Felt252,
+) -> Result<StarknetState, ApiError> {
+    with_session(session, |session| {
+        let mut state = session.state.clone();
+        state.open_caller_context((contract_address, session.caller_address));
+        Ok(state)
+    })
+}
+
+/// Keeps the state left by a successful call of a session for its next calls.
+pub(crate) fn save_state(session: u32, state: StarknetState) -> Result<(), ApiError> {
+    with_session(session, |session| {
+        session.state = state;
+        Ok(())
+    })
+}
+
+fn with_session<T>(
+    session_id: u32,
+    f: impl FnOnce(&mut StarknetSession) -> Result<T, ApiError>,
+) -> Result<T, ApiError> {
+    SESSIONS.with_borrow_mut(|sessions| {
+        let session = sessions.get_mut(&session_id).ok_or_else(|| {
+            ApiError::invalid_request(format!("Unknown Starknet session {session_id}."))
+        })?;
+        f(session)
+    })
+}
+
+fn update_session(
+    session: u32,
+    f: impl FnOnce(&mut StarknetSession) -> Result<(), ApiError>,
+) -> String {
+    let response = match with_session(session, f) {
+        Ok(()) => StarknetSessionResponse { success: true, error: None },
+        Err(error) => StarknetSessionResponse { success: false, error: Some(error) },
+    };
+    serde_json::to_string(&response).expect("serialize Starknet session response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{close_starknet_session, open_starknet_session, prank, warp};
+    use crate::call_contract;
+
+    const MINTABLE_CLASS: &str = include_str!(
+        "../../cairo-lang-starknet/test_data/mintable__mintable_erc20_ownable.contract_class.json"
+    );
+
+    #[test]
+    fn session_keeps_state_between_calls() {
+        let contract_class: Value = serde_json::from_str(MINTABLE_CLASS).unwrap();
+        let call = |session: u32, entry_point: &str, calldata: Value| {
+            let request = json!({
+                "contract_class": contract_class,
+                "entry_point": entry_point,
+                "calldata": calldata,
+                "contract_address": "0x100",
+                "session": session
+            });
+            serde_json::from_str::<Value>(&call_contract(&request.to_string())).unwrap()
+        };
+        let session = open_starknet_session();
+
+        let response: Value = serde_json::from_str(&prank(session, "0x1")).unwrap();
+        assert_eq!(response["success"], true, "response={response}");
+        let response = call(session, "approve", json!(["0x2", "7", "0"]));
+        assert_eq!(response["success"], true, "response={response}");
+        let response = call(session, "allowance", json!(["0x1", "0x2"]));
+        assert_eq!(response["values"], json!(["7", "0"]), "response={response}");
+
+        assert!(close_starknet_session(session));
+        let response: Value = serde_json::from_str(&warp(session, 1700000000)).unwrap();
+        assert_eq!(response["error"]["kind"], "invalid-request");
+    }
+}
@@ -0,0 +1,159 @@
+//! Reads of contract storage variables by name, computing their addresses as the storage of a
+//! contract does, so users can assert on the storage left by a call without getter entry points.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_starknet_classes::keccak::starknet_keccak;
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::parse;
+use num_bigint::BigUint;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use starknet_types_core::felt::Felt as Felt252;
+use starknet_types_core::hash::{Pedersen, StarkHash};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::contract::parse_felt;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadStorageRequest {
+    /// The storage of a contract, mapping storage addresses to values, such as the `storage`
+    /// returned by `call_contract`.
+    #[serde(default)]
+    pub storage: BTreeMap<String, String>,
+    /// The storage variable, followed by the members of the storage nodes it goes through, such as
+    /// `balances` or `config.owner`.
+    pub variable_path: String,
+    /// The keys of the `Map` entry to read, serialized as felts, a `u256` taking two.
+    #[serde(default)]
+    pub keys: Vec<String>,
+    /// The number of felts of the value to read, such as 2 for a `u256`.
+    #[serde(default = "default_length")]
+    pub length: usize,
+    /// Whether to return the debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct ReadStorageResponse {
+    pub success: bool,
+    /// The storage address of the value, as a hex string.
+    pub address: Option<String>,
+    /// The felts of the value, read from `address` on; unset addresses read `0`.
+    pub values: Vec<String>,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Reads a storage variable, or an entry of a `Map`, from the storage of a contract. See
+/// [ReadStorageRequest].
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn read_storage(request_json: &str) -> String {
+    let request: ReadStorageRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => return serialize_read_storage_response(Err(error), None),
+    };
+
+    let (result, debug_log) = logging::capture(request.debug, || {
+        error::catch_internal(|| {
+            read_storage_request(request).unwrap_or_else(|error| ReadStorageResponse {
+                error: Some(error),
+                ..Default::default()
+            })
+        })
+    });
+    serialize_read_storage_response(result, debug_log)
+}
+
+fn read_storage_request(request: ReadStorageRequest) -> Result<ReadStorageResponse, ApiError> {
+    let storage = request
+        .storage
+        .iter()
+        .map(|(address, value)| Ok((parse_felt(address)?, parse_felt(value)?)))
+        .collect::<Result<BTreeMap<_, _>, ApiError>>()?;
+    let keys = request.keys.iter().map(|key| parse_felt(key)).collect::<Result<Vec<_>, _>>()?;
+    let address = storage_address(&request.variable_path, &keys)?;
+    let values = (0..request.length)
+        .map(|offset| {
+            let value = storage.get(&(address + Felt252::from(offset))).copied();
+            value.unwrap_or_default().to_string()
+        })
+        .collect();
+    Ok(ReadStorageResponse {
+        success: true,
+        address: Some(format!("{:#x}", address.to_biguint())),
+        values,
+        ..Default::default()
+    })
+}
+
+/// Returns the storage address of the variable at `variable_path`, at the entry of `keys`, as
+/// computed by the storage paths of the corelib: the selector of the variable, hashed with the
+/// selectors of the storage node members and the keys, reduced below `2^251 - 256`.
+fn storage_address(variable_path: &str, keys: &[Felt252]) -> Result<Felt252, ApiError> {
+    let mut names = variable_path.split('.');
+    let variable = names.next().unwrap_or_default();
+    if variable.is_empty() {
+        return Err(ApiError::invalid_request("`variable_path` must name a storage variable."));
+    }
+    let selector =
+        |name: &str| Felt252::from_bytes_be_slice(&starknet_keccak(name.as_bytes()).to_bytes_be());
+    let hash = names
+        .map(selector)
+        .chain(keys.iter().copied())
+        .fold(selector(variable), |hash, value| Pedersen::hash(&hash, &value));
+    let bound = (BigUint::from(1u32) << 251) - 256u32;
+    Ok(if hash.to_biguint() < bound { hash } else { hash - Felt252::from(bound) })
+}
+
+fn default_length() -> usize {
+    1
+}
+
+fn serialize_read_storage_response(
+    result: Result<ReadStorageResponse, ApiError>,
+    debug_log: Option<DebugLog>,
+) -> String {
+    let mut response = result
+        .unwrap_or_else(|error| ReadStorageResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+    serde_json::to_string(&response).expect("serialize read storage response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::read_storage;
+    use crate::call_contract;
+
+    const HELLO_STARKNET_CLASS: &str = include_str!(
+        "../../cairo-lang-starknet/test_data/hello_starknet__hello_starknet.contract_class.json"
+    );
+
+    #[test]
+    fn read_storage_reads_storage_left_by_call() {
+        let contract_class: Value = serde_json::from_str(HELLO_STARKNET_CLASS).unwrap();
+        let request = json!({
+            "contract_class": contract_class,
+            "entry_point": "increase_balance",
+            "calldata": ["5"]
+        });
+        let response = call_contract(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+        assert_eq!(response_json["success"], true, "response={response}");
+
+        let request = json!({
+            "storage": response_json["storage"],
+            "variable_path": "balance"
+        });
+        let response = read_storage(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["values"], json!(["5"]));
+    }
+}
@@ -0,0 +1,51 @@
+//! Suggestions of existing functions for a requested function that matches none of them.
+
+use crate::entrypoints::is_user_function;
+
+/// The maximal number of suggested functions.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Returns the user functions among `functions` whose path is the closest to `requested`, best
+/// first. Functions too far from `requested` to be a typo of it are left out.
+pub(crate) fn function_suggestions<'a>(
+    requested: &str,
+    functions: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let requested = requested.trim_start_matches("::");
+    let max_distance = (requested.chars().count() / 3).max(2);
+    let mut candidates = functions
+        .into_iter()
+        .filter(|function| is_user_function(function))
+        .filter_map(|function| {
+            // The requested function is matched against the end of the paths, so it is compared
+            // with every suffix of a path starting after a `::`.
+            let distance =
+                path_suffixes(function).map(|suffix| edit_distance(requested, suffix)).min()?;
+            (distance <= max_distance).then_some((distance, function))
+        })
+        .collect::<Vec<_>>();
+    candidates.sort();
+    candidates.into_iter().take(MAX_SUGGESTIONS).map(|(_, function)| function.to_string()).collect()
+}
+
+/// Returns `path` and its suffixes following each `::`, e.g. `a::b`, then `b` for `a::b`.
+fn path_suffixes(path: &str) -> impl Iterator<Item = &str> {
+    std::iter::once(path).chain(path.match_indices("::").map(|(idx, _)| &path[idx + 2..]))
+}
+
+/// Returns the Levenshtein distance between `a` and `b`, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    // The distances from the prefix of `a` handled so far to each prefix of `b`.
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_char != *b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
@@ -0,0 +1,467 @@
+//! Running all the `#[test]` functions of a project at once, as `cairo-test` does.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_wasm_utils::error::{self, ApiError, ErrorKind};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{corelib, limits, parse, source};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::junit;
+use crate::test_session::{RunTestResponse, TestSession, TestStatus};
+use crate::testing::prepare_test_db;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RunTestsRequest {
+    pub crate_name: String,
+    #[serde(deserialize_with = "source::deserialize_files")]
+    #[schemars(with = "BTreeMap<String, source::SourceContent>")]
+    pub files: BTreeMap<String, String>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    /// Whether to compile with the Starknet plugin, for tests of contracts.
+    #[serde(default)]
+    pub starknet: bool,
+    /// The gas of the tests without `#[available_gas]`, instead of the `u32::MAX` of `cairo-test`.
+    pub available_gas: Option<usize>,
+    /// Runs only the tests whose names contain the filter, as `scarb test <filter>` does, or match
+    /// it as a whole when it holds `*` wildcards.
+    pub filter: Option<String>,
+    /// The format of the `report` of the response, besides the JSON of the tests.
+    #[serde(default)]
+    pub report_format: ReportFormat,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// A format of test reports.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    /// No report besides the JSON of the tests.
+    #[default]
+    Json,
+    /// A JUnit XML report, as ingested by CI systems.
+    Junit,
+}
+
+/// The run of a `#[test]` function.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TestRun {
+    /// The full path of the test function.
+    pub name: String,
+    #[serde(flatten)]
+    pub result: RunTestResponse,
+}
+
+/// The number of tests of each outcome.
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct TestSummary {
+    pub passed: usize,
+    /// The failed tests, including those that could not be run.
+    pub failed: usize,
+    pub ignored: usize,
+    /// The tests not matching the filter of the request, which were not run.
+    pub filtered_out: usize,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct RunTestsResponse {
+    /// Whether the tests compiled and were all run, whatever their outcomes.
+    pub success: bool,
+    /// The runs of the tests, in the order of their declarations.
+    pub tests: Vec<TestRun>,
+    pub summary: TestSummary,
+    /// The report of the tests in the `report_format` of the request, when not `json` and the
+    /// tests were all run.
+    pub report: Option<String>,
+    pub diagnostics: String,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Compiles a project with the test plugin and runs all its `#[test]` functions.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn run_tests(request_json: &str) -> String {
+    let request: RunTestsRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serde_json::to_string(&RunTestsResponse {
+                error: Some(error),
+                ..Default::default()
+            })
+            .expect("serialize error response");
+        }
+    };
+
+    let crate_name = request.crate_name.clone();
+    let report_format = request.report_format;
+    let (result, debug_log) =
+        logging::capture(request.debug, || error::catch_internal(|| run_tests_request(request)));
+    let mut response = result
+        .unwrap_or_else(|error| RunTestsResponse { error: Some(error), ..Default::default() });
+    if report_format == ReportFormat::Junit && response.success {
+        response.report = Some(junit::report(&crate_name, &response.tests));
+    }
+    response.debug_log = debug_log;
+
+    serde_json::to_string(&response).expect("serialize run tests response")
+}
+
+fn run_tests_request(request: RunTestsRequest) -> RunTestsResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return RunTestsResponse { error: Some(error), ..Default::default() };
+    }
+
+    let project = InMemoryProject {
+        main_crate_name: request.crate_name,
+        main_crate_files: request.files,
+        corelib_files: corelib::resolve(request.corelib_files),
+        main_crate_settings: None,
+    };
+    let (db, main_crate_inputs, diagnostics) = match prepare_test_db(&project, request.starknet) {
+        Ok(prepared) => prepared,
+        Err((error, diagnostics)) => {
+            return RunTestsResponse { diagnostics, error: Some(error), ..Default::default() };
+        }
+    };
+    let session =
+        match TestSession::compile(&db, main_crate_inputs, request.starknet, request.available_gas)
+        {
+            Ok(session) => session,
+            Err(error) => {
+                return RunTestsResponse { diagnostics, error: Some(error), ..Default::default() };
+            }
+        };
+
+    let mut tests = vec![];
+    let mut summary = TestSummary::default();
+    for name in session.test_names() {
+        if let Some(filter) = &request.filter
+            && !matches_filter(name, filter)
+        {
+            summary.filtered_out += 1;
+            continue;
+        }
+        let result = session
+            .run(name)
+            .unwrap_or_else(|error| RunTestResponse { error: Some(error), ..Default::default() });
+        match result.status {
+            Some(TestStatus::Passed) => summary.passed += 1,
+            Some(TestStatus::Ignored) => summary.ignored += 1,
+            Some(TestStatus::Failed) | None => summary.failed += 1,
+        }
+        let cancelled =
+            result.error.as_ref().is_some_and(|error| error.kind == ErrorKind::Cancelled);
+        tests.push(TestRun { name: name.to_string(), result });
+        // The remaining tests would be cancelled as well.
+        if cancelled {
+            return RunTestsResponse {
+                tests,
+                summary,
+                diagnostics,
+                error: Some(ApiError::new(ErrorKind::Cancelled, "The test run was cancelled.")),
+                ..Default::default()
+            };
+        }
+    }
+
+    RunTestsResponse { success: true, tests, summary, diagnostics, ..Default::default() }
+}
+
+/// Returns whether the test `name` matches `filter`.
+fn matches_filter(name: &str, filter: &str) -> bool {
+    if !filter.contains('*') {
+        return name.contains(filter);
+    }
+    let parts = filter.split('*').collect::<Vec<_>>();
+    let (first, middle, last) = (parts[0], &parts[1..parts.len() - 1], parts[parts.len() - 1]);
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::run_tests;
+
+    #[test]
+    fn run_tests_reports_each_test_and_summary() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn adds() {
+        println!(\"adding\");
+        assert_eq!(super::add(1, 2), 3);
+    }
+
+    #[test]
+    fn adds_wrongly() {
+        assert_eq!(super::add(1, 2), 4);
+    }
+
+    #[test]
+    #[ignore]
+    fn ignored() {}
+}
+"
+            }
+        });
+
+        let response = run_tests(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(
+            response_json["summary"],
+            json!({ "passed": 1, "failed": 1, "ignored": 1, "filtered_out": 0 })
+        );
+        let tests = response_json["tests"].as_array().unwrap();
+        assert_eq!(tests[0]["name"], "test::tests::adds");
+        assert_eq!(tests[0]["status"], "passed");
+        assert_eq!(tests[0]["stdout"], "adding\n");
+        assert_eq!(tests[1]["status"], "failed");
+        assert!(tests[1]["panic_message"].as_str().unwrap().contains("assertion"));
+        assert_eq!(tests[2]["status"], "ignored");
+    }
+
+    #[test]
+    fn run_tests_decodes_assertion_failures() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+#[derive(Drop, Debug, PartialEq)]
+struct Point { x: u32, y: u32 }
+
+#[cfg(test)]
+mod tests {
+    use super::Point;
+
+    #[test]
+    fn compares_points() {
+        let a = Point { x: 1, y: 2 };
+        let b = Point { x: 1, y: 3 };
+        assert_eq!(a, b);
+    }
+}
+"
+            }
+        });
+
+        let response = run_tests(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        let test = &response_json["tests"][0];
+        assert_eq!(test["status"], "failed", "response={response}");
+        assert_eq!(
+            test["assertion"],
+            json!({
+                "expression": "a == b",
+                "message": null,
+                "lhs": { "expression": "a", "value": "Point { x: 1, y: 2 }" },
+                "rhs": { "expression": "b", "value": "Point { x: 1, y: 3 }" },
+                "differences": [{ "path": ".y", "left": "2", "right": "3" }]
+            })
+        );
+    }
+
+    #[test]
+    fn run_tests_applies_filter() {
+        let lib = "\
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn adds() {}
+
+    #[test]
+    fn adds_twice() {}
+
+    #[test]
+    fn subtracts() {}
+}
+";
+        let run = |filter: &str| -> Value {
+            let request =
+                json!({ "crate_name": "test", "files": { "lib.cairo": lib }, "filter": filter });
+            serde_json::from_str(&run_tests(&request.to_string())).expect("valid JSON response")
+        };
+
+        let response = run("adds");
+        assert_eq!(response["summary"]["passed"], 2, "response={response}");
+        assert_eq!(response["summary"]["filtered_out"], 1);
+
+        let response = run("test::*::adds");
+        assert_eq!(response["tests"][0]["name"], "test::tests::adds", "response={response}");
+        assert_eq!(response["summary"]["filtered_out"], 2);
+    }
+
+    #[test]
+    fn run_tests_applies_default_gas_to_tests_without_attribute() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn count(n: u32) -> u32 {
+    let mut i = 0;
+    while i != n {
+        i += 1;
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn counts() {
+        assert_eq!(super::count(1000), 1000);
+    }
+
+    #[test]
+    #[available_gas(100000000)]
+    fn counts_with_gas() {
+        assert_eq!(super::count(1000), 1000);
+    }
+}
+"
+            },
+            "available_gas": 10000
+        });
+
+        let response = run_tests(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["tests"][0]["status"], "failed", "response={response}");
+        assert_eq!(response_json["tests"][1]["status"], "passed");
+    }
+
+    #[test]
+    fn run_tests_reports_junit() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn passes() {}
+
+    #[test]
+    fn fails() {
+        assert!(1 == 2, \"1 < 2\");
+    }
+
+    #[test]
+    #[ignore]
+    fn ignored() {}
+}
+"
+            },
+            "report_format": "junit"
+        });
+
+        let response = run_tests(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        let report = response_json["report"].as_str().expect("JUnit report");
+        assert!(
+            report.contains(
+                "<testsuite name=\"test\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\">"
+            ),
+            "report={report}"
+        );
+        assert!(report.contains("<testcase name=\"passes\" classname=\"test::tests\"/>"));
+        assert!(report.contains("<failure message=\"The test panicked.\">1 &lt; 2</failure>"));
+        assert!(report.contains("<skipped/>"));
+    }
+
+    #[test]
+    fn run_tests_reports_reentrant_calls() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+use starknet::ContractAddress;
+
+#[starknet::interface]
+trait IPinger<T> {
+    fn ping(self: @T, other: ContractAddress, depth: u32);
+}
+
+#[starknet::contract]
+mod pinger {
+    use starknet::{ContractAddress, get_contract_address};
+    use super::{IPingerDispatcher, IPingerDispatcherTrait};
+
+    #[storage]
+    struct Storage {}
+
+    #[abi(embed_v0)]
+    impl PingerImpl of super::IPinger<ContractState> {
+        fn ping(self: @ContractState, other: ContractAddress, depth: u32) {
+            if depth != 0 {
+                IPingerDispatcher { contract_address: other }
+                    .ping(get_contract_address(), depth - 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::syscalls::deploy_syscall;
+    use super::{IPingerDispatcher, IPingerDispatcherTrait, pinger};
+
+    #[test]
+    fn pings_back() {
+        let (a, _) = deploy_syscall(pinger::TEST_CLASS_HASH, 0, [].span(), false).unwrap();
+        let (b, _) = deploy_syscall(pinger::TEST_CLASS_HASH, 1, [].span(), false).unwrap();
+        IPingerDispatcher { contract_address: a }.ping(b, 2);
+    }
+}
+"
+            },
+            "starknet": true
+        });
+
+        let response = run_tests(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        let test = &response_json["tests"][0];
+        assert_eq!(test["status"], "passed", "response={response}");
+        // `a` calls `b`, which calls `a` back.
+        let reentrant_calls = test["reentrant_calls"].as_array().unwrap();
+        assert_eq!(reentrant_calls.len(), 1, "response={response}");
+        let calls = reentrant_calls[0]["calls"].as_array().unwrap();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0]["contract_address"], reentrant_calls[0]["contract_address"]);
+        assert_eq!(calls[2]["contract_address"], calls[0]["contract_address"]);
+        assert_ne!(calls[1]["contract_address"], calls[0]["contract_address"]);
+    }
+}
@@ -0,0 +1,393 @@
+//! Sessions holding the compiled tests of a project, so a UI can run them one at a time without
+//! recompiling the project for each run.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+
+use cairo_lang_compiler::db::RootDatabase;
+use cairo_lang_compiler::diagnostics::DiagnosticsReporter;
+use cairo_lang_defs::ids::TopLevelLanguageElementId;
+use cairo_lang_filesystem::ids::CrateInput;
+use cairo_lang_runner::{
+    CancellationCheck, RunResultValue, RunnerError, SierraCasmRunner, StarknetState,
+};
+use cairo_lang_sierra_to_casm::metadata::MetadataComputationConfig;
+use cairo_lang_test_plugin::test_config::{PanicExpectation, TestExpectation};
+use cairo_lang_test_plugin::{
+    TestCompilationMetadata, TestConfig, TestsCompilationConfig, compile_test_prepared_db,
+};
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use cairo_lang_wasm_utils::cancellation;
+use cairo_lang_wasm_utils::error::{self, ApiError, ErrorKind};
+use salsa::Database;
+use schemars::JsonSchema;
+use serde::Serialize;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::reentrancy::{self, ReentrantCall};
+use crate::{
+    AssertionFailure, CANCELLATION_CHECK_INTERVAL, VmResources, assertion, debug_output,
+    panic_message, suggestions, testing,
+};
+
+/// The compiled tests of a project.
+pub(crate) struct TestSession {
+    runner: SierraCasmRunner,
+    /// The configurations of the tests, by name.
+    tests: OrderedHashMap<String, TestConfig>,
+}
+
+thread_local! {
+    static SESSIONS: RefCell<HashMap<u32, TestSession>> = RefCell::new(HashMap::new());
+    static NEXT_SESSION_ID: Cell<u32> = const { Cell::new(1) };
+}
+
+/// The outcome of a test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    /// The test is marked `#[ignore]` and was not run.
+    Ignored,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct RunTestResponse {
+    /// The outcome of the test, `None` when it could not be run.
+    pub status: Option<TestStatus>,
+    /// Why the test failed, such as a `#[should_panic]` test returning or panicking with other
+    /// data than expected.
+    pub failure: Option<String>,
+    pub panicked: bool,
+    /// The returned felts, or the panic data when the test panicked.
+    pub values: Vec<String>,
+    /// The panic data decoded to text, when the test panicked.
+    pub panic_message: Option<String>,
+    /// The compared values of a failed comparison assertion, such as `assert_eq!`.
+    pub assertion: Option<AssertionFailure>,
+    pub stdout: String,
+    /// The output of debug prints of values other than strings, followed by the panic message
+    /// when the test panicked.
+    pub stderr: String,
+    /// The gas used by the test, as estimated by `cairo-test`.
+    pub gas_usage: Option<usize>,
+    pub resources: Option<VmResources>,
+    /// The chains of contract calls re-entering a contract, such as `A -> B -> A`, made by the
+    /// test, reverted ones included.
+    pub reentrant_calls: Vec<ReentrantCall>,
+    pub error: Option<ApiError>,
+}
+
+/// Compiles the tests of the crates into a new session, returning its id.
+pub(crate) fn open(
+    db: &RootDatabase,
+    crate_inputs: Vec<CrateInput>,
+    starknet: bool,
+    default_gas: Option<usize>,
+) -> Result<u32, ApiError> {
+    let session = TestSession::compile(db, crate_inputs, starknet, default_gas)?;
+    let session_id = NEXT_SESSION_ID.get();
+    NEXT_SESSION_ID.set(session_id + 1);
+    SESSIONS.with_borrow_mut(|sessions| sessions.insert(session_id, session));
+    Ok(session_id)
+}
+
+impl TestSession {
+    /// Compiles the tests of the crates. The tests without `#[available_gas]` run with
+    /// `default_gas` when set, instead of the default of the test plugin.
+    pub(crate) fn compile(
+        db: &RootDatabase,
+        crate_inputs: Vec<CrateInput>,
+        starknet: bool,
+        default_gas: Option<usize>,
+    ) -> Result<Self, ApiError> {
+        let tests_using_default_gas = default_gas
+            .map(|_| {
+                let db: &dyn Database = db;
+                testing::find_tests(db, crate_inputs.clone())
+                    .into_iter()
+                    .filter(|(func_id, _)| !testing::sets_available_gas(db, *func_id))
+                    .map(|(func_id, _)| func_id.full_path(db))
+                    .collect::<HashSet<_>>()
+            })
+            .unwrap_or_default();
+        let config = TestsCompilationConfig {
+            starknet,
+            contract_declarations: None,
+            contract_crate_ids: None,
+            executable_crate_ids: None,
+            add_statements_functions: false,
+            add_statements_code_locations: false,
+            add_functions_debug_info: false,
+            replace_ids: true,
+        };
+        // The diagnostics were checked when preparing the database.
+        let compiled = compile_test_prepared_db(
+            db,
+            config,
+            crate_inputs,
+            DiagnosticsReporter::ignoring().allow_warnings(),
+        )
+        .map_err(|error| ApiError::compilation(format!("{error:#}")))?;
+        let TestCompilationMetadata { mut named_tests, function_set_costs, contracts_info, .. } =
+            compiled.metadata;
+        for (name, config) in &mut named_tests {
+            if tests_using_default_gas.contains(name) {
+                config.available_gas = default_gas;
+            }
+        }
+        let runner = SierraCasmRunner::new(
+            compiled.sierra_program.program,
+            Some(MetadataComputationConfig {
+                function_set_costs,
+                linear_gas_solver: true,
+                linear_ap_change_solver: true,
+                skip_non_linear_solver_comparisons: false,
+                compute_runtime_costs: false,
+            }),
+            contracts_info,
+            None,
+        )
+        .map_err(|error| ApiError::execution(format!("Failed setting up runner: {error}")))?
+        // The session outlives the check installed by the host when it is opened, so the check is
+        // always polled.
+        .with_cancellation(CancellationCheck {
+            check_interval: CANCELLATION_CHECK_INTERVAL,
+            is_cancelled: Box::new(cancellation::is_cancelled),
+        });
+        Ok(TestSession { runner, tests: named_tests.into_iter().collect() })
+    }
+
+    /// Returns the names of the tests, in the order of their declarations.
+    pub(crate) fn test_names(&self) -> impl Iterator<Item = &str> {
+        self.tests.keys().map(String::as_str)
+    }
+
+    /// Runs the test named `test_id`.
+    pub(crate) fn run(&self, test_id: &str) -> Result<RunTestResponse, ApiError> {
+        let Some(config) = self.tests.get(test_id) else {
+            let suggestions = suggestions::function_suggestions(test_id, self.test_names());
+            return Err(ApiError::invalid_request(format!("Unknown test `{test_id}`."))
+                .with_suggestions(suggestions));
+        };
+        if config.ignored {
+            return Ok(RunTestResponse { status: Some(TestStatus::Ignored), ..Default::default() });
+        }
+
+        let runner = &self.runner;
+        let func = runner.find_function(test_id).map_err(|error| {
+            ApiError::execution(format!("Failed finding test `{test_id}`: {error}"))
+        })?;
+        let result = runner
+            .run_function_with_starknet_context(
+                func,
+                vec![],
+                config.available_gas,
+                StarknetState::default(),
+            )
+            .map_err(|error| {
+                let error = match error {
+                    RunnerError::Aborted { error, .. } => *error,
+                    error => error,
+                };
+                let kind = match error {
+                    RunnerError::Cancelled => ErrorKind::Cancelled,
+                    _ => ErrorKind::Execution,
+                };
+                ApiError::new(kind, format!("Failed to run test `{test_id}`: {error}"))
+            })?;
+
+        let (panicked, values) = match result.value {
+            RunResultValue::Success(values) => (false, values),
+            RunResultValue::Panic(values) => (true, values),
+        };
+        let panic_message = panicked.then(|| panic_message::panic_message(&values));
+        // As in `cairo-test`, a panic matches an exact expectation when its data equals the
+        // expected felts, which strings are serialized to as `ByteArray`s.
+        let failure = match (&config.expectation, &panic_message) {
+            (TestExpectation::Success, Some(_)) => Some("The test panicked.".to_string()),
+            (TestExpectation::Panics(_), None) => {
+                Some("The test was expected to panic, but returned.".to_string())
+            }
+            (TestExpectation::Panics(PanicExpectation::Exact(expected)), Some(panic_message))
+                if *expected != values =>
+            {
+                Some(format!(
+                    "The test panicked with `{panic_message}`, but was expected to panic with \
+                     `{}`.",
+                    panic_message::panic_message(expected)
+                ))
+            }
+            _ => None,
+        };
+        // As in `cairo-test`, the gas used is the gas consumed from the available gas, or the gas
+        // required upfront by tests with static gas.
+        let gas_usage = match (config.available_gas, result.gas_counter) {
+            (Some(available_gas), Some(gas_counter)) => usize::try_from(gas_counter.to_biguint())
+                .ok()
+                .map(|remaining_gas| available_gas.saturating_sub(remaining_gas)),
+            _ => runner.initial_required_gas(func),
+        };
+        let (stdout, stderr) = debug_output::split(result.stdout);
+        let stderr = panic_message::with_panic_line(stderr, panic_message.as_deref());
+        Ok(RunTestResponse {
+            status: Some(if failure.is_none() { TestStatus::Passed } else { TestStatus::Failed }),
+            failure,
+            panicked,
+            values: values.iter().map(ToString::to_string).collect(),
+            assertion: panic_message.as_deref().and_then(assertion::decode),
+            panic_message,
+            stdout,
+            stderr,
+            gas_usage,
+            resources: Some(VmResources::from(&result.used_resources)),
+            reentrant_calls: reentrancy::reentrant_calls(&result.starknet_state),
+            error: None,
+        })
+    }
+}
+
+/// Runs the test named `test_id`, as listed by `list_tests`, with the program compiled in a
+/// session.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn run_test(session: u32, test_id: &str) -> String {
+    let response = error::catch_internal(|| run_test_in_session(session, test_id))
+        .and_then(|result| result)
+        .unwrap_or_else(|error| RunTestResponse { error: Some(error), ..Default::default() });
+    serde_json::to_string(&response).expect("serialize run test response")
+}
+
+/// Frees the compiled tests of a session. Returns whether the session existed.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn close_test_session(session: u32) -> bool {
+    SESSIONS.with_borrow_mut(|sessions| sessions.remove(&session).is_some())
+}
+
+fn run_test_in_session(session_id: u32, test_id: &str) -> Result<RunTestResponse, ApiError> {
+    SESSIONS.with_borrow(|sessions| {
+        let session = sessions.get(&session_id).ok_or_else(|| {
+            ApiError::invalid_request(format!("Unknown test session {session_id}."))
+        })?;
+        session.run(test_id)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{close_test_session, run_test};
+    use crate::list_tests;
+
+    #[test]
+    fn run_test_reuses_session() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn adds() {
+        assert_eq!(super::add(1, 2), 3);
+    }
+
+    #[test]
+    fn adds_wrongly() {
+        assert_eq!(super::add(1, 2), 4);
+    }
+
+    #[test]
+    #[should_panic(expected: ('u32_add Overflow',))]
+    fn overflows() {
+        super::add(0xffffffff, 1);
+    }
+
+    #[test]
+    #[ignore]
+    fn ignored() {}
+}
+"
+            },
+            "session": true
+        });
+
+        let response = list_tests(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+        assert_eq!(response_json["success"], true, "response={response}");
+        let session = response_json["session"].as_u64().unwrap() as u32;
+
+        let run = |test_id: &str| -> Value {
+            serde_json::from_str(&run_test(session, test_id)).expect("valid JSON response")
+        };
+        assert_eq!(run("test::tests::adds")["status"], "passed");
+        assert_eq!(run("test::tests::overflows")["status"], "passed");
+        assert_eq!(run("test::tests::ignored")["status"], "ignored");
+        let failed = run("test::tests::adds_wrongly");
+        assert_eq!(failed["status"], "failed", "response={failed}");
+        assert_eq!(
+            failed["assertion"]["differences"],
+            json!([{ "path": "", "left": "3", "right": "4" }])
+        );
+        assert_eq!(run("test::tests::add")["error"]["kind"], "invalid-request");
+
+        assert!(close_test_session(session));
+        assert_eq!(run("test::tests::adds")["error"]["kind"], "invalid-request");
+    }
+
+    #[test]
+    fn run_test_explains_should_panic_failures() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[should_panic(expected: \"Out of range\")]
+    fn panics_with_message() {
+        panic!(\"Out of range\");
+    }
+
+    #[test]
+    #[should_panic(expected: 'other')]
+    fn panics_differently() {
+        panic!(\"Out of range\");
+    }
+
+    #[test]
+    #[should_panic]
+    fn returns() {}
+}
+"
+            },
+            "session": true
+        });
+
+        let response = list_tests(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+        let session = response_json["session"].as_u64().unwrap() as u32;
+        let run = |test_id: &str| -> Value {
+            serde_json::from_str(&run_test(session, test_id)).expect("valid JSON response")
+        };
+
+        let passed = run("test::tests::panics_with_message");
+        assert_eq!(passed["status"], "passed", "response={passed}");
+        assert_eq!(passed["failure"], Value::Null);
+        assert_eq!(
+            run("test::tests::panics_differently")["failure"],
+            "The test panicked with `Out of range`, but was expected to panic with `other`."
+        );
+        assert_eq!(
+            run("test::tests::returns")["failure"],
+            "The test was expected to panic, but returned."
+        );
+        assert!(close_test_session(session));
+    }
+}
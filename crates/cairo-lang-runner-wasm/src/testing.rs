@@ -0,0 +1,286 @@
+//! Discovery of the `#[test]` functions of a project, compiled with the test plugin as
+//! `cairo-test` does.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::db::RootDatabase;
+use cairo_lang_compiler::diagnostics::DiagnosticsReporter;
+use cairo_lang_compiler::project::{InMemoryProject, setup_in_memory_project};
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::{
+    FreeFunctionId, FunctionWithBodyId, LanguageElementId, TopLevelLanguageElementId,
+};
+use cairo_lang_filesystem::cfg::{Cfg, CfgSet};
+use cairo_lang_filesystem::ids::CrateInput;
+use cairo_lang_semantic::items::function_with_body::FunctionWithBodySemantic;
+use cairo_lang_starknet::starknet_plugin_suite;
+use cairo_lang_test_plugin::test_config::{PanicExpectation, TestExpectation};
+use cairo_lang_test_plugin::{TestConfig, test_plugin_suite, try_extract_test_config};
+use cairo_lang_wasm_utils::error::{self, ApiError};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{corelib, limits, parse, source};
+use salsa::Database;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(all(target_arch = "wasm32", feature = "wasm-exports"))]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::test_session;
+
+/// The attribute setting the gas of a test, private to the test plugin.
+const AVAILABLE_GAS_ATTR: &str = "available_gas";
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListTestsRequest {
+    pub crate_name: String,
+    #[serde(deserialize_with = "source::deserialize_files")]
+    #[schemars(with = "BTreeMap<String, source::SourceContent>")]
+    pub files: BTreeMap<String, String>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    /// Whether to compile with the Starknet plugin, for tests of contracts.
+    #[serde(default)]
+    pub starknet: bool,
+    /// Whether to also compile the tests into a session, for running them with `run_test`.
+    #[serde(default)]
+    pub session: bool,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// A `#[test]` function.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TestCase {
+    /// The full path of the test function.
+    pub name: String,
+    /// Whether the test is marked `#[ignore]`.
+    pub ignored: bool,
+    /// Whether the test is marked `#[should_panic]`.
+    pub should_panic: bool,
+    /// The panic data the test expects, as felts, when `#[should_panic]` sets `expected`.
+    pub expected_panic: Option<Vec<String>>,
+    /// The gas available to the test, set by `#[available_gas]` or `u32::MAX` by default, `None`
+    /// for `#[available_gas(static)]`.
+    pub available_gas: Option<usize>,
+    /// The file and line where the test function starts, with its attributes.
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct ListTestsResponse {
+    pub success: bool,
+    pub tests: Vec<TestCase>,
+    /// The id of the session holding the compiled tests, when requested through `session`.
+    pub session: Option<u32>,
+    pub diagnostics: String,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+/// Lists the `#[test]` functions of a project with their attributes, without running them unless
+/// a session is requested to run them with `run_test`.
+#[cfg_attr(all(target_arch = "wasm32", feature = "wasm-exports"), wasm_bindgen)]
+pub fn list_tests(request_json: &str) -> String {
+    let request: ListTestsRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serde_json::to_string(&ListTestsResponse {
+                error: Some(error),
+                ..Default::default()
+            })
+            .expect("serialize error response");
+        }
+    };
+
+    let (result, debug_log) =
+        logging::capture(request.debug, || error::catch_internal(|| list_tests_request(request)));
+    let mut response = result
+        .unwrap_or_else(|error| ListTestsResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+
+    serde_json::to_string(&response).expect("serialize list tests response")
+}
+
+fn list_tests_request(request: ListTestsRequest) -> ListTestsResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return ListTestsResponse { error: Some(error), ..Default::default() };
+    }
+
+    let project = InMemoryProject {
+        main_crate_name: request.crate_name,
+        main_crate_files: request.files,
+        corelib_files: corelib::resolve(request.corelib_files),
+        main_crate_settings: None,
+    };
+    let (db, main_crate_inputs, diagnostics) = match prepare_test_db(&project, request.starknet) {
+        Ok(prepared) => prepared,
+        Err((error, diagnostics)) => {
+            return ListTestsResponse { diagnostics, error: Some(error), ..Default::default() };
+        }
+    };
+
+    let session = if request.session {
+        match test_session::open(&db, main_crate_inputs.clone(), request.starknet, None) {
+            Ok(session) => Some(session),
+            Err(error) => {
+                return ListTestsResponse { diagnostics, error: Some(error), ..Default::default() };
+            }
+        }
+    } else {
+        None
+    };
+
+    let db: &dyn Database = &db;
+    let tests = find_tests(db, main_crate_inputs)
+        .into_iter()
+        .map(|(func_id, config)| {
+            let (should_panic, expected_panic) = match config.expectation {
+                TestExpectation::Success => (false, None),
+                TestExpectation::Panics(PanicExpectation::Any) => (true, None),
+                TestExpectation::Panics(PanicExpectation::Exact(felts)) => {
+                    (true, Some(felts.iter().map(ToString::to_string).collect()))
+                }
+            };
+            TestCase {
+                name: func_id.full_path(db),
+                ignored: config.ignored,
+                should_panic,
+                expected_panic,
+                available_gas: config.available_gas,
+                location: function_location(db, func_id),
+            }
+        })
+        .collect();
+
+    ListTestsResponse { success: true, tests, session, diagnostics, ..Default::default() }
+}
+
+/// Prepares the database of `project` with the `test` configuration and the test plugin, and
+/// checks the diagnostics of its main crate, returning them along with the database.
+pub(crate) fn prepare_test_db(
+    project: &InMemoryProject,
+    starknet: bool,
+) -> Result<(RootDatabase, Vec<CrateInput>, String), (ApiError, String)> {
+    let mut builder = RootDatabase::builder();
+    builder
+        .with_cfg(CfgSet::from_iter([Cfg::name("test"), Cfg::kv("target", "test")]))
+        .with_default_plugin_suite(test_plugin_suite());
+    if starknet {
+        builder.with_default_plugin_suite(starknet_plugin_suite());
+    }
+    let mut db = builder
+        .build()
+        .map_err(|error| (ApiError::compilation(error.to_string()), String::new()))?;
+    let main_crate_inputs = setup_in_memory_project(&mut db, project)
+        .map_err(|error| (ApiError::invalid_request(error.to_string()), String::new()))?;
+
+    let mut diagnostics = String::new();
+    if DiagnosticsReporter::write_to_string(&mut diagnostics)
+        .with_crates(&main_crate_inputs)
+        .allow_warnings()
+        .check(&db)
+    {
+        return Err((ApiError::compilation("Compilation failed."), diagnostics));
+    }
+    Ok((db, main_crate_inputs, diagnostics))
+}
+
+/// Returns the `#[test]` functions of the crates, with their configurations, in the order of their
+/// declarations.
+pub(crate) fn find_tests<'db>(
+    db: &'db dyn Database,
+    crate_inputs: Vec<CrateInput>,
+) -> Vec<(FreeFunctionId<'db>, TestConfig)> {
+    CrateInput::into_crate_ids(db, crate_inputs)
+        .into_iter()
+        .flat_map(|crate_id| db.crate_modules(crate_id).iter().copied())
+        .flat_map(|module_id| db.module_free_functions_ids(module_id).into_iter().flatten())
+        .filter_map(|func_id| {
+            let attributes =
+                db.function_with_body_attributes(FunctionWithBodyId::Free(*func_id)).ok()?;
+            Some((*func_id, try_extract_test_config(db, attributes).ok()??))
+        })
+        .collect()
+}
+
+/// Returns whether a test function sets its gas with `#[available_gas]`, instead of running with
+/// the default gas of the test plugin.
+pub(crate) fn sets_available_gas<'db>(db: &'db dyn Database, func_id: FreeFunctionId<'db>) -> bool {
+    db.function_with_body_attributes(FunctionWithBodyId::Free(func_id)).is_ok_and(|attributes| {
+        attributes.iter().any(|attr| attr.id.long(db) == AVAILABLE_GAS_ATTR)
+    })
+}
+
+/// Returns the `file:line` location of the start of a function, including its attributes.
+fn function_location<'db>(db: &'db dyn Database, func_id: FreeFunctionId<'db>) -> Option<String> {
+    let location = func_id.stable_location(db).span_in_file(db);
+    let position = location.span.start.position_in_file(db, location.file_id)?;
+    Some(format!("{}:{}", location.file_id.full_path(db), position.line + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::list_tests;
+
+    #[test]
+    fn list_tests_reports_attributes() {
+        let request = json!({
+            "crate_name": "test",
+            "files": {
+                "lib.cairo": "\
+fn add(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn adds() {
+        assert_eq!(super::add(1, 2), 3);
+    }
+
+    #[test]
+    #[ignore]
+    #[available_gas(100000)]
+    #[should_panic(expected: ('u32_add Overflow',))]
+    fn overflows() {
+        super::add(0xffffffff, 1);
+    }
+}
+"
+            }
+        });
+
+        let response = list_tests(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(
+            response_json["tests"],
+            json!([
+                {
+                    "name": "test::tests::adds",
+                    "ignored": false,
+                    "should_panic": false,
+                    "expected_panic": null,
+                    "available_gas": 4294967295,
+                    "location": "lib.cairo:7"
+                },
+                {
+                    "name": "test::tests::overflows",
+                    "ignored": true,
+                    "should_panic": true,
+                    "expected_panic": ["155785504323917466144735657540098748279"],
+                    "available_gas": 100000,
+                    "location": "lib.cairo:12"
+                }
+            ])
+        );
+    }
+}
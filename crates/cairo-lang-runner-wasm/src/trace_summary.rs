@@ -0,0 +1,46 @@
+//! A down-sampled execution timeline, small enough to transfer to a UI instead of the full trace.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_runner::profiling::TimelineBucket;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// A range of consecutive steps of a run.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TraceBucket {
+    /// The index of the first step of the bucket.
+    pub first_step: usize,
+    pub steps: usize,
+    /// The function most steps of the bucket were spent in.
+    pub dominant_function: String,
+    /// The number of steps of the bucket spent in `dominant_function`.
+    pub dominant_function_steps: usize,
+    /// The number of invocations of libfuncs using each builtin.
+    pub builtins: BTreeMap<String, usize>,
+}
+
+/// Converts the timeline collected by the runner, naming functions by their Sierra names.
+pub(crate) fn summarize(
+    timeline: Vec<TimelineBucket>,
+    function_names: &[String],
+) -> Vec<TraceBucket> {
+    timeline
+        .into_iter()
+        .map(|bucket| {
+            let (dominant_function_idx, dominant_function_steps) = bucket
+                .function_weights
+                .iter()
+                .max_by_key(|(_, steps)| **steps)
+                .map(|(idx, steps)| (*idx, *steps))
+                .expect("Timeline buckets are never empty.");
+            TraceBucket {
+                first_step: bucket.first_step,
+                steps: bucket.steps,
+                dominant_function: function_names[dominant_function_idx].clone(),
+                dominant_function_steps,
+                builtins: bucket.builtin_invocations.into_iter().collect(),
+            }
+        })
+        .collect()
+}
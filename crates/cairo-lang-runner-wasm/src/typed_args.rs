@@ -0,0 +1,249 @@
+//! Encoding of JSON values into the arguments of a function, following its Sierra signature.
+//!
+//! Sierra types carry no member or variant names, so structs are given as arrays of their members
+//! in declaration order, and enums as `{ "variant": <index>, "value": <payload> }` objects. Strings
+//! are encoded as `ByteArray`s.
+
+use cairo_lang_runnable_utils::builder::RunnableBuilder;
+use cairo_lang_runner::Arg;
+use cairo_lang_sierra::ids::ConcreteTypeId;
+use cairo_lang_sierra::program::{Function, GenericArg};
+use cairo_lang_sierra_to_casm::invocations::enm::get_variant_selector;
+use cairo_lang_utils::byte_array::BYTES_IN_WORD;
+use cairo_lang_wasm_utils::error::ApiError;
+use num_bigint::{BigInt, Sign};
+use serde_json::Value;
+use starknet_types_core::felt::Felt as Felt252;
+
+/// Integer types, with their number of bits and whether they are signed.
+pub(crate) const INTEGER_TYPES: [(&str, u64, bool); 10] = [
+    ("u8", 8, false),
+    ("u16", 16, false),
+    ("u32", 32, false),
+    ("u64", 64, false),
+    ("u128", 128, false),
+    ("i8", 8, true),
+    ("i16", 16, true),
+    ("i32", 32, true),
+    ("i64", 64, true),
+    ("i128", 128, true),
+];
+
+/// Types other than integers represented by a single felt.
+pub(crate) const FELT_TYPES: [&str; 6] =
+    ["felt252", "bytes31", "ContractAddress", "ClassHash", "StorageAddress", "StorageBaseAddress"];
+
+/// Encodes `values`, one for each user parameter of `func`, into the arguments of a run.
+pub(crate) fn encode_args(
+    builder: &RunnableBuilder,
+    func: &Function,
+    values: &[Value],
+) -> Result<Vec<Arg>, ApiError> {
+    let param_types = func
+        .signature
+        .param_types
+        .iter()
+        .filter(|ty| builder.is_user_arg_type(&builder.type_long_id(ty).generic_id))
+        .collect::<Vec<_>>();
+    if param_types.len() != values.len() {
+        return Err(ApiError::invalid_request(format!(
+            "Function expects {} arguments, got {}.",
+            param_types.len(),
+            values.len()
+        )));
+    }
+    let mut args = vec![];
+    for (param_idx, (ty, value)) in param_types.into_iter().zip(values).enumerate() {
+        encode_value(builder, ty, value, &mut args).map_err(|error| {
+            ApiError::invalid_request(format!("Invalid argument {param_idx}: {error}"))
+        })?;
+    }
+    Ok(args)
+}
+
+/// Appends the encoding of `value` as a `ty` to `args`.
+fn encode_value(
+    builder: &RunnableBuilder,
+    ty: &ConcreteTypeId,
+    value: &Value,
+    args: &mut Vec<Arg>,
+) -> Result<(), String> {
+    let long_id = builder.type_long_id(ty);
+    let generic_id = long_id.generic_id.0.as_str();
+    match (generic_id, &long_id.generic_args[..]) {
+        ("Struct", [GenericArg::UserType(user_type), members @ ..]) => {
+            let name = user_type.debug_name.as_deref().unwrap_or_default();
+            if name == "core::integer::u256" && !value.is_array() {
+                let value = parse_integer(value)?;
+                if value.sign() == Sign::Minus || value.bits() > 256 {
+                    return Err(format!("`{value}` is out of range for `u256`."));
+                }
+                let low_mask = (BigInt::from(1) << 128) - 1;
+                args.push(Arg::Value(Felt252::from(&(&value & low_mask))));
+                args.push(Arg::Value(Felt252::from(&(value >> 128))));
+                return Ok(());
+            }
+            if name == "core::byte_array::ByteArray" && !value.is_array() {
+                let text = match value {
+                    Value::String(text) => Some(text.as_str()),
+                    Value::Object(fields)
+                        if fields.get("type").and_then(Value::as_str) == Some("bytearray") =>
+                    {
+                        fields.get("value").and_then(Value::as_str)
+                    }
+                    _ => None,
+                };
+                let text = text.ok_or_else(|| format!("Expected a string, got `{value}`."))?;
+                encode_byte_array(text, args);
+                return Ok(());
+            }
+            // A span is given as the array it wraps.
+            if name.starts_with("core::array::Span::") && value.is_array() {
+                let [GenericArg::Type(snapshot)] = members else {
+                    return Err(format!("Unexpected layout of `{name}`."));
+                };
+                return encode_value(builder, snapshot, value, args);
+            }
+            let Value::Array(values) = value else {
+                return Err(format!(
+                    "Expected an array of the members of `{name}`, got `{value}`."
+                ));
+            };
+            if values.len() != members.len() {
+                return Err(format!(
+                    "`{name}` has {} members, got {} values.",
+                    members.len(),
+                    values.len()
+                ));
+            }
+            for (member, value) in members.iter().zip(values) {
+                let GenericArg::Type(member) = member else {
+                    return Err(format!("Unexpected layout of `{name}`."));
+                };
+                encode_value(builder, member, value, args)?;
+            }
+            Ok(())
+        }
+        ("Enum", [GenericArg::UserType(user_type), variants @ ..]) => {
+            let name = user_type.debug_name.as_deref().unwrap_or_default();
+            let null = Value::Null;
+            let (variant_idx, payload) = match value {
+                Value::Bool(value) if name == "core::bool" => (usize::from(*value), &null),
+                // `Option` is given as its value, or `null` for `None`.
+                Value::Null if name.starts_with("core::option::Option::") => (1, &null),
+                _ if name.starts_with("core::option::Option::") => (0, value),
+                Value::Object(fields) => {
+                    let variant_idx = fields
+                        .get("variant")
+                        .and_then(Value::as_u64)
+                        .ok_or_else(|| format!("Expected a `variant` index for `{name}`."))?;
+                    (variant_idx as usize, fields.get("value").unwrap_or(&null))
+                }
+                _ => {
+                    return Err(format!(
+                        "Expected a `{{ \"variant\": .., \"value\": .. }}` object for `{name}`, \
+                         got `{value}`."
+                    ));
+                }
+            };
+            let Some(GenericArg::Type(variant)) = variants.get(variant_idx) else {
+                return Err(format!("`{name}` has no variant {variant_idx}."));
+            };
+            let selector = get_variant_selector(variants.len(), variant_idx)
+                .map_err(|error| format!("Failed encoding `{name}`: {error}"))?;
+            args.push(Arg::Value(Felt252::from(selector)));
+            // Smaller variants are padded with zeros before their payload.
+            let padding = builder.type_size(ty) - 1 - builder.type_size(variant);
+            args.extend((0..padding).map(|_| Arg::Value(Felt252::ZERO)));
+            encode_unit_or_value(builder, variant, payload, args)
+        }
+        ("Array", [GenericArg::Type(element)]) => {
+            let Value::Array(values) = value else {
+                return Err(format!("Expected an array, got `{value}`."));
+            };
+            let mut elements = vec![];
+            for value in values {
+                encode_value(builder, element, value, &mut elements)?;
+            }
+            args.push(Arg::Array(elements));
+            Ok(())
+        }
+        ("Snapshot" | "NonZero", [GenericArg::Type(inner)]) => {
+            encode_value(builder, inner, value, args)
+        }
+        _ => {
+            if let Some((_, bits, signed)) =
+                INTEGER_TYPES.iter().find(|(name, ..)| *name == generic_id)
+            {
+                let value = parse_integer(value)?;
+                let in_range = if *signed {
+                    let bound = BigInt::from(1) << (bits - 1);
+                    -&bound <= value && value < bound
+                } else {
+                    value.sign() != Sign::Minus && value.bits() <= *bits
+                };
+                if !in_range {
+                    return Err(format!("`{value}` is out of range for `{generic_id}`."));
+                }
+                args.push(Arg::Value(Felt252::from(&value)));
+                Ok(())
+            } else if FELT_TYPES.contains(&generic_id) {
+                args.push(Arg::Value(Felt252::from(&parse_integer(value)?)));
+                Ok(())
+            } else {
+                Err(format!("Arguments of type `{generic_id}` are not supported."))
+            }
+        }
+    }
+}
+
+/// Appends the encoding of `text` as a `ByteArray` to `args`: its full words of 31 bytes, followed
+/// by the remaining bytes as the pending word and their count.
+pub(crate) fn encode_byte_array(text: &str, args: &mut Vec<Arg>) {
+    let mut words = text.as_bytes().chunks_exact(BYTES_IN_WORD);
+    args.push(Arg::Array(
+        words.by_ref().map(|word| Arg::Value(Felt252::from_bytes_be_slice(word))).collect(),
+    ));
+    let pending_word = words.remainder();
+    args.push(Arg::Value(Felt252::from_bytes_be_slice(pending_word)));
+    args.push(Arg::Value(Felt252::from(pending_word.len())));
+}
+
+/// Encodes the payload of an enum variant, where `null` stands for the unit type.
+fn encode_unit_or_value(
+    builder: &RunnableBuilder,
+    ty: &ConcreteTypeId,
+    value: &Value,
+    args: &mut Vec<Arg>,
+) -> Result<(), String> {
+    if value.is_null() && builder.type_size(ty) == 0 {
+        return Ok(());
+    }
+    encode_value(builder, ty, value, args)
+}
+
+/// Parses an integer from a JSON number, or a decimal or `0x`-prefixed hex string, possibly
+/// negative.
+fn parse_integer(value: &Value) -> Result<BigInt, String> {
+    let invalid = || format!("Expected an integer, got `{value}`.");
+    match value {
+        Value::Number(number) => number
+            .as_i64()
+            .map(BigInt::from)
+            .or_else(|| number.as_u64().map(BigInt::from))
+            .ok_or_else(invalid),
+        Value::String(string) => {
+            let (negative, digits) = match string.strip_prefix('-') {
+                Some(digits) => (true, digits),
+                None => (false, string.as_str()),
+            };
+            let magnitude = match digits.strip_prefix("0x") {
+                Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16),
+                None => BigInt::parse_bytes(digits.as_bytes(), 10),
+            }
+            .ok_or_else(invalid)?;
+            Ok(if negative { -magnitude } else { magnitude })
+        }
+        _ => Err(invalid()),
+    }
+}
@@ -0,0 +1,395 @@
+//! Decoding of the return values of a function into JSON values, following its Sierra signature.
+//!
+//! The decoding mirrors the encoding of [crate::typed_args]: structs are decoded to arrays of their
+//! members, enums to `{ "variant": <index>, "value": <payload> }` objects, and `ByteArray`s to
+//! strings. Integers are given as decimal strings, as they may exceed the safe integers of
+//! JavaScript.
+
+use cairo_lang_runnable_utils::builder::RunnableBuilder;
+use cairo_lang_runner::short_string::as_cairo_short_string;
+use cairo_lang_sierra::ids::ConcreteTypeId;
+use cairo_lang_sierra::program::{Function, GenericArg};
+use cairo_lang_sierra_to_casm::invocations::enm::get_variant_selector;
+use cairo_lang_utils::byte_array::BYTES_IN_WORD;
+use num_bigint::BigInt;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use starknet_types_core::felt::Felt as Felt252;
+
+use crate::typed_args::{FELT_TYPES, INTEGER_TYPES};
+
+/// The variant of an `Option` or a `Result` returned by a function.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ReturnVariant {
+    /// `Some`, `None`, `Ok` or `Err`.
+    pub variant: String,
+    /// The payload of the variant, decoded as in `decoded_values`, `null` for `None`.
+    pub value: Value,
+    /// The payload of an `Err` holding a felt, such as an error code, decoded as a short string.
+    pub error_message: Option<String>,
+}
+
+/// Decodes the `values` returned by a successful run of `func` into a JSON value.
+///
+/// `memory` holds the relocated memory of the run, which arrays and boxes point into. A function
+/// returning several values is decoded as an array of them.
+pub(crate) fn decode_return_values(
+    builder: &RunnableBuilder,
+    func: &Function,
+    values: &[Felt252],
+    memory: &[Option<Felt252>],
+) -> Result<Value, String> {
+    let mut return_types = func
+        .signature
+        .ret_types
+        .iter()
+        .filter(|ty| builder.is_user_arg_type(&builder.type_long_id(ty).generic_id));
+    let (Some(ty), None) = (return_types.next(), return_types.next()) else {
+        return Ok(Value::Null);
+    };
+    let long_id = builder.type_long_id(ty);
+    // A function that may panic returns a `PanicResult`, whose values on success are those of its
+    // first variant: a tuple of the actual return value.
+    if let [GenericArg::UserType(user_type), GenericArg::Type(ok_type), ..] =
+        &long_id.generic_args[..]
+        && user_type
+            .debug_name
+            .as_deref()
+            .is_some_and(|name| name.starts_with("core::panics::PanicResult::"))
+    {
+        return match decode_value(builder, ok_type, values, memory)? {
+            Value::Array(mut values) if values.len() == 1 => Ok(values.remove(0)),
+            value => Ok(value),
+        };
+    }
+    decode_value(builder, ty, values, memory)
+}
+
+/// Renders the `values` returned by a successful run of `func` as `<type>: <value>` strings, such
+/// as `u256: 340282366920938463463374607431768211456`: one for each member of a returned tuple, or
+/// one for another return value.
+pub(crate) fn pretty_return_values(
+    builder: &RunnableBuilder,
+    func: &Function,
+    values: &[Felt252],
+    memory: &[Option<Felt252>],
+) -> Result<Vec<String>, String> {
+    let Some(ty) = return_type(builder, func) else {
+        return Ok(vec![]);
+    };
+    let members = match tuple_members(builder, &ty) {
+        members if !members.is_empty() => members,
+        _ => vec![ty],
+    };
+    let mut offset = 0;
+    let mut pretty = vec![];
+    for member in members {
+        let size = builder.type_size(&member) as usize;
+        let member_values =
+            values.get(offset..offset + size).ok_or("Unexpected layout of the return value.")?;
+        let value = decode_value(builder, &member, member_values, memory)?;
+        let type_name = type_name(&member);
+        let rendered = match &value {
+            Value::String(text) if type_name == "ByteArray" => format!("{text:?}"),
+            Value::Null if type_name.starts_with("Option<") => "None".into(),
+            Value::Null => "()".into(),
+            value => render(value),
+        };
+        pretty.push(format!("{type_name}: {rendered}"));
+        offset += size;
+    }
+    Ok(pretty)
+}
+
+/// Returns the variant of the `Option` or `Result` returned by a successful run of `func`, with its
+/// decoded payload, or `None` if `func` returns another type.
+pub(crate) fn return_variant(
+    builder: &RunnableBuilder,
+    func: &Function,
+    values: &[Felt252],
+    memory: &[Option<Felt252>],
+) -> Result<Option<ReturnVariant>, String> {
+    let Some(ty) = return_type(builder, func) else {
+        return Ok(None);
+    };
+    let long_id = builder.type_long_id(&ty);
+    let ("Enum", [GenericArg::UserType(user_type), variants @ ..]) =
+        (long_id.generic_id.0.as_str(), &long_id.generic_args[..])
+    else {
+        return Ok(None);
+    };
+    let name = user_type.debug_name.as_deref().unwrap_or_default();
+    let variant_names = if name.starts_with("core::option::Option::") {
+        ["Some", "None"]
+    } else if name.starts_with("core::result::Result::") {
+        ["Ok", "Err"]
+    } else {
+        return Ok(None);
+    };
+    let selector = values.first().ok_or_else(|| format!("Missing selector of `{name}`."))?;
+    let selector = to_usize(selector)?;
+    let variant_idx = (0..variants.len())
+        .find(|idx| get_variant_selector(variants.len(), *idx).ok() == Some(selector))
+        .ok_or_else(|| format!("Invalid selector {selector} of `{name}`."))?;
+    let GenericArg::Type(variant) = &variants[variant_idx] else {
+        return Err(format!("Unexpected layout of `{name}`."));
+    };
+    // Smaller variants are padded with zeros before their payload.
+    let payload_values = &values[values.len() - builder.type_size(variant) as usize..];
+    let value = decode_value(builder, variant, payload_values, memory)?;
+    let error_message = match payload_values {
+        [error]
+            if variant_names[variant_idx] == "Err"
+                && FELT_TYPES.contains(&builder.type_long_id(variant).generic_id.0.as_str()) =>
+        {
+            as_cairo_short_string(error).filter(|text| !text.is_empty())
+        }
+        _ => None,
+    };
+    Ok(Some(ReturnVariant { variant: variant_names[variant_idx].into(), value, error_message }))
+}
+
+/// Returns the type of the value returned by `func`, unwrapped from its `PanicResult`, if it
+/// returns one.
+fn return_type(builder: &RunnableBuilder, func: &Function) -> Option<ConcreteTypeId> {
+    let mut return_types = func
+        .signature
+        .ret_types
+        .iter()
+        .filter(|ty| builder.is_user_arg_type(&builder.type_long_id(ty).generic_id));
+    let (Some(ty), None) = (return_types.next(), return_types.next()) else {
+        return None;
+    };
+    // The values of a `PanicResult` on success are those of its first variant: a tuple of the
+    // actual return value.
+    match &builder.type_long_id(ty).generic_args[..] {
+        [GenericArg::UserType(user_type), GenericArg::Type(ok_type), ..]
+            if user_type
+                .debug_name
+                .as_deref()
+                .is_some_and(|name| name.starts_with("core::panics::PanicResult::")) =>
+        {
+            match &tuple_members(builder, ok_type)[..] {
+                [value_type] => Some(value_type.clone()),
+                _ => Some(ok_type.clone()),
+            }
+        }
+        _ => Some(ty.clone()),
+    }
+}
+
+/// Returns the member types of `ty` if it is a tuple, and none otherwise.
+fn tuple_members(builder: &RunnableBuilder, ty: &ConcreteTypeId) -> Vec<ConcreteTypeId> {
+    let long_id = builder.type_long_id(ty);
+    match (long_id.generic_id.0.as_str(), &long_id.generic_args[..]) {
+        ("Struct", [GenericArg::UserType(user_type), members @ ..])
+            if user_type.debug_name.as_deref() == Some("Tuple") =>
+        {
+            members
+                .iter()
+                .filter_map(|member| match member {
+                    GenericArg::Type(member) => Some(member.clone()),
+                    _ => None,
+                })
+                .collect()
+        }
+        _ => vec![],
+    }
+}
+
+/// Returns the name of `ty` without module paths, such as `Array<u32>` for
+/// `core::array::Array::<core::integer::u32>`.
+fn type_name(ty: &ConcreteTypeId) -> String {
+    let Some(name) = ty.debug_name.as_deref() else {
+        return ty.to_string();
+    };
+    let mut short_name = String::new();
+    let mut path = String::new();
+    for c in name.replace("::<", "<").chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+        } else {
+            short_name.push_str(path.rsplit("::").next().unwrap_or_default());
+            path.clear();
+            short_name.push(c);
+        }
+    }
+    short_name.push_str(path.rsplit("::").next().unwrap_or_default());
+    short_name
+}
+
+/// Renders a decoded value without the quotes of its JSON strings.
+fn render(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(values) => {
+            format!("[{}]", values.iter().map(render).collect::<Vec<_>>().join(", "))
+        }
+        Value::Object(variant) => match (variant.get("variant"), variant.get("value")) {
+            (Some(index), Some(Value::Null)) => format!("variant {index}"),
+            (Some(index), Some(value)) => format!("variant {index}({})", render(value)),
+            _ => value.to_string(),
+        },
+        value => value.to_string(),
+    }
+}
+
+/// Returns the output of a successful run of the wrapper of an `#[executable]` function: the
+/// serialization of the return value of the function, which the wrapper returns as an array.
+pub(crate) fn executable_output(
+    values: &[Felt252],
+    memory: &[Option<Felt252>],
+) -> Result<Vec<Felt252>, String> {
+    let [start, end] = values else {
+        return Err("Unexpected layout of the output of an executable.".into());
+    };
+    read_range(memory, start, end)
+}
+
+/// Decodes the `values` of a `ty`, whose length is the size of `ty`.
+fn decode_value(
+    builder: &RunnableBuilder,
+    ty: &ConcreteTypeId,
+    values: &[Felt252],
+    memory: &[Option<Felt252>],
+) -> Result<Value, String> {
+    let long_id = builder.type_long_id(ty);
+    let generic_id = long_id.generic_id.0.as_str();
+    match (generic_id, &long_id.generic_args[..]) {
+        ("Struct", [GenericArg::UserType(user_type), members @ ..]) => {
+            let name = user_type.debug_name.as_deref().unwrap_or_default();
+            if name == "core::integer::u256"
+                && let [low, high] = values
+            {
+                let value = (high.to_bigint() << 128) + low.to_bigint();
+                return Ok(Value::String(value.to_string()));
+            }
+            if name == "core::byte_array::ByteArray"
+                && let [data_start, data_end, pending_word, pending_word_len] = values
+            {
+                let words = read_range(memory, data_start, data_end)?;
+                let pending_word_len = to_usize(pending_word_len)?;
+                let mut bytes = vec![];
+                for word in &words {
+                    bytes.extend_from_slice(&word.to_bytes_be()[32 - BYTES_IN_WORD..]);
+                }
+                if pending_word_len > BYTES_IN_WORD {
+                    return Err(format!("Invalid pending word length {pending_word_len}."));
+                }
+                bytes.extend_from_slice(&pending_word.to_bytes_be()[32 - pending_word_len..]);
+                return Ok(Value::String(String::from_utf8_lossy(&bytes).into_owned()));
+            }
+            // A span is decoded as the array it wraps.
+            if name.starts_with("core::array::Span::")
+                && let [GenericArg::Type(snapshot)] = members
+            {
+                return decode_value(builder, snapshot, values, memory);
+            }
+            if members.is_empty() {
+                return Ok(Value::Null);
+            }
+            let mut offset = 0;
+            let mut decoded = vec![];
+            for member in members {
+                let GenericArg::Type(member) = member else {
+                    return Err(format!("Unexpected layout of `{name}`."));
+                };
+                let size = builder.type_size(member) as usize;
+                let member_values = values
+                    .get(offset..offset + size)
+                    .ok_or_else(|| format!("Unexpected layout of `{name}`."))?;
+                decoded.push(decode_value(builder, member, member_values, memory)?);
+                offset += size;
+            }
+            Ok(Value::Array(decoded))
+        }
+        ("Enum", [GenericArg::UserType(user_type), variants @ ..]) => {
+            let name = user_type.debug_name.as_deref().unwrap_or_default();
+            let selector =
+                values.first().ok_or_else(|| format!("Missing selector of `{name}`."))?;
+            let selector = to_usize(selector)?;
+            let variant_idx = (0..variants.len())
+                .find(|idx| get_variant_selector(variants.len(), *idx).ok() == Some(selector))
+                .ok_or_else(|| format!("Invalid selector {selector} of `{name}`."))?;
+            let GenericArg::Type(variant) = &variants[variant_idx] else {
+                return Err(format!("Unexpected layout of `{name}`."));
+            };
+            // Smaller variants are padded with zeros before their payload.
+            let payload_size = builder.type_size(variant) as usize;
+            let payload =
+                decode_value(builder, variant, &values[values.len() - payload_size..], memory)?;
+            if name == "core::bool" {
+                Ok(Value::Bool(variant_idx == 1))
+            } else if name.starts_with("core::option::Option::") {
+                Ok(if variant_idx == 0 { payload } else { Value::Null })
+            } else {
+                Ok(serde_json::json!({ "variant": variant_idx, "value": payload }))
+            }
+        }
+        ("Array", [GenericArg::Type(element)]) => {
+            let [start, end] = values else {
+                return Err("Unexpected layout of an array.".into());
+            };
+            let cells = read_range(memory, start, end)?;
+            let size = builder.type_size(element) as usize;
+            if size == 0 {
+                return Err("Arrays of zero-sized types are not supported.".into());
+            }
+            Ok(Value::Array(
+                cells
+                    .chunks(size)
+                    .map(|element_values| decode_value(builder, element, element_values, memory))
+                    .collect::<Result<_, _>>()?,
+            ))
+        }
+        ("Box", [GenericArg::Type(inner)]) => {
+            let [address] = values else {
+                return Err("Unexpected layout of a box.".into());
+            };
+            let start = to_usize(address)?;
+            let end = Felt252::from(start + builder.type_size(inner) as usize);
+            let cells = read_range(memory, address, &end)?;
+            decode_value(builder, inner, &cells, memory)
+        }
+        ("Snapshot" | "NonZero", [GenericArg::Type(inner)]) => {
+            decode_value(builder, inner, values, memory)
+        }
+        _ => {
+            let [value] = values else {
+                return Err(format!("Return values of type `{generic_id}` are not supported."));
+            };
+            if let Some((_, bits, signed)) =
+                INTEGER_TYPES.iter().find(|(name, ..)| *name == generic_id)
+            {
+                let mut value = value.to_bigint();
+                // Negative values are represented as their sum with the prime.
+                if *signed && value.bits() > *bits {
+                    value -= Felt252::MAX.to_bigint() + BigInt::from(1);
+                }
+                Ok(Value::String(value.to_string()))
+            } else if FELT_TYPES.contains(&generic_id) {
+                Ok(Value::String(value.to_string()))
+            } else {
+                Err(format!("Return values of type `{generic_id}` are not supported."))
+            }
+        }
+    }
+}
+
+/// Reads the cells of `memory` from `start` to `end`.
+fn read_range(
+    memory: &[Option<Felt252>],
+    start: &Felt252,
+    end: &Felt252,
+) -> Result<Vec<Felt252>, String> {
+    let (start, end) = (to_usize(start)?, to_usize(end)?);
+    memory
+        .get(start..end)
+        .and_then(|cells| cells.iter().copied().collect())
+        .ok_or_else(|| format!("Failed reading memory from {start} to {end}."))
+}
+
+fn to_usize(value: &Felt252) -> Result<usize, String> {
+    value.to_bigint().try_into().map_err(|_| format!("`{value}` is not a valid size or address."))
+}
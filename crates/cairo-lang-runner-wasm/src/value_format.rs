@@ -0,0 +1,37 @@
+//! Representations of the returned felts beyond decimal, for clients without a felt library.
+
+use cairo_lang_runner::short_string::as_cairo_short_string;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use starknet_types_core::felt::Felt as Felt252;
+
+/// The representations of the returned felts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueFormat {
+    /// Only the decimal `values`.
+    #[default]
+    Decimal,
+    /// The decimal `values`, along with `formatted_values`.
+    All,
+}
+
+/// A returned felt in several representations.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FormattedValue {
+    pub decimal: String,
+    /// The `0x`-prefixed hex representation.
+    pub hex: String,
+    /// The text of the felt as a short string, if it is a non-empty printable one.
+    pub short_string: Option<String>,
+}
+
+impl FormattedValue {
+    pub(crate) fn new(value: &Felt252) -> Self {
+        Self {
+            decimal: value.to_string(),
+            hex: format!("{:#x}", value.to_biguint()),
+            short_string: as_cairo_short_string(value).filter(|text| !text.is_empty()),
+        }
+    }
+}
@@ -6,6 +6,8 @@ use std::sync::Arc;
 use std::vec::IntoIter;
 
 use ark_ff::{BigInteger, PrimeField};
+use ark_secp256k1 as secp256k1;
+use ark_secp256r1 as secp256r1;
 use cairo_lang_casm::hints::{CoreHint, DeprecatedHint, ExternalHint, Hint, StarknetHint};
 use cairo_lang_casm::operand::{
     BinOpOperand, CellRef, DerefOrImmediate, Operation, Register, ResOperand,
@@ -42,7 +44,6 @@ use num_integer::{ExtendedGcd, Integer};
 use num_traits::{Signed, ToPrimitive, Zero};
 use rand::Rng;
 use starknet_types_core::felt::{Felt as Felt252, NonZeroFelt};
-use {ark_secp256k1 as secp256k1, ark_secp256r1 as secp256r1};
 
 use self::contract_address::calculate_contract_address;
 use self::dict_manager::DictSquashExecScope;
@@ -110,6 +111,10 @@ pub struct CairoHintProcessor<'a> {
     pub panic_traceback: Vec<(Relocatable, Relocatable)>,
     /// Captured stdout written by debug print hints during execution.
     pub captured_stdout: String,
+    /// The number of bytes of stdout after which it stops being captured, if limited.
+    pub max_stdout_bytes: Option<usize>,
+    /// Whether the captured stdout was truncated to `max_stdout_bytes`.
+    pub stdout_truncated: bool,
 }
 
 pub fn cell_ref_to_relocatable(cell_ref: &CellRef, vm: &VirtualMachine) -> Relocatable {
@@ -144,10 +149,21 @@ pub struct StarknetState {
     deployed_contracts: HashMap<Felt252, Felt252>,
     /// A mapping from contract address to logs.
     logs: HashMap<Felt252, ContractLogs>,
+    /// The events emitted by contracts, in the order they were emitted. Unlike `logs`, not
+    /// consumed by `pop_log`.
+    emitted_events: Vec<EmittedEvent>,
+    /// The messages sent to L1 by contracts, in the order they were sent. Unlike `logs`, not
+    /// consumed by `pop_l2_to_l1_message`.
+    sent_l2_to_l1_messages: Vec<SentL2ToL1Message>,
     /// The simulated execution info.
     exec_info: ExecutionInfo,
     /// A mock history, mapping block number to the class hash.
     block_hash: HashMap<u64, Felt252>,
+    /// The calls to contract entry points in progress, the outermost first.
+    call_stack: Vec<ContractCall>,
+    /// The chains of calls re-entering a contract, each from the call entering the contract to
+    /// the call re-entering it. Kept when the calls are reverted.
+    reentrant_calls: Vec<Vec<ContractCall>>,
 }
 impl StarknetState {
     /// Replaces the addresses in the context.
@@ -170,6 +186,103 @@ impl StarknetState {
         self.exec_info.contract_address = old_contract_address;
         self.exec_info.caller_address = old_caller_address;
     }
+
+    /// Sets the value of a storage address of a contract.
+    pub fn set_storage(&mut self, contract_address: Felt252, address: Felt252, value: Felt252) {
+        self.storage.entry(contract_address).or_default().insert(address, value);
+    }
+
+    /// Sets the block number returned by `get_execution_info`.
+    pub fn set_block_number(&mut self, block_number: Felt252) {
+        self.exec_info.block_info.block_number = block_number;
+    }
+
+    /// Sets the block timestamp returned by `get_execution_info`.
+    pub fn set_block_timestamp(&mut self, block_timestamp: Felt252) {
+        self.exec_info.block_info.block_timestamp = block_timestamp;
+    }
+
+    /// Sets the sequencer address returned by `get_execution_info`.
+    pub fn set_sequencer_address(&mut self, sequencer_address: Felt252) {
+        self.exec_info.block_info.sequencer_address = sequencer_address;
+    }
+
+    /// Sets the chain id of the transaction returned by `get_execution_info`.
+    pub fn set_chain_id(&mut self, chain_id: Felt252) {
+        self.exec_info.tx_info.chain_id = chain_id;
+    }
+
+    /// Returns the addresses and values set in the storage of a contract.
+    pub fn contract_storage(
+        &self,
+        contract_address: Felt252,
+    ) -> impl Iterator<Item = (&Felt252, &Felt252)> {
+        self.storage.get(&contract_address).into_iter().flatten()
+    }
+
+    /// Returns the events emitted by contracts, in the order they were emitted.
+    pub fn emitted_events(&self) -> &[EmittedEvent] {
+        &self.emitted_events
+    }
+
+    /// Returns the messages sent to L1 by contracts, in the order they were sent.
+    pub fn sent_l2_to_l1_messages(&self) -> &[SentL2ToL1Message] {
+        &self.sent_l2_to_l1_messages
+    }
+
+    /// Returns the chains of calls re-entering a contract, such as `A -> B -> A`, each from the
+    /// call entering the contract to the call re-entering it.
+    pub fn reentrant_calls(&self) -> &[Vec<ContractCall>] {
+        &self.reentrant_calls
+    }
+
+    /// Enters a call to a contract entry point, recording the chain of calls if it re-enters a
+    /// contract being called.
+    fn enter_call(&mut self, call: ContractCall) {
+        if let Some(first) = self
+            .call_stack
+            .iter()
+            .position(|entered| entered.contract_address == call.contract_address)
+        {
+            let mut chain = self.call_stack[first..].to_vec();
+            chain.push(call.clone());
+            self.reentrant_calls.push(chain);
+        }
+        self.call_stack.push(call);
+    }
+
+    /// Exits the innermost call to a contract entry point.
+    fn exit_call(&mut self) {
+        self.call_stack.pop();
+    }
+}
+
+/// A call to an entry point of a contract.
+#[derive(Clone, Debug)]
+pub struct ContractCall {
+    /// The address of the called contract.
+    pub contract_address: Felt252,
+    /// The function of the entry point.
+    pub entry_point: FunctionId,
+}
+
+/// An event emitted by a contract.
+#[derive(Clone, Debug)]
+pub struct EmittedEvent {
+    /// The address of the emitting contract.
+    pub from_address: Felt252,
+    pub keys: Vec<Felt252>,
+    pub data: Vec<Felt252>,
+}
+
+/// A message sent to L1 by a contract.
+#[derive(Clone, Debug)]
+pub struct SentL2ToL1Message {
+    /// The address of the sending contract.
+    pub from_address: Felt252,
+    /// The L1 address of the recipient.
+    pub to_address: Felt252,
+    pub payload: Vec<Felt252>,
 }
 
 /// Object storing logs for a contract.
@@ -379,6 +492,40 @@ mod gas_costs {
     pub const STORAGE_WRITE: usize = 50 * STEP;
 }
 
+/// Returns the gas deducted by a call of the syscall `selector`, such as `StorageRead`, on top of
+/// the gas charged by the compiler, or `None` for unsupported syscalls.
+///
+/// The rounds of `Keccak` are charged separately, and the gas of the entry points run by
+/// `CallContract`, `LibraryCall` and `Deploy` is deducted as they run.
+pub fn syscall_gas_cost(selector: &str) -> Option<usize> {
+    Some(match selector {
+        "StorageWrite" => gas_costs::STORAGE_WRITE,
+        "StorageRead" => gas_costs::STORAGE_READ,
+        "GetBlockHash" => gas_costs::GET_BLOCK_HASH,
+        "GetExecutionInfo" => gas_costs::GET_EXECUTION_INFO,
+        "EmitEvent" => gas_costs::EMIT_EVENT,
+        "SendMessageToL1" => gas_costs::SEND_MESSAGE_TO_L1,
+        "Keccak" => gas_costs::KECCAK,
+        "Sha256ProcessBlock" => gas_costs::SHA256_PROCESS_BLOCK,
+        "Secp256k1New" => gas_costs::SECP256K1_NEW,
+        "Secp256k1Add" => gas_costs::SECP256K1_ADD,
+        "Secp256k1Mul" => gas_costs::SECP256K1_MUL,
+        "Secp256k1GetPointFromX" => gas_costs::SECP256K1_GET_POINT_FROM_X,
+        "Secp256k1GetXy" => gas_costs::SECP256K1_GET_XY,
+        "Secp256r1New" => gas_costs::SECP256R1_NEW,
+        "Secp256r1Add" => gas_costs::SECP256R1_ADD,
+        "Secp256r1Mul" => gas_costs::SECP256R1_MUL,
+        "Secp256r1GetPointFromX" => gas_costs::SECP256R1_GET_POINT_FROM_X,
+        "Secp256r1GetXy" => gas_costs::SECP256R1_GET_XY,
+        "Deploy" => gas_costs::DEPLOY,
+        "CallContract" => gas_costs::CALL_CONTRACT,
+        "LibraryCall" => gas_costs::LIBRARY_CALL,
+        "ReplaceClass" => gas_costs::REPLACE_CLASS,
+        "GetClassHashAt" => gas_costs::GET_CLASS_HASH_AT,
+        _ => return None,
+    })
+}
+
 /// Deducts gas from the given gas counter or fails the syscall if there is not enough gas.
 macro_rules! deduct_gas {
     ($gas:ident, $amount:ident) => {
@@ -431,13 +578,20 @@ impl HintProcessorLogic for CairoHintProcessor<'_> {
         let hint = match hint {
             Hint::Starknet(hint) => hint,
             Hint::Core(core_hint_base) => {
-                return execute_core_hint_base(
+                let stdout_len = self.captured_stdout.len();
+                let result = execute_core_hint_base(
                     vm,
                     exec_scopes,
                     core_hint_base,
                     self.no_temporary_segments,
                     &mut self.captured_stdout,
                 );
+                if self.stdout_truncated {
+                    self.captured_stdout.truncate(stdout_len);
+                } else {
+                    self.limit_stdout();
+                }
+                return result;
             }
             Hint::External(hint) => {
                 return self.execute_external_hint(vm, hint);
@@ -507,6 +661,10 @@ pub trait StarknetHintProcessor: HintProcessor {
     fn take_stdout(&mut self) -> String {
         String::new()
     }
+    /// Whether the captured stdout was truncated to its size limit.
+    fn stdout_truncated(&self) -> bool {
+        false
+    }
 }
 
 impl StarknetHintProcessor for CairoHintProcessor<'_> {
@@ -521,6 +679,10 @@ impl StarknetHintProcessor for CairoHintProcessor<'_> {
     fn take_stdout(&mut self) -> String {
         std::mem::take(&mut self.captured_stdout)
     }
+
+    fn stdout_truncated(&self) -> bool {
+        self.stdout_truncated
+    }
 }
 
 /// Wrapper trait for a VM owner.
@@ -993,6 +1155,11 @@ impl CairoHintProcessor<'_> {
     ) -> Result<SyscallResult, HintError> {
         deduct_gas!(gas_counter, EMIT_EVENT);
         let contract = self.starknet_state.exec_info.contract_address;
+        self.starknet_state.emitted_events.push(EmittedEvent {
+            from_address: contract,
+            keys: keys.clone(),
+            data: data.clone(),
+        });
         self.starknet_state.logs.entry(contract).or_default().events.push_back((keys, data));
         Ok(SyscallResult::Success(vec![]))
     }
@@ -1006,6 +1173,11 @@ impl CairoHintProcessor<'_> {
     ) -> Result<SyscallResult, HintError> {
         deduct_gas!(gas_counter, SEND_MESSAGE_TO_L1);
         let contract = self.starknet_state.exec_info.contract_address;
+        self.starknet_state.sent_l2_to_l1_messages.push(SentL2ToL1Message {
+            from_address: contract,
+            to_address,
+            payload: payload.clone(),
+        });
         self.starknet_state
             .logs
             .entry(contract)
@@ -1062,7 +1234,12 @@ impl CairoHintProcessor<'_> {
             let old_addrs = self
                 .starknet_state
                 .open_caller_context((deployed_contract_address, deployer_address));
+            self.starknet_state.enter_call(ContractCall {
+                contract_address: deployed_contract_address,
+                entry_point: constructor.clone(),
+            });
             let res = self.call_entry_point(gas_counter, runner, constructor, calldata, vm);
+            self.starknet_state.exit_call();
             self.starknet_state.close_caller_context(old_addrs);
             match res {
                 Ok(value) => value,
@@ -1119,7 +1296,10 @@ impl CairoHintProcessor<'_> {
             contract_address,
             self.starknet_state.exec_info.contract_address,
         ));
+        self.starknet_state
+            .enter_call(ContractCall { contract_address, entry_point: entry_point.clone() });
         let res = self.call_entry_point(gas_counter, runner, entry_point, calldata, vm);
+        self.starknet_state.exit_call();
         self.starknet_state.close_caller_context(old_addrs);
 
         match res {
@@ -1224,7 +1404,11 @@ impl CairoHintProcessor<'_> {
                 self.starknet_state.clone(),
             )
             .expect("Internal runner error.");
-        self.captured_stdout.push_str(&res.stdout);
+        if !self.stdout_truncated {
+            self.captured_stdout.push_str(&res.stdout);
+            self.stdout_truncated = res.stdout_truncated;
+            self.limit_stdout();
+        }
         self.syscalls_used_resources += res.used_resources;
         *gas_counter = res.gas_counter.unwrap().to_usize().unwrap();
         match res.value {
@@ -1233,8 +1417,26 @@ impl CairoHintProcessor<'_> {
                 Ok(segment_with_data(vm, read_array_result_as_vec(&res.memory, &value).into_iter())
                     .expect("failed to allocate segment"))
             }
-            RunResultValue::Panic(panic_data) => Err(panic_data),
+            RunResultValue::Panic(panic_data) => {
+                self.starknet_state.reentrant_calls = res.starknet_state.reentrant_calls;
+                Err(panic_data)
+            }
+        }
+    }
+
+    /// Truncates the captured stdout to `max_stdout_bytes`, at a character boundary, once it
+    /// exceeds it. Nothing more is captured afterwards.
+    fn limit_stdout(&mut self) {
+        let Some(max_stdout_bytes) = self.max_stdout_bytes else { return };
+        if self.captured_stdout.len() <= max_stdout_bytes {
+            return;
+        }
+        let mut end = max_stdout_bytes;
+        while !self.captured_stdout.is_char_boundary(end) {
+            end -= 1;
         }
+        self.captured_stdout.truncate(end);
+        self.stdout_truncated = true;
     }
 
     /// Executes a cheatcode.
@@ -2305,7 +2507,7 @@ pub fn read_felts(
 }
 
 /// Reads the result of a function call that returns `Array<felt252>`.
-fn read_array_result_as_vec(memory: &[Option<Felt252>], value: &[Felt252]) -> Vec<Felt252> {
+pub fn read_array_result_as_vec(memory: &[Option<Felt252>], value: &[Felt252]) -> Vec<Felt252> {
     // TODO(spapini): Handle failures.
     let [res_start, res_end] = value else {
         panic!("Unexpected return value from contract call");
@@ -2342,18 +2544,107 @@ pub fn extract_buffer(buffer: &ResOperand) -> (&CellRef, Felt252) {
     (cell, base_offset)
 }
 
+/// A check of a run in progress, made every `interval` steps.
+pub struct RunCheck<'a> {
+    /// The number of steps between two checks.
+    pub interval: usize,
+    /// Returns whether the run should go on, given the VM between two steps.
+    pub check: &'a mut dyn FnMut(&VirtualMachine) -> bool,
+}
+
+/// A hint processor stopping the run after `remaining` steps, on top of the limits of `inner`.
+pub(crate) struct StepBudget<'a> {
+    pub(crate) inner: &'a mut dyn HintProcessor,
+    pub(crate) remaining: usize,
+}
+
+impl HintProcessorLogic for StepBudget<'_> {
+    fn execute_hint(
+        &mut self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data: &Box<dyn Any>,
+    ) -> Result<(), HintError> {
+        self.inner.execute_hint(vm, exec_scopes, hint_data)
+    }
+
+    fn compile_hint(
+        &self,
+        hint_code: &str,
+        ap_tracking_data: &ApTracking,
+        reference_ids: &HashMap<String, usize>,
+        references: &[HintReference],
+        accessible_scopes: &[String],
+        constants: Arc<HashMap<String, Felt252>>,
+    ) -> Result<Box<dyn Any>, VirtualMachineError> {
+        self.inner.compile_hint(
+            hint_code,
+            ap_tracking_data,
+            reference_ids,
+            references,
+            accessible_scopes,
+            constants,
+        )
+    }
+}
+
+impl ResourceTracker for StepBudget<'_> {
+    fn consumed(&self) -> bool {
+        self.remaining == 0 || self.inner.consumed()
+    }
+
+    fn consume_step(&mut self) {
+        self.remaining = self.remaining.saturating_sub(1);
+        self.inner.consume_step()
+    }
+
+    fn get_n_steps(&self) -> Option<usize> {
+        self.inner.get_n_steps()
+    }
+
+    fn run_resources(&self) -> &RunResources {
+        self.inner.run_resources()
+    }
+}
+
+/// Runs until `end` in spans of `run_check.interval` steps, stopping the run with the error of the
+/// unfinished span once the check between two spans fails.
+fn run_until_pc_with_check(
+    runner: &mut CairoRunner,
+    end: Relocatable,
+    hint_processor: &mut dyn HintProcessor,
+    run_check: RunCheck<'_>,
+) -> Result<(), VirtualMachineError> {
+    loop {
+        let mut budget = StepBudget { inner: &mut *hint_processor, remaining: run_check.interval };
+        match runner.run_until_pc(end, &mut budget) {
+            Err(error) if budget.remaining == 0 => {
+                if !(run_check.check)(&runner.vm) {
+                    return Err(error);
+                }
+            }
+            result => return result,
+        }
+    }
+}
+
 /// Runs CairoRunner on layout with prime.
-/// Allows injecting custom CairoRunner.
+/// Allows injecting custom CairoRunner, and checking the run in progress with `run_check`.
 pub fn run_function_with_runner(
     additional_initialization: impl FnOnce(&mut VirtualMachine) -> Result<(), Box<CairoRunError>>,
     hint_processor: &mut dyn HintProcessor,
     runner: &mut CairoRunner,
+    run_check: Option<RunCheck<'_>>,
 ) -> Result<(), Box<CairoRunError>> {
     let end = runner.initialize(true).map_err(CairoRunError::from)?;
 
     additional_initialization(&mut runner.vm)?;
 
-    runner.run_until_pc(end, hint_processor).map_err(CairoRunError::from)?;
+    match run_check {
+        Some(run_check) => run_until_pc_with_check(runner, end, hint_processor, run_check),
+        None => runner.run_until_pc(end, hint_processor),
+    }
+    .map_err(CairoRunError::from)?;
     runner.end_run(true, false, hint_processor, false).map_err(CairoRunError::from)?;
     runner.relocate(true, true).map_err(CairoRunError::from)?;
     Ok(())
@@ -2412,13 +2703,19 @@ pub fn run_function<'a, 'b: 'a>(
     additional_initialization: impl FnOnce(&mut VirtualMachine) -> Result<(), Box<CairoRunError>>,
     hint_processor: &mut dyn HintProcessor,
     hints_dict: HashMap<usize, Vec<HintParams>>,
+    run_check: Option<RunCheck<'_>>,
 ) -> Result<RunFunctionResult, Box<CairoRunError>> {
     let data: Vec<MaybeRelocatable> =
         bytecode.map(Felt252::from).map(MaybeRelocatable::from).collect();
     let mut runner = build_cairo_runner(data, builtins, hints_dict)?;
 
-    run_function_with_runner(additional_initialization, hint_processor, &mut runner)?;
+    run_function_with_runner(additional_initialization, hint_processor, &mut runner, run_check)?;
 
+    Ok(run_function_result(runner))
+}
+
+/// Collects the result of a run of `runner` finished by [run_function_with_runner].
+pub fn run_function_result(runner: CairoRunner) -> RunFunctionResult {
     let used_resources = runner
         .get_execution_resources()
         .expect("Failed to get execution resources, but the run was successful.");
@@ -2426,12 +2723,12 @@ pub fn run_function<'a, 'b: 'a>(
     let relocated_trace = runner.relocated_trace.unwrap();
     let memory = runner.relocated_memory;
 
-    Ok(RunFunctionResult {
+    RunFunctionResult {
         ap: relocated_trace.last().unwrap().ap,
         used_resources,
         memory,
         relocated_trace,
-    })
+    }
 }
 
 /// Formats the given felts as a debug string.
@@ -2473,6 +2770,10 @@ impl FormattedItem {
     pub fn quote_if_string(self) -> String {
         if self.is_string { format!("\"{}\"", self.item) } else { self.item }
     }
+    /// Returns whether the item is a string.
+    pub fn is_string(&self) -> bool {
+        self.is_string
+    }
 }
 
 /// Formats a string or a short string / `felt252`. Returns the formatted string and a boolean
@@ -133,11 +133,19 @@ fn test_runner(function: CasmContext, n_returns: usize, expected: &[i128]) {
         markers: Default::default(),
         panic_traceback: Default::default(),
         captured_stdout: String::new(),
+        max_stdout_bytes: None,
+        stdout_truncated: false,
     };
 
-    let RunFunctionResult { ap, memory, .. } =
-        run_function(program.bytecode.iter(), vec![], |_| Ok(()), &mut hint_processor, hints_dict)
-            .expect("Running code failed.");
+    let RunFunctionResult { ap, memory, .. } = run_function(
+        program.bytecode.iter(),
+        vec![],
+        |_| Ok(()),
+        &mut hint_processor,
+        hints_dict,
+        None,
+    )
+    .expect("Running code failed.");
     let ret_memory = memory.into_iter().skip(ap - n_returns);
     assert_eq!(
         ret_memory.take(n_returns).map(|cell| cell.unwrap()).collect_vec(),
@@ -165,11 +173,19 @@ fn test_allocate_segment() {
         markers: Default::default(),
         panic_traceback: Default::default(),
         captured_stdout: String::new(),
+        max_stdout_bytes: None,
+        stdout_truncated: false,
     };
 
-    let RunFunctionResult { ap, memory, .. } =
-        run_function(program.bytecode.iter(), vec![], |_| Ok(()), &mut hint_processor, hints_dict)
-            .expect("Running code failed.");
+    let RunFunctionResult { ap, memory, .. } = run_function(
+        program.bytecode.iter(),
+        vec![],
+        |_| Ok(()),
+        &mut hint_processor,
+        hints_dict,
+        None,
+    )
+    .expect("Running code failed.");
     let ptr = memory[ap]
         .as_ref()
         .expect("Uninitialized value.")
@@ -7,7 +7,7 @@ use cairo_lang_sierra::extensions::NamedType;
 use cairo_lang_sierra::extensions::enm::EnumType;
 use cairo_lang_sierra::extensions::gas::{CostTokenType, GasBuiltinType};
 use cairo_lang_sierra::ids::{ConcreteTypeId, GenericTypeId};
-use cairo_lang_sierra::program::{Function, GenericArg};
+use cairo_lang_sierra::program::{Function, GenericArg, StatementIdx};
 use cairo_lang_sierra_to_casm::metadata::MetadataComputationConfig;
 use cairo_lang_starknet::contract::ContractInfo;
 use cairo_lang_utils::casts::IntoOrPanic;
@@ -16,22 +16,30 @@ use cairo_lang_utils::{extract_matches, require};
 use cairo_vm::hint_processor::hint_processor_definition::HintProcessor;
 use cairo_vm::serde::deserialize_program::HintParams;
 use cairo_vm::types::builtin_name::BuiltinName;
+use cairo_vm::types::relocatable::MaybeRelocatable;
 use cairo_vm::vm::errors::cairo_run_errors::CairoRunError;
 use cairo_vm::vm::runners::cairo_runner::{ExecutionResources, RunResources};
+use cairo_vm::vm::trace::trace_entry::RelocatedTraceEntry;
 use cairo_vm::vm::vm_core::VirtualMachine;
 use casm_run::hint_to_hint_params;
-pub use casm_run::{CairoHintProcessor, StarknetState};
+pub use casm_run::{
+    CairoHintProcessor, ContractCall, EmittedEvent, SentL2ToL1Message, StarknetState,
+};
 use num_bigint::BigInt;
 use num_traits::ToPrimitive;
 use profiling::ProfilingInfo;
 use starknet_types_core::felt::Felt as Felt252;
 use thiserror::Error;
 
-use crate::casm_run::{RunFunctionResult, StarknetHintProcessor};
+use crate::casm_run::{RunCheck, RunFunctionResult, StarknetHintProcessor, StepBudget};
+use crate::loop_detection::{LoopDetectionConfig, RepeatedCall, find_repeated_call};
+use crate::partial_run::PartialRun;
 use crate::profiling::ProfilerConfig;
 
 pub mod casm_run;
 pub mod clap;
+pub mod loop_detection;
+pub mod partial_run;
 pub mod profiling;
 pub mod short_string;
 
@@ -49,6 +57,18 @@ pub enum RunnerError {
     ArgumentsSizeMismatch { expected: usize, actual: usize },
     #[error(transparent)]
     CairoRunError(#[from] Box<CairoRunError>),
+    #[error(
+        "Possible infinite loop after {steps} steps: `{function}` calls itself with unchanged \
+         arguments."
+    )]
+    PossibleInfiniteLoop { function: String, statement_idx: StatementIdx, steps: usize },
+    #[error("Exceeded the limit of {max_steps} steps.")]
+    StepLimitExceeded { max_steps: usize },
+    #[error("The run was cancelled.")]
+    Cancelled,
+    /// A run stopped by `error` before its end, with the state it reached.
+    #[error("{error}")]
+    Aborted { error: Box<RunnerError>, partial_run: Box<PartialRun> },
 }
 
 /// The full result of a run with Starknet state.
@@ -57,10 +77,14 @@ pub struct RunResultStarknet {
     pub memory: Vec<Option<Felt252>>,
     pub value: RunResultValue,
     pub stdout: String,
+    /// Whether `stdout` was truncated to the size limit of the runner.
+    pub stdout_truncated: bool,
     pub starknet_state: StarknetState,
     pub used_resources: StarknetExecutionResources,
     /// The profiling info of the run, if requested.
     pub profiling_info: Option<ProfilingInfo>,
+    /// The relocated trace of the run, including the header calling the function, if requested.
+    pub trace: Option<Vec<RelocatedTraceEntry>>,
 }
 
 /// The full result of a run.
@@ -72,6 +96,8 @@ pub struct RunResult {
     pub used_resources: ExecutionResources,
     /// The profiling info of the run, if requested.
     pub profiling_info: Option<ProfilingInfo>,
+    /// The relocated trace of the run, including the header calling the function, if requested.
+    pub trace: Option<Vec<RelocatedTraceEntry>>,
 }
 
 /// The execution resources in a run.
@@ -175,6 +201,14 @@ pub struct PreparedStarknetContext {
     pub builtins: Vec<BuiltinName>,
 }
 
+/// A check polled during runs, stopping them once it returns `true`, e.g. when the user of an
+/// interactive session asks to stop a run.
+pub struct CancellationCheck {
+    /// The number of steps between two polls.
+    pub check_interval: usize,
+    pub is_cancelled: Box<dyn Fn() -> bool>,
+}
+
 /// Runner enabling running a Sierra program on the VM.
 pub struct SierraCasmRunner {
     /// Builder for runnable functions.
@@ -183,6 +217,19 @@ pub struct SierraCasmRunner {
     starknet_contracts_info: OrderedHashMap<Felt252, ContractInfo>,
     /// Whether to run the profiler when running using this runner.
     run_profiler: Option<ProfilingInfoCollectionConfig>,
+    /// Whether to stop runs looking like infinite loops.
+    loop_detection: Option<LoopDetectionConfig>,
+    /// The number of steps after which runs are stopped, including the header calling the
+    /// function.
+    max_steps: Option<usize>,
+    /// The check stopping runs cancelled by the caller.
+    cancellation: Option<CancellationCheck>,
+    /// Gas costs of builtin token types overriding [token_gas_cost].
+    token_gas_costs: OrderedHashMap<CostTokenType, usize>,
+    /// Whether to return the relocated trace of the runs.
+    collect_trace: bool,
+    /// The number of bytes of stdout the runs capture, if limited.
+    max_stdout_bytes: Option<usize>,
 }
 impl SierraCasmRunner {
     pub fn new(
@@ -195,9 +242,65 @@ impl SierraCasmRunner {
             builder: RunnableBuilder::new(sierra_program, metadata_config)?,
             starknet_contracts_info,
             run_profiler,
+            loop_detection: None,
+            max_steps: None,
+            cancellation: None,
+            token_gas_costs: Default::default(),
+            collect_trace: false,
+            max_stdout_bytes: None,
         })
     }
 
+    /// Stops the runs of this runner once they look like infinite loops, see [loop_detection].
+    pub fn with_loop_detection(mut self, config: LoopDetectionConfig) -> Self {
+        self.loop_detection = Some(config);
+        self
+    }
+
+    /// Stops the runs of this runner after `max_steps` steps, with a
+    /// [RunnerError::StepLimitExceeded] error.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Stops the runs of this runner with a [RunnerError::Cancelled] error once `check` reports
+    /// them cancelled.
+    pub fn with_cancellation(mut self, check: CancellationCheck) -> Self {
+        self.cancellation = Some(check);
+        self
+    }
+
+    /// Overrides the gas costs of the given builtin token types, such as
+    /// [CostTokenType::Pedersen], in the runs of this runner.
+    pub fn with_token_gas_costs(mut self, costs: OrderedHashMap<CostTokenType, usize>) -> Self {
+        self.token_gas_costs = costs;
+        self
+    }
+
+    /// Returns the relocated trace of the runs of this runner in [RunResult::trace].
+    pub fn with_trace_collection(mut self) -> Self {
+        self.collect_trace = true;
+        self
+    }
+
+    /// Stops capturing the stdout of the runs of this runner after `max_stdout_bytes` bytes,
+    /// setting [RunResultStarknet::stdout_truncated]. The runs go on.
+    pub fn with_max_stdout_bytes(mut self, max_stdout_bytes: usize) -> Self {
+        self.max_stdout_bytes = Some(max_stdout_bytes);
+        self
+    }
+
+    /// Returns the gas cost of a token type, taking the overrides of this runner into account.
+    pub fn token_gas_cost(&self, token_type: CostTokenType) -> usize {
+        self.token_gas_costs.get(&token_type).copied().unwrap_or_else(|| token_gas_cost(token_type))
+    }
+
+    /// Returns the builder of the runnable functions of the program.
+    pub fn builder(&self) -> &RunnableBuilder {
+        &self.builder
+    }
+
     /// Runs the VM starting from a function in the context of a given Starknet state.
     pub fn run_function_with_starknet_context(
         &self,
@@ -219,8 +322,16 @@ impl SierraCasmRunner {
         hint_processor: &mut dyn StarknetHintProcessor,
         PreparedStarknetContext { hints_dict, bytecode, builtins }: PreparedStarknetContext,
     ) -> Result<RunResultStarknet, RunnerError> {
-        let RunResult { gas_counter, memory, value, used_resources, profiling_info } =
-            self.run_function(func, hint_processor, hints_dict, bytecode.iter(), builtins)?;
+        let RunResult { gas_counter, memory, value, used_resources, profiling_info, trace } =
+            match self.run_function(func, hint_processor, hints_dict, bytecode.iter(), builtins) {
+                Ok(result) => result,
+                Err(RunnerError::Aborted { error, mut partial_run }) => {
+                    partial_run.stdout = hint_processor.take_stdout();
+                    partial_run.stdout_truncated = hint_processor.stdout_truncated();
+                    return Err(RunnerError::Aborted { error, partial_run });
+                }
+                Err(error) => return Err(error),
+            };
         let mut all_used_resources = hint_processor.take_syscalls_used_resources();
         all_used_resources.basic_resources += &used_resources;
         Ok(RunResultStarknet {
@@ -228,9 +339,11 @@ impl SierraCasmRunner {
             memory,
             value,
             stdout: hint_processor.take_stdout(),
+            stdout_truncated: hint_processor.stdout_truncated(),
             starknet_state: hint_processor.take_starknet_state(),
             used_resources: all_used_resources,
             profiling_info,
+            trace,
         })
     }
 
@@ -277,14 +390,75 @@ impl SierraCasmRunner {
         let return_types =
             self.builder.generic_id_and_size_from_concrete(&func.signature.ret_types);
         let data_len = bytecode.len();
+        let loop_detection = match &self.loop_detection {
+            Some(config) => Some((config, self.load_offset(func)?)),
+            None => None,
+        };
+        let cancellation = self.cancellation.as_ref();
+        // The run is checked at the shortest interval of the checks, each running at its own.
+        let check_interval = loop_detection
+            .map(|(config, _)| config.check_interval)
+            .into_iter()
+            .chain(cancellation.map(|cancellation| cancellation.check_interval))
+            .min();
+        let mut run_steps = 0;
+        let mut checked_steps = 0;
+        let mut repeated_call = None;
+        let mut cancelled = false;
+        let mut check_run = |vm: &VirtualMachine| {
+            run_steps += check_interval.unwrap_or_default();
+            if cancellation.is_some_and(|cancellation| (cancellation.is_cancelled)()) {
+                cancelled = true;
+                return false;
+            }
+            let Some((config, load_offset)) = loop_detection else {
+                return true;
+            };
+            if run_steps < checked_steps + config.check_interval {
+                return true;
+            }
+            checked_steps = run_steps;
+            repeated_call = find_repeated_call(&self.builder, load_offset, config, vm);
+            repeated_call.is_none()
+        };
+        let mut step_limit;
+        let hint_processor: &mut dyn HintProcessor = match self.max_steps {
+            Some(max_steps) => {
+                step_limit = StepBudget { inner: hint_processor, remaining: max_steps };
+                &mut step_limit
+            }
+            None => hint_processor,
+        };
+        let data = bytecode.map(Felt252::from).map(MaybeRelocatable::from).collect();
+        let mut cairo_runner = casm_run::build_cairo_runner(data, builtins, hints_dict)?;
+        let run_result = casm_run::run_function_with_runner(
+            |vm| initialize_vm(vm, data_len, &|token_type| self.token_gas_cost(token_type)),
+            hint_processor,
+            &mut cairo_runner,
+            check_interval.map(|interval| RunCheck { interval, check: &mut check_run }),
+        );
+        if cancelled {
+            return Err(self.aborted(func, &cairo_runner.vm, RunnerError::Cancelled));
+        }
+        if let Some(RepeatedCall { function_idx, statement_idx }) = repeated_call {
+            let error = RunnerError::PossibleInfiniteLoop {
+                function: self.builder.sierra_program().funcs[function_idx].id.to_string(),
+                statement_idx,
+                steps: checked_steps,
+            };
+            return Err(self.aborted(func, &cairo_runner.vm, error));
+        }
+        if let Err(error) = run_result {
+            let error = match self.max_steps {
+                Some(max_steps) if cairo_runner.vm.get_current_step() >= max_steps => {
+                    RunnerError::StepLimitExceeded { max_steps }
+                }
+                _ => error.into(),
+            };
+            return Err(self.aborted(func, &cairo_runner.vm, error));
+        }
         let RunFunctionResult { ap, mut used_resources, memory, relocated_trace } =
-            casm_run::run_function(
-                bytecode,
-                builtins,
-                |vm| initialize_vm(vm, data_len),
-                hint_processor,
-                hints_dict,
-            )?;
+            casm_run::run_function_result(cairo_runner);
 
         // The execution from the header created by self.builder.create_entry_code().
         // We expect the last trace entry to be the `ret` instruction at the end of the header.
@@ -308,7 +482,17 @@ impl SierraCasmRunner {
             }
         };
 
-        let Self { builder, starknet_contracts_info: _, run_profiler } = self;
+        let Self {
+            builder,
+            starknet_contracts_info: _,
+            run_profiler,
+            loop_detection: _,
+            max_steps: _,
+            cancellation: _,
+            token_gas_costs: _,
+            collect_trace,
+            max_stdout_bytes: _,
+        } = self;
 
         // The real program starts right after the header.
         let load_offset = header_end + 1;
@@ -317,7 +501,22 @@ impl SierraCasmRunner {
             ProfilingInfo::from_trace(builder, load_offset, config, &relocated_trace)
         });
 
-        Ok(RunResult { gas_counter, memory, value, used_resources, profiling_info })
+        let trace = collect_trace.then_some(relocated_trace);
+
+        Ok(RunResult { gas_counter, memory, value, used_resources, profiling_info, trace })
+    }
+
+    /// Returns the offset at which the program is loaded when running `func`, right after the
+    /// header calling it.
+    fn load_offset(&self, func: &Function) -> Result<usize, BuildError> {
+        let header = self.builder.create_wrapper_info(func, EntryCodeConfig::testing())?.header;
+        Ok(header.iter().map(|instruction| instruction.body.op_size()).sum())
+    }
+
+    /// Wraps the `error` aborting a run of `func` with the state the run reached in `vm`.
+    fn aborted(&self, func: &Function, vm: &VirtualMachine, error: RunnerError) -> RunnerError {
+        let partial_run = PartialRun::capture(&self.builder, self.load_offset(func).ok(), vm);
+        RunnerError::Aborted { error: Box::new(error), partial_run: Box::new(partial_run) }
     }
 
     /// Prepares context for running a function in the context of a given Starknet state.
@@ -347,6 +546,8 @@ impl SierraCasmRunner {
             markers: Default::default(),
             panic_traceback: Default::default(),
             captured_stdout: String::new(),
+            max_stdout_bytes: self.max_stdout_bytes,
+            stdout_truncated: false,
         };
         Ok((
             hint_processor,
@@ -506,7 +707,9 @@ impl SierraCasmRunner {
         Some(
             gas_info.function_costs[&func.id]
                 .iter()
-                .map(|(token_type, val)| val.into_or_panic::<usize>() * token_gas_cost(*token_type))
+                .map(|(token_type, val)| {
+                    val.into_or_panic::<usize>() * self.token_gas_cost(*token_type)
+                })
                 .sum(),
         )
     }
@@ -523,6 +726,9 @@ pub struct ProfilingInfoCollectionConfig {
     /// The resulting dictionary can be pretty huge hence this feature is optional and disabled by
     /// default.
     pub collect_scoped_sierra_statement_weights: bool,
+    /// If set, the runner also collects a timeline of the run, splitting its steps into this
+    /// number of buckets of consecutive steps.
+    pub timeline_buckets: Option<usize>,
 }
 
 impl ProfilingInfoCollectionConfig {
@@ -558,13 +764,20 @@ impl Default for ProfilingInfoCollectionConfig {
                 MAX_STACK_TRACE_DEPTH_DEFAULT
             },
             collect_scoped_sierra_statement_weights: false,
+            timeline_buckets: None,
         }
     }
 }
 
 /// Initializes a VM by adding a new segment with builtins cost and a necessary pointer at the end
 /// of the program, as well as placing the arguments at the initial AP values.
-pub fn initialize_vm(vm: &mut VirtualMachine, data_len: usize) -> Result<(), Box<CairoRunError>> {
+///
+/// `token_gas_cost` gives the costs to write to the segment, usually [token_gas_cost].
+pub fn initialize_vm(
+    vm: &mut VirtualMachine,
+    data_len: usize,
+    token_gas_cost: &dyn Fn(CostTokenType) -> usize,
+) -> Result<(), Box<CairoRunError>> {
     // Create the builtin cost segment, with dummy values.
     let builtin_cost_segment = vm.add_memory_segment();
     for token_type in CostTokenType::iter_precost() {
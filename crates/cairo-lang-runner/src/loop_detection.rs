@@ -0,0 +1,107 @@
+//! Detection of infinite loops during a run.
+//!
+//! Loops are compiled to recursive functions, so a loop that makes no progress shows up in the call
+//! stack as nested frames of the same function, called with unchanged arguments.
+
+use cairo_lang_runnable_utils::builder::RunnableBuilder;
+use cairo_lang_sierra::program::{Function, StatementIdx};
+use cairo_vm::types::relocatable::{MaybeRelocatable, Relocatable};
+use cairo_vm::vm::vm_core::VirtualMachine;
+
+use crate::profiling::user_function_idx_by_sierra_statement_idx;
+
+/// Configuration for the detection of infinite loops during a run.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct LoopDetectionConfig {
+    /// The number of steps between two checks of the call stack.
+    pub check_interval: usize,
+    /// The number of nested frames of a function with unchanged arguments from which the run is
+    /// considered an infinite loop.
+    pub repeated_frames: usize,
+}
+
+impl Default for LoopDetectionConfig {
+    fn default() -> Self {
+        Self { check_interval: 100_000, repeated_frames: 3 }
+    }
+}
+
+/// A function found calling itself with unchanged arguments.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct RepeatedCall {
+    /// The index of the function in the Sierra program.
+    pub function_idx: usize,
+    /// The Sierra statement of the recursive call.
+    pub statement_idx: StatementIdx,
+}
+
+/// Walks the call stack of `vm` from the innermost frame, looking for
+/// `config.repeated_frames` nested frames of the same function with equal user arguments.
+///
+/// `load_offset` is the offset of `builder.casm_program()` in the program segment.
+pub fn find_repeated_call(
+    builder: &RunnableBuilder,
+    load_offset: usize,
+    config: &LoopDetectionConfig,
+    vm: &VirtualMachine,
+) -> Option<RepeatedCall> {
+    let sierra_statement_info = &builder.casm_program().debug_info.sierra_statement_info;
+    let bytecode_len = sierra_statement_info.last()?.end_offset;
+    let mut pc = vm.get_pc().offset;
+    let mut fp = vm.get_fp();
+    let mut previous_frame = None;
+    let mut repeated_frames = 1;
+    // The frames of the header calling the function are outside of the program, ending the walk.
+    while let Some(real_pc) = pc.checked_sub(load_offset)
+        && real_pc < bytecode_len
+    {
+        let statement_idx = builder.casm_program().sierra_statement_index_by_pc(real_pc);
+        let function_idx =
+            user_function_idx_by_sierra_statement_idx(builder.sierra_program(), statement_idx);
+        let args = user_args(builder, &builder.sierra_program().funcs[function_idx], vm, fp)?;
+        let frame = (function_idx, args);
+        if previous_frame.as_ref() == Some(&frame) {
+            repeated_frames += 1;
+            if repeated_frames >= config.repeated_frames {
+                return Some(RepeatedCall { function_idx, statement_idx });
+            }
+        } else {
+            repeated_frames = 1;
+        }
+        previous_frame = Some(frame);
+
+        // The return address follows the call instruction, so the caller is identified by the
+        // instruction before it.
+        let return_pc = vm.get_relocatable((fp - 1).ok()?).ok()?;
+        fp = vm.get_relocatable((fp - 2).ok()?).ok()?;
+        pc = return_pc.offset.checked_sub(1)?;
+    }
+    None
+}
+
+/// Returns the values of the user arguments of the frame of `func` at `fp`, leaving out implicits
+/// such as builtins and the gas counter, which change even when the function makes no progress.
+fn user_args(
+    builder: &RunnableBuilder,
+    func: &Function,
+    vm: &VirtualMachine,
+    fp: Relocatable,
+) -> Option<Vec<Option<MaybeRelocatable>>> {
+    let params = builder.generic_id_and_size_from_concrete(&func.signature.param_types);
+    let params_size: usize = params.iter().map(|(_, size)| *size as usize).sum();
+    // The arguments are pushed right before the frame pointer and the return address.
+    let mut address = (fp - (params_size + 2)).ok()?;
+    let mut args = vec![];
+    for (ty, size) in params {
+        let size = size as usize;
+        if builder.is_user_arg_type(&ty) {
+            args.extend(
+                (0..size).map(|offset| {
+                    (address + offset).ok().and_then(|address| vm.get_maybe(&address))
+                }),
+            );
+        }
+        address = (address + size).ok()?;
+    }
+    Some(args)
+}
@@ -0,0 +1,73 @@
+//! The state of runs aborted before their end, giving feedback on failing programs.
+
+use cairo_lang_runnable_utils::builder::RunnableBuilder;
+use cairo_lang_sierra::extensions::NamedType;
+use cairo_lang_sierra::extensions::gas::GasBuiltinType;
+use cairo_lang_sierra::program::{Function, StatementIdx};
+use cairo_vm::vm::vm_core::VirtualMachine;
+use starknet_types_core::felt::Felt as Felt252;
+
+use crate::profiling::user_function_idx_by_sierra_statement_idx;
+
+/// The state of a run when it was aborted by an error.
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+pub struct PartialRun {
+    /// The number of steps run before the abort, including the header calling the function.
+    pub steps: usize,
+    /// The Sierra statement being run, if the run was aborted within the program.
+    pub statement_idx: Option<StatementIdx>,
+    /// The gas counter at the entry of the innermost function call, if the function takes one.
+    pub gas_counter: Option<Felt252>,
+    /// The output printed before the abort.
+    pub stdout: String,
+    /// Whether `stdout` was truncated to the size limit of the runner.
+    pub stdout_truncated: bool,
+}
+
+impl PartialRun {
+    /// Captures the state of `vm`, running the program of `builder` loaded at `load_offset`.
+    pub fn capture(
+        builder: &RunnableBuilder,
+        load_offset: Option<usize>,
+        vm: &VirtualMachine,
+    ) -> Self {
+        let bytecode_len = builder
+            .casm_program()
+            .debug_info
+            .sierra_statement_info
+            .last()
+            .map_or(0, |info| info.end_offset);
+        // The header calling the function is outside of the program.
+        let statement_idx = load_offset
+            .and_then(|load_offset| vm.get_pc().offset.checked_sub(load_offset))
+            .filter(|real_pc| *real_pc < bytecode_len)
+            .map(|real_pc| builder.casm_program().sierra_statement_index_by_pc(real_pc));
+        let gas_counter = statement_idx.and_then(|statement_idx| {
+            let function_idx =
+                user_function_idx_by_sierra_statement_idx(builder.sierra_program(), statement_idx);
+            gas_counter(builder, &builder.sierra_program().funcs[function_idx], vm)
+        });
+        Self {
+            steps: vm.get_current_step(),
+            statement_idx,
+            gas_counter,
+            stdout: String::new(),
+            stdout_truncated: false,
+        }
+    }
+}
+
+/// Returns the gas counter passed to the frame of `func` at the current frame pointer of `vm`.
+fn gas_counter(builder: &RunnableBuilder, func: &Function, vm: &VirtualMachine) -> Option<Felt252> {
+    let params = builder.generic_id_and_size_from_concrete(&func.signature.param_types);
+    let params_size: usize = params.iter().map(|(_, size)| *size as usize).sum();
+    // The arguments are pushed right before the frame pointer and the return address.
+    let mut address = (vm.get_fp() - (params_size + 2)).ok()?;
+    for (ty, size) in params {
+        if ty == GasBuiltinType::ID {
+            return vm.get_integer(address).ok().map(|gas| gas.into_owned());
+        }
+        address = (address + size as usize).ok()?;
+    }
+    None
+}
@@ -2,8 +2,15 @@ use std::fmt::{Debug, Display};
 
 use cairo_lang_lowering::ids::FunctionLongId;
 use cairo_lang_runnable_utils::builder::RunnableBuilder;
+use cairo_lang_sierra::extensions::bitwise::BitwiseType;
+use cairo_lang_sierra::extensions::circuit::{AddModType, MulModType};
 use cairo_lang_sierra::extensions::core::CoreConcreteLibfunc;
-use cairo_lang_sierra::ids::ConcreteLibfuncId;
+use cairo_lang_sierra::extensions::ec::EcOpType;
+use cairo_lang_sierra::extensions::pedersen::PedersenType;
+use cairo_lang_sierra::extensions::poseidon::PoseidonType;
+use cairo_lang_sierra::extensions::range_check::{RangeCheck96Type, RangeCheckType};
+use cairo_lang_sierra::extensions::{ConcreteLibfunc, NamedType};
+use cairo_lang_sierra::ids::{ConcreteLibfuncId, GenericTypeId};
 use cairo_lang_sierra::program::{GenStatement, Program, StatementIdx};
 use cairo_lang_sierra_generator::db::SierraGenGroup;
 use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
@@ -19,6 +26,18 @@ use crate::ProfilingInfoCollectionConfig;
 #[path = "profiling_test.rs"]
 mod test;
 
+/// The builtins whose usage is tracked in the timeline of a run.
+const TIMELINE_BUILTINS: [GenericTypeId; 8] = [
+    RangeCheckType::ID,
+    RangeCheck96Type::ID,
+    PedersenType::ID,
+    PoseidonType::ID,
+    BitwiseType::ID,
+    EcOpType::ID,
+    AddModType::ID,
+    MulModType::ID,
+];
+
 /// Profiler configuration.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ProfilerConfig {
@@ -45,6 +64,9 @@ pub struct ProfilingInfo {
     /// The number of steps in the trace that originated from each Sierra statement.
     pub sierra_statement_weights: UnorderedHashMap<StatementIdx, usize>,
 
+    /// The number of times each Sierra statement was executed.
+    pub sierra_statement_counts: UnorderedHashMap<StatementIdx, usize>,
+
     /// A map of weights of each stack trace.
     /// The key is a function stack trace of an executed function. The stack trace is represented
     /// as a vector of indices of the functions in the stack (indices of the functions according to
@@ -59,6 +81,25 @@ pub struct ProfilingInfo {
     /// and loops (which are tail recursion).
     /// The entries are sorted in the order they occur.
     pub scoped_sierra_statement_weights: OrderedHashMap<(Vec<usize>, StatementIdx), usize>,
+
+    /// The steps of the run split into buckets of consecutive steps, if requested by
+    /// [ProfilingInfoCollectionConfig::timeline_buckets].
+    pub timeline: Option<Vec<TimelineBucket>>,
+}
+
+/// A range of consecutive steps of a run.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct TimelineBucket {
+    /// The index of the first step of the bucket, from the start of the run.
+    pub first_step: usize,
+    /// The number of steps in the bucket.
+    pub steps: usize,
+    /// The number of steps of the bucket spent in each user function. The key is the index of the
+    /// function in the Sierra program.
+    pub function_weights: OrderedHashMap<usize, usize>,
+    /// The number of invocations of libfuncs using each builtin, by the builtin's type name.
+    /// Function calls and memory libfuncs, which only pass builtins along, are not counted.
+    pub builtin_invocations: OrderedHashMap<String, usize>,
 }
 
 impl ProfilingInfo {
@@ -93,9 +134,24 @@ impl ProfilingInfo {
         // Note the header and footer (CASM instructions added for running the program by the
         // runner). Both header and footer are not counted when collecting the weights.
         let mut sierra_statement_weights = UnorderedHashMap::default();
+        // The number of executions of each Sierra statement, counted on their first step.
+        let mut sierra_statement_counts = UnorderedHashMap::default();
         // Total weight of Sierra statements grouped by the respective (collapsed) user function
         // call stack.
         let mut scoped_sierra_statement_weights = OrderedHashMap::default();
+        // The number of steps in each timeline bucket, computed from the number of steps of the
+        // program (excluding the header and the footer).
+        let timeline_bucket_size = profiling_config.timeline_buckets.map(|buckets| {
+            let program_steps = trace
+                .iter()
+                .filter(|step| {
+                    step.pc.checked_sub(load_offset).is_some_and(|real_pc| real_pc < bytecode_len)
+                })
+                .count();
+            program_steps.div_ceil(buckets.max(1)).max(1)
+        });
+        let mut timeline = Vec::new();
+        let mut step_idx = 0;
         for step in trace {
             // Skip the header.
             let Some(real_pc) = step.pc.checked_sub(load_offset) else {
@@ -125,6 +181,11 @@ impl ProfilingInfo {
             );
 
             *sierra_statement_weights.entry(sierra_statement_idx).or_insert(0) += 1;
+            let statement_start =
+                sierra_statement_info[sierra_statement_idx.0].start_offset == real_pc;
+            if statement_start {
+                *sierra_statement_counts.entry(sierra_statement_idx).or_insert(0) += 1;
+            }
 
             if profiling_config.collect_scoped_sierra_statement_weights {
                 // The current stack trace, including the current function (recursive calls
@@ -145,6 +206,42 @@ impl ProfilingInfo {
                 panic!("Failed fetching statement index {}", sierra_statement_idx.0);
             };
 
+            if let Some(bucket_size) = timeline_bucket_size {
+                if step_idx % bucket_size == 0 {
+                    timeline.push(TimelineBucket {
+                        first_step: step_idx,
+                        steps: 0,
+                        function_weights: OrderedHashMap::default(),
+                        builtin_invocations: OrderedHashMap::default(),
+                    });
+                }
+                let bucket = timeline.last_mut().unwrap();
+                bucket.steps += 1;
+                *bucket.function_weights.entry(user_function_idx).or_insert(0) += 1;
+
+                // Count the libfunc invocation on the first step of its statement.
+                if let GenStatement::Invocation(invocation) = gen_statement
+                    && statement_start
+                {
+                    let libfunc = builder.registry().get_libfunc(&invocation.libfunc_id).unwrap();
+                    if !matches!(
+                        libfunc,
+                        CoreConcreteLibfunc::FunctionCall(_) | CoreConcreteLibfunc::Mem(_)
+                    ) {
+                        for param in libfunc.param_signatures() {
+                            let generic_id = &builder.type_long_id(&param.ty).generic_id;
+                            if TIMELINE_BUILTINS.contains(generic_id) {
+                                *bucket
+                                    .builtin_invocations
+                                    .entry(generic_id.0.to_string())
+                                    .or_insert(0) += 1;
+                            }
+                        }
+                    }
+                }
+            }
+            step_idx += 1;
+
             match gen_statement {
                 GenStatement::Invocation(invocation) => {
                     if matches!(
@@ -182,8 +279,10 @@ impl ProfilingInfo {
 
         ProfilingInfo {
             sierra_statement_weights,
+            sierra_statement_counts,
             stack_trace_weights,
             scoped_sierra_statement_weights,
+            timeline: timeline_bucket_size.map(|_| timeline),
         }
     }
 }
@@ -0,0 +1,84 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, fs, io};
+
+fn main() -> io::Result<()> {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR"));
+    let corelib_root = manifest_dir.join("../../corelib/src").canonicalize()?;
+
+    println!("cargo:rerun-if-changed={}", corelib_root.display());
+
+    let mut files = Vec::new();
+    collect_files(&corelib_root, &corelib_root, &mut files)?;
+    files.sort();
+
+    let mut generated = String::new();
+    generated.push_str("pub(crate) static EMBEDDED_CORELIB_FILES: &[(&str, &str)] = &[\n");
+
+    for rel_path in files {
+        let abs_path = corelib_root.join(&rel_path).canonicalize()?;
+        let rel_path_str =
+            rel_path.iter().map(|part| part.to_string_lossy()).collect::<Vec<_>>().join("/");
+        let abs_path_str = abs_path.to_string_lossy().replace('\\', "\\\\");
+        generated.push_str(&format!(
+            "    (\"{}\", include_str!(\"{}\")),\n",
+            rel_path_str, abs_path_str
+        ));
+    }
+
+    generated.push_str("];\n");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR"));
+    fs::write(out_dir.join("embedded_corelib.rs"), generated)?;
+
+    emit_build_info(&manifest_dir);
+    Ok(())
+}
+
+/// Sets the environment variables read by `build_info`: the git commit of the sources, whether
+/// they had uncommitted changes, the upstream release they are based on and the build time.
+fn emit_build_info(manifest_dir: &Path) {
+    let git_dir = manifest_dir.join("../../.git");
+    // The build script reruns when a commit is made or checked out, refreshing the build time too.
+    println!("cargo:rerun-if-changed={}", git_dir.join("HEAD").display());
+    println!("cargo:rerun-if-changed={}", git_dir.join("refs/heads").display());
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let git = |args: &[&str]| {
+        let output = Command::new("git").args(args).current_dir(manifest_dir).output().ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    if let Some(commit) = git(&["rev-parse", "HEAD"]) {
+        println!("cargo:rustc-env=CAIRO_WASM_GIT_COMMIT={commit}");
+    }
+    if let Some(status) = git(&["status", "--porcelain", "--untracked-files=no"]) {
+        println!("cargo:rustc-env=CAIRO_WASM_GIT_DIRTY={}", !status.is_empty());
+    }
+    if let Some(tag) = git(&["describe", "--tags", "--abbrev=0", "--match", "v*"]) {
+        println!("cargo:rustc-env=CAIRO_WASM_UPSTREAM_RELEASE={tag}");
+    }
+    // Reproducible builds set the timestamp through `SOURCE_DATE_EPOCH`.
+    let timestamp = env::var("SOURCE_DATE_EPOCH").ok().or_else(|| {
+        SystemTime::now().duration_since(UNIX_EPOCH).ok().map(|time| time.as_secs().to_string())
+    });
+    if let Some(timestamp) = timestamp {
+        println!("cargo:rustc-env=CAIRO_WASM_BUILD_TIMESTAMP={timestamp}");
+    }
+}
+
+fn collect_files(root: &Path, current: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(current)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(root, &path, out)?;
+            continue;
+        }
+        if file_type.is_file() && path.extension().is_some_and(|ext| ext == "cairo") {
+            out.push(path.strip_prefix(root).expect("strip_prefix").to_path_buf());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+//! Accounting of the heap allocations made while serving a request, so hosts enforcing per-request
+//! memory quotas can detect pathological inputs.
+//!
+//! The accounting wraps the system allocator, and is only available when the `allocation-stats`
+//! feature installs [CountingAllocator] as the global allocator. The counters are shared by all
+//! threads; a WASM module serves its requests on a single thread, but native hosts serving requests
+//! concurrently get approximate figures.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The number of allocations made since the module was started.
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+/// The number of bytes allocated since the module was started, freed ones included.
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+/// The number of bytes currently allocated.
+static HEAP_SIZE: AtomicUsize = AtomicUsize::new(0);
+/// The largest value of [HEAP_SIZE] since the current measurement started.
+static PEAK_HEAP_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "allocation-stats")]
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// The system allocator, counting the allocations it serves.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        HEAP_SIZE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        // A reallocation is counted as freeing the old block and allocating the new one.
+        if !new_ptr.is_null() {
+            HEAP_SIZE.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_allocation(new_size);
+        }
+        new_ptr
+    }
+}
+
+fn record_allocation(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed);
+    let heap_size = HEAP_SIZE.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_HEAP_SIZE.fetch_max(heap_size, Ordering::Relaxed);
+}
+
+/// The heap allocations made while serving a request.
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct AllocationStats {
+    /// The number of allocations, reallocations included.
+    pub allocations: usize,
+    /// The number of bytes allocated, freed ones included.
+    pub allocated_bytes: usize,
+    /// The largest growth of the heap over its size when the request started, in bytes.
+    pub peak_heap_growth: usize,
+    /// The number of bytes still allocated when the request completed, such as caches it filled.
+    pub retained_bytes: usize,
+}
+
+/// Runs `f`, returning the allocations it made if the `allocation-stats` feature is enabled.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<AllocationStats>) {
+    if !cfg!(feature = "allocation-stats") {
+        return (f(), None);
+    }
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let allocated_bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let heap_size = HEAP_SIZE.load(Ordering::Relaxed);
+    // The peak restarts from the current size, and the enclosing peak is restored afterwards so
+    // nested measurements do not hide it.
+    let enclosing_peak = PEAK_HEAP_SIZE.swap(heap_size, Ordering::Relaxed);
+    let result = f();
+    let peak = PEAK_HEAP_SIZE.fetch_max(enclosing_peak, Ordering::Relaxed);
+
+    let stats = AllocationStats {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed).wrapping_sub(allocations),
+        allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed).wrapping_sub(allocated_bytes),
+        peak_heap_growth: peak.saturating_sub(heap_size),
+        retained_bytes: HEAP_SIZE.load(Ordering::Relaxed).saturating_sub(heap_size),
+    };
+    (result, Some(stats))
+}
@@ -0,0 +1,55 @@
+//! The provenance of the module, so bug reports from its users can be tied to the exact build.
+//!
+//! The git details are captured by the build script, and are missing when the sources were built
+//! outside of a git checkout.
+
+use std::collections::BTreeSet;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The provenance of the module.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct BuildInfo {
+    /// The version of the crates, which is that of the Cairo compiler they embed.
+    pub version: String,
+    /// The git commit the module was built from.
+    pub git_commit: Option<String>,
+    /// Whether the sources had uncommitted changes when the module was built.
+    pub git_dirty: Option<bool>,
+    /// The latest upstream release tag reachable from the commit, such as `v2.15.0`.
+    pub upstream_release: Option<String>,
+    /// The build time, in seconds since the Unix epoch, or `SOURCE_DATE_EPOCH` for reproducible
+    /// builds.
+    pub build_timestamp: Option<u64>,
+    /// The cargo features the module was built with.
+    pub features: BTreeSet<String>,
+}
+
+/// Returns the provenance of a module built with `features`, the enabled features of the crate
+/// exporting it.
+pub fn build_info(features: &[(&str, bool)]) -> BuildInfo {
+    let mut features = features
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(feature, _)| feature.to_string())
+        .collect::<BTreeSet<_>>();
+    // The allocator is installed by this crate, whichever crate enabled the feature.
+    if cfg!(feature = "allocation-stats") {
+        features.insert("allocation-stats".into());
+    }
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION").into(),
+        git_commit: option_env!("CAIRO_WASM_GIT_COMMIT").map(Into::into),
+        git_dirty: option_env!("CAIRO_WASM_GIT_DIRTY").map(|dirty| dirty == "true"),
+        upstream_release: option_env!("CAIRO_WASM_UPSTREAM_RELEASE").map(Into::into),
+        build_timestamp: option_env!("CAIRO_WASM_BUILD_TIMESTAMP")
+            .and_then(|timestamp| timestamp.parse().ok()),
+        features,
+    }
+}
+
+/// Returns the [BuildInfo] of a module built with `features` as JSON.
+pub fn build_info_json(features: &[(&str, bool)]) -> String {
+    serde_json::to_string(&build_info(features)).expect("serialize build info")
+}
@@ -0,0 +1,45 @@
+//! Cooperative cancellation of runs, through a check installed by the host and polled by the
+//! runner.
+//!
+//! A WASM instance runs a request to completion without yielding to the event loop, so the check
+//! must read state the host can change meanwhile, such as a `SharedArrayBuffer` written by another
+//! thread.
+
+use std::cell::RefCell;
+
+thread_local! {
+    static CHECK: RefCell<Option<Box<dyn Fn() -> bool>>> = const { RefCell::new(None) };
+}
+
+/// Installs `check`, returning whether the runs in progress should stop, or removes the installed
+/// check if `None`.
+pub fn set_check(check: Option<Box<dyn Fn() -> bool>>) {
+    CHECK.set(check);
+}
+
+/// Returns whether a check is installed.
+pub fn is_enabled() -> bool {
+    CHECK.with_borrow(Option::is_some)
+}
+
+/// Returns whether the installed check reports the runs as cancelled.
+pub fn is_cancelled() -> bool {
+    CHECK.with_borrow(|check| check.as_ref().is_some_and(|check| check()))
+}
+
+/// Installs a check reporting the runs as cancelled while the first element of `flag` is non-zero.
+#[cfg(target_arch = "wasm32")]
+pub fn set_flag(flag: js_sys::Int32Array) {
+    set_check(Some(Box::new(move || {
+        js_sys::Atomics::load(&flag, 0).is_ok_and(|value| value != 0)
+    })));
+}
+
+/// Installs a check calling `callback`, reporting the runs as cancelled when it returns a truthy
+/// value.
+#[cfg(target_arch = "wasm32")]
+pub fn set_callback(callback: js_sys::Function) {
+    set_check(Some(Box::new(move || {
+        callback.call0(&js_sys::global()).is_ok_and(|value| value.is_truthy())
+    })));
+}
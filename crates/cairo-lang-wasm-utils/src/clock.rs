@@ -0,0 +1,18 @@
+//! Wall-clock timestamps usable both natively and in the browser.
+//!
+//! `std::time::Instant` panics on `wasm32-unknown-unknown`, so the host's `Date.now()` is used
+//! there instead.
+
+/// Returns the current time in milliseconds since the Unix epoch.
+#[cfg(target_arch = "wasm32")]
+pub fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+/// Returns the current time in milliseconds since the Unix epoch.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |duration| duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,125 @@
+//! The programs compiled by `compile_program`, kept by the module and referred to by a handle.
+//!
+//! The handles are shared by the crates of the module, so a UI can compile a project once, then
+//! run, analyze and disassemble the program, and query the signatures and types of its sources,
+//! without sending the program or the sources back each time.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+
+use cairo_lang_sierra::program::{Program, StatementIdx};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::ApiError;
+use crate::state::StateCache;
+
+thread_local! {
+    static PROGRAMS: RefCell<HashMap<u32, CompiledProgram>> = RefCell::new(HashMap::new());
+    static NEXT_PROGRAM_ID: Cell<u32> = const { Cell::new(1) };
+}
+
+/// The compiled programs in module state snapshots, so that their handles stay valid in restored
+/// instances.
+pub const STATE_CACHE: StateCache = StateCache { name: "compiled_programs", snapshot, restore };
+
+/// A program kept by the module, with what it was compiled from.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CompiledProgram {
+    /// The project the program was compiled from.
+    pub project: ProjectSources,
+    pub program: Program,
+    /// The locations of the statements of `program` in the sources of `project`.
+    pub debug_info: StatementLocations,
+}
+
+/// The sources of a project, as given in a request.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProjectSources {
+    pub crate_name: String,
+    pub files: BTreeMap<String, String>,
+    /// The corelib of the request, or `None` for the embedded one.
+    pub corelib_files: Option<BTreeMap<String, String>>,
+}
+
+/// The source locations of the statements of a program, computed when it was compiled, as the
+/// database resolving them is not kept.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct StatementLocations {
+    /// The location of each statement, as `file:line`.
+    pub locations: Vec<Option<String>>,
+    /// The lines of all the locations of each statement, which include the call sites of the
+    /// functions inlined into it.
+    pub lines: Vec<Vec<(String, usize)>>,
+}
+
+impl StatementLocations {
+    /// Returns the location of a statement, as `file:line`.
+    pub fn location(&self, statement_idx: StatementIdx) -> Option<String> {
+        self.locations.get(statement_idx.0).cloned().flatten()
+    }
+
+    /// Returns the lines of all the locations of a statement.
+    pub fn lines(&self, statement_idx: StatementIdx) -> Vec<(String, usize)> {
+        self.lines.get(statement_idx.0).cloned().unwrap_or_default()
+    }
+}
+
+/// The compiled programs of a module state snapshot.
+#[derive(Serialize, Deserialize)]
+struct ProgramsState {
+    /// The handle of the next compiled program.
+    next_program: u32,
+    programs: BTreeMap<u32, CompiledProgram>,
+}
+
+/// Keeps `program`, returning its handle.
+pub fn insert(program: CompiledProgram) -> u32 {
+    let program_id = NEXT_PROGRAM_ID.get();
+    NEXT_PROGRAM_ID.set(program_id + 1);
+    PROGRAMS.with_borrow_mut(|programs| programs.insert(program_id, program));
+    program_id
+}
+
+/// Returns the program of a handle returned by [insert].
+pub fn get(program: u32) -> Result<CompiledProgram, ApiError> {
+    PROGRAMS.with_borrow(|programs| {
+        programs
+            .get(&program)
+            .cloned()
+            .ok_or_else(|| ApiError::invalid_request(format!("Unknown program {program}.")))
+    })
+}
+
+/// Frees the program of a handle returned by [insert]. Returns whether the program existed.
+pub fn remove(program: u32) -> bool {
+    PROGRAMS.with_borrow_mut(|programs| programs.remove(&program).is_some())
+}
+
+fn snapshot() -> Value {
+    let state = ProgramsState {
+        next_program: NEXT_PROGRAM_ID.get(),
+        programs: PROGRAMS.with_borrow(|programs| {
+            programs.iter().map(|(id, program)| (*id, program.clone())).collect()
+        }),
+    };
+    serde_json::to_value(state).expect("serialize compiled programs")
+}
+
+/// Replaces the compiled programs by those returned by [snapshot].
+fn restore(contents: Value) -> Result<(), ApiError> {
+    let state = serde_json::from_value::<ProgramsState>(contents).map_err(|error| {
+        ApiError::invalid_request(format!(
+            "Invalid `compiled_programs` cache in module state: {error}"
+        ))
+    })?;
+    if state.programs.keys().any(|id| *id >= state.next_program) {
+        return Err(ApiError::invalid_request(
+            "Invalid `compiled_programs` cache in module state: a handle is not below \
+             `next_program`.",
+        ));
+    }
+    NEXT_PROGRAM_ID.set(state.next_program);
+    PROGRAMS.set(state.programs.into_iter().collect());
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! Module-wide configuration set by the host through the `configure` export.
+//!
+//! The configuration lives in a thread local: a WASM instance is single-threaded, and native
+//! callers (such as tests) running on different threads don't observe each other's settings.
+
+use std::cell::RefCell;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ApiError;
+use crate::gas::GasPolicy;
+use crate::limits::InputLimits;
+
+/// The configuration applied to every request served by the module.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct HostConfig {
+    pub limits: InputLimits,
+    pub gas: GasPolicy,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConfigureResponse {
+    pub success: bool,
+    /// The configuration in effect after the call.
+    pub config: HostConfig,
+    pub error: Option<ApiError>,
+}
+
+thread_local! {
+    static CONFIG: RefCell<HostConfig> = RefCell::new(HostConfig::default());
+}
+
+/// Returns the configuration in effect.
+pub fn current() -> HostConfig {
+    CONFIG.with_borrow(Clone::clone)
+}
+
+/// Replaces the configuration in effect.
+pub fn set(config: HostConfig) {
+    CONFIG.set(config);
+}
+
+/// Parses `config_json` as a [HostConfig] and applies it, replacing the previous configuration.
+/// Omitted fields take their default values. Returns a serialized [ConfigureResponse].
+pub fn configure(config_json: &str) -> String {
+    let response = match serde_json::from_str::<HostConfig>(config_json) {
+        Ok(config) => {
+            set(config.clone());
+            ConfigureResponse { success: true, config, error: None }
+        }
+        Err(error) => ConfigureResponse {
+            success: false,
+            config: current(),
+            error: Some(ApiError::invalid_request(format!(
+                "Failed parsing configuration JSON: {error}"
+            ))),
+        },
+    };
+    serde_json::to_string(&response).expect("serialize configure response")
+}
@@ -0,0 +1,48 @@
+//! The Cairo corelib, embedded into the binary at build time.
+
+use std::collections::BTreeMap;
+
+include!(concat!(env!("OUT_DIR"), "/embedded_corelib.rs"));
+
+/// Returns the paths of the embedded corelib files, relative to `corelib/src`.
+pub fn manifest() -> Vec<&'static str> {
+    EMBEDDED_CORELIB_FILES.iter().map(|(path, _)| *path).collect()
+}
+
+/// Returns [manifest] serialized as a JSON array.
+pub fn manifest_json() -> String {
+    serde_json::to_string(&manifest()).expect("serialize corelib manifest")
+}
+
+/// Returns the embedded corelib as a map from relative path to file content.
+pub fn embedded_files() -> BTreeMap<String, String> {
+    EMBEDDED_CORELIB_FILES
+        .iter()
+        .map(|(path, content)| ((*path).to_string(), (*content).to_string()))
+        .collect()
+}
+
+/// Returns the corelib files to compile against: `overrides` if the request provided them, the
+/// embedded corelib otherwise.
+pub fn resolve(overrides: Option<BTreeMap<String, String>>) -> BTreeMap<String, String> {
+    overrides.unwrap_or_else(embedded_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{embedded_files, manifest, resolve};
+
+    #[test]
+    fn embedded_corelib_has_lib_file() {
+        assert!(manifest().contains(&"lib.cairo"));
+        assert_eq!(manifest().len(), embedded_files().len());
+    }
+
+    #[test]
+    fn overrides_replace_embedded_corelib() {
+        let overrides = BTreeMap::from([("lib.cairo".to_string(), String::new())]);
+        assert_eq!(resolve(Some(overrides.clone())), overrides);
+    }
+}
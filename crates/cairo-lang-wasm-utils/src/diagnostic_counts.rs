@@ -0,0 +1,56 @@
+//! Counts of the diagnostics of each file, so multi-file project UIs can badge their file tree
+//! without parsing the diagnostics text.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::diagnostic_listener::StreamedDiagnostic;
+
+/// The number of diagnostics of a file, by severity.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, JsonSchema)]
+pub struct FileDiagnosticCounts {
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// Returns the counts of the diagnostics of `diagnostics`, as formatted by the compiler, by file.
+///
+/// Files are named as in the `-->` lines of the diagnostics, and the diagnostics of the files
+/// generated from a file by macros, such as `lib.cairo[expanded]`, are counted in the file itself.
+/// Diagnostics without a location, or located in hidden files, are not counted.
+pub fn by_file(diagnostics: &str) -> BTreeMap<String, FileDiagnosticCounts> {
+    let mut counts = BTreeMap::<String, FileDiagnosticCounts>::new();
+    for entry in entries(diagnostics) {
+        let diagnostic = StreamedDiagnostic::parse(entry);
+        let Some(location) = diagnostic.location else {
+            continue;
+        };
+        let file = location.file.split('[').next().unwrap_or(&location.file);
+        let file_counts = counts.entry(file.to_string()).or_default();
+        match diagnostic.severity.as_str() {
+            "warning" => file_counts.warnings += 1,
+            _ => file_counts.errors += 1,
+        }
+    }
+    counts
+}
+
+/// Splits `diagnostics` into its entries, each starting with an `error` or `warning` line. The
+/// lines of the annotated sources are indented, so they never start an entry.
+fn entries(diagnostics: &str) -> Vec<&str> {
+    let mut starts = vec![];
+    let mut offset = 0;
+    for line in diagnostics.split_inclusive('\n') {
+        let is_header = ["error", "warning"].iter().any(|severity| {
+            line.strip_prefix(severity).is_some_and(|rest| rest.starts_with(['[', ':']))
+        });
+        if is_header {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+    let ends = starts.iter().skip(1).copied().chain([diagnostics.len()]);
+    starts.iter().zip(ends).map(|(start, end)| &diagnostics[*start..end]).collect()
+}
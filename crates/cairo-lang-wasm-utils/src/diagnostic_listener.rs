@@ -0,0 +1,93 @@
+//! Streaming of the compiler diagnostics to a listener installed by the host, so slow compiles of
+//! big projects can display their errors as they are produced instead of with the response.
+
+use std::cell::RefCell;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+thread_local! {
+    static LISTENER: RefCell<Option<Box<dyn Fn(&StreamedDiagnostic)>>> =
+        const { RefCell::new(None) };
+}
+
+/// A diagnostic, as passed to the listener.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct StreamedDiagnostic {
+    /// `error` or `warning`.
+    pub severity: String,
+    /// The code of the diagnostic, such as `E0006`, if it has one.
+    pub error_code: Option<String>,
+    /// The message of the diagnostic, without its location.
+    pub message: String,
+    /// The start of the span of the diagnostic, `None` for diagnostics without a location.
+    pub location: Option<DiagnosticLocation>,
+    /// The diagnostic as written in the `diagnostics` of the response.
+    pub text: String,
+}
+
+/// The start of the span of a diagnostic.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DiagnosticLocation {
+    /// The file, as named in the `--> ` line of the diagnostic.
+    pub file: String,
+    /// The 1-based line.
+    pub line: usize,
+    /// The 1-based column.
+    pub column: usize,
+}
+
+impl StreamedDiagnostic {
+    /// Parses a diagnostic formatted by the compiler, as in `error[E0006]: Identifier not found.`
+    /// followed by a ` --> lib.cairo:1:20` location line and the annotated source.
+    pub fn parse(text: &str) -> Self {
+        let first_line = text.lines().next().unwrap_or_default();
+        let (head, message) = first_line.split_once(": ").unwrap_or(("error", first_line));
+        let (severity, error_code) = match head.split_once('[') {
+            Some((severity, code)) => (severity, Some(code.trim_end_matches(']').to_string())),
+            None => (head, None),
+        };
+        let location = text
+            .lines()
+            .find_map(|line| line.trim_start().strip_prefix("--> "))
+            .and_then(|location| {
+                let mut parts = location.rsplitn(3, ':');
+                let column = parts.next()?.parse().ok()?;
+                let line = parts.next()?.parse().ok()?;
+                Some(DiagnosticLocation { file: parts.next()?.to_string(), line, column })
+            });
+        Self {
+            severity: severity.to_string(),
+            error_code,
+            message: message.to_string(),
+            location,
+            text: text.to_string(),
+        }
+    }
+}
+
+/// Installs `listener`, called with each diagnostic as it is reported, or removes the installed
+/// listener if `None`.
+pub fn set_listener(listener: Option<Box<dyn Fn(&StreamedDiagnostic)>>) {
+    LISTENER.set(listener);
+}
+
+/// Passes the diagnostic `text`, as formatted by the compiler, to the installed listener.
+pub fn report(text: &str) {
+    LISTENER.with_borrow(|listener| {
+        if let Some(listener) = listener {
+            listener(&StreamedDiagnostic::parse(text));
+        }
+    });
+}
+
+/// Installs a listener calling `callback` with each diagnostic serialized as a
+/// [StreamedDiagnostic].
+#[cfg(target_arch = "wasm32")]
+pub fn set_callback(callback: js_sys::Function) {
+    set_listener(Some(Box::new(move |diagnostic| {
+        let diagnostic = serde_json::to_string(diagnostic).expect("serialize diagnostic");
+        // The host is notified on a best-effort basis: its errors do not fail the compilation.
+        let _ = callback.call1(&js_sys::global(), &js_sys::JsString::from(diagnostic));
+    })));
+}
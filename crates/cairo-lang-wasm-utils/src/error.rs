@@ -0,0 +1,139 @@
+//! The error object of the WASM API responses, and conversion of internal panics into it.
+
+use std::any::Any;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::cell::{Cell, RefCell};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The category of an [ApiError].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorKind {
+    /// The request is malformed or refers to something that does not exist.
+    InvalidRequest,
+    /// The Cairo project failed to compile. See the diagnostics for details.
+    Compilation,
+    /// The program could not be executed.
+    Execution,
+    /// The request exceeds a limit configured by the host.
+    LimitExceeded,
+    /// The run exceeded the `max_steps` of the request.
+    StepLimitExceeded,
+    /// The run was cancelled by the host.
+    Cancelled,
+    /// A bug in the compiler or runner: a panic was caught while serving the request.
+    Internal,
+}
+
+/// An error returned in the `error` field of a response.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ApiError {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// The backtrace of an internal error, if the platform supports capturing one.
+    pub backtrace: Option<String>,
+    /// Valid values close to an invalid one of the request, such as existing function names, best
+    /// first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+}
+
+impl ApiError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into(), backtrace: None, suggestions: vec![] }
+    }
+
+    pub fn with_suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+
+    pub fn invalid_request(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::InvalidRequest, message)
+    }
+
+    pub fn compilation(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Compilation, message)
+    }
+
+    pub fn execution(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Execution, message)
+    }
+}
+
+thread_local! {
+    /// Whether a [catch_internal] call is in progress on this thread.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    /// The backtrace of the last panic caught by [catch_internal] on this thread.
+    static LAST_BACKTRACE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+/// Runs `f`, converting a panic into an [ErrorKind::Internal] error.
+///
+/// Every request builds its own compilation database, so nothing but the unwound stack is left
+/// behind by the panic, and the module keeps serving requests. Note that catching requires
+/// unwinding: a module built with `panic = "abort"` (the default of `wasm32-unknown-unknown`)
+/// still traps, which is why `scripts/wasm_pack.sh` builds with `panic = "unwind"`.
+pub fn catch_internal<T>(f: impl FnOnce() -> T) -> Result<T, ApiError> {
+    install_hook();
+    let was_catching = CATCHING.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.set(was_catching);
+    result.map_err(|payload| ApiError {
+        kind: ErrorKind::Internal,
+        message: panic_message(payload.as_ref()),
+        backtrace: LAST_BACKTRACE.take(),
+        suggestions: vec![],
+    })
+}
+
+/// Installs a panic hook recording the backtrace of panics caught by [catch_internal] instead of
+/// printing them. Other panics are forwarded to the previous hook.
+fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if CATCHING.get() {
+                let backtrace = Backtrace::force_capture();
+                let backtrace = (backtrace.status() == BacktraceStatus::Captured)
+                    .then(|| backtrace.to_string());
+                LAST_BACKTRACE.set(backtrace);
+            } else {
+                previous_hook(info);
+            }
+        }));
+    });
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic payload.".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorKind, catch_internal};
+
+    #[test]
+    fn converts_panics_into_internal_errors() {
+        assert_eq!(catch_internal(|| 7).unwrap(), 7);
+
+        let error = catch_internal::<()>(|| panic!("compiler bug {}", 7)).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Internal);
+        assert_eq!(error.message, "compiler bug 7");
+
+        // The module keeps working after a caught panic.
+        assert_eq!(catch_internal(|| 8).unwrap(), 8);
+    }
+}
@@ -0,0 +1,29 @@
+//! The gas policy of the host, filling in and capping the `available_gas` of the runs.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+/// Defaults and caps of the gas available to the runs. `None` means unset, which is the default.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct GasPolicy {
+    /// The gas available to the runs whose request sets no `available_gas`.
+    pub default_available_gas: Option<usize>,
+    /// The maximal gas available to a run. Larger `available_gas` values are lowered to it.
+    pub max_available_gas: Option<usize>,
+}
+
+impl GasPolicy {
+    /// Returns the gas available to a run whose request sets `available_gas`.
+    pub fn apply(&self, available_gas: Option<usize>) -> Option<usize> {
+        let available_gas = available_gas.or(self.default_available_gas)?;
+        Some(self.max_available_gas.map_or(available_gas, |max| available_gas.min(max)))
+    }
+}
+
+/// Returns the gas policy of the current [config::HostConfig].
+pub fn current() -> GasPolicy {
+    config::current().gas
+}
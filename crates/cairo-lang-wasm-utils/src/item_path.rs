@@ -0,0 +1,25 @@
+//! Matching of the item paths given by requests, such as the function to run.
+
+/// Returns whether `suffix` designates the item at the full `path`: `suffix` is either the whole
+/// path or its trailing `::`-separated segments, so that `add` matches `math::add` but not
+/// `math::safe_add`. A leading `::`, as in `::main`, is ignored.
+pub fn matches_suffix(path: &str, suffix: &str) -> bool {
+    let suffix = suffix.strip_prefix("::").unwrap_or(suffix);
+    path == suffix || path.strip_suffix(suffix).is_some_and(|prefix| prefix.ends_with("::"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches_suffix;
+
+    #[test]
+    fn matches_whole_segments() {
+        assert!(matches_suffix("test::math::add", "add"));
+        assert!(matches_suffix("test::math::add", "::add"));
+        assert!(matches_suffix("test::math::add", "math::add"));
+        assert!(matches_suffix("test::math::add", "test::math::add"));
+        assert!(!matches_suffix("test::math::safe_add", "add"));
+        assert!(!matches_suffix("test::math::safe_add", "::add"));
+        assert!(!matches_suffix("test::math::add", "th::add"));
+    }
+}
@@ -0,0 +1,24 @@
+//! Shared utilities for the WASM-friendly Cairo crates.
+//!
+//! Both `cairo-lang-compiler-wasm` and `cairo-lang-runner-wasm` depend on this crate, so a bundle
+//! linking both of them carries a single copy of the embedded corelib.
+
+pub mod allocations;
+pub mod build_info;
+pub mod cancellation;
+pub mod clock;
+pub mod compiled_programs;
+pub mod config;
+pub mod corelib;
+pub mod diagnostic_counts;
+pub mod diagnostic_listener;
+pub mod error;
+pub mod gas;
+pub mod item_path;
+pub mod limits;
+pub mod logging;
+pub mod parse;
+pub mod plugins;
+pub mod source;
+pub mod state;
+pub mod telemetry;
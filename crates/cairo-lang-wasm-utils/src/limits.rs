@@ -0,0 +1,203 @@
+//! Size limits on request inputs, checked before any work is done.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::error::{ApiError, ErrorKind};
+
+/// Limits on the sources of a request. `None` means unlimited, which is the default.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct InputLimits {
+    /// The maximal number of source files, counting corelib overrides.
+    pub max_files: Option<usize>,
+    /// The maximal total size of the sources in bytes.
+    pub max_total_source_bytes: Option<usize>,
+    /// The maximal size of a single source file in bytes.
+    pub max_file_bytes: Option<usize>,
+    /// The maximal size in bytes of a single literal of the sources, such as a string or a number,
+    /// whose compilation grows with its size.
+    pub max_literal_bytes: Option<usize>,
+}
+
+impl InputLimits {
+    /// Checks the `(path, content)` pairs of a request against the limits.
+    pub fn check_files<'a>(
+        &self,
+        files: impl IntoIterator<Item = (&'a String, &'a String)>,
+    ) -> Result<(), ApiError> {
+        let mut file_count = 0;
+        let mut total_bytes = 0;
+        for (path, content) in files {
+            file_count += 1;
+            total_bytes += content.len();
+            self.check_file_size(path, content.len())?;
+            self.check_literals(path, content)?;
+        }
+        if let Some(max_files) = self.max_files
+            && file_count > max_files
+        {
+            return Err(limit_exceeded(format!(
+                "Request has {file_count} files, exceeding the limit of {max_files} files."
+            )));
+        }
+        self.check_total_bytes(total_bytes)
+    }
+
+    /// Checks the size in bytes of a single file against the limits.
+    pub fn check_file_size(&self, path: &str, size: usize) -> Result<(), ApiError> {
+        match self.max_file_bytes {
+            Some(max_file_bytes) if size > max_file_bytes => Err(limit_exceeded(format!(
+                "File `{path}` is {size} bytes long, exceeding the limit of {max_file_bytes} \
+                 bytes."
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the sizes of the literals of a Cairo source file against the limits.
+    pub fn check_literals(&self, path: &str, content: &str) -> Result<(), ApiError> {
+        let Some(max_literal_bytes) = self.max_literal_bytes else {
+            return Ok(());
+        };
+        match find_literal_longer_than(content, max_literal_bytes) {
+            Some((line, size)) => Err(limit_exceeded(format!(
+                "Literal at `{path}:{line}` is {size} bytes long, exceeding the limit of \
+                 {max_literal_bytes} bytes."
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks a single source that is not split into files, such as a Sierra program.
+    pub fn check_source(&self, name: &str, source: &str) -> Result<(), ApiError> {
+        if let Some(max_file_bytes) = self.max_file_bytes
+            && source.len() > max_file_bytes
+        {
+            return Err(limit_exceeded(format!(
+                "{name} is {} bytes long, exceeding the limit of {max_file_bytes} bytes.",
+                source.len()
+            )));
+        }
+        self.check_total_bytes(source.len())
+    }
+
+    fn check_total_bytes(&self, total_bytes: usize) -> Result<(), ApiError> {
+        match self.max_total_source_bytes {
+            Some(max_total_bytes) if total_bytes > max_total_bytes => Err(limit_exceeded(format!(
+                "Request sources are {total_bytes} bytes long, exceeding the limit of \
+                 {max_total_bytes} bytes."
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns the limits of the current [config::HostConfig].
+pub fn current() -> InputLimits {
+    config::current().limits
+}
+
+/// Returns the line and the size of the first literal of `content` longer than `max_bytes`.
+///
+/// Only string, short string and number literals are recognized, skipping comments, which is enough
+/// to bound them without parsing the file.
+fn find_literal_longer_than(content: &str, max_bytes: usize) -> Option<(usize, usize)> {
+    let bytes = content.as_bytes();
+    let mut line = 1;
+    let mut index = 0;
+    while index < bytes.len() {
+        let start = index;
+        match bytes[index] {
+            b'\n' => {
+                line += 1;
+                index += 1;
+            }
+            b'/' if bytes.get(index + 1) == Some(&b'/') => {
+                while index < bytes.len() && bytes[index] != b'\n' {
+                    index += 1;
+                }
+            }
+            quote @ (b'"' | b'\'') => {
+                let start_line = line;
+                index += 1;
+                while index < bytes.len() && bytes[index] != quote {
+                    match bytes[index] {
+                        b'\\' => index += 1,
+                        b'\n' => line += 1,
+                        _ => {}
+                    }
+                    index += 1;
+                }
+                let size = index.min(bytes.len()) - start - 1;
+                if size > max_bytes {
+                    return Some((start_line, size));
+                }
+                index += 1;
+            }
+            byte if byte.is_ascii_alphanumeric() || byte == b'_' => {
+                while index < bytes.len()
+                    && (bytes[index].is_ascii_alphanumeric() || bytes[index] == b'_')
+                {
+                    index += 1;
+                }
+                // Identifiers are bounded by the file size alone.
+                if byte.is_ascii_digit() && index - start > max_bytes {
+                    return Some((line, index - start));
+                }
+            }
+            _ => index += 1,
+        }
+    }
+    None
+}
+
+fn limit_exceeded(message: String) -> ApiError {
+    ApiError::new(ErrorKind::LimitExceeded, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::InputLimits;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn check_files_against_limits() {
+        let files = BTreeMap::from([
+            ("lib.cairo".to_string(), "mod a;".to_string()),
+            ("a.cairo".to_string(), "fn f() {}".to_string()),
+        ]);
+        assert!(InputLimits::default().check_files(&files).is_ok());
+
+        let limits = InputLimits { max_files: Some(1), ..Default::default() };
+        assert_eq!(limits.check_files(&files).unwrap_err().kind, ErrorKind::LimitExceeded);
+
+        let limits = InputLimits { max_file_bytes: Some(8), ..Default::default() };
+        let error = limits.check_files(&files).unwrap_err();
+        assert!(error.message.contains("a.cairo"), "message={}", error.message);
+
+        let limits = InputLimits { max_total_source_bytes: Some(15), ..Default::default() };
+        assert_eq!(limits.check_files(&files).unwrap_err().kind, ErrorKind::LimitExceeded);
+    }
+
+    #[test]
+    fn check_literals_against_limits() {
+        let limits = InputLimits { max_literal_bytes: Some(8), ..Default::default() };
+        let source =
+            "// A comment longer than the limit.\nfn f() -> felt252 {\n    'abcdefgh'\n}\n";
+        assert!(limits.check_literals("lib.cairo", source).is_ok());
+
+        let source =
+            "fn f() -> ByteArray {\n    let n = 0x1234;\n    \"a \\\"long\\\" string\"\n}\n";
+        let error = limits.check_literals("lib.cairo", source).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::LimitExceeded);
+        assert!(error.message.contains("`lib.cairo:3` is 17 bytes"), "message={}", error.message);
+
+        let source = "const N: u256 = 1234567890;";
+        let error = limits.check_literals("lib.cairo", source).unwrap_err();
+        assert!(error.message.contains("`lib.cairo:1` is 10 bytes"), "message={}", error.message);
+    }
+}
@@ -0,0 +1,120 @@
+//! Capturing of the compiler's `tracing` events into a bounded in-memory buffer.
+//!
+//! There is no stderr to log into inside a browser, so when a request asks for `debug` output the
+//! events are collected while the request is served and returned with the response instead.
+
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use tracing::Level;
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+
+/// The maximal number of entries kept in a [DebugLog]. Older entries are dropped first.
+pub const DEBUG_LOG_CAPACITY: usize = 2000;
+
+/// The events logged while serving a request.
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct DebugLog {
+    /// The formatted events, oldest first.
+    pub entries: Vec<String>,
+    /// The number of older entries dropped to keep the log within [DEBUG_LOG_CAPACITY].
+    pub dropped: usize,
+}
+
+/// Runs `f`, collecting the `tracing` events of level `DEBUG` and above it emits if `enabled`.
+///
+/// Events of `salsa` are excluded, as in the native `init_logging`. The collecting subscriber is
+/// only installed for the duration of `f` on the current thread.
+pub fn capture<T>(enabled: bool, f: impl FnOnce() -> T) -> (T, Option<DebugLog>) {
+    if !enabled {
+        return (f(), None);
+    }
+
+    let buffer = LogBuffer::default();
+    let layer = tracing_subscriber::fmt::layer()
+        .without_time()
+        .with_ansi(false)
+        .with_level(true)
+        .with_target(true)
+        .with_writer(buffer.clone())
+        .with_filter(filter_fn(|meta| {
+            *meta.level() <= Level::DEBUG && !meta.target().starts_with("salsa")
+        }));
+    let subscriber = tracing_subscriber::registry().with(layer);
+
+    let result = tracing::subscriber::with_default(subscriber, f);
+    (result, Some(buffer.take()))
+}
+
+/// A shared ring buffer the formatting layer writes events into.
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<LogBufferState>>);
+
+#[derive(Default)]
+struct LogBufferState {
+    entries: VecDeque<String>,
+    dropped: usize,
+}
+
+impl LogBuffer {
+    fn take(&self) -> DebugLog {
+        let mut state = self.0.lock().unwrap();
+        DebugLog {
+            entries: std::mem::take(&mut state.entries).into(),
+            dropped: std::mem::take(&mut state.dropped),
+        }
+    }
+}
+
+impl io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The formatting layer writes each event in a single call.
+        let mut state = self.0.lock().unwrap();
+        for line in String::from_utf8_lossy(buf).lines() {
+            if state.entries.len() == DEBUG_LOG_CAPACITY {
+                state.entries.pop_front();
+                state.dropped += 1;
+            }
+            state.entries.push_back(line.to_string());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogBuffer {
+    type Writer = LogBuffer;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::capture;
+
+    #[test]
+    fn captures_debug_events_only_when_enabled() {
+        let (value, log) = capture(true, || {
+            tracing::debug!(target: "optimization", "inlining `main`");
+            tracing::trace!("too verbose");
+            7
+        });
+        assert_eq!(value, 7);
+        let log = log.unwrap();
+        assert_eq!(log.entries.len(), 1);
+        assert!(log.entries[0].contains("inlining `main`"), "entries={:?}", log.entries);
+
+        let (_, log) = capture(false, || tracing::debug!("ignored"));
+        assert!(log.is_none());
+    }
+}
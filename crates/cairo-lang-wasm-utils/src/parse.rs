@@ -0,0 +1,125 @@
+//! Parsing of the JSON requests of the endpoints.
+//!
+//! Unknown fields are ignored by default, as serde does. Requests with `"strict": true` reject them
+//! instead, so typos such as `availible_gas` fail loudly rather than leaving the field unset. The
+//! fields are checked against the JSON Schema of the request, as returned by `api_schema`.
+
+use schemars::JsonSchema;
+use schemars::generate::SchemaSettings;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::error::ApiError;
+
+/// The top-level field enabling the strict mode, accepted by every request.
+const STRICT_FIELD: &str = "strict";
+
+/// Parses a request of type `T`, rejecting its unknown fields when it is strict.
+pub fn request<T: DeserializeOwned + JsonSchema>(request_json: &str) -> Result<T, ApiError> {
+    let request = serde_json::from_str(request_json).map_err(|error| {
+        ApiError::invalid_request(format!("Failed parsing request JSON: {error}"))
+    })?;
+    // Parsing succeeded, so the JSON is valid.
+    let value: Value = serde_json::from_str(request_json).expect("valid request JSON");
+    if value.get(STRICT_FIELD) != Some(&Value::Bool(true)) {
+        return Ok(request);
+    }
+
+    let schema =
+        SchemaSettings::default().for_deserialize().into_generator().into_root_schema_for::<T>();
+    let schema = schema.as_value();
+    let defs = schema.get("$defs").and_then(Value::as_object);
+    let mut unknown = vec![];
+    collect_unknown_fields(&value, schema, defs, "", &mut unknown);
+    unknown.retain(|path| path != STRICT_FIELD);
+    if unknown.is_empty() {
+        return Ok(request);
+    }
+    let unknown = unknown.iter().map(|path| format!("`{path}`")).collect::<Vec<_>>().join(", ");
+    Err(ApiError::invalid_request(format!("Unknown fields in the strict request: {unknown}.")))
+}
+
+/// Pushes to `unknown` the paths of the fields of `value` not declared by `schema`, where `path` is
+/// the path of `value`.
+fn collect_unknown_fields(
+    value: &Value,
+    schema: &Value,
+    defs: Option<&Map<String, Value>>,
+    path: &str,
+    unknown: &mut Vec<String>,
+) {
+    let mut schemas = vec![];
+    collect_subschemas(schema, defs, &mut schemas);
+    match value {
+        Value::Object(fields) => {
+            // The fields of all the variants of an enum are accepted.
+            let properties = schemas
+                .iter()
+                .filter_map(|schema| schema.get("properties")?.as_object())
+                .collect::<Vec<_>>();
+            // The schema of the values of maps.
+            let values_schema = schemas
+                .iter()
+                .find_map(|schema| schema.get("additionalProperties").filter(|s| s.is_object()));
+            for (name, field) in fields {
+                let field_path =
+                    if path.is_empty() { name.clone() } else { format!("{path}.{name}") };
+                match properties
+                    .iter()
+                    .find_map(|properties| properties.get(name))
+                    .or(values_schema)
+                {
+                    Some(field_schema) => {
+                        collect_unknown_fields(field, field_schema, defs, &field_path, unknown)
+                    }
+                    // Objects without declared fields, such as arbitrary JSON, accept any field.
+                    None if !properties.is_empty() => unknown.push(field_path),
+                    None => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                let item_schema = schemas.iter().find_map(|schema| {
+                    schema
+                        .get("prefixItems")
+                        .and_then(|items| items.get(index))
+                        .or_else(|| schema.get("items").filter(|items| items.is_object()))
+                });
+                if let Some(item_schema) = item_schema {
+                    collect_unknown_fields(
+                        item,
+                        item_schema,
+                        defs,
+                        &format!("{path}[{index}]"),
+                        unknown,
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Pushes to `schemas` the schema and the schemas it refers to or combines.
+fn collect_subschemas<'a>(
+    schema: &'a Value,
+    defs: Option<&'a Map<String, Value>>,
+    schemas: &mut Vec<&'a Value>,
+) {
+    let Some(object) = schema.as_object() else {
+        return;
+    };
+    schemas.push(schema);
+    if let Some(name) =
+        object.get("$ref").and_then(Value::as_str).and_then(|path| path.strip_prefix("#/$defs/"))
+        && let Some(def) = defs.and_then(|defs| defs.get(name))
+    {
+        collect_subschemas(def, defs, schemas);
+    }
+    for combinator in ["allOf", "anyOf", "oneOf"] {
+        for subschema in object.get(combinator).and_then(Value::as_array).into_iter().flatten() {
+            collect_subschemas(subschema, defs, schemas);
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! The plugins the in-memory projects of the modules are compiled with.
+
+use cairo_lang_plugins::plugins::ForeignTestAttributesPlugin;
+use cairo_lang_semantic::plugin::PluginSuite;
+
+/// Returns the plugin suite to pass to `prepare_in_memory_project_db`.
+///
+/// In-memory projects are usually pasted from Scarb projects, so test attributes are accepted and
+/// test functions are dropped, see [`ForeignTestAttributesPlugin`].
+pub fn in_memory_project_suite() -> PluginSuite {
+    let mut suite = PluginSuite::default();
+    suite.add_plugin::<ForeignTestAttributesPlugin>();
+    suite
+}
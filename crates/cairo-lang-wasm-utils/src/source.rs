@@ -0,0 +1,188 @@
+//! Decoding of the source files of a request.
+//!
+//! Sources pasted from other editors often carry a byte order mark or Windows line endings, which
+//! are normalized away. A file may also be given as an array of bytes, in which case it must be
+//! valid UTF-8; otherwise the request is rejected with an error naming the file.
+//!
+//! Endpoints accepting [SourceFiles] also take files as `{ "content": .., "hidden": true }`
+//! objects, marking them as hidden from the responses, such as the test harnesses graders add to
+//! the code of students.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::Utf8Error;
+
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+/// The content of a source file in a request: either text or raw bytes.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum SourceContent {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl SourceContent {
+    fn decode(self, path: &str) -> Result<String, String> {
+        let text = match self {
+            SourceContent::Text(text) => text,
+            SourceContent::Bytes(bytes) => match String::from_utf8(bytes) {
+                Ok(text) => text,
+                Err(error) => {
+                    return Err(invalid_utf8_message(path, error.utf8_error(), error.as_bytes()));
+                }
+            },
+        };
+        Ok(normalize(&text))
+    }
+}
+
+/// Strips a leading byte order mark and converts `\r\n` and lone `\r` line endings to `\n`.
+pub fn normalize(content: &str) -> String {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    if content.contains('\r') {
+        content.replace("\r\n", "\n").replace('\r', "\n")
+    } else {
+        content.to_string()
+    }
+}
+
+/// Deserializes a map from file path to [SourceContent], decoding and normalizing every file.
+pub fn deserialize_files<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, String>, D::Error> {
+    decode_files(BTreeMap::deserialize(deserializer)?)
+}
+
+/// Like [deserialize_files], for an optional map.
+pub fn deserialize_optional_files<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<BTreeMap<String, String>>, D::Error> {
+    Option::deserialize(deserializer)?.map(decode_files).transpose()
+}
+
+/// The source files of a request, with the paths of those hidden from its response.
+#[derive(Debug, Default)]
+pub struct SourceFiles {
+    pub contents: BTreeMap<String, String>,
+    /// The paths of the files whose content and locations must not appear in the response.
+    pub hidden: BTreeSet<String>,
+}
+
+/// A source file of a request, optionally marked as hidden.
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum SourceEntry {
+    Content(SourceContent),
+    Marked {
+        content: SourceContent,
+        #[serde(default)]
+        hidden: bool,
+    },
+}
+
+impl<'de> Deserialize<'de> for SourceFiles {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut files = SourceFiles::default();
+        for (path, entry) in BTreeMap::<String, SourceEntry>::deserialize(deserializer)? {
+            let content = match entry {
+                SourceEntry::Content(content) => content,
+                SourceEntry::Marked { content, hidden } => {
+                    if hidden {
+                        files.hidden.insert(path.clone());
+                    }
+                    content
+                }
+            };
+            let text = content.decode(&path).map_err(D::Error::custom)?;
+            files.contents.insert(path, text);
+        }
+        Ok(files)
+    }
+}
+
+impl JsonSchema for SourceFiles {
+    fn schema_name() -> Cow<'static, str> {
+        "SourceFiles".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        BTreeMap::<String, SourceEntry>::json_schema(generator)
+    }
+}
+
+/// Deserializes a single [SourceContent], decoding and normalizing it.
+pub fn deserialize_source<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    SourceContent::deserialize(deserializer)?.decode("source").map_err(D::Error::custom)
+}
+
+fn decode_files<E: Error>(
+    files: BTreeMap<String, SourceContent>,
+) -> Result<BTreeMap<String, String>, E> {
+    files
+        .into_iter()
+        .map(|(path, content)| {
+            let text = content.decode(&path).map_err(E::custom)?;
+            Ok((path, text))
+        })
+        .collect()
+}
+
+/// Returns the error message for a file whose content `bytes` is not valid UTF-8.
+pub fn invalid_utf8_message(path: &str, error: Utf8Error, bytes: &[u8]) -> String {
+    let offset = error.valid_up_to();
+    let line = bytes[..offset].iter().filter(|byte| **byte == b'\n').count() + 1;
+    format!(
+        "File `{path}` is not valid UTF-8: invalid byte at offset {offset} (line {line}). Save \
+         the file with UTF-8 encoding."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Deserialize)]
+    struct Request {
+        #[serde(deserialize_with = "super::deserialize_files")]
+        files: BTreeMap<String, String>,
+    }
+
+    #[test]
+    fn normalizes_bom_and_line_endings() {
+        let request = json!({ "files": {
+            "lib.cairo": "\u{feff}fn main() {\r\n}\r",
+            "a.cairo": [102, 110, 32, 97, 40, 41, 32, 123, 125],
+        }});
+        let request: Request = serde_json::from_value(request).unwrap();
+        assert_eq!(request.files["lib.cairo"], "fn main() {\n}\n");
+        assert_eq!(request.files["a.cairo"], "fn a() {}");
+    }
+
+    #[test]
+    fn collects_hidden_files() {
+        let files = json!({
+            "lib.cairo": "mod tests;",
+            "tests.cairo": { "content": "\u{feff}fn check() {}", "hidden": true },
+        });
+        let files: super::SourceFiles = serde_json::from_value(files).unwrap();
+        assert_eq!(files.contents["tests.cairo"], "fn check() {}");
+        assert_eq!(files.hidden.into_iter().collect::<Vec<_>>(), ["tests.cairo"]);
+    }
+
+    #[test]
+    fn reports_invalid_utf8_with_path() {
+        let request = json!({ "files": { "lib.cairo": [10, 102, 255] } });
+        let error = serde_json::from_value::<Request>(request).err().unwrap().to_string();
+        assert!(
+            error
+                .contains("File `lib.cairo` is not valid UTF-8: invalid byte at offset 2 (line 2)"),
+            "error={error}"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Snapshots of the module state, so hosts spawning many short-lived workers can restore the state
+//! of a prepared instance in each of them instead of replaying its setup.
+//!
+//! The snapshot holds the configuration set through `configure`, and the caches the crates of the
+//! module keep across requests, such as memoized run responses and compiled programs. The compiler
+//! itself keeps no state across requests: each request builds its own database, corelib included,
+//! and databases cannot be serialized.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{self, HostConfig};
+use crate::error::ApiError;
+
+/// The state of a module, as returned by `snapshot_state`.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleState {
+    /// The version of the module that took the snapshot. Snapshots are only restored by the same
+    /// version.
+    pub version: String,
+    /// The configuration in effect.
+    pub config: HostConfig,
+    /// The contents of the caches of the module, by cache name.
+    #[serde(default)]
+    pub caches: BTreeMap<String, Value>,
+}
+
+/// A cache kept by a crate of the module across requests, captured in the [ModuleState].
+pub struct StateCache {
+    /// The name of the cache in [ModuleState::caches].
+    pub name: &'static str,
+    /// Returns the contents of the cache.
+    pub snapshot: fn() -> Value,
+    /// Replaces the contents of the cache by contents returned by `snapshot`.
+    pub restore: fn(Value) -> Result<(), ApiError>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RestoreStateResponse {
+    pub success: bool,
+    pub error: Option<ApiError>,
+}
+
+/// Returns the state of the module, with the contents of `caches`, serialized as a [ModuleState].
+pub fn snapshot_state(caches: &[StateCache]) -> String {
+    let state = ModuleState {
+        version: env!("CARGO_PKG_VERSION").into(),
+        config: config::current(),
+        caches: caches.iter().map(|cache| (cache.name.to_string(), (cache.snapshot)())).collect(),
+    };
+    serde_json::to_string(&state).expect("serialize module state")
+}
+
+/// Parses `state_json` as a [ModuleState] returned by [snapshot_state] and applies it, restoring
+/// the contents of `caches`. Returns a serialized [RestoreStateResponse].
+pub fn restore_state(state_json: &str, caches: &[StateCache]) -> String {
+    let result = serde_json::from_str::<ModuleState>(state_json)
+        .map_err(|error| {
+            ApiError::invalid_request(format!("Failed parsing module state JSON: {error}"))
+        })
+        .and_then(|state| {
+            let version = env!("CARGO_PKG_VERSION");
+            if state.version != version {
+                return Err(ApiError::invalid_request(format!(
+                    "The state was taken by version {} of the module, not by this version, \
+                     {version}.",
+                    state.version
+                )));
+            }
+            if let Some(name) =
+                state.caches.keys().find(|name| caches.iter().all(|cache| cache.name != *name))
+            {
+                return Err(ApiError::invalid_request(format!(
+                    "The state holds the `{name}` cache, which this module does not keep."
+                )));
+            }
+            config::set(state.config);
+            for (name, contents) in state.caches {
+                let cache = caches.iter().find(|cache| cache.name == name);
+                (cache.expect("Unknown caches were rejected.").restore)(contents)?;
+            }
+            Ok(())
+        });
+    let response = match result {
+        Ok(()) => RestoreStateResponse { success: true, error: None },
+        Err(error) => RestoreStateResponse { success: false, error: Some(error) },
+    };
+    serde_json::to_string(&response).expect("serialize restore state response")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use serde_json::{Value, json};
+
+    use super::{StateCache, restore_state, snapshot_state};
+    use crate::config;
+
+    thread_local! {
+        static COUNTER: Cell<u64> = const { Cell::new(0) };
+    }
+
+    const COUNTER_CACHE: StateCache = StateCache {
+        name: "counter",
+        snapshot: || COUNTER.get().into(),
+        restore: |contents| {
+            COUNTER.set(contents.as_u64().unwrap());
+            Ok(())
+        },
+    };
+
+    #[test]
+    fn restore_state_applies_snapshot() {
+        config::configure(&json!({ "limits": { "max_files": 3 } }).to_string());
+        COUNTER.set(7);
+        let snapshot = snapshot_state(&[COUNTER_CACHE]);
+        config::configure("{}");
+        COUNTER.set(0);
+
+        let response: Value =
+            serde_json::from_str(&restore_state(&snapshot, &[COUNTER_CACHE])).unwrap();
+        assert_eq!(response["success"], true, "response={response}");
+        assert_eq!(config::current().limits.max_files, Some(3));
+        assert_eq!(COUNTER.get(), 7);
+
+        // A module without the cache cannot restore it.
+        let response: Value = serde_json::from_str(&restore_state(&snapshot, &[])).unwrap();
+        assert_eq!(response["error"]["kind"], "invalid-request", "response={response}");
+
+        let mut stale: Value = serde_json::from_str(&snapshot).unwrap();
+        stale["version"] = "0.0.0".into();
+        let response: Value =
+            serde_json::from_str(&restore_state(&stale.to_string(), &[COUNTER_CACHE])).unwrap();
+        assert_eq!(response["error"]["kind"], "invalid-request", "response={response}");
+    }
+}
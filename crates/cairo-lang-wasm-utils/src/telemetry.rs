@@ -0,0 +1,84 @@
+//! Opt-in reporting of the outcomes of requests to a listener installed by the host, so hosted
+//! services can aggregate health metrics without inspecting the responses.
+//!
+//! The events are anonymized: they hold no sources, names or values of the requests, and the size
+//! of the sources is bucketed.
+
+use std::cell::RefCell;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::clock;
+use crate::error::{ApiError, ErrorKind};
+
+thread_local! {
+    static LISTENER: RefCell<Option<Box<dyn Fn(&TelemetryEvent)>>> = const { RefCell::new(None) };
+}
+
+/// The smallest bucket of the size of the sources, in bytes.
+const MIN_SIZE_BUCKET: usize = 1024;
+
+/// The outcome of a request, as passed to the listener.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TelemetryEvent {
+    /// The endpoint serving the request, such as `compile`.
+    pub endpoint: String,
+    /// The time taken to serve the request, in milliseconds.
+    pub duration_ms: f64,
+    pub success: bool,
+    /// The kind of the error of the response, if any.
+    pub error_kind: Option<ErrorKind>,
+    /// The size of the sources of the request rounded up to a power of two, in bytes, and at least
+    /// 1024.
+    pub source_size_bucket: usize,
+}
+
+/// Installs `listener`, called with the outcome of each request, or removes the installed listener
+/// if `None`.
+pub fn set_listener(listener: Option<Box<dyn Fn(&TelemetryEvent)>>) {
+    LISTENER.set(listener);
+}
+
+/// Returns the total size of the `(path, content)` pairs of a request, in bytes.
+pub fn source_bytes<'a>(files: impl IntoIterator<Item = (&'a String, &'a String)>) -> usize {
+    files.into_iter().map(|(_, content)| content.len()).sum()
+}
+
+/// Passes the outcome of a request of `endpoint` started at `started_ms`, as given by
+/// [clock::now_ms], to the installed listener.
+pub fn report(endpoint: &str, started_ms: f64, source_bytes: usize, error: Option<&ApiError>) {
+    report_outcome(endpoint, started_ms, source_bytes, error.map(|error| error.kind));
+}
+
+/// Same as [report], for a request whose outcome is only known by the kind of its error, if any,
+/// such as one answered with a cached response.
+pub fn report_outcome(
+    endpoint: &str,
+    started_ms: f64,
+    source_bytes: usize,
+    error_kind: Option<ErrorKind>,
+) {
+    LISTENER.with_borrow(|listener| {
+        if let Some(listener) = listener {
+            listener(&TelemetryEvent {
+                endpoint: endpoint.to_string(),
+                duration_ms: clock::now_ms() - started_ms,
+                success: error_kind.is_none(),
+                error_kind,
+                source_size_bucket: source_bytes.max(MIN_SIZE_BUCKET).next_power_of_two(),
+            });
+        }
+    });
+}
+
+/// Installs a listener calling `callback` with the outcome of each request serialized as a
+/// [TelemetryEvent].
+#[cfg(target_arch = "wasm32")]
+pub fn set_callback(callback: js_sys::Function) {
+    set_listener(Some(Box::new(move |event| {
+        let event = serde_json::to_string(event).expect("serialize telemetry event");
+        // The host is notified on a best-effort basis: its errors do not fail the request.
+        let _ = callback.call1(&js_sys::global(), &js_sys::JsString::from(event));
+    })));
+}
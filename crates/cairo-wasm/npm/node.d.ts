@@ -0,0 +1,18 @@
+export * from "./nodejs/cairo_wasm";
+
+/** Map from `/`-separated paths relative to the crate root to file contents. */
+export type CairoFiles = Record<string, string>;
+
+/** Request fields other than `files`. `crate_name` defaults to `"main"`. */
+export type DirRequest = Record<string, unknown>;
+
+export function readProjectDir(dir: string): CairoFiles;
+export function readProjectDirAsync(dir: string): Promise<CairoFiles>;
+
+export function compileDir(dir: string, request?: DirRequest): any;
+export function compileAndRunDir(dir: string, request?: DirRequest): any;
+export function checkDir(dir: string, request?: DirRequest): any;
+
+export function compileDirAsync(dir: string, request?: DirRequest): Promise<any>;
+export function compileAndRunDirAsync(dir: string, request?: DirRequest): Promise<any>;
+export function checkDirAsync(dir: string, request?: DirRequest): Promise<any>;
@@ -0,0 +1,156 @@
+// Node.js entry point of the `cairo-wasm` package.
+//
+// The WASM module is instantiated synchronously when this file is required, so every API function
+// can be called right away. On top of the raw JSON API, this file provides helpers reading a Cairo
+// project from disk into the `files` map of a request.
+
+"use strict";
+
+const fs = require("fs");
+const path = require("path");
+
+const wasm = require("./nodejs/cairo_wasm.js");
+
+/**
+ * Collects the `.cairo` files under `dir` into a map from `/`-separated relative paths to contents.
+ *
+ * If `dir` contains a `src` directory (the Scarb layout), the files are read from there, so that
+ * `src/lib.cairo` becomes `lib.cairo`.
+ */
+function readProjectDir(dir) {
+    const root = sourceRoot(dir);
+    const files = {};
+    for (const relative of listCairoFiles(root)) {
+        files[relative] = fileContent(fs.readFileSync(path.join(root, relative)));
+    }
+    return files;
+}
+
+/** Asynchronous variant of `readProjectDir`. */
+async function readProjectDirAsync(dir) {
+    const root = await sourceRootAsync(dir);
+    const files = {};
+    const relatives = await listCairoFilesAsync(root);
+    await Promise.all(
+        relatives.map(async (relative) => {
+            files[relative] = fileContent(await fs.promises.readFile(path.join(root, relative)));
+        }),
+    );
+    return files;
+}
+
+/** Compiles the project in `dir`. `request` holds the other fields of the compile request. */
+function compileDir(dir, request = {}) {
+    return callWithFiles(wasm.compile, readProjectDir(dir), request);
+}
+
+/** Compiles and runs the project in `dir`. `request` holds the other fields of the request. */
+function compileAndRunDir(dir, request = {}) {
+    return callWithFiles(wasm.compile_and_run, readProjectDir(dir), request);
+}
+
+/** Checks the project in `dir`. `request` holds the other fields of the check request. */
+function checkDir(dir, request = {}) {
+    return callWithFiles(wasm.check, readProjectDir(dir), request);
+}
+
+/** Asynchronous variant of `compileDir`. Only reading the project is asynchronous. */
+async function compileDirAsync(dir, request = {}) {
+    return callWithFiles(wasm.compile, await readProjectDirAsync(dir), request);
+}
+
+/** Asynchronous variant of `compileAndRunDir`. Only reading the project is asynchronous. */
+async function compileAndRunDirAsync(dir, request = {}) {
+    return callWithFiles(wasm.compile_and_run, await readProjectDirAsync(dir), request);
+}
+
+/** Asynchronous variant of `checkDir`. Only reading the project is asynchronous. */
+async function checkDirAsync(dir, request = {}) {
+    return callWithFiles(wasm.check, await readProjectDirAsync(dir), request);
+}
+
+/**
+ * Decodes a file read from disk. Content that is not valid UTF-8 is passed to the module as an
+ * array of bytes, which rejects it with an error naming the file and the offending offset.
+ */
+function fileContent(bytes) {
+    try {
+        return new TextDecoder("utf-8", { fatal: true, ignoreBOM: true }).decode(bytes);
+    } catch {
+        return Array.from(bytes);
+    }
+}
+
+function callWithFiles(endpoint, files, request) {
+    const fullRequest = { crate_name: "main", ...request, files };
+    return JSON.parse(endpoint(JSON.stringify(fullRequest)));
+}
+
+function sourceRoot(dir) {
+    const src = path.join(dir, "src");
+    try {
+        if (fs.statSync(src).isDirectory()) {
+            return src;
+        }
+    } catch (error) {
+        if (error.code !== "ENOENT") {
+            throw error;
+        }
+    }
+    return dir;
+}
+
+async function sourceRootAsync(dir) {
+    const src = path.join(dir, "src");
+    try {
+        if ((await fs.promises.stat(src)).isDirectory()) {
+            return src;
+        }
+    } catch (error) {
+        if (error.code !== "ENOENT") {
+            throw error;
+        }
+    }
+    return dir;
+}
+
+function listCairoFiles(root, relativeDir = "") {
+    const result = [];
+    for (const entry of fs.readdirSync(path.join(root, relativeDir), { withFileTypes: true })) {
+        const relative = relativeDir ? `${relativeDir}/${entry.name}` : entry.name;
+        if (entry.isDirectory()) {
+            result.push(...listCairoFiles(root, relative));
+        } else if (entry.isFile() && entry.name.endsWith(".cairo")) {
+            result.push(relative);
+        }
+    }
+    return result.sort();
+}
+
+async function listCairoFilesAsync(root, relativeDir = "") {
+    const result = [];
+    const entries = await fs.promises.readdir(path.join(root, relativeDir), {
+        withFileTypes: true,
+    });
+    for (const entry of entries) {
+        const relative = relativeDir ? `${relativeDir}/${entry.name}` : entry.name;
+        if (entry.isDirectory()) {
+            result.push(...(await listCairoFilesAsync(root, relative)));
+        } else if (entry.isFile() && entry.name.endsWith(".cairo")) {
+            result.push(relative);
+        }
+    }
+    return result.sort();
+}
+
+module.exports = {
+    ...wasm,
+    readProjectDir,
+    readProjectDirAsync,
+    compileDir,
+    compileAndRunDir,
+    checkDir,
+    compileDirAsync,
+    compileAndRunDirAsync,
+    checkDirAsync,
+};
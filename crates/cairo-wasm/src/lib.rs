@@ -0,0 +1,534 @@
+//! A single WASM module exposing the Cairo compiler, runner and formatter.
+//!
+//! The compile and run endpoints delegate to `cairo-lang-compiler-wasm` and
+//! `cairo-lang-runner-wasm`, which are linked without their own `wasm-bindgen` exports. Every
+//! endpoint accepts a JSON request and returns a JSON response with `success`, `diagnostics` and
+//! `error` fields.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::diagnostics::DiagnosticsReporter;
+use cairo_lang_compiler::prepare_in_memory_project_db;
+use cairo_lang_compiler::project::InMemoryProject;
+use cairo_lang_formatter::cairo_formatter::FormattingError;
+use cairo_lang_formatter::{CairoFormatter, FormatOutcome, FormatterConfig};
+use cairo_lang_lowering::utils::InliningStrategy;
+use cairo_lang_wasm_utils::diagnostic_counts::{self, FileDiagnosticCounts};
+use cairo_lang_wasm_utils::error::{self, ApiError, ErrorKind};
+use cairo_lang_wasm_utils::logging::{self, DebugLog};
+use cairo_lang_wasm_utils::{
+    build_info, config, corelib, diagnostic_listener, limits, parse, plugins, source, state,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+mod schema;
+pub use schema::api_schema;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CheckRequest {
+    pub crate_name: String,
+    #[serde(deserialize_with = "source::deserialize_files")]
+    #[schemars(with = "BTreeMap<String, source::SourceContent>")]
+    pub files: BTreeMap<String, String>,
+    #[serde(default, deserialize_with = "source::deserialize_optional_files")]
+    #[schemars(with = "Option<BTreeMap<String, source::SourceContent>>")]
+    pub corelib_files: Option<BTreeMap<String, String>>,
+    /// Whether to return the compiler's debug logs in the response.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct CheckResponse {
+    pub success: bool,
+    pub diagnostics: String,
+    /// The number of errors and warnings of each file of `diagnostics`.
+    pub diagnostics_by_file: BTreeMap<String, FileDiagnosticCounts>,
+    pub error: Option<ApiError>,
+    pub debug_log: Option<DebugLog>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FormatRequest {
+    #[serde(deserialize_with = "source::deserialize_source")]
+    #[schemars(with = "source::SourceContent")]
+    pub source: String,
+}
+
+#[derive(Debug, Default, Serialize, JsonSchema)]
+pub struct FormatResponse {
+    pub success: bool,
+    pub formatted: Option<String>,
+    pub changed: bool,
+    pub diagnostics: String,
+    pub error: Option<ApiError>,
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn compile(request_json: &str) -> String {
+    cairo_lang_compiler_wasm::compile(request_json)
+}
+
+/// Unpacks a zip or tar archive of a Cairo project into a compile request.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn import_archive(archive: &[u8]) -> String {
+    cairo_lang_compiler_wasm::import_archive(archive)
+}
+
+/// Returns the parameters and the return type of a function, for generating input forms.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn function_signature(request_json: &str, function: &str) -> String {
+    cairo_lang_compiler_wasm::function_signature(request_json, function)
+}
+
+/// Returns how a struct or an enum serializes, for building codecs of its values.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn type_layout(request_json: &str, type_path: &str) -> String {
+    cairo_lang_compiler_wasm::type_layout(request_json, type_path)
+}
+
+/// Returns the CASM of a Sierra program, or of a program compiled by `compile_program`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn disassemble(request_json: &str) -> String {
+    cairo_lang_compiler_wasm::disassemble(request_json)
+}
+
+/// Returns the values of the constants and the tags of the enum variants of a project.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn extract_constants(request_json: &str) -> String {
+    cairo_lang_compiler_wasm::extract_constants(request_json)
+}
+
+/// Returns the files of a project with a function inlined at its call sites.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn inline_function(request_json: &str, function: &str) -> String {
+    cairo_lang_compiler_wasm::inline_function(request_json, function)
+}
+
+/// Returns a minimized project raising the same internal compiler error as the given one.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn minimize(request_json: &str) -> String {
+    cairo_lang_compiler_wasm::minimize(request_json)
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn compile_and_run(request_json: &str) -> String {
+    cairo_lang_runner_wasm::compile_and_run(request_json)
+}
+
+/// Compiles a project once and runs several of its functions, sharing the output of a setup run.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn compile_and_run_batch(request_json: &str) -> String {
+    cairo_lang_runner_wasm::compile_and_run_batch(request_json)
+}
+
+/// Compiles a project and runs a function with random arguments until it fails.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn fuzz(request_json: &str) -> String {
+    cairo_lang_runner_wasm::fuzz(request_json)
+}
+
+/// Compiles a project and finds the smallest `available_gas` a function runs with.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn estimate_gas(request_json: &str) -> String {
+    cairo_lang_runner_wasm::estimate_gas(request_json)
+}
+
+/// Compiles a project once and runs it on test cases, comparing the outputs with the expected ones.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn grade(request_json: &str, cases_json: &str) -> String {
+    cairo_lang_runner_wasm::grade(request_json, cases_json)
+}
+
+/// Compiles a project once and runs a function several times, reporting the runs differing from the
+/// first one.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn check_determinism(request_json: &str, runs: usize) -> String {
+    cairo_lang_runner_wasm::check_determinism(request_json, runs)
+}
+
+/// Compiles a project once and keeps the program, returning a handle accepted by `run_sierra`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn compile_program(request_json: &str) -> String {
+    cairo_lang_runner_wasm::compile_program(request_json)
+}
+
+/// Frees a program compiled by `compile_program`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn close_program(program: u32) -> bool {
+    cairo_lang_runner_wasm::close_program(program)
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn run_sierra(request_json: &str) -> String {
+    cairo_lang_runner_wasm::run_sierra(request_json)
+}
+
+/// Runs an entry point of an executable assembled elsewhere, skipping Sierra.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn run_casm(request_json: &str) -> String {
+    cairo_lang_runner_wasm::run_casm(request_json)
+}
+
+/// Runs a standalone entry point of an executable in proof mode and proves the run with Stwo.
+#[cfg(feature = "prove")]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn prove(request_json: &str) -> String {
+    cairo_lang_runner_wasm::prove(request_json)
+}
+
+/// Calls an entry point of a contract class against provided storage values.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn call_contract(request_json: &str) -> String {
+    cairo_lang_runner_wasm::call_contract(request_json)
+}
+
+/// Opens a session keeping the Starknet state of successive `call_contract` calls.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn open_starknet_session() -> u32 {
+    cairo_lang_runner_wasm::open_starknet_session()
+}
+
+/// Frees the state of a session opened by `open_starknet_session`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn close_starknet_session(session: u32) -> bool {
+    cairo_lang_runner_wasm::close_starknet_session(session)
+}
+
+/// Sets the block timestamp seen by the next calls of a Starknet session.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn warp(session: u32, block_timestamp: u64) -> String {
+    cairo_lang_runner_wasm::warp(session, block_timestamp)
+}
+
+/// Sets the block number seen by the next calls of a Starknet session.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn roll(session: u32, block_number: u64) -> String {
+    cairo_lang_runner_wasm::roll(session, block_number)
+}
+
+/// Sets the caller address of the next calls of a Starknet session.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn prank(session: u32, caller_address: &str) -> String {
+    cairo_lang_runner_wasm::prank(session, caller_address)
+}
+
+/// Reads a storage variable or a `Map` entry from the storage of a contract.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn read_storage(request_json: &str) -> String {
+    cairo_lang_runner_wasm::read_storage(request_json)
+}
+
+/// Checks a contract class against the size limits of declarations.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn validate_declare(request_json: &str) -> String {
+    cairo_lang_runner_wasm::validate_declare(request_json)
+}
+
+/// Lists the runnable functions of a project or a Sierra program.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn list_entrypoints(request_json: &str) -> String {
+    cairo_lang_runner_wasm::list_entrypoints(request_json)
+}
+
+/// Returns the libfunc, the types and the source location of a statement of a Sierra program.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn explain_sierra_statement(request_json: &str, index: usize) -> String {
+    cairo_lang_runner_wasm::explain_sierra_statement(request_json, index)
+}
+
+/// Returns the part of a Sierra program reachable from one of its functions.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn slice_program(request_json: &str, function: &str) -> String {
+    cairo_lang_runner_wasm::slice_program(request_json, function)
+}
+
+/// Returns the gas costs of the branches of each libfunc of a project or a Sierra program.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn libfunc_costs(request_json: &str) -> String {
+    cairo_lang_runner_wasm::libfunc_costs(request_json)
+}
+
+/// Lists the `#[test]` functions of a project with their attributes, without running them.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn list_tests(request_json: &str) -> String {
+    cairo_lang_runner_wasm::list_tests(request_json)
+}
+
+/// Compiles a project with the test plugin and runs all its `#[test]` functions.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn run_tests(request_json: &str) -> String {
+    cairo_lang_runner_wasm::run_tests(request_json)
+}
+
+/// Runs a test compiled in a session opened by `list_tests`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn run_test(session: u32, test_id: &str) -> String {
+    cairo_lang_runner_wasm::run_test(session, test_id)
+}
+
+/// Frees the compiled tests of a session opened by `list_tests`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn close_test_session(session: u32) -> bool {
+    cairo_lang_runner_wasm::close_test_session(session)
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn examples() -> String {
+    cairo_lang_runner_wasm::examples()
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn self_test() -> String {
+    cairo_lang_runner_wasm::self_test()
+}
+
+/// Reports the diagnostics of the main crate without generating Sierra.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn check(request_json: &str) -> String {
+    let request: CheckRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serialize_check_response(CheckResponse {
+                error: Some(error),
+                ..Default::default()
+            });
+        }
+    };
+
+    let (result, debug_log) =
+        logging::capture(request.debug, || error::catch_internal(|| check_request(request)));
+    let mut response =
+        result.unwrap_or_else(|error| CheckResponse { error: Some(error), ..Default::default() });
+    response.debug_log = debug_log;
+    response.diagnostics_by_file = diagnostic_counts::by_file(&response.diagnostics);
+    serialize_check_response(response)
+}
+
+fn check_request(request: CheckRequest) -> CheckResponse {
+    if let Err(error) = limits::current()
+        .check_files(request.files.iter().chain(request.corelib_files.iter().flatten()))
+    {
+        return CheckResponse { error: Some(error), ..Default::default() };
+    }
+
+    let project = InMemoryProject {
+        main_crate_name: request.crate_name,
+        main_crate_files: request.files,
+        corelib_files: corelib::resolve(request.corelib_files),
+        main_crate_settings: None,
+    };
+    let (db, main_crate_inputs) = match prepare_in_memory_project_db(
+        &project,
+        InliningStrategy::Default,
+        plugins::in_memory_project_suite(),
+    ) {
+        Ok(prepared) => prepared,
+        Err(error) => {
+            return CheckResponse {
+                error: Some(ApiError::invalid_request(error.to_string())),
+                ..Default::default()
+            };
+        }
+    };
+
+    let mut diagnostics = String::new();
+    let found_errors = DiagnosticsReporter::callback(|diagnostic| {
+        let diagnostic = diagnostic.to_string();
+        diagnostic_listener::report(&diagnostic);
+        diagnostics.push_str(&diagnostic);
+    })
+    .with_crates(&main_crate_inputs)
+    .allow_warnings()
+    .check(&db);
+
+    CheckResponse {
+        success: !found_errors,
+        diagnostics,
+        diagnostics_by_file: BTreeMap::new(),
+        error: found_errors.then(|| ApiError::compilation("Compilation failed.")),
+        debug_log: None,
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn format(request_json: &str) -> String {
+    let request: FormatRequest = match parse::request(request_json) {
+        Ok(request) => request,
+        Err(error) => {
+            return serialize_format_response(FormatResponse {
+                error: Some(error),
+                ..Default::default()
+            });
+        }
+    };
+
+    if let Err(error) = limits::current().check_source("Source", &request.source) {
+        return serialize_format_response(FormatResponse {
+            error: Some(error),
+            ..Default::default()
+        });
+    }
+
+    let response = error::catch_internal(|| format_request(request))
+        .unwrap_or_else(|error| FormatResponse { error: Some(error), ..Default::default() });
+    serialize_format_response(response)
+}
+
+fn format_request(request: FormatRequest) -> FormatResponse {
+    let formatter = CairoFormatter::new(FormatterConfig::default());
+    match formatter.format_to_string(&request.source) {
+        Ok(FormatOutcome::Identical(formatted)) => FormatResponse {
+            success: true,
+            formatted: Some(formatted),
+            changed: false,
+            diagnostics: String::new(),
+            error: None,
+        },
+        Ok(FormatOutcome::DiffFound(diff)) => FormatResponse {
+            success: true,
+            formatted: Some(diff.formatted),
+            changed: true,
+            diagnostics: String::new(),
+            error: None,
+        },
+        Err(FormattingError::ParsingError(error)) => FormatResponse {
+            diagnostics: error.to_string(),
+            error: Some(ApiError::compilation("Failed parsing source.")),
+            ..Default::default()
+        },
+        Err(FormattingError::Error(error)) => FormatResponse {
+            error: Some(ApiError::new(ErrorKind::Internal, error.to_string())),
+            ..Default::default()
+        },
+    }
+}
+
+/// Returns the provenance of the module, such as its git commit and features. See
+/// [cairo_lang_wasm_utils::build_info::BuildInfo].
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn build_info() -> String {
+    build_info::build_info_json(&[])
+}
+
+/// Applies the module-wide configuration, such as input limits. See
+/// [cairo_lang_wasm_utils::config::HostConfig].
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn configure(config_json: &str) -> String {
+    config::configure(config_json)
+}
+
+/// Returns the module state, such as its configuration, cached runs and compiled programs, for
+/// restoring it in other instances with `restore_state`. See
+/// [cairo_lang_wasm_utils::state::ModuleState].
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn snapshot_state() -> String {
+    state::snapshot_state(cairo_lang_runner_wasm::STATE_CACHES)
+}
+
+/// Restores a module state returned by `snapshot_state`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn restore_state(state_json: &str) -> String {
+    state::restore_state(state_json, cairo_lang_runner_wasm::STATE_CACHES)
+}
+
+/// Makes the runs stop with a `cancelled` response once the first element of `flag` is non-zero.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_cancellation_flag(flag: js_sys::Int32Array) {
+    cairo_lang_runner_wasm::set_cancellation_flag(flag);
+}
+
+/// Makes the runs stop with a `cancelled` response once `callback` returns a truthy value.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_cancellation_callback(callback: js_sys::Function) {
+    cairo_lang_runner_wasm::set_cancellation_callback(callback);
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn clear_cancellation() {
+    cairo_lang_runner_wasm::clear_cancellation();
+}
+
+/// Makes the compilations call `callback` with each diagnostic as it is reported.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_diagnostics_callback(callback: js_sys::Function) {
+    cairo_lang_compiler_wasm::set_diagnostics_callback(callback);
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn clear_diagnostics_callback() {
+    cairo_lang_compiler_wasm::clear_diagnostics_callback();
+}
+
+/// Makes the compile and run requests call `callback` with their outcomes.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn set_telemetry_callback(callback: js_sys::Function) {
+    cairo_lang_compiler_wasm::set_telemetry_callback(callback);
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn clear_telemetry_callback() {
+    cairo_lang_compiler_wasm::clear_telemetry_callback();
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn embedded_corelib_manifest() -> String {
+    corelib::manifest_json()
+}
+
+fn serialize_check_response(response: CheckResponse) -> String {
+    serde_json::to_string(&response).expect("serialize check response")
+}
+
+fn serialize_format_response(response: FormatResponse) -> String {
+    serde_json::to_string(&response).expect("serialize format response")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{build_info, check, format};
+
+    #[test]
+    fn check_reports_main_crate_errors() {
+        let request = json!({
+            "crate_name": "test",
+            "files": { "lib.cairo": "fn main() -> felt252 { x }" }
+        });
+
+        let response = check(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], false, "response={response}");
+        assert!(response_json["diagnostics"].as_str().unwrap().contains("lib.cairo"));
+    }
+
+    #[test]
+    fn format_source() {
+        let request = json!({ "source": "fn main()->felt252{7}" });
+
+        let response = format(&request.to_string());
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["success"], true, "response={response}");
+        assert_eq!(response_json["changed"], true);
+        assert_eq!(response_json["formatted"], "fn main() -> felt252 {\n    7\n}\n");
+    }
+    #[test]
+    fn build_info_reports_provenance() {
+        let response = build_info();
+        let response_json: Value = serde_json::from_str(&response).expect("valid JSON response");
+
+        assert_eq!(response_json["version"], env!("CARGO_PKG_VERSION"));
+        // The allocator is only installed by the WASM build.
+        assert_eq!(response_json["features"], json!([]));
+    }
+}
@@ -0,0 +1,130 @@
+//! JSON Schemas of the requests and responses of the endpoints, for clients validating them or
+//! generating bindings in languages other than TypeScript.
+
+use cairo_lang_compiler_wasm::{
+    CompileRequest, CompileResponse, DisassembleRequest, DisassembleResponse,
+    ExtractConstantsRequest, ExtractConstantsResponse, FunctionSignatureRequest,
+    FunctionSignatureResponse, ImportArchiveResponse, InlineFunctionRequest,
+    InlineFunctionResponse, MinimizeRequest, MinimizeResponse, TypeLayoutRequest,
+    TypeLayoutResponse,
+};
+use cairo_lang_runner_wasm::examples::Example;
+use cairo_lang_runner_wasm::{
+    BatchRunResponse, CallContractRequest, CompileAndRunBatchRequest, CompileAndRunRequest,
+    CompileProgramRequest, CompileProgramResponse, DeterminismResponse, EstimateGasRequest,
+    EstimateGasResponse, ExplainStatementRequest, ExplainStatementResponse, FuzzRequest,
+    FuzzResponse, GradeCase, GradeResponse, LibfuncCostsRequest, LibfuncCostsResponse,
+    ListEntrypointsRequest, ListEntrypointsResponse, ListTestsRequest, ListTestsResponse,
+    ReadStorageRequest, ReadStorageResponse, RunCasmRequest, RunResponse, RunSierraRequest,
+    RunTestResponse, RunTestsRequest, RunTestsResponse, SelfTestResponse, SliceProgramRequest,
+    SliceProgramResponse, StarknetSessionResponse, ValidateDeclareRequest, ValidateDeclareResponse,
+};
+use cairo_lang_wasm_utils::build_info::BuildInfo;
+use cairo_lang_wasm_utils::config::{ConfigureResponse, HostConfig};
+use cairo_lang_wasm_utils::diagnostic_listener::StreamedDiagnostic;
+use cairo_lang_wasm_utils::state::{ModuleState, RestoreStateResponse};
+use cairo_lang_wasm_utils::telemetry::TelemetryEvent;
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, Schema};
+use serde_json::{Map, Value, json};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{CheckRequest, CheckResponse, FormatRequest, FormatResponse};
+
+/// Returns an object mapping each endpoint to the JSON Schemas of its JSON arguments, such as
+/// `request`, and of its `response`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn api_schema() -> String {
+    let schemas = [
+        ("compile", endpoint::<CompileRequest, CompileResponse>()),
+        ("import_archive", json!({ "response": response::<ImportArchiveResponse>() })),
+        ("function_signature", endpoint::<FunctionSignatureRequest, FunctionSignatureResponse>()),
+        ("type_layout", endpoint::<TypeLayoutRequest, TypeLayoutResponse>()),
+        ("disassemble", endpoint::<DisassembleRequest, DisassembleResponse>()),
+        ("extract_constants", endpoint::<ExtractConstantsRequest, ExtractConstantsResponse>()),
+        ("inline_function", endpoint::<InlineFunctionRequest, InlineFunctionResponse>()),
+        ("minimize", endpoint::<MinimizeRequest, MinimizeResponse>()),
+        ("compile_and_run", endpoint::<CompileAndRunRequest, RunResponse>()),
+        ("compile_and_run_batch", endpoint::<CompileAndRunBatchRequest, BatchRunResponse>()),
+        ("fuzz", endpoint::<FuzzRequest, FuzzResponse>()),
+        ("estimate_gas", endpoint::<EstimateGasRequest, EstimateGasResponse>()),
+        (
+            "grade",
+            json!({
+                "request": request::<CompileAndRunRequest>(),
+                "cases": request::<Vec<GradeCase>>(),
+                "response": response::<GradeResponse>(),
+            }),
+        ),
+        ("check_determinism", endpoint::<CompileAndRunRequest, DeterminismResponse>()),
+        ("compile_program", endpoint::<CompileProgramRequest, CompileProgramResponse>()),
+        ("run_sierra", endpoint::<RunSierraRequest, RunResponse>()),
+        ("run_casm", endpoint::<RunCasmRequest, RunResponse>()),
+        #[cfg(feature = "prove")]
+        ("prove", endpoint::<RunCasmRequest, RunResponse>()),
+        ("call_contract", endpoint::<CallContractRequest, RunResponse>()),
+        ("read_storage", endpoint::<ReadStorageRequest, ReadStorageResponse>()),
+        ("warp", json!({ "response": response::<StarknetSessionResponse>() })),
+        ("roll", json!({ "response": response::<StarknetSessionResponse>() })),
+        ("prank", json!({ "response": response::<StarknetSessionResponse>() })),
+        ("validate_declare", endpoint::<ValidateDeclareRequest, ValidateDeclareResponse>()),
+        ("list_entrypoints", endpoint::<ListEntrypointsRequest, ListEntrypointsResponse>()),
+        (
+            "explain_sierra_statement",
+            endpoint::<ExplainStatementRequest, ExplainStatementResponse>(),
+        ),
+        ("slice_program", endpoint::<SliceProgramRequest, SliceProgramResponse>()),
+        ("libfunc_costs", endpoint::<LibfuncCostsRequest, LibfuncCostsResponse>()),
+        ("list_tests", endpoint::<ListTestsRequest, ListTestsResponse>()),
+        ("run_test", json!({ "response": response::<RunTestResponse>() })),
+        ("run_tests", endpoint::<RunTestsRequest, RunTestsResponse>()),
+        ("examples", json!({ "response": response::<Vec<Example>>() })),
+        ("self_test", json!({ "response": response::<SelfTestResponse>() })),
+        ("check", endpoint::<CheckRequest, CheckResponse>()),
+        ("format", endpoint::<FormatRequest, FormatResponse>()),
+        ("build_info", json!({ "response": response::<BuildInfo>() })),
+        ("configure", endpoint::<HostConfig, ConfigureResponse>()),
+        ("snapshot_state", json!({ "response": response::<ModuleState>() })),
+        ("restore_state", endpoint::<ModuleState, RestoreStateResponse>()),
+        // The JSON argument of the callback, not of the endpoint.
+        ("set_diagnostics_callback", json!({ "diagnostic": response::<StreamedDiagnostic>() })),
+        ("set_telemetry_callback", json!({ "event": response::<TelemetryEvent>() })),
+    ];
+    let schemas = schemas.map(|(endpoint, schemas)| (endpoint.to_string(), schemas));
+    serde_json::to_string(&Map::from_iter(schemas)).expect("serialize API schema")
+}
+
+/// Returns the schemas of an endpoint taking a `request` and returning a `response`.
+fn endpoint<Request: JsonSchema, Response: JsonSchema>() -> Value {
+    json!({ "request": request::<Request>(), "response": response::<Response>() })
+}
+
+/// Returns the schema of `T` as accepted in a request.
+fn request<T: JsonSchema>() -> Schema {
+    SchemaSettings::default().for_deserialize().into_generator().into_root_schema_for::<T>()
+}
+
+/// Returns the schema of `T` as returned in a response.
+fn response<T: JsonSchema>() -> Schema {
+    SchemaSettings::default().for_serialize().into_generator().into_root_schema_for::<T>()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::api_schema;
+
+    #[test]
+    fn api_schema_describes_endpoints() {
+        let schemas: Value = serde_json::from_str(&api_schema()).expect("valid JSON response");
+
+        let request = &schemas["compile_and_run"]["request"];
+        assert_eq!(request["type"], "object");
+        assert!(request["required"].as_array().unwrap().contains(&"crate_name".into()));
+        assert!(request["properties"]["files"].is_object());
+        assert!(schemas["compile_and_run"]["response"]["properties"]["values"].is_object());
+        assert!(schemas["grade"]["cases"]["items"].is_object());
+    }
+}
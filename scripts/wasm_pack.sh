@@ -0,0 +1,27 @@
+#!/bin/bash
+
+# Builds the `cairo-wasm` npm package: a `nodejs` output loaded synchronously by `require`, and a
+# `bundler` output loaded asynchronously by ES module bundlers.
+#
+# Internal panics are returned as `internal` errors by catching them, which requires unwinding:
+# `wasm32-unknown-unknown` aborts on panic by default. The module is therefore built on nightly
+# with a standard library rebuilt for `panic=unwind`, using WebAssembly exception handling.
+#
+# The module owns its allocator, so it counts the allocations of each request.
+
+set -ex
+
+CRATE_DIR=crates/cairo-wasm
+PKG_DIR=$CRATE_DIR/pkg
+
+export RUSTUP_TOOLCHAIN=${RUSTUP_TOOLCHAIN:-nightly}
+export RUSTFLAGS="-C panic=unwind -C target-feature=+exception-handling ${RUSTFLAGS:-}"
+
+rm -rf $PKG_DIR
+for TARGET in nodejs bundler; do
+    wasm-pack build $CRATE_DIR --release --target $TARGET --out-dir pkg/$TARGET --out-name cairo_wasm \
+        -- --features allocation-stats -Z build-std=std,panic_unwind
+    # The package manifest is provided by `npm/package.json`.
+    rm -f $PKG_DIR/$TARGET/package.json $PKG_DIR/$TARGET/.gitignore
+done
+cp $CRATE_DIR/npm/* $PKG_DIR/
@@ -0,0 +1,46 @@
+// Checks the WASM module built by `scripts/wasm_pack.sh`, where panics behave differently than in
+// native tests: an internal compiler error must be returned as an `internal` error, and the module
+// must keep serving requests after it.
+//
+// Usage: scripts/wasm_pack.sh && node scripts/wasm_test.js
+
+"use strict";
+
+const assert = require("assert");
+const path = require("path");
+
+const cairo = require(path.join(__dirname, "../crates/cairo-wasm/pkg/node.js"));
+
+const MAIN = "fn main() -> felt252 {\n    7\n}\n";
+
+function request(endpoint, body) {
+    return JSON.parse(cairo[endpoint](JSON.stringify(body)));
+}
+
+// A corelib without the core types makes the compiler panic.
+const crashed = request("compile", {
+    crate_name: "test",
+    files: { "lib.cairo": MAIN },
+    corelib_files: { "lib.cairo": "" },
+});
+assert.strictEqual(crashed.success, false, JSON.stringify(crashed));
+assert.strictEqual(crashed.error.kind, "internal", JSON.stringify(crashed));
+
+// Minimization detects the internal error of each candidate project the same way.
+const minimized = request("minimize", {
+    crate_name: "test",
+    files: { "lib.cairo": MAIN, "utils.cairo": "fn unused() {}\n" },
+    corelib_files: { "lib.cairo": "" },
+});
+assert.strictEqual(minimized.success, true, JSON.stringify(minimized));
+assert.strictEqual(typeof minimized.internal_error, "string", JSON.stringify(minimized));
+
+const run = request("compile_and_run", { crate_name: "test", files: { "lib.cairo": MAIN } });
+assert.strictEqual(run.success, true, JSON.stringify(run));
+assert.deepStrictEqual(run.values, ["7"]);
+assert.ok(run.allocations.allocations > 0, JSON.stringify(run));
+
+const buildInfo = JSON.parse(cairo.build_info());
+assert.deepStrictEqual(buildInfo.features, ["allocation-stats"], JSON.stringify(buildInfo));
+
+console.log("WASM module checks passed.");